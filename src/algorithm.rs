@@ -1,5 +1,26 @@
-use crate::tsplib::{Solution, TsplibInstance};
-use indicatif::{ProgressBar, ProgressStyle};
+use crate::algorithms::local_search::base::LocalSearch;
+use crate::algorithms::local_search::report::LocalSearchReport;
+use crate::algorithms::msls::Msls;
+use crate::bounds;
+use crate::convergence::ConvergenceTrace;
+use crate::counters::{self, OpCounts};
+use crate::elite::EliteArchive;
+use crate::flat_json::{join_list, plain_string};
+use crate::postprocess::rebalance;
+use crate::progress::ExperimentProgress;
+use crate::seed::SeedStream;
+use crate::significance;
+use crate::table::{Align, TextTable};
+use crate::tsplib::{Objective, Solution, TsplibInstance};
+use crate::utils::fnv1a;
+use crate::weight::{Cost, Weight};
+use rand::RngCore;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 pub type ProgressCallback<'a> = &'a mut dyn FnMut(String);
@@ -10,106 +31,415 @@ pub trait TspAlgorithm {
     fn solve_with_feedback(
         &self,
         instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
     ) -> Solution;
 }
 
 #[derive(Debug, Clone)]
 pub struct RunResult {
-    pub cost: i32,
+    pub cost: Cost,
     pub solution: Solution,
     pub time_ms: u128,
-    pub iterations: Option<usize>,
+    /// Thread time summed over the run, for runs that work on several threads.
+    pub cpu_time_ms: Option<u128>,
+    pub iterations: Option<IterationInfo>,
+}
+
+/// What the iterations of an algorithm are; their counts only compare within a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IterationUnit {
+    /// Main-loop iterations of a local search: one per applied move, plus the final scan
+    /// that finds none.
+    LocalSearchSteps,
+    /// Perturbations, each followed by a local search (ILS, the hyperheuristic; the latter
+    /// also counts its first local search, on the unperturbed initial solution).
+    Perturbations,
+    /// Destroy-repair rounds of LNS.
+    DestroyRepairs,
+    /// Children built by recombination (HAE).
+    Children,
+    /// Local searches from fresh initial solutions, restarted until the deadline.
+    Restarts,
+    /// Random moves applied by the random walk.
+    RandomMoves,
+}
+
+impl IterationUnit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::LocalSearchSteps => "ls-steps",
+            Self::Perturbations => "perturbations",
+            Self::DestroyRepairs => "destroy-repairs",
+            Self::Children => "children",
+            Self::Restarts => "restarts",
+            Self::RandomMoves => "random-moves",
+        }
+    }
+}
+
+/// How much work a run did, in counts that compare across algorithms: the main-loop
+/// iterations in the algorithm's own unit, the complete local search runs they made and the
+/// move evaluations they took.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IterationInfo {
+    pub unit: IterationUnit,
+    pub outer: usize,
+    /// Local searches run to their end or the budget, including the one on the initial
+    /// solution.
+    pub inner_ls_runs: usize,
+    /// Move evaluations, or `None` where they were not counted. The metaheuristics take
+    /// them from [`crate::budget::Budget::evaluations_since_start`], so only with the
+    /// `op-counters` feature.
+    pub evaluations: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExperimentStats {
     pub algorithm_name: String,
     pub instance_name: String,
-    pub min_cost: i32,
-    pub max_cost: i32,
+    pub min_cost: Cost,
+    pub max_cost: Cost,
     pub avg_cost: f64,
     pub best_solution: Solution,
     pub avg_time_ms: f64,
+    /// Average summed thread time, for parallel algorithms; compare it with the
+    /// single-threaded algorithms' wall time.
+    pub avg_cpu_time_ms: Option<f64>,
     pub avg_iterations: Option<f64>,
+    /// Unit of [`Self::avg_iterations`], if every run reported the same one.
+    pub iteration_unit: Option<IterationUnit>,
+    /// Average [`IterationInfo::inner_ls_runs`] and [`IterationInfo::evaluations`], over the
+    /// runs that reported them.
+    pub avg_ls_runs: Option<f64>,
+    pub avg_evaluations: Option<f64>,
     pub num_runs: usize,
+    /// Final cost of every run in order, kept even without [`ExperimentOptions::keep_runs`]
+    /// for significance tests.
+    pub costs: Vec<Cost>,
+    /// Move statistics merged over all runs, for local search experiments.
+    pub ls_report: Option<LocalSearchReport>,
+    /// Every run in order, when requested through [`ExperimentOptions::keep_runs`].
+    pub runs: Vec<RunResult>,
+    /// Operations summed over all runs; `None` unless built with the `op-counters` feature.
+    pub op_counts: Option<OpCounts>,
+    /// Best cost over time of every run, for algorithms that report it.
+    pub convergence: Vec<ConvergenceTrace>,
+    /// Wall time of every run in order, kept like [`Self::costs`].
+    pub times_ms: Vec<u128>,
+    /// Nominal time limit of the runs, for timed experiments (see
+    /// [`ExperimentOptions::time_limit`]).
+    pub time_limit: Option<Duration>,
+}
+
+impl ExperimentStats {
+    /// Mean of [`ConvergenceTrace::primal_integral`] over the runs, or `None` if the runs
+    /// were not traced.
+    pub fn avg_primal_integral(&self, reference: Cost) -> Option<f64> {
+        if self.convergence.is_empty() {
+            return None;
+        }
+        let sum: f64 = self
+            .convergence
+            .iter()
+            .map(|trace| trace.primal_integral(reference))
+            .sum();
+        Some(sum / self.convergence.len() as f64)
+    }
+
+    /// The work of an average run in comparable units, e.g. `512.3 children, 513.3 local
+    /// searches, 40182335 move evaluations`, or `None` if the runs reported no
+    /// [`IterationInfo`] or mixed units.
+    pub fn format_work_per_run(&self) -> Option<String> {
+        let unit = self.iteration_unit?;
+        let mut text = format!(
+            "{:.1} {}, {:.1} local searches",
+            self.avg_iterations?,
+            unit.as_str(),
+            self.avg_ls_runs?
+        );
+        if let Some(evaluations) = self.avg_evaluations {
+            write!(text, ", {:.0} move evaluations", evaluations).expect("writing to a String");
+        }
+        Some(text)
+    }
+
+    /// A warning if no run got past its start, e.g. an HAE whose initial population used up
+    /// the whole time limit, so the results only show the starting solutions.
+    pub fn idle_warning(&self) -> Option<String> {
+        let unit = self.iteration_unit?;
+        (self.avg_iterations? == 0.0).then(|| {
+            format!(
+                "No run got past its start (0 {}) before the budget ran out; raise the time limit",
+                unit.as_str()
+            )
+        })
+    }
+
+    /// How far the runs went past [`Self::time_limit`], or `None` if the experiment was not
+    /// timed or every run stopped within the tolerance: [`OVERSHOOT_TOLERANCE`] of the
+    /// limit, but at least [`OVERSHOOT_FLOOR`]. The time-limited algorithms check their
+    /// deadline between iterations, so a run ends up to one iteration late; on large
+    /// instances that iteration can be long enough to skew a comparison.
+    pub fn time_overshoot(&self) -> Option<TimeOvershoot> {
+        let limit = self.time_limit?;
+        let allowed = limit
+            .mul_f64(1.0 + OVERSHOOT_TOLERANCE)
+            .max(limit + OVERSHOOT_FLOOR);
+        let over = self
+            .times_ms
+            .iter()
+            .filter(|&&ms| ms > allowed.as_millis())
+            .count();
+        (over > 0).then(|| TimeOvershoot {
+            limit,
+            runs_over: over,
+            runs: self.times_ms.len(),
+            max_time_ms: self.times_ms.iter().copied().max().unwrap_or(0),
+        })
+    }
+}
+
+/// Share of the time limit a run may exceed it by before [`ExperimentStats::time_overshoot`]
+/// reports it.
+pub const OVERSHOOT_TOLERANCE: f64 = 0.05;
+/// Overshoot always tolerated, as timer and scheduling noise on short limits.
+pub const OVERSHOOT_FLOOR: Duration = Duration::from_millis(10);
+
+/// Runs of an experiment that exceeded their time limit, from
+/// [`ExperimentStats::time_overshoot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOvershoot {
+    pub limit: Duration,
+    pub runs_over: usize,
+    pub runs: usize,
+    pub max_time_ms: u128,
+}
+
+impl fmt::Display for TimeOvershoot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} runs exceeded the {} ms time limit by more than {:.0}% (longest {} ms)",
+            self.runs_over,
+            self.runs,
+            self.limit.as_millis(),
+            100.0 * OVERSHOOT_TOLERANCE,
+            self.max_time_ms
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExperimentOptions<'a> {
+    /// Keep each run's final solution in [`ExperimentStats::runs`], not just the best one.
+    pub keep_runs: bool,
+    /// Archive each run's final solution is offered to, so that algorithms run later can
+    /// warm-start from it.
+    pub elites: Option<&'a EliteArchive>,
+    /// Time limit the runs were given, checked against their wall time by
+    /// [`ExperimentStats::time_overshoot`].
+    pub time_limit: Option<Duration>,
+    /// What the run costs are measured in; the default is the total length of both cycles.
+    pub objective: Objective,
+    /// Size slack to [`rebalance`] each run's final solution with before it is costed, for
+    /// the min-max objective; `None` keeps the solutions as the algorithm returned them.
+    pub rebalance: Option<usize>,
+    /// Master stream of the experiment. Run `r` of an algorithm on an instance draws from
+    /// its child for the instance name, then the algorithm name, then `r`, so it does not
+    /// depend on what else the experiment runs.
+    pub seeds: SeedStream,
 }
 
 pub fn run_experiment(
     algorithm: &(dyn TspAlgorithm + Send + Sync),
     instance: &TsplibInstance,
     num_runs: usize,
+    options: ExperimentOptions,
 ) -> ExperimentStats {
-    run_experiment_base(algorithm.name(), instance, num_runs, |progress_callback| {
-        (
-            algorithm.solve_with_feedback(instance, progress_callback),
-            None,
-        )
-    })
+    run_experiment_base(
+        algorithm.name(),
+        instance,
+        num_runs,
+        options,
+        |rng, progress_callback| {
+            RunOutput::new(algorithm.solve_with_feedback(instance, rng, progress_callback))
+        },
+    )
 }
 
-pub type TimedSolveFn<'a, T> =
-    Box<dyn Fn(&T, &TsplibInstance, ProgressCallback) -> (Solution, usize) + Send + Sync + 'a>;
+/// Runs a local search `num_runs` times, attaching the move statistics merged over all runs.
+/// The iterations column reports local search steps.
+pub fn run_local_search_experiment(
+    local_search: &LocalSearch,
+    instance: &TsplibInstance,
+    num_runs: usize,
+    options: ExperimentOptions,
+) -> ExperimentStats {
+    let merged = RefCell::new(LocalSearchReport::default());
+    let mut stats = run_experiment_base(
+        local_search.name(),
+        instance,
+        num_runs,
+        options,
+        |rng, progress_callback| {
+            let (solution, report) =
+                local_search.solve_with_report(instance, rng, progress_callback);
+            merged.borrow_mut().merge(&report);
+            RunOutput {
+                iterations: Some(IterationInfo {
+                    unit: IterationUnit::LocalSearchSteps,
+                    outer: report.iterations as usize,
+                    inner_ls_runs: report.runs as usize,
+                    evaluations: Some(report.evaluated.total()),
+                }),
+                ..RunOutput::new(solution)
+            }
+        },
+    );
+    stats.ls_report = Some(merged.into_inner());
+    stats
+}
 
-pub fn run_timed_experiment<T: Send + Sync + ?Sized>(
+pub type TracedSolveFn<'a, T> = Box<
+    dyn Fn(
+            &T,
+            &TsplibInstance,
+            &mut dyn RngCore,
+            ProgressCallback,
+        ) -> (Solution, IterationInfo, ConvergenceTrace)
+        + Send
+        + Sync
+        + 'a,
+>;
+
+/// Runs a timed solver that also traces its best cost over time, keeping the traces in
+/// [`ExperimentStats::convergence`].
+pub fn run_traced_experiment<T: Send + Sync + ?Sized>(
     algorithm: &T,
-    timed_solve_fn: TimedSolveFn<T>,
+    traced_solve_fn: TracedSolveFn<T>,
     instance: &TsplibInstance,
     num_runs: usize,
     algorithm_name: &str,
+    options: ExperimentOptions,
+) -> ExperimentStats {
+    run_experiment_base(
+        algorithm_name,
+        instance,
+        num_runs,
+        options,
+        |rng, progress_callback| {
+            let (solution, iterations, trace) =
+                traced_solve_fn(algorithm, instance, rng, progress_callback);
+            RunOutput {
+                iterations: Some(iterations),
+                trace: Some(trace),
+                ..RunOutput::new(solution)
+            }
+        },
+    )
+}
+
+/// Runs an MSLS `num_runs` times, also recording each run's summed iteration time, which
+/// stays comparable with single-threaded algorithms when the iterations run in parallel.
+pub fn run_msls_experiment(
+    msls: &Msls,
+    instance: &TsplibInstance,
+    num_runs: usize,
+    options: ExperimentOptions,
 ) -> ExperimentStats {
-    run_experiment_base(algorithm_name, instance, num_runs, |progress_callback| {
-        let (solution, iterations) = timed_solve_fn(algorithm, instance, progress_callback);
-        (solution, Some(iterations))
-    })
+    run_experiment_base(
+        msls.name(),
+        instance,
+        num_runs,
+        options,
+        |rng, progress_callback| {
+            let outcome = msls.solve_with_outcome(instance, rng, progress_callback);
+            RunOutput {
+                cpu_time: Some(outcome.cpu_time),
+                ..RunOutput::new(outcome.solution)
+            }
+        },
+    )
+}
+
+/// What one run hands back to [`run_experiment_base`].
+struct RunOutput {
+    solution: Solution,
+    iterations: Option<IterationInfo>,
+    trace: Option<ConvergenceTrace>,
+    cpu_time: Option<Duration>,
+}
+
+impl RunOutput {
+    fn new(solution: Solution) -> Self {
+        Self {
+            solution,
+            iterations: None,
+            trace: None,
+            cpu_time: None,
+        }
+    }
 }
 
 fn run_experiment_base<F>(
     algorithm_name: &str,
     instance: &TsplibInstance,
     num_runs: usize,
+    options: ExperimentOptions,
     solve_fn: F,
 ) -> ExperimentStats
 where
-    F: Fn(ProgressCallback) -> (Solution, Option<usize>),
+    F: Fn(&mut dyn RngCore, ProgressCallback) -> RunOutput,
 {
     if num_runs == 0 {
         return ExperimentStats {
             algorithm_name: algorithm_name.to_string(),
             instance_name: instance.name.clone(),
-            min_cost: 0,
-            max_cost: 0,
+            min_cost: Cost::ZERO,
+            max_cost: Cost::ZERO,
             avg_cost: 0.0,
             best_solution: Solution::new(vec![], vec![]),
             avg_time_ms: 0.0,
+            avg_cpu_time_ms: None,
             avg_iterations: None,
+            iteration_unit: None,
+            avg_ls_runs: None,
+            avg_evaluations: None,
             num_runs: 0,
+            costs: Vec::new(),
+            ls_report: None,
+            runs: Vec::new(),
+            op_counts: None,
+            convergence: Vec::new(),
+            times_ms: Vec::new(),
+            time_limit: options.time_limit,
         };
     }
 
     let mut results = Vec::with_capacity(num_runs);
+    let mut convergence = Vec::new();
 
-    let pb = ProgressBar::new(num_runs as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template(
-                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}",
-            )
-            .unwrap()
-            .progress_chars("# >-"),
-    );
-    pb.set_prefix(format!("Running {}", algorithm_name));
-    pb.set_message("Starting...");
+    let progress = ExperimentProgress::new(algorithm_name, num_runs);
+    let ops_before = counters::snapshot();
+    let seeds = options
+        .seeds
+        .child(fnv1a(instance.name.as_bytes()))
+        .child(fnv1a(algorithm_name.as_bytes()));
 
     for run_index in 0..num_runs {
+        let mut rng = seeds.child(run_index as u64).rng();
         let start = Instant::now();
 
-        let mut callback = |status: String| {
-            pb.set_message(format!("[Run {}/{}] {}", run_index + 1, num_runs, status));
-        };
+        progress.start_run(run_index);
+        let mut callback = |status: String| progress.report(status);
 
-        let (solution, iterations_opt) = solve_fn(&mut callback);
+        let output = solve_fn(&mut rng, &mut callback);
+        let mut solution = output.solution;
         let elapsed = start.elapsed();
+        if let Some(size_slack) = options.rebalance {
+            rebalance(&mut solution, instance, size_slack);
+        }
 
         assert!(
             solution.is_valid(instance),
@@ -117,24 +447,29 @@ where
             algorithm_name
         );
 
+        if let Some(elites) = options.elites {
+            elites.offer(&solution, instance, algorithm_name);
+        }
         let result = RunResult {
-            cost: solution.calculate_cost(instance),
+            cost: options.objective.value(solution.cycle_costs(instance)),
             solution,
             time_ms: elapsed.as_millis(),
-            iterations: iterations_opt,
+            cpu_time_ms: output.cpu_time.map(|cpu| cpu.as_millis()),
+            iterations: output.iterations,
         };
         results.push(result);
-        pb.inc(1);
-        pb.set_message("Done run.");
+        convergence.extend(output.trace);
+        progress.finish_run();
     }
-    pb.finish_with_message("Finished all runs.");
+    progress.finish();
+    let op_counts = counters::snapshot()
+        .zip(ops_before)
+        .map(|(after, before)| after.since(before));
 
-    let mut min_cost = i32::MAX;
-    let mut max_cost = i32::MIN;
-    let mut sum_cost: i64 = 0;
+    let mut min_cost = Cost::MAX;
+    let mut max_cost = Cost::MIN;
+    let mut sum_cost = 0.0;
     let mut sum_time: u128 = 0;
-    let mut sum_iterations: u64 = 0;
-    let mut iteration_count = 0;
     let mut best_solution = None;
 
     for result in &results {
@@ -143,53 +478,348 @@ where
             best_solution = Some(result.solution.clone());
         }
         max_cost = max_cost.max(result.cost);
-        sum_cost += result.cost as i64;
+        sum_cost += result.cost.to_f64();
         sum_time += result.time_ms;
-        if let Some(iters) = result.iterations {
-            sum_iterations += iters as u64;
-            iteration_count += 1;
-        }
     }
 
     let final_best_solution = best_solution.expect("Best solution should exist if num_runs > 0");
-    let avg_iterations = if iteration_count > 0 {
-        Some(sum_iterations as f64 / iteration_count as f64)
-    } else {
-        None
+    let cpu_times: Vec<u128> = results.iter().filter_map(|r| r.cpu_time_ms).collect();
+    let avg_cpu_time_ms = (!cpu_times.is_empty())
+        .then(|| cpu_times.iter().sum::<u128>() as f64 / cpu_times.len() as f64);
+    let infos: Vec<IterationInfo> = results.iter().filter_map(|r| r.iterations).collect();
+    let average = |values: Vec<f64>| {
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
     };
+    let avg_iterations = average(infos.iter().map(|info| info.outer as f64).collect());
+    let avg_ls_runs = average(infos.iter().map(|info| info.inner_ls_runs as f64).collect());
+    let avg_evaluations = average(
+        infos
+            .iter()
+            .filter_map(|info| info.evaluations)
+            .map(|evaluations| evaluations as f64)
+            .collect(),
+    );
+    let iteration_unit = infos
+        .first()
+        .map(|info| info.unit)
+        .filter(|&unit| infos.iter().all(|info| info.unit == unit));
 
     ExperimentStats {
         algorithm_name: algorithm_name.to_string(),
         instance_name: instance.name.clone(),
         min_cost,
         max_cost,
-        avg_cost: sum_cost as f64 / num_runs as f64,
+        avg_cost: sum_cost / num_runs as f64,
         best_solution: final_best_solution,
         avg_time_ms: sum_time as f64 / num_runs as f64,
+        avg_cpu_time_ms,
         avg_iterations,
+        iteration_unit,
+        avg_ls_runs,
+        avg_evaluations,
         num_runs,
+        costs: results.iter().map(|result| result.cost).collect(),
+        times_ms: results.iter().map(|result| result.time_ms).collect(),
+        time_limit: options.time_limit,
+        ls_report: None,
+        runs: if options.keep_runs {
+            results
+        } else {
+            Vec::new()
+        },
+        op_counts,
+        convergence,
     }
 }
 
-pub fn format_stats_row(stats: &ExperimentStats) -> String {
+/// Columns of [`stats_cells`].
+pub const STATS_COLUMNS: [(&str, Align); 4] = [
+    ("Algorithm", Align::Left),
+    ("Cost (min - avg - max)", Align::Left),
+    ("Time (ms, avg)", Align::Right),
+    ("Iterations (avg)", Align::Right),
+];
+
+/// The algorithm name, costs, average time and average iterations of `stats`, as cells of
+/// [`STATS_COLUMNS`].
+pub fn stats_cells(stats: &ExperimentStats) -> Vec<String> {
     if stats.num_runs == 0 {
-        return format!(
-            "| {:<28} | No runs executed | N/A | N/A |",
-            stats.algorithm_name
-        );
+        return vec![
+            stats.algorithm_name.clone(),
+            "No runs executed".to_string(),
+            "N/A".to_string(),
+            "N/A".to_string(),
+        ];
     }
     let iter_str = match stats.avg_iterations {
         Some(avg_iters) => format!("{:.1}", avg_iters),
         None => "N/A".to_string(),
     };
-    let algo_name_padded = format!("{:<28}", stats.algorithm_name);
-    format!(
-        "| {} | {} ({:.2} - {}) | {:>14.2} | {:>16} |",
-        algo_name_padded,
-        stats.min_cost,
-        stats.avg_cost,
-        stats.max_cost,
-        stats.avg_time_ms,
-        iter_str
-    )
+    vec![
+        stats.algorithm_name.clone(),
+        format!(
+            "{} ({:.2} - {})",
+            stats.min_cost, stats.avg_cost, stats.max_cost
+        ),
+        format!("{:.2}", stats.avg_time_ms),
+        iter_str,
+    ]
+}
+
+/// ANSI styles of the ranked summary rows.
+const STYLE_BEST: &str = "\x1b[1;32m";
+const STYLE_TIED: &str = "\x1b[33m";
+const STYLE_RESET: &str = "\x1b[0m";
+
+/// The results table with the algorithms of every instance ranked by average cost. The last
+/// column compares each algorithm with the best one of its instance using the Mann-Whitney test
+/// of [`significance`]. `~` means the run costs are not significantly different, so the rank
+/// difference may be noise. With `color`, the best row is bold green and the tied rows yellow.
+/// Gaps are measured against `references[instance]`.
+pub fn format_ranked_summary(
+    results: &[(String, ExperimentStats)],
+    references: &HashMap<String, Option<Cost>>,
+    color: bool,
+) -> Vec<String> {
+    let mut columns = vec![("Instance", Align::Left), ("#", Align::Left)];
+    columns.extend(STATS_COLUMNS);
+    columns.extend([("Gap to ref (min)", Align::Right), ("vs best", Align::Left)]);
+    let mut table = TextTable::new(&columns);
+    // Per row, its style and the lines printed below it.
+    let mut row_extras: Vec<(&str, Vec<String>)> = Vec::new();
+    let mut instances: Vec<&str> = Vec::new();
+    for (instance_name, _) in results {
+        if !instances.contains(&instance_name.as_str()) {
+            instances.push(instance_name);
+        }
+    }
+    for instance_name in instances {
+        let mut ranked: Vec<&ExperimentStats> = results
+            .iter()
+            .filter(|(name, _)| name == instance_name)
+            .map(|(_, stats)| stats)
+            .collect();
+        ranked.sort_by(|a, b| a.avg_cost.total_cmp(&b.avg_cost));
+        let best = ranked[0];
+        for (rank, stats) in ranked.iter().enumerate() {
+            let gap = match references.get(instance_name).copied().flatten() {
+                Some(bound) => format!("{:.2}%", bounds::gap_percent(stats.min_cost, bound)),
+                None => "N/A".to_string(),
+            };
+            let (comparison, style) = if rank == 0 {
+                ("best".to_string(), STYLE_BEST)
+            } else {
+                // Samples too small to test cannot be told from the best either.
+                let tied = significance::indistinguishable(&best.costs, &stats.costs);
+                let comparison = match significance::mann_whitney_p(&best.costs, &stats.costs) {
+                    Some(p) if tied => format!("~ p={:.3}", p),
+                    Some(p) => format!("worse p={:.3}", p),
+                    None => "N/A".to_string(),
+                };
+                (comparison, if tied { STYLE_TIED } else { "" })
+            };
+            let mut cells = vec![instance_name.to_string(), (rank + 1).to_string()];
+            cells.extend(stats_cells(stats));
+            cells.extend([gap, comparison]);
+            table.push(cells);
+            let mut extras = Vec::new();
+            if let Some(work) = stats.format_work_per_run() {
+                extras.push(format!("|   Work per run: {}", work));
+            }
+            if let Some(ops) = &stats.op_counts {
+                extras.push(format!("|   {}", ops.format_per_run(stats.num_runs)));
+            }
+            if let Some(overshoot) = stats.time_overshoot() {
+                extras.push(format!("|   [WARN] {}", overshoot));
+            }
+            if let Some(warning) = stats.idle_warning() {
+                extras.push(format!("|   [WARN] {}", warning));
+            }
+            row_extras.push((style, extras));
+        }
+    }
+
+    let mut table_lines = table.lines().into_iter();
+    let mut lines: Vec<String> = table_lines.by_ref().take(2).collect();
+    for (row, (style, extras)) in table_lines.zip(row_extras) {
+        lines.push(if color && !style.is_empty() {
+            format!("{}{}{}", style, row, STYLE_RESET)
+        } else {
+            row
+        });
+        lines.extend(extras);
+    }
+    lines
+}
+
+/// One table over all instances: for every algorithm, on how many instances it ran, its
+/// mean rank by average cost (as in [`format_ranked_summary`]), on how many it ranked first,
+/// and how far its average cost lies above the best average of the instance, averaged over
+/// the instances. Sorted by mean rank.
+pub fn format_combined_summary(results: &[(String, ExperimentStats)]) -> Vec<String> {
+    // Per algorithm, in order of first appearance: (instances, rank sum, wins, excess sum).
+    let mut totals: Vec<(&str, usize, usize, usize, f64)> = Vec::new();
+    let mut instances: Vec<&str> = Vec::new();
+    for (instance_name, _) in results {
+        if !instances.contains(&instance_name.as_str()) {
+            instances.push(instance_name);
+        }
+    }
+    for &instance_name in &instances {
+        let mut ranked: Vec<&ExperimentStats> = results
+            .iter()
+            .filter(|(name, _)| name == instance_name)
+            .map(|(_, stats)| stats)
+            .collect();
+        ranked.sort_by(|a, b| a.avg_cost.total_cmp(&b.avg_cost));
+        let best = ranked[0].avg_cost;
+        for (rank, stats) in ranked.iter().enumerate() {
+            let index = match totals
+                .iter()
+                .position(|total| total.0 == stats.algorithm_name)
+            {
+                Some(index) => index,
+                None => {
+                    totals.push((&stats.algorithm_name, 0, 0, 0, 0.0));
+                    totals.len() - 1
+                }
+            };
+            let total = &mut totals[index];
+            total.1 += 1;
+            total.2 += rank + 1;
+            total.3 += usize::from(rank == 0);
+            total.4 += 100.0 * (stats.avg_cost - best) / best.abs().max(f64::MIN_POSITIVE);
+        }
+    }
+    totals.sort_by(|a, b| (a.2 as f64 / a.1 as f64).total_cmp(&(b.2 as f64 / b.1 as f64)));
+
+    let mut table = TextTable::new(&[
+        ("Algorithm", Align::Left),
+        ("Instances", Align::Right),
+        ("Mean rank", Align::Right),
+        ("Wins", Align::Right),
+        ("Above best avg (mean)", Align::Right),
+    ]);
+    for (name, count, rank_sum, wins, excess_sum) in totals {
+        table.push(vec![
+            name.to_string(),
+            count.to_string(),
+            format!("{:.2}", rank_sum as f64 / count as f64),
+            wins.to_string(),
+            format!("{:.2}%", excess_sum / count as f64),
+        ]);
+    }
+    let mut lines = vec![format!("Across {} instances:", instances.len())];
+    lines.extend(table.lines());
+    lines
+}
+
+/// Writes one CSV row per experiment with columns
+/// `instance,algorithm,runs,min,avg,max,avg_time_ms,avg_iterations,primal_integral,`
+/// `time_limit_ms,max_time_ms,runs_over_limit,iteration_unit,avg_ls_runs,avg_evaluations`.
+/// The primal integral is measured against `references[instance]` and left empty for
+/// untraced experiments or instances without a reference; the time limit columns are empty
+/// for experiments without a time limit, and `runs_over_limit` counts the runs
+/// [`ExperimentStats::time_overshoot`] reports. `avg_iterations` counts `iteration_unit`
+/// (see [`IterationUnit::as_str`]); the last three columns are empty where the runs did not
+/// report them.
+pub fn write_stats_csv<'a>(
+    stats: impl IntoIterator<Item = &'a ExperimentStats>,
+    references: &HashMap<String, Cost>,
+    path: &Path,
+) -> io::Result<()> {
+    let mut out = String::from(
+        "instance,algorithm,runs,min,avg,max,avg_time_ms,avg_iterations,primal_integral,\
+         time_limit_ms,max_time_ms,runs_over_limit,iteration_unit,avg_ls_runs,avg_evaluations\n",
+    );
+    for stats in stats {
+        let avg_iterations = stats
+            .avg_iterations
+            .map(|iters| format!("{:.1}", iters))
+            .unwrap_or_default();
+        let primal_integral = references
+            .get(&stats.instance_name)
+            .and_then(|&reference| stats.avg_primal_integral(reference))
+            .map(|integral| format!("{:.6}", integral))
+            .unwrap_or_default();
+        let time_limit = match stats.time_limit {
+            Some(limit) => format!(
+                "{},{},{}",
+                limit.as_millis(),
+                stats.times_ms.iter().max().copied().unwrap_or(0),
+                stats
+                    .time_overshoot()
+                    .map_or(0, |overshoot| overshoot.runs_over)
+            ),
+            None => ",,".to_string(),
+        };
+        let work = format!(
+            "{},{},{}",
+            stats.iteration_unit.map_or("", IterationUnit::as_str),
+            stats
+                .avg_ls_runs
+                .map(|runs| format!("{:.1}", runs))
+                .unwrap_or_default(),
+            stats
+                .avg_evaluations
+                .map(|evaluations| format!("{:.0}", evaluations))
+                .unwrap_or_default()
+        );
+        writeln!(
+            out,
+            "{},\"{}\",{},{},{:.2},{},{:.2},{},{},{},{}",
+            stats.instance_name,
+            stats.algorithm_name.replace('"', "\"\""),
+            stats.num_runs,
+            stats.min_cost,
+            stats.avg_cost,
+            stats.max_cost,
+            stats.avg_time_ms,
+            avg_iterations,
+            primal_integral,
+            time_limit,
+            work
+        )
+        .expect("writing to a String cannot fail");
+    }
+    fs::write(path, out)
+}
+
+/// Writes every retained run of `stats` to `<dir>/run_<i>.json` (one flat JSON object with
+/// the cost, time, [`IterationInfo`] and both cycles). Writes nothing but the directory if the runs
+/// were not kept.
+pub fn write_run_archive(stats: &ExperimentStats, dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (i, run) in stats.runs.iter().enumerate() {
+        let iterations = run
+            .iterations
+            .map(|info| {
+                let evaluations = info
+                    .evaluations
+                    .map(|evaluations| format!(r#","evaluations":{}"#, evaluations))
+                    .unwrap_or_default();
+                format!(
+                    r#","iterations":{},"iteration_unit":"{}","ls_runs":{}{}"#,
+                    info.outer,
+                    info.unit.as_str(),
+                    info.inner_ls_runs,
+                    evaluations
+                )
+            })
+            .unwrap_or_default();
+        let json = format!(
+            r#"{{"instance":"{}","algorithm":"{}","run":{},"cost":{},"time_ms":{}{},"closed":{},"cycle1":[{}],"cycle2":[{}]}}"#,
+            plain_string(&stats.instance_name),
+            plain_string(&stats.algorithm_name),
+            i,
+            run.cost,
+            run.time_ms,
+            iterations,
+            run.solution.is_closed,
+            join_list(&run.solution.cycle1),
+            join_list(&run.solution.cycle2)
+        );
+        fs::write(dir.join(format!("run_{}.json", i)), json + "\n")?;
+    }
+    Ok(())
 }
@@ -0,0 +1,94 @@
+use crate::weight::{Cost, Weight};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// When an iterated search moves its current solution to a new candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Acceptance {
+    /// Only candidates strictly better than the current solution, so the current solution
+    /// is always the best one found.
+    #[default]
+    BetterOnly,
+    /// Every candidate.
+    Always,
+    /// Worse candidates with probability `exp(-(candidate - current) / T)`, where the
+    /// temperature `T` starts at `initial_temperature` (in cost units) and is multiplied by
+    /// `cooling` every iteration.
+    SimulatedAnnealing {
+        initial_temperature: f64,
+        cooling: f64,
+    },
+    /// Record-to-record travel: candidates at most `deviation` (a fraction) above the best
+    /// cost found so far.
+    RecordToRecord { deviation: f64 },
+}
+
+impl Acceptance {
+    /// Whether the search moves to `candidate` at `iteration` (counting from 1).
+    pub fn accepts<R: Rng + ?Sized>(
+        &self,
+        candidate: Cost,
+        current: Cost,
+        best: Cost,
+        iteration: usize,
+        rng: &mut R,
+    ) -> bool {
+        match *self {
+            Acceptance::BetterOnly => (candidate - current).is_improvement(),
+            Acceptance::Always => true,
+            Acceptance::SimulatedAnnealing {
+                initial_temperature,
+                cooling,
+            } => {
+                if candidate <= current {
+                    return true;
+                }
+                let temperature = initial_temperature * cooling.powi(iteration as i32);
+                temperature > 0.0
+                    && rng.random_bool((-(candidate - current).to_f64() / temperature).exp())
+            }
+            Acceptance::RecordToRecord { deviation } => {
+                candidate.to_f64() <= best.to_f64() + deviation * best.to_f64().abs()
+            }
+        }
+    }
+
+    /// Parses `better-only`, `always`, `annealing:<initial temperature>,<cooling>` or
+    /// `rrt:<deviation>`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.split_once(':') {
+            None => [Acceptance::BetterOnly, Acceptance::Always]
+                .into_iter()
+                .find(|acceptance| acceptance.name() == text),
+            Some(("annealing", parameters)) => {
+                let (initial_temperature, cooling) = parameters.split_once(',')?;
+                let initial_temperature: f64 = initial_temperature.parse().ok()?;
+                let cooling: f64 = cooling.parse().ok()?;
+                (initial_temperature > 0.0 && (0.0..=1.0).contains(&cooling)).then_some(
+                    Acceptance::SimulatedAnnealing {
+                        initial_temperature,
+                        cooling,
+                    },
+                )
+            }
+            Some(("rrt", deviation)) => {
+                let deviation: f64 = deviation.parse().ok()?;
+                (deviation >= 0.0).then_some(Acceptance::RecordToRecord { deviation })
+            }
+            Some(_) => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match *self {
+            Acceptance::BetterOnly => "better-only".to_string(),
+            Acceptance::Always => "always".to_string(),
+            Acceptance::SimulatedAnnealing {
+                initial_temperature,
+                cooling,
+            } => format!("SA(T0={}, cooling={})", initial_temperature, cooling),
+            Acceptance::RecordToRecord { deviation } => format!("RRT(deviation={})", deviation),
+        }
+    }
+}
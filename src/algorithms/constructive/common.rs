@@ -0,0 +1,93 @@
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered, Weight};
+
+/// Cost increase of inserting `vertex` on the edge `(prev, next)`.
+pub fn insertion_cost(prev: usize, vertex: usize, next: usize, instance: &TsplibInstance) -> Cost {
+    instance.distance(prev, vertex) + instance.distance(vertex, next)
+        - instance.distance(prev, next)
+}
+
+/// Cheapest position to insert `vertex` into `cycle`, as `(insert_pos, cost_delta)` where
+/// `insert_pos` is the index the vertex should occupy after `Vec::insert`.
+pub fn cheapest_insertion(
+    vertex: usize,
+    cycle: &[usize],
+    instance: &TsplibInstance,
+) -> (usize, Cost) {
+    let n = cycle.len();
+    if n == 0 {
+        return (0, Cost::ZERO);
+    }
+    (0..n)
+        .map(|i| {
+            let prev = cycle[i];
+            let next = cycle[(i + 1) % n];
+            (i + 1, insertion_cost(prev, vertex, next, instance))
+        })
+        .min_by_key(|&(_, cost)| Ordered(cost))
+        .expect("non-empty cycle has at least one edge")
+}
+
+/// Cost change of replacing the vertex at `pos` of `cycle` by `vertex`.
+fn replacement_cost(cycle: &[usize], pos: usize, vertex: usize, instance: &TsplibInstance) -> Cost {
+    let n = cycle.len();
+    if n < 2 {
+        return Cost::ZERO;
+    }
+    let prev = cycle[(pos + n - 1) % n];
+    let next = cycle[(pos + 1) % n];
+    let old = cycle[pos];
+    instance.distance(prev, vertex) + instance.distance(vertex, next)
+        - instance.distance(prev, old)
+        - instance.distance(old, next)
+}
+
+/// Moves every pinned vertex into its cycle by swapping it with the unpinned vertex of that
+/// cycle whose exchange is cheapest; cycle sizes do not change. A pinned vertex left out on a
+/// prize-collecting instance takes the place of the evicted vertex, which becomes unvisited.
+pub fn enforce_pins(solution: &mut Solution, instance: &TsplibInstance) {
+    if !instance.has_pins() {
+        return;
+    }
+    for vertex in 0..instance.size() {
+        let Some(target) = instance.pinned_cycle(vertex) else {
+            continue;
+        };
+        let current = solution.find_node(vertex);
+        if current.is_some_and(|(cycle, _)| cycle == target) {
+            continue;
+        }
+
+        let target_cycle = solution.get_cycle(target);
+        let best = (0..target_cycle.len())
+            .filter(|&pos| !instance.is_pinned(target_cycle[pos]))
+            .map(|pos| {
+                let evicted = target_cycle[pos];
+                let mut cost = replacement_cost(target_cycle, pos, vertex, instance);
+                if let Some((cycle, vertex_pos)) = current {
+                    let source = solution.get_cycle(cycle);
+                    cost += replacement_cost(source, vertex_pos, evicted, instance);
+                }
+                (pos, cost)
+            })
+            .min_by_key(|&(_, cost)| Ordered(cost));
+        let Some((pos, _)) = best else {
+            // Every vertex of the target cycle is pinned there; validation reports it.
+            continue;
+        };
+
+        let evicted = std::mem::replace(&mut solution.get_cycle_mut(target)[pos], vertex);
+        if let Some((cycle, vertex_pos)) = current {
+            solution.get_cycle_mut(cycle)[vertex_pos] = evicted;
+        }
+    }
+}
+
+/// Adapts a freshly built two-cycle solution to the instance: single-cycle instances get the
+/// cycles merged into one tour, then pinned vertices are moved into their cycles.
+pub fn finish_construction(solution: &mut Solution, instance: &TsplibInstance) {
+    if instance.is_single_cycle() {
+        solution.merge_cycles(instance);
+    }
+    enforce_pins(solution, instance);
+}
@@ -0,0 +1,174 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::StartStrategy;
+use crate::algorithms::constructive::common::{
+    cheapest_insertion, finish_construction, insertion_cost,
+};
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered};
+use rand::RngCore;
+
+/// Convex-hull insertion: the vertices are split between the two seeds (each vertex goes to
+/// the seed it is relatively closer to), every cycle starts as the convex hull of its part
+/// and the interior points are added by cheapest insertion.
+pub struct ConvexHullInsertion {
+    pub start_strategy: StartStrategy,
+}
+
+impl ConvexHullInsertion {
+    pub fn new(start_strategy: StartStrategy) -> Self {
+        Self { start_strategy }
+    }
+
+    /// Splits all vertices into two balanced groups around the seeds `s1` and `s2`.
+    fn partition(instance: &TsplibInstance, s1: usize, s2: usize) -> (Vec<usize>, Vec<usize>) {
+        let n = instance.size();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&v| Ordered(instance.distance(v, s1) - instance.distance(v, s2)));
+        let part2 = order.split_off(n.div_ceil(2));
+        (order, part2)
+    }
+
+    /// Convex hull of `vertices` in counter-clockwise order (Andrew's monotone chain).
+    /// Collinear points on the hull boundary are dropped.
+    fn convex_hull(instance: &TsplibInstance, vertices: &[usize]) -> Vec<usize> {
+        let point = |v: usize| instance.coordinates[v];
+        let mut sorted = vertices.to_vec();
+        sorted.sort_by(|&a, &b| point(a).partial_cmp(&point(b)).unwrap());
+        sorted.dedup_by(|a, b| point(*a) == point(*b));
+        if sorted.len() < 3 {
+            return sorted;
+        }
+
+        let cross = |o: usize, a: usize, b: usize| {
+            let (o, a, b) = (point(o), point(a), point(b));
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+
+        let mut hull: Vec<usize> = Vec::with_capacity(sorted.len() + 1);
+        // Lower chain left to right, then upper chain right to left.
+        for (pass, chain) in [
+            sorted.clone(),
+            sorted.iter().rev().skip(1).copied().collect(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let floor = if pass == 0 { 0 } else { hull.len() - 1 };
+            for v in chain {
+                while hull.len() >= floor + 2
+                    && cross(hull[hull.len() - 2], hull[hull.len() - 1], v) <= 0.0
+                {
+                    hull.pop();
+                }
+                hull.push(v);
+            }
+        }
+        // Each chain ends where the next one starts; the upper chain closes on the first point.
+        hull.pop();
+        hull
+    }
+
+    /// Grows `cycle` by cheapest insertion until all of `remaining` is inserted. Each pending
+    /// vertex caches its best edge, recomputed only when that edge is the one destroyed.
+    fn cheapest_insertion_fill(
+        cycle: &mut Vec<usize>,
+        mut remaining: Vec<usize>,
+        instance: &TsplibInstance,
+    ) {
+        if cycle.is_empty() {
+            match remaining.pop() {
+                Some(v) => cycle.push(v),
+                None => return,
+            }
+        }
+
+        // (cost, prev, next) of the best edge for each pending vertex, parallel to `remaining`.
+        let best_edge = |v: usize, cycle: &[usize]| {
+            let (pos, cost) = cheapest_insertion(v, cycle, instance);
+            (cost, cycle[pos - 1], cycle[pos % cycle.len()])
+        };
+        let mut best: Vec<(Cost, usize, usize)> =
+            remaining.iter().map(|&v| best_edge(v, cycle)).collect();
+
+        while !remaining.is_empty() {
+            let idx = (0..remaining.len())
+                .min_by_key(|&i| Ordered(best[i].0))
+                .expect("remaining is non-empty");
+            let vertex = remaining.swap_remove(idx);
+            let (_, prev, next) = best.swap_remove(idx);
+
+            let prev_pos = cycle
+                .iter()
+                .position(|&x| x == prev)
+                .expect("cached edge endpoint is in the cycle");
+            cycle.insert(prev_pos + 1, vertex);
+
+            for (i, &v) in remaining.iter().enumerate() {
+                if (best[i].1, best[i].2) == (prev, next) {
+                    best[i] = best_edge(v, cycle);
+                } else {
+                    for (a, b) in [(prev, vertex), (vertex, next)] {
+                        let cost = insertion_cost(a, v, b, instance);
+                        if cost < best[i].0 {
+                            best[i] = (cost, a, b);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for ConvexHullInsertion {
+    fn default() -> Self {
+        Self::new(StartStrategy::RandomNearAntipodal)
+    }
+}
+
+impl TspAlgorithm for ConvexHullInsertion {
+    fn name(&self) -> &str {
+        "Convex Hull Insertion"
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        let n = instance.size();
+        progress_callback(format!("[Init] Size: {}", n));
+
+        if n == 0 {
+            return Solution::new(vec![], vec![]);
+        }
+        if n == 1 {
+            return Solution::new(vec![0], vec![]);
+        }
+
+        let (start1, start2) = self.start_strategy.select(instance, rng);
+        progress_callback(format!("[Init] Start nodes: {}, {}", start1, start2));
+
+        let (part1, part2) = Self::partition(instance, start1, start2);
+        let mut cycles = Vec::with_capacity(2);
+        for (c, part) in [part1, part2].into_iter().enumerate() {
+            let mut cycle = Self::convex_hull(instance, &part);
+            progress_callback(format!(
+                "[C{}] Hull: {} of {} vertices",
+                c + 1,
+                cycle.len(),
+                part.len()
+            ));
+            let remaining: Vec<usize> = part.into_iter().filter(|v| !cycle.contains(v)).collect();
+            Self::cheapest_insertion_fill(&mut cycle, remaining, instance);
+            cycles.push(cycle);
+        }
+
+        progress_callback("[Finished]".to_string());
+        let cycle2 = cycles.pop().unwrap();
+        let cycle1 = cycles.pop().unwrap();
+        let mut solution = Solution::new(cycle1, cycle2);
+        finish_construction(&mut solution, instance);
+        solution
+    }
+}
@@ -0,0 +1,154 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::common::finish_construction;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered};
+use rand::RngCore;
+
+const NONE: usize = usize::MAX;
+
+/// Greedy-edge construction: all edges are scanned from the shortest and an edge is kept
+/// unless it would give a vertex degree 3 or close a premature subcycle. The resulting path
+/// fragments are chained into one tour (nearest free endpoint first), which is then cut into
+/// two balanced arcs at the cheapest split and each arc is closed into a cycle.
+///
+/// Sorting every pair costs O(n^2 log n) time and O(n^2) memory, so this is meant for the
+/// TSPLIB-sized instances rather than huge generated ones.
+pub struct GreedyEdge;
+
+impl GreedyEdge {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn find(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v {
+            parent[v] = parent[parent[v]];
+            v = parent[v];
+        }
+        v
+    }
+
+    /// Greedy matching phase: adjacency lists of the path fragments (`NONE` marks a free slot).
+    fn build_fragments(instance: &TsplibInstance) -> Vec<[usize; 2]> {
+        let n = instance.size();
+        let mut edges: Vec<(Ordered<Cost>, u32, u32)> =
+            Vec::with_capacity(n * n.saturating_sub(1) / 2);
+        for i in 0..n {
+            for j in i + 1..n {
+                edges.push((Ordered(instance.distance(i, j)), i as u32, j as u32));
+            }
+        }
+        edges.sort_unstable();
+
+        let mut adj = vec![[NONE; 2]; n];
+        let mut parent: Vec<usize> = (0..n).collect();
+        let mut added = 0;
+        for (_, a, b) in edges {
+            if added + 1 >= n {
+                break;
+            }
+            let (a, b) = (a as usize, b as usize);
+            if adj[a][1] != NONE || adj[b][1] != NONE {
+                continue;
+            }
+            let (ra, rb) = (Self::find(&mut parent, a), Self::find(&mut parent, b));
+            if ra == rb {
+                continue;
+            }
+            parent[ra] = rb;
+            let slot_a = usize::from(adj[a][0] != NONE);
+            let slot_b = usize::from(adj[b][0] != NONE);
+            adj[a][slot_a] = b;
+            adj[b][slot_b] = a;
+            added += 1;
+        }
+        adj
+    }
+
+    /// Chains the fragments into a single vertex order, always continuing with the fragment
+    /// whose free endpoint is nearest to the current path end.
+    fn chain_fragments(adj: &[[usize; 2]], instance: &TsplibInstance) -> Vec<usize> {
+        let n = adj.len();
+        let mut endpoints: Vec<usize> = (0..n).filter(|&v| adj[v][1] == NONE).collect();
+        let mut visited = vec![false; n];
+        let mut tour = Vec::with_capacity(n);
+
+        let mut start = endpoints.first().copied();
+        while let Some(mut v) = start {
+            let mut prev = NONE;
+            loop {
+                visited[v] = true;
+                tour.push(v);
+                let next = adj[v].iter().copied().find(|&u| u != NONE && u != prev);
+                match next {
+                    Some(u) if !visited[u] => {
+                        prev = v;
+                        v = u;
+                    }
+                    _ => break,
+                }
+            }
+            endpoints.retain(|&e| !visited[e]);
+            start = endpoints
+                .iter()
+                .copied()
+                .min_by_key(|&e| Ordered(instance.distance(v, e)));
+        }
+        tour
+    }
+
+    /// Cuts `tour` into a contiguous arc of `ceil(n/2)` vertices and the rest, choosing the
+    /// rotation with the lowest total cost of the two closed cycles.
+    fn split_tour(tour: &[usize], instance: &TsplibInstance) -> (Vec<usize>, Vec<usize>) {
+        let n = tour.len();
+        let h = n.div_ceil(2);
+        let at = |i: usize| tour[i % n];
+        let best_start = (0..n)
+            .min_by_key(|&i| {
+                let before = at(i + n - 1);
+                let (first, last, after) = (at(i), at(i + h - 1), at(i + h));
+                Ordered(
+                    instance.distance(last, first) + instance.distance(before, after)
+                        - instance.distance(before, first)
+                        - instance.distance(last, after),
+                )
+            })
+            .unwrap_or(0);
+
+        let rotated: Vec<usize> = (0..n).map(|k| at(best_start + k)).collect();
+        let (cycle1, cycle2) = rotated.split_at(h);
+        (cycle1.to_vec(), cycle2.to_vec())
+    }
+}
+
+impl Default for GreedyEdge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TspAlgorithm for GreedyEdge {
+    fn name(&self) -> &str {
+        "Greedy Edge"
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        _rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        let n = instance.size();
+        progress_callback(format!("[Init] Size: {}", n));
+
+        let adj = Self::build_fragments(instance);
+        progress_callback("[Fragments] Greedy matching done".to_string());
+        let tour = Self::chain_fragments(&adj, instance);
+        let (cycle1, cycle2) = Self::split_tour(&tour, instance);
+
+        progress_callback("[Finished]".to_string());
+        let mut solution = Solution::new(cycle1, cycle2);
+        finish_construction(&mut solution, instance);
+        solution
+    }
+}
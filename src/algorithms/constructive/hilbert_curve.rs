@@ -0,0 +1,103 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::common::finish_construction;
+use crate::tsplib::{Solution, TsplibInstance};
+use rand::RngCore;
+
+/// Space-filling-curve construction: vertices are sorted by their position along a Hilbert
+/// curve laid over the bounding box, the order is cut in half and each half closed into a
+/// cycle. Runs in O(n log n) and never looks at the distance matrix.
+pub struct HilbertCurve {
+    /// Curve resolution: the bounding box is discretised into a `2^order x 2^order` grid.
+    pub order: u32,
+}
+
+impl HilbertCurve {
+    pub fn new(order: u32) -> Self {
+        assert!(
+            (1..=31).contains(&order),
+            "Hilbert curve order must be in 1..=31"
+        );
+        Self { order }
+    }
+
+    /// Distance along the Hilbert curve of cell `(x, y)` in a `2^order` grid.
+    fn hilbert_index(order: u32, mut x: u64, mut y: u64) -> u64 {
+        let side = 1u64 << order;
+        let mut d = 0;
+        let mut s = side / 2;
+        while s > 0 {
+            let rx = u64::from(x & s > 0);
+            let ry = u64::from(y & s > 0);
+            d += s * s * ((3 * rx) ^ ry);
+            // Rotate the quadrant so the sub-curve has the canonical orientation.
+            if ry == 0 {
+                if rx == 1 {
+                    x = side - 1 - x;
+                    y = side - 1 - y;
+                }
+                std::mem::swap(&mut x, &mut y);
+            }
+            s /= 2;
+        }
+        d
+    }
+
+    /// All vertices ordered along the curve.
+    fn curve_order(&self, instance: &TsplibInstance) -> Vec<usize> {
+        let coords = &instance.coordinates;
+        let (min_x, max_x, min_y, max_y) = coords.iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(a, b, c, d), &(x, y)| (a.min(x), b.max(x), c.min(y), d.max(y)),
+        );
+        // A single scale for both axes keeps the curve's locality isotropic.
+        let extent = (max_x - min_x).max(max_y - min_y).max(f64::MIN_POSITIVE);
+        let cells = ((1u64 << self.order) - 1) as f64;
+
+        let mut keyed: Vec<(u64, usize)> = coords
+            .iter()
+            .enumerate()
+            .map(|(v, &(x, y))| {
+                let gx = ((x - min_x) / extent * cells).round() as u64;
+                let gy = ((y - min_y) / extent * cells).round() as u64;
+                (Self::hilbert_index(self.order, gx, gy), v)
+            })
+            .collect();
+        keyed.sort_unstable();
+        keyed.into_iter().map(|(_, v)| v).collect()
+    }
+}
+
+impl Default for HilbertCurve {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl TspAlgorithm for HilbertCurve {
+    fn name(&self) -> &str {
+        "Hilbert Curve"
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        _rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        let n = instance.size();
+        progress_callback(format!("[Init] Size: {}", n));
+
+        let mut cycle1 = self.curve_order(instance);
+        let cycle2 = cycle1.split_off(n.div_ceil(2));
+
+        progress_callback("[Finished]".to_string());
+        let mut solution = Solution::new(cycle1, cycle2);
+        finish_construction(&mut solution, instance);
+        solution
+    }
+}
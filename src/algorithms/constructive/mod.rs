@@ -1 +1,10 @@
+pub mod common;
+pub mod convex_hull_insertion;
+pub mod greedy_edge;
+pub mod hilbert_curve;
+pub mod nearest_neighbor_insertion;
+pub mod prize_collecting_insertion;
+pub mod start;
 pub mod weighted_regret_cycle;
+
+pub use start::StartStrategy;
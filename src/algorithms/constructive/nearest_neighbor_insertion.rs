@@ -0,0 +1,109 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::StartStrategy;
+use crate::algorithms::constructive::common::{cheapest_insertion, finish_construction};
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered};
+use rand::RngCore;
+
+/// Nearest-neighbour construction with insertion anywhere: the cycles grow alternately, each
+/// step taking the available vertex closest to any vertex of the current cycle and inserting
+/// it at its cheapest position (instead of appending it after the last vertex).
+pub struct NearestNeighborInsertion {
+    pub start_strategy: StartStrategy,
+}
+
+impl NearestNeighborInsertion {
+    pub fn new(start_strategy: StartStrategy) -> Self {
+        Self { start_strategy }
+    }
+
+    /// Lowers each available vertex's distance-to-cycle after `added` joined that cycle.
+    fn update_nearest(
+        nearest: &mut [Cost],
+        available: &[usize],
+        added: usize,
+        instance: &TsplibInstance,
+    ) {
+        for &v in available {
+            nearest[v] = nearest[v].min(instance.distance(added, v));
+        }
+    }
+}
+
+impl Default for NearestNeighborInsertion {
+    fn default() -> Self {
+        Self::new(StartStrategy::RandomNearAntipodal)
+    }
+}
+
+impl TspAlgorithm for NearestNeighborInsertion {
+    fn name(&self) -> &str {
+        "Nearest Neighbor (Insert Anywhere)"
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        let n = instance.size();
+        progress_callback(format!("[Init] Size: {}", n));
+
+        if n == 0 {
+            return Solution::new(vec![], vec![]);
+        }
+        if n == 1 {
+            return Solution::new(vec![0], vec![]);
+        }
+
+        let (start1, start2) = self.start_strategy.select(instance, rng);
+        progress_callback(format!("[Init] Start nodes: {}, {}", start1, start2));
+
+        let mut cycles = [vec![start1], vec![start2]];
+        let target1 = n.div_ceil(2);
+        let mut available: Vec<usize> = (0..n).filter(|&x| x != start1 && x != start2).collect();
+
+        // Distance from every vertex to the closest vertex already in each cycle.
+        let mut nearest = [vec![Cost::MAX; n], vec![Cost::MAX; n]];
+        Self::update_nearest(&mut nearest[0], &available, start1, instance);
+        Self::update_nearest(&mut nearest[1], &available, start2, instance);
+
+        let total = available.len();
+        let mut current = 0;
+        while !available.is_empty() {
+            // Keep the cycle sizes balanced: once one cycle is full, the other takes the rest.
+            if cycles[0].len() >= target1 {
+                current = 1;
+            } else if cycles[1].len() >= n - target1 {
+                current = 0;
+            }
+
+            let idx = available
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, &v)| Ordered(nearest[current][v]))
+                .map(|(idx, _)| idx)
+                .expect("available is non-empty");
+            let vertex = available.swap_remove(idx);
+
+            let (pos, _) = cheapest_insertion(vertex, &cycles[current], instance);
+            cycles[current].insert(pos, vertex);
+            Self::update_nearest(&mut nearest[current], &available, vertex, instance);
+
+            progress_callback(format!(
+                "[{}% C{}] Avail: {}",
+                (total - available.len()) * 100 / total.max(1),
+                current + 1,
+                available.len()
+            ));
+            current = 1 - current;
+        }
+
+        progress_callback("[Finished]".to_string());
+        let [cycle1, cycle2] = cycles;
+        let mut solution = Solution::new(cycle1, cycle2);
+        finish_construction(&mut solution, instance);
+        solution
+    }
+}
@@ -0,0 +1,55 @@
+use crate::tsplib::TsplibInstance;
+use crate::weight::Ordered;
+use rand::Rng;
+
+/// How a constructive heuristic picks the two seed vertices of its cycles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartStrategy {
+    /// The globally most distant pair of vertices (deterministic).
+    MaxDistancePair,
+    /// Two distinct vertices drawn uniformly at random.
+    RandomPair,
+    /// A random first vertex and a random second one among the vertices farthest from it.
+    RandomNearAntipodal,
+}
+
+impl StartStrategy {
+    /// Fraction of vertices (farthest from the first seed) eligible as the second seed in
+    /// `RandomNearAntipodal`.
+    const ANTIPODAL_FRACTION: f64 = 0.05;
+
+    /// Returns two distinct seed vertices. Requires `instance.size() >= 2`.
+    pub fn select<R: Rng + ?Sized>(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut R,
+    ) -> (usize, usize) {
+        let n = instance.size();
+        assert!(
+            n >= 2,
+            "At least two vertices are needed to seed two cycles"
+        );
+
+        match *self {
+            StartStrategy::MaxDistancePair => (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .max_by_key(|&(i, j)| Ordered(instance.distance(i, j)))
+                .expect("n >= 2 guarantees at least one pair"),
+            StartStrategy::RandomPair => {
+                let first = rng.random_range(0..n);
+                let second = (first + rng.random_range(1..n)) % n;
+                (first, second)
+            }
+            StartStrategy::RandomNearAntipodal => {
+                let first = rng.random_range(0..n);
+                let mut others: Vec<usize> = (0..n).filter(|&j| j != first).collect();
+                let pool = ((others.len() as f64 * Self::ANTIPODAL_FRACTION).ceil() as usize)
+                    .clamp(1, others.len());
+                others.select_nth_unstable_by_key(pool - 1, |&j| {
+                    std::cmp::Reverse(Ordered(instance.distance(first, j)))
+                });
+                (first, others[rng.random_range(0..pool)])
+            }
+        }
+    }
+}
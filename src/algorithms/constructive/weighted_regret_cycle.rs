@@ -1,118 +1,251 @@
 use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::StartStrategy;
+use crate::algorithms::constructive::common::{finish_construction, insertion_cost};
 use crate::tsplib::{Solution, TsplibInstance};
-use rand::Rng;
-use rand::thread_rng;
+use crate::weight::{Cost, Ordered, Weight};
+use rand::RngCore;
+use rayon::prelude::*;
+
+/// Smallest number of insertion caches refreshed as one parallel task.
+const MIN_CACHES_PER_TASK: usize = 16;
 
 pub struct WeightedRegretCycle {
     pub k_regret: usize,
     pub regret_weight: f64,
     pub greedy_weight: f64,
+    pub start_strategy: StartStrategy,
+    name_str: String,
 }
 
 impl WeightedRegretCycle {
-    pub fn new(regret_weight: f64, greedy_weight: f64) -> Self {
-        Self {
-            k_regret: 2,
+    /// `k_regret` is the number of cheapest insertion positions the regret looks at
+    /// (2 gives the classic 2-regret).
+    pub fn new(k_regret: usize, regret_weight: f64, greedy_weight: f64) -> Self {
+        assert!(k_regret >= 1, "k-regret needs k >= 1");
+        let mut heuristic = Self {
+            k_regret,
             regret_weight,
             greedy_weight,
+            start_strategy: StartStrategy::RandomNearAntipodal,
+            name_str: String::new(),
+        };
+        heuristic.name_str = heuristic.build_name();
+        heuristic
+    }
+
+    pub fn with_start_strategy(mut self, start_strategy: StartStrategy) -> Self {
+        self.start_strategy = start_strategy;
+        self.name_str = self.build_name();
+        self
+    }
+
+    fn build_name(&self) -> String {
+        let mut name = if self.regret_weight == 1.0 && self.greedy_weight == 0.0 {
+            format!("{}-Regret Cycle", self.k_regret)
+        } else {
+            format!(
+                "Weighted {}-Regret Cycle (w_regret={}, w_greedy={})",
+                self.k_regret, self.regret_weight, self.greedy_weight
+            )
+        };
+        if self.start_strategy == StartStrategy::MaxDistancePair {
+            name += " from the most distant pair";
         }
+        name
     }
 
     pub fn default() -> Self {
-        Self::new(1.0, -1.0)
+        Self::new(2, 1.0, -1.0)
     }
 
+    /// Returns the index into `available` of the vertex nearest to `from`,
+    /// so the caller can `swap_remove` it in O(1).
     fn find_nearest(&self, from: usize, available: &[usize], instance: &TsplibInstance) -> usize {
         available
             .iter()
-            .min_by_key(|&&vertex| instance.distance(from, vertex))
-            .copied()
-            .unwrap_or(available[0])
+            .enumerate()
+            .min_by_key(|&(_, &vertex)| Ordered(instance.distance(from, vertex)))
+            .map_or(0, |(idx, _)| idx)
     }
 
-    fn calculate_insertion_cost(
+    /// Computes the `k_regret` cheapest insertion edges of `vertex` into `cycle` from scratch.
+    fn full_insertion_cache(
         &self,
         vertex: usize,
-        pos: usize,
         cycle: &[usize],
         instance: &TsplibInstance,
-    ) -> i32 {
-        if cycle.is_empty() {
-            return 0;
-        }
-        if cycle.len() == 1 {
-            return instance.distance(cycle[0], vertex) * 2;
-        }
-
-        let prev = cycle[if pos == 0 { cycle.len() - 1 } else { pos - 1 }];
-        let next = cycle[pos % cycle.len()];
+    ) -> InsertionCache {
+        let n = cycle.len();
+        let mut costs: Vec<InsertionEdge> = (0..n)
+            .map(|i| {
+                let prev = cycle[i];
+                let next = cycle[(i + 1) % n];
+                InsertionEdge {
+                    cost: insertion_cost(prev, vertex, next, instance),
+                    prev,
+                    next,
+                }
+            })
+            .collect();
 
-        instance.distance(prev, vertex) + instance.distance(vertex, next)
-            - instance.distance(prev, next)
+        // Only the k cheapest edges matter; partial selection avoids sorting the whole cycle.
+        let k = self.k_regret;
+        if costs.len() > k {
+            costs.select_nth_unstable_by_key(k - 1, |e| Ordered(e.cost));
+            costs.truncate(k);
+        }
+        costs.sort_unstable_by_key(|e| Ordered(e.cost));
+        InsertionCache { best: costs }
     }
 
-    fn calculate_weighted_score(
+    /// Refreshes a vertex's cache after `inserted` was placed between `prev` and `next`.
+    /// Only the two new edges are evaluated unless the destroyed edge was among the
+    /// cached best ones, in which case the cache is rebuilt from the cycle.
+    fn update_insertion_cache(
         &self,
+        cache: &mut InsertionCache,
         vertex: usize,
+        (prev, inserted, next): (usize, usize, usize),
         cycle: &[usize],
         instance: &TsplibInstance,
-    ) -> (f64, usize) {
-        if cycle.is_empty() {
-            return (0.0, 0);
+    ) {
+        if cache.best.iter().any(|e| e.prev == prev && e.next == next) {
+            *cache = self.full_insertion_cache(vertex, cycle, instance);
+            return;
         }
 
-        let mut costs: Vec<(usize, i32)> = (0..=cycle.len())
-            .map(|pos| {
-                (
-                    pos,
-                    self.calculate_insertion_cost(vertex, pos, cycle, instance),
-                )
-            })
-            .collect();
-
-        costs.sort_by_key(|&(_, cost)| cost);
-
-        let best_cost = costs[0].1;
-        let k_best_cost = costs
-            .get(self.k_regret - 1)
-            .map_or(best_cost, |&(_, cost)| cost);
-        let regret = k_best_cost - best_cost;
+        let limit = self.k_regret;
+        for (a, b) in [(prev, inserted), (inserted, next)] {
+            let edge = InsertionEdge {
+                cost: insertion_cost(a, vertex, b, instance),
+                prev: a,
+                next: b,
+            };
+            let at = cache.best.partition_point(|e| e.cost <= edge.cost);
+            if at < limit {
+                cache.best.insert(at, edge);
+                cache.best.truncate(limit);
+            }
+        }
+    }
 
-        let weighted_score =
-            self.regret_weight * regret as f64 + self.greedy_weight * best_cost as f64;
+    /// Weighted sum of the k-regret, `sum_{j=2..k} (c_j - c_1)`, and the cheapest insertion
+    /// cost `c_1`. When the cycle has fewer than k edges the regret sums over the available ones.
+    fn calculate_weighted_score(&self, cache: &InsertionCache) -> f64 {
+        let best_cost = cache.best[0].cost;
+        let regret: Cost = cache.best[1..].iter().map(|e| e.cost - best_cost).sum();
 
-        (weighted_score, costs[0].0)
+        self.regret_weight * regret.to_f64() + self.greedy_weight * best_cost.to_f64()
     }
 
+    /// Returns (index into `available`, cached best insertion edge) of the vertex with the
+    /// highest weighted score.
     fn select_best_vertex(
         &self,
-        cycle: &[usize],
         available: &[usize],
-        instance: &TsplibInstance,
-    ) -> Option<(usize, usize)> {
-        if available.is_empty() {
-            return None;
-        }
-
+        caches: &[InsertionCache],
+    ) -> Option<(usize, InsertionEdge)> {
         available
             .iter()
-            .map(|&vertex| {
-                let (score, pos) = self.calculate_weighted_score(vertex, cycle, instance);
-                (vertex, pos, score)
+            .enumerate()
+            .map(|(idx, &vertex)| {
+                let cache = &caches[vertex];
+                (idx, cache.best[0], self.calculate_weighted_score(cache))
             })
             .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(v, p, _)| (v, p))
+            .map(|(idx, edge, _)| (idx, edge))
+    }
+
+    /// Inserts the selected vertex into `cycle` on its best edge and refreshes the
+    /// insertion caches of all vertices still available for that cycle.
+    fn insert_and_update(
+        &self,
+        cycle: &mut Vec<usize>,
+        caches: &mut [InsertionCache],
+        available: &mut Vec<usize>,
+        instance: &TsplibInstance,
+    ) {
+        let Some((best_idx, edge)) = self.select_best_vertex(available, caches) else {
+            return;
+        };
+        let best_vertex = available.swap_remove(best_idx);
+        let prev_pos = cycle
+            .iter()
+            .position(|&v| v == edge.prev)
+            .expect("Cached insertion edge must belong to the cycle");
+        cycle.insert(prev_pos + 1, best_vertex);
+
+        let waiting = availability_mask(available, caches.len());
+        let cycle = &*cycle;
+        caches
+            .par_iter_mut()
+            .enumerate()
+            .with_min_len(MIN_CACHES_PER_TASK)
+            .filter(|&(vertex, _)| waiting[vertex])
+            .for_each(|(vertex, cache)| {
+                self.update_insertion_cache(
+                    cache,
+                    vertex,
+                    (edge.prev, best_vertex, edge.next),
+                    cycle,
+                    instance,
+                );
+            });
+    }
+
+    /// Insertion caches of the `waiting` vertices into `cycle`, indexed by vertex.
+    fn initial_insertion_caches(
+        &self,
+        waiting: &[bool],
+        cycle: &[usize],
+        instance: &TsplibInstance,
+    ) -> Vec<InsertionCache> {
+        (0..waiting.len())
+            .into_par_iter()
+            .with_min_len(MIN_CACHES_PER_TASK)
+            .map(|vertex| {
+                if waiting[vertex] {
+                    self.full_insertion_cache(vertex, cycle, instance)
+                } else {
+                    InsertionCache::default()
+                }
+            })
+            .collect()
     }
 }
 
+/// `mask[v]` is whether `v` is in `available`.
+fn availability_mask(available: &[usize], n: usize) -> Vec<bool> {
+    let mut mask = vec![false; n];
+    for &vertex in available {
+        mask[vertex] = true;
+    }
+    mask
+}
+
+/// A candidate insertion of a vertex on the cycle edge `(prev, next)`.
+#[derive(Debug, Clone, Copy)]
+struct InsertionEdge {
+    cost: Cost,
+    prev: usize,
+    next: usize,
+}
+
+/// The cheapest insertion edges of one vertex into one cycle, sorted by cost.
+#[derive(Debug, Clone, Default)]
+struct InsertionCache {
+    best: Vec<InsertionEdge>,
+}
+
 impl TspAlgorithm for WeightedRegretCycle {
     fn name(&self) -> &str {
-        "Weighted 2-Regret Cycle"
+        &self.name_str
     }
 
     fn solve_with_feedback(
         &self,
         instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
     ) -> Solution {
         let n = instance.size();
@@ -125,42 +258,39 @@ impl TspAlgorithm for WeightedRegretCycle {
             return Solution::new(vec![0], vec![]);
         }
 
-        let mut rng = thread_rng();
-        let start1 = rng.gen_range(0..n);
-
-        let start2 = (0..n)
-            .filter(|&j| j != start1)
-            .max_by_key(|&j| instance.distance(start1, j))
-            .expect("Should find a furthest node if n >= 2");
+        let (start1, start2) = self.start_strategy.select(instance, rng);
 
         let mut cycle1 = vec![start1];
         let mut cycle2 = vec![start2];
         let mut available: Vec<usize> = (0..n).filter(|&x| x != start1 && x != start2).collect();
-        let initial_available_count = available.len();
+        let _initial_available_count = available.len();
 
         progress_callback(format!("[Init] Start nodes: {}, {}", start1, start2));
 
         if !available.is_empty() {
-            let nearest1 = self.find_nearest(start1, &available, instance);
+            let nearest1 = available.swap_remove(self.find_nearest(start1, &available, instance));
             cycle1.push(nearest1);
-            available.retain(|&x| x != nearest1);
             progress_callback(format!("[Init Cycle 1] Added {}", nearest1));
 
             if !available.is_empty() {
-                let nearest2 = self.find_nearest(start2, &available, instance);
+                let nearest2 =
+                    available.swap_remove(self.find_nearest(start2, &available, instance));
                 cycle2.push(nearest2);
-                available.retain(|&x| x != nearest2);
                 progress_callback(format!("[Init Cycle 2] Added {}", nearest2));
             }
         }
 
+        let waiting = availability_mask(&available, n);
+        let mut caches1 = self.initial_insertion_caches(&waiting, &cycle1, instance);
+        let mut caches2 = self.initial_insertion_caches(&waiting, &cycle2, instance);
+
         let mut current_cycle_id = 1;
         let total_iterations = available.len();
         let mut iterations_done = 0;
 
         while !available.is_empty() {
             iterations_done += 1;
-            let progress_percent = (iterations_done * 100 / total_iterations.max(1));
+            let progress_percent = iterations_done * 100 / total_iterations.max(1);
 
             if current_cycle_id == 1 {
                 progress_callback(format!(
@@ -168,12 +298,7 @@ impl TspAlgorithm for WeightedRegretCycle {
                     progress_percent,
                     available.len()
                 ));
-                if let Some((best_vertex, best_pos)) =
-                    self.select_best_vertex(&cycle1, &available, instance)
-                {
-                    cycle1.insert(best_pos, best_vertex);
-                    available.retain(|&x| x != best_vertex);
-                }
+                self.insert_and_update(&mut cycle1, &mut caches1, &mut available, instance);
                 current_cycle_id = 2;
             } else {
                 progress_callback(format!(
@@ -181,16 +306,13 @@ impl TspAlgorithm for WeightedRegretCycle {
                     progress_percent,
                     available.len()
                 ));
-                if let Some((best_vertex, best_pos)) =
-                    self.select_best_vertex(&cycle2, &available, instance)
-                {
-                    cycle2.insert(best_pos, best_vertex);
-                    available.retain(|&x| x != best_vertex);
-                }
+                self.insert_and_update(&mut cycle2, &mut caches2, &mut available, instance);
                 current_cycle_id = 1;
             }
         }
         progress_callback("[Finished]".to_string());
-        Solution::new(cycle1, cycle2)
+        let mut solution = Solution::new(cycle1, cycle2);
+        finish_construction(&mut solution, instance);
+        solution
     }
 }
@@ -0,0 +1,345 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::moves::types::CycleId;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered, Weight};
+use rand::RngCore;
+
+/// Largest instance the exact solver accepts; the DP table holds `2^n * n` entries.
+pub const MAX_EXACT_VERTICES: usize = 18;
+
+const UNREACHED: Cost = Cost::MAX;
+
+/// Exact solver for tiny instances. A Held–Karp table gives the optimal tour over every
+/// vertex subset, and the best split into cycle 1 (`ceil(n/2)` vertices, respecting pins)
+/// and cycle 2 is found by enumerating the subsets of that size. In single-cycle mode the
+/// tour over all vertices is returned. Prize-collecting instances are not supported.
+#[derive(Debug, Clone, Default)]
+pub struct HeldKarp;
+
+impl HeldKarp {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// `dp[mask * n + j]`: shortest path that starts at the lowest vertex of `mask`, visits all
+/// of `mask` and ends at `j`.
+struct PathTable<'a> {
+    instance: &'a TsplibInstance,
+    n: usize,
+    dp: Vec<Cost>,
+}
+
+impl<'a> PathTable<'a> {
+    fn build(instance: &'a TsplibInstance) -> Self {
+        let n = instance.size();
+        let mut dp = vec![UNREACHED; (1usize << n) * n];
+        for root in 0..n {
+            dp[(1 << root) * n + root] = Cost::ZERO;
+        }
+        for mask in 1usize..(1 << n) {
+            let root = mask.trailing_zeros() as usize;
+            for j in (0..n).filter(|&j| mask & (1 << j) != 0) {
+                let cost = dp[mask * n + j];
+                if cost == UNREACHED {
+                    continue;
+                }
+                // Only vertices above the root extend the path, so each subset keeps one root.
+                for k in (root + 1..n).filter(|&k| mask & (1 << k) == 0) {
+                    let slot = &mut dp[(mask | 1 << k) * n + k];
+                    *slot = (*slot).min(cost + instance.distance(j, k));
+                }
+            }
+        }
+        Self { instance, n, dp }
+    }
+
+    /// Length of the optimal closed tour over `mask` and the vertex it ends at before
+    /// returning to the root. A single vertex costs 0.
+    fn tour(&self, mask: usize) -> (Cost, usize) {
+        let root = mask.trailing_zeros() as usize;
+        if mask == 1 << root {
+            return (Cost::ZERO, root);
+        }
+        (0..self.n)
+            .filter(|&j| j != root && mask & (1 << j) != 0)
+            .map(|j| {
+                (
+                    self.dp[mask * self.n + j] + self.instance.distance(j, root),
+                    j,
+                )
+            })
+            .min_by_key(|&(cost, j)| (Ordered(cost), j))
+            .expect("a mask with two vertices has a non-root vertex")
+    }
+
+    /// Vertex order of the optimal tour over `mask`, starting at its root.
+    fn reconstruct(&self, mask: usize) -> Vec<usize> {
+        if mask == 0 {
+            return Vec::new();
+        }
+        let (_, mut j) = self.tour(mask);
+        let mut mask = mask;
+        let mut path = vec![j];
+        while mask.count_ones() > 1 {
+            let prev_mask = mask ^ (1 << j);
+            let cost = self.dp[mask * self.n + j];
+            let prev = (0..self.n)
+                .filter(|&k| prev_mask & (1 << k) != 0)
+                .find(|&k| {
+                    let prev_cost = self.dp[prev_mask * self.n + k];
+                    prev_cost != UNREACHED && prev_cost + self.instance.distance(k, j) == cost
+                })
+                .expect("every table entry has a predecessor");
+            path.push(prev);
+            mask = prev_mask;
+            j = prev;
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl TspAlgorithm for HeldKarp {
+    fn name(&self) -> &str {
+        "Held-Karp (exact)"
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        _rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        let n = instance.size();
+        assert!(
+            n <= MAX_EXACT_VERTICES,
+            "Held-Karp supports at most {} vertices, got {}",
+            MAX_EXACT_VERTICES,
+            n
+        );
+        assert!(
+            !instance.is_prize_collecting(),
+            "Held-Karp does not support prize-collecting instances"
+        );
+        progress_callback(format!("[Init] Size: {}", n));
+        if n == 0 {
+            return Solution::new(vec![], vec![]);
+        }
+
+        let table = PathTable::build(instance);
+        let full = (1usize << n) - 1;
+        if instance.is_single_cycle() {
+            progress_callback(format!("[Finished] Optimum: {}", table.tour(full).0));
+            return Solution::new(table.reconstruct(full), vec![]);
+        }
+
+        let mask_of = |cycle: CycleId| {
+            (0..n)
+                .filter(|&v| instance.pinned_cycle(v) == Some(cycle))
+                .fold(0usize, |mask, v| mask | 1 << v)
+        };
+        let (required, forbidden) = (mask_of(CycleId::Cycle1), mask_of(CycleId::Cycle2));
+        let size1 = n.div_ceil(2) as u32;
+        let (best_cost, best_mask) = (1usize..=full)
+            .filter(|&mask| {
+                mask.count_ones() == size1 && mask & required == required && mask & forbidden == 0
+            })
+            .map(|mask| {
+                let rest = full ^ mask;
+                let cost = table.tour(mask).0
+                    + if rest == 0 {
+                        Cost::ZERO
+                    } else {
+                        table.tour(rest).0
+                    };
+                (cost, mask)
+            })
+            .min_by_key(|&(cost, mask)| (Ordered(cost), mask))
+            .expect("pins leave at least one feasible split");
+
+        progress_callback(format!("[Finished] Optimum: {}", best_cost));
+        Solution::new(
+            table.reconstruct(best_mask),
+            table.reconstruct(full ^ best_mask),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::hae::{Hae, HaeParams, Replacement};
+    use crate::algorithms::ils::Ils;
+    use crate::algorithms::local_search::base::{
+        InitialSolutionType, LocalSearch, NeighborhoodType, SearchVariant,
+    };
+    use crate::algorithms::perturbation::SmallPerturbation;
+    use crate::budget::Budget;
+    use crate::seed::SeedStream;
+    use crate::tsplib::{EdgeWeightType, SolutionError};
+    use rand::Rng;
+    use thiserror::Error;
+
+    #[derive(Debug, Error, PartialEq)]
+    enum ExactCheckError {
+        #[error("Invalid solution: {0}")]
+        InvalidSolution(#[from] SolutionError),
+        #[error("Cost {cost} is below the exact optimum {optimum}")]
+        BelowOptimum { cost: Cost, optimum: Cost },
+    }
+
+    /// Uniformly random Euclidean instance with `n` vertices in a `side x side` square, for
+    /// checking heuristics against [`HeldKarp`].
+    fn random_instance<R: Rng + ?Sized>(n: usize, side: f64, rng: &mut R) -> TsplibInstance {
+        let coordinates = (0..n)
+            .map(|_| (rng.random_range(0.0..side), rng.random_range(0.0..side)))
+            .collect();
+        TsplibInstance::from_coordinates(format!("random{}", n), EdgeWeightType::Euc2D, coordinates)
+    }
+
+    /// Sanity check for cost and delta bookkeeping: runs `algorithm` `runs` times on `instance`,
+    /// drawing from `rng`, and fails if a solution is invalid or costs less than the exact
+    /// optimum. Returns the optimum.
+    fn check_against_optimum(
+        instance: &TsplibInstance,
+        algorithm: &dyn TspAlgorithm,
+        runs: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Cost, ExactCheckError> {
+        let mut quiet = |_: String| {};
+        let optimum = HeldKarp::new()
+            .solve_with_feedback(instance, rng, &mut quiet)
+            .calculate_cost(instance);
+        for _ in 0..runs {
+            let solution = algorithm.solve_with_feedback(instance, rng, &mut quiet);
+            solution.validate(instance, 0)?;
+            let cost = solution.calculate_cost(instance);
+            if (cost - optimum).is_improvement() {
+                return Err(ExactCheckError::BelowOptimum { cost, optimum });
+            }
+        }
+        Ok(optimum)
+    }
+
+    /// Random instances per size, and runs of every algorithm on each.
+    const CASES: u64 = 5;
+    const RUNS: usize = 3;
+
+    /// A metaheuristic stopped after it has built `max_solutions` solutions.
+    struct Bounded<F> {
+        max_solutions: u64,
+        solve: F,
+    }
+
+    impl<F: Fn(&TsplibInstance, &Budget, &mut dyn RngCore) -> Solution> TspAlgorithm for Bounded<F> {
+        fn name(&self) -> &str {
+            "bounded"
+        }
+
+        fn solve_with_feedback(
+            &self,
+            instance: &TsplibInstance,
+            rng: &mut dyn RngCore,
+            _: ProgressCallback,
+        ) -> Solution {
+            (self.solve)(
+                instance,
+                &Budget::unlimited().with_max_solutions(self.max_solutions),
+                rng,
+            )
+        }
+    }
+
+    fn local_searches() -> Vec<LocalSearch> {
+        let mut local_searches = Vec::new();
+        for neighborhood in [
+            NeighborhoodType::VertexExchange,
+            NeighborhoodType::EdgeExchange,
+        ] {
+            for variant in [
+                SearchVariant::Steepest,
+                SearchVariant::Greedy,
+                SearchVariant::CandidateSteepest(3),
+                SearchVariant::MoveListSteepest,
+                SearchVariant::SampledSteepest(0.5),
+            ] {
+                local_searches.push(LocalSearch::new(
+                    variant,
+                    neighborhood,
+                    InitialSolutionType::Random,
+                ));
+            }
+        }
+        local_searches
+    }
+
+    /// Runs `algorithm` on random instances of 4 to 10 vertices and checks it never beats
+    /// the exact optimum.
+    fn check_on_random_instances(name: &str, algorithm: &dyn TspAlgorithm) {
+        let stream = SeedStream::new(4389);
+        for n in 4..=10 {
+            for case in 0..CASES {
+                let mut rng = stream.child(n as u64 * CASES + case).rng();
+                let mut instance = random_instance(n, 1000.0, &mut rng);
+                instance.precompute_nearest_neighbors(3);
+                if let Err(e) = check_against_optimum(&instance, algorithm, RUNS, &mut rng) {
+                    panic!("{} on {} (case {}): {}", name, instance.name, case, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn local_search_never_beats_the_optimum() {
+        for local_search in local_searches() {
+            check_on_random_instances(local_search.name(), &local_search);
+        }
+    }
+
+    #[test]
+    fn ils_never_beats_the_optimum() {
+        for local_search in local_searches() {
+            let ils = Ils::new(local_search, Box::new(SmallPerturbation::new(3)));
+            let bounded = Bounded {
+                max_solutions: 20,
+                solve: |instance: &TsplibInstance, budget: &Budget, rng: &mut dyn RngCore| {
+                    ils.solve_traced(instance, budget, rng, &mut |_| {}).0
+                },
+            };
+            check_on_random_instances(ils.name(), &bounded);
+        }
+    }
+
+    #[test]
+    fn hae_never_beats_the_optimum() {
+        let local_search = LocalSearch::new(
+            SearchVariant::Steepest,
+            NeighborhoodType::EdgeExchange,
+            InitialSolutionType::Random,
+        );
+        for (with_local, replacement) in [
+            (true, Replacement::SteadyState),
+            (false, Replacement::SteadyState),
+            (true, Replacement::MuPlusLambda { children: 4 }),
+            (true, Replacement::Crowding),
+        ] {
+            let hae = Hae::from_params(
+                local_search.clone(),
+                HaeParams {
+                    pop_size: 4,
+                    with_local,
+                    replacement,
+                    ..HaeParams::default()
+                },
+            );
+            let bounded = Bounded {
+                max_solutions: 20,
+                solve: |instance: &TsplibInstance, budget: &Budget, rng: &mut dyn RngCore| {
+                    hae.solve_within(instance, budget, rng, &mut |_| {}).0
+                },
+            };
+            check_on_random_instances(hae.name(), &bounded);
+        }
+    }
+}
@@ -1,69 +1,429 @@
-use crate::algorithm::{ProgressCallback, TspAlgorithm};
-use crate::tsplib::{Solution, TsplibInstance, CycleId};
+use crate::algorithm::{IterationInfo, IterationUnit, ProgressCallback, TspAlgorithm};
 use crate::algorithms::local_search::base::LocalSearch;
+use crate::algorithms::population::PopulationTelemetry;
+use crate::analysis::common_edges;
+use crate::budget::Budget;
+use crate::convergence::ConvergenceTrace;
+use crate::elite::EliteArchive;
+use crate::pareto::ParetoArchive;
+use crate::progress::nested;
+use crate::seed::SeedStream;
+use crate::tsplib::{CycleId, Solution, SolutionIndex, TsplibInstance};
+use crate::weight::{Cost, Ordered, Weight};
 // use crate::utils::generate_random_solution; // unused
-use crate::algorithms::perturbation::repair;
-use rand::{Rng, thread_rng};
+use crate::algorithms::perturbation::{LargePerturbation, Perturbation, repair};
+use rand::{Rng, RngCore};
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+
+/// How HAE builds a child from two parents before repairing it with weighted regret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Crossover {
+    /// Parent 1 minus both endpoints of every edge parent 2 lacks, minus a random 20% of the
+    /// vertices for diversification.
+    #[default]
+    ForeignEdgeRemoval,
+    /// Exactly the edges common to both parents, kept as path fragments in parent 1's order;
+    /// every vertex on none of them is removed. Identical parents give an identical child.
+    CommonEdges,
+}
+
+impl Crossover {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Crossover::ForeignEdgeRemoval => "foreign-edge-removal",
+            Crossover::CommonEdges => "common-edges",
+        }
+    }
+}
+
+/// How HAE draws the two parents of a child.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectionStrategy {
+    /// Every member equally likely.
+    #[default]
+    Uniform,
+    /// Roulette wheel over the fitness `worst - cost + 1`, so better members are drawn more
+    /// often and the worst one still has a small chance.
+    FitnessProportional,
+    /// Members drawn with probability proportional to `exp(-(cost - best) / temperature)`,
+    /// with the temperature in cost units: high temperatures approach uniform selection,
+    /// low ones almost always pick the best members.
+    Boltzmann { temperature: f64 },
+}
+
+impl SelectionStrategy {
+    pub fn name(&self) -> String {
+        match *self {
+            SelectionStrategy::Uniform => "uniform".to_string(),
+            SelectionStrategy::FitnessProportional => "roulette".to_string(),
+            SelectionStrategy::Boltzmann { temperature } => {
+                format!("boltzmann(T={})", temperature)
+            }
+        }
+    }
+
+    /// Draws two distinct parents from `pop`, which holds at least two members.
+    fn select_parents<R: Rng + ?Sized>(
+        &self,
+        pop: &[(Solution, Cost)],
+        rng: &mut R,
+    ) -> (usize, usize) {
+        let best = pop
+            .iter()
+            .map(|&(_, cost)| cost)
+            .min_by_key(|&cost| Ordered(cost))
+            .unwrap_or(Cost::ZERO);
+        let worst = pop
+            .iter()
+            .map(|&(_, cost)| cost)
+            .max_by_key(|&cost| Ordered(cost))
+            .unwrap_or(Cost::ZERO);
+        let weights: Vec<f64> = match *self {
+            SelectionStrategy::Uniform => return select_parents(pop.len(), rng),
+            SelectionStrategy::FitnessProportional => pop
+                .iter()
+                .map(|&(_, cost)| (worst - cost).to_f64() + 1.0)
+                .collect(),
+            SelectionStrategy::Boltzmann { temperature } => pop
+                .iter()
+                .map(|&(_, cost)| (-(cost - best).to_f64() / temperature).exp())
+                .collect(),
+        };
+        let i1 = roulette(&weights, None, rng);
+        let i2 = roulette(&weights, Some(i1), rng);
+        (i1, i2)
+    }
+}
+
+/// LNS mutation of HAE children: with probability `probability`, a child is destroyed and
+/// repaired as by [`LargePerturbation`] with `destroy_fraction` before its local search.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mutation {
+    pub probability: f64,
+    pub destroy_fraction: f64,
+}
+
+/// How children enter the population. In every mode a child that exactly duplicates a member
+/// is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Replacement {
+    /// One child per iteration. It replaces the worst member if it is a new best, or if it is
+    /// better than the worst and not within `min_diff` of any member.
+    SteadyState,
+    /// (μ+λ): every generation builds `children` children in parallel, and the best
+    /// `pop_size` of parents and children survive, skipping solutions within `min_diff` of
+    /// a better survivor while enough others remain.
+    MuPlusLambda { children: usize },
+    /// Crowding: one child per iteration, which replaces the member sharing the most edges
+    /// with it if it is better than that member, so similar solutions compete with each
+    /// other instead of crowding out the rest.
+    Crowding,
+}
+
+/// Parameters of [`Hae`] apart from its base local search. Config files may leave out any
+/// of them, which then keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HaeParams {
+    pub pop_size: usize,
+    /// Children within this cost of a member count as duplicates.
+    pub min_diff: Cost,
+    /// Whether children are locally optimised.
+    pub with_local: bool,
+    pub replacement: Replacement,
+    pub crossover: Crossover,
+    pub selection: SelectionStrategy,
+    pub mutation: Option<Mutation>,
+}
+
+impl Default for HaeParams {
+    fn default() -> Self {
+        Self {
+            pop_size: 20,
+            min_diff: Cost::from_i32(40),
+            with_local: true,
+            replacement: Replacement::SteadyState,
+            crossover: Crossover::default(),
+            selection: SelectionStrategy::default(),
+            mutation: None,
+        }
+    }
+}
 
 pub struct Hae {
     base_local_search: LocalSearch,
-    pop_size: usize,
-    min_diff: i32,
-    with_local: bool,
+    params: HaeParams,
+    /// Mutation operator built from `params.mutation`.
+    mutation: Option<LargePerturbation>,
+    elites: Option<Arc<EliteArchive>>,
+    initial_solution: Option<Arc<Solution>>,
+    /// Whether every child is validated, and repaired if needed, right after recombination.
+    audit_children: bool,
     name_str: String,
 }
 
 impl Hae {
-    pub fn new(
-        base_local_search: LocalSearch,
-        pop_size: usize,
-        min_diff: i32,
-        with_local: bool,
-    ) -> Self {
-        let variant = if with_local { "HAE+LS" } else { "HAE" };
-        let name_str = format!(
-            "{} (Base: {}, pop={}, min_diff={})",
-            variant,
-            base_local_search.name(),
-            pop_size,
-            min_diff
-        );
-        Self {
-            base_local_search,
-            pop_size,
-            min_diff,
-            with_local,
-            name_str,
+    pub fn from_params(base_local_search: LocalSearch, params: HaeParams) -> Self {
+        assert!(params.pop_size >= 2, "HAE needs at least two parents");
+        if let Replacement::MuPlusLambda { children } = params.replacement {
+            assert!(children >= 1, "A generation needs at least one child");
         }
+        if let Some(mutation) = params.mutation {
+            assert!(
+                (0.0..=1.0).contains(&mutation.probability),
+                "Mutation probability must be between 0 and 1"
+            );
+        }
+        if let SelectionStrategy::Boltzmann { temperature } = params.selection {
+            assert!(
+                temperature > 0.0,
+                "Boltzmann selection needs a positive temperature"
+            );
+        }
+        let mut hae = Self {
+            base_local_search,
+            params,
+            mutation: params
+                .mutation
+                .map(|mutation| LargePerturbation::new(mutation.destroy_fraction)),
+            elites: None,
+            initial_solution: None,
+            audit_children: false,
+            name_str: String::new(),
+        };
+        hae.name_str = hae.build_name();
+        hae
+    }
+
+    fn build_name(&self) -> String {
+        let variant = if self.params.with_local {
+            "HAE+LS"
+        } else {
+            "HAE"
+        };
+        let crossover_info = if self.params.crossover == Crossover::default() {
+            String::new()
+        } else {
+            format!(", crossover={}", self.params.crossover.name())
+        };
+        let selection_info = if self.params.selection == SelectionStrategy::default() {
+            String::new()
+        } else {
+            format!(", selection={}", self.params.selection.name())
+        };
+        let mutation_info = match (&self.mutation, self.params.mutation) {
+            (Some(operator), Some(mutation)) => {
+                format!(", mutation={}@{}", operator.name(), mutation.probability)
+            }
+            _ => String::new(),
+        };
+        let replacement_info = match self.params.replacement {
+            Replacement::SteadyState => String::new(),
+            Replacement::Crowding => ", crowding".to_string(),
+            Replacement::MuPlusLambda { children } => format!(", (mu+lambda) lambda={}", children),
+        };
+        format!(
+            "{} (Base: {}, pop={}, min_diff={}{}{}{}{})",
+            variant,
+            self.base_local_search.name(),
+            self.params.pop_size,
+            self.params.min_diff,
+            replacement_info,
+            selection_info,
+            crossover_info,
+            mutation_info
+        )
+    }
+
+    /// Seeds up to half of every initial population with the best elites of `elites`
+    /// instead of fresh local optima, and offers the final best back to it.
+    pub fn with_elites(mut self, elites: Arc<EliteArchive>) -> Self {
+        self.elites = Some(elites);
+        self
+    }
+
+    /// Puts a locally optimised copy of `solution` into every initial population (ahead of
+    /// any elite), e.g. to keep improving a solution saved by an earlier run.
+    pub fn with_initial_solution(mut self, solution: Solution) -> Self {
+        self.initial_solution = Some(Arc::new(solution));
+        self
+    }
+
+    /// Validates every child after recombination and mutation and repairs whatever
+    /// [`Solution::validate`] rejects, logging the violations, instead of leaving an invalid
+    /// child for the check at the end of the run to fail on.
+    pub fn with_child_audit(mut self) -> Self {
+        self.audit_children = true;
+        self
     }
 
     pub fn name(&self) -> &str {
         &self.name_str
     }
 
-    pub fn solve_timed(
+    /// Runs until `budget` is exhausted, which also bounds the inner local search and repair
+    /// steps.
+    pub fn solve_within(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo) {
+        self.run(instance, budget, rng, progress_callback, None, None, None)
+    }
+
+    /// [`Self::solve_within`] that also returns the best cost over time.
+    pub fn solve_traced(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo, ConvergenceTrace) {
+        let mut trace = ConvergenceTrace::new();
+        let (solution, iterations) = self.run(
+            instance,
+            budget,
+            rng,
+            progress_callback,
+            None,
+            Some(&mut trace),
+            None,
+        );
+        (solution, iterations, trace)
+    }
+
+    /// [`Self::solve_within`] that also snapshots the population into `telemetry` and
+    /// reports each snapshot through the progress callback.
+    pub fn solve_with_telemetry(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        telemetry: &mut PopulationTelemetry,
+    ) -> (Solution, IterationInfo) {
+        self.run(
+            instance,
+            budget,
+            rng,
+            progress_callback,
+            None,
+            None,
+            Some(telemetry),
+        )
+    }
+
+    /// [`Self::solve_within`] that also offers the initial population and every child to
+    /// `archive`, collecting the cost/balance trade-offs visited along the way.
+    pub fn solve_with_archive(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        archive: &mut ParetoArchive,
+    ) -> (Solution, IterationInfo) {
+        self.run(
+            instance,
+            budget,
+            rng,
+            progress_callback,
+            Some(archive),
+            None,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run(
         &self,
         instance: &TsplibInstance,
-        time_limit: Duration,
-        mut progress_callback: ProgressCallback,
-    ) -> (Solution, usize) {
-        let mut rng = thread_rng();
-        let start_time = Instant::now();
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        mut archive: Option<&mut ParetoArchive>,
+        mut trace: Option<&mut ConvergenceTrace>,
+        mut telemetry: Option<&mut PopulationTelemetry>,
+    ) -> (Solution, IterationInfo) {
+        // Member `i` of the initial population starts from stream `i`.
+        let init_streams = SeedStream::from_rng(rng);
 
         // 1. Generate initial population
-        let mut pop: Vec<(Solution, i32)> = Vec::with_capacity(self.pop_size);
-        for i in 0..self.pop_size {
-            progress_callback(format!("[Init {}] Generating initial LS", i + 1));
-            let sol = self
-                .base_local_search
-                .solve_with_feedback(instance, &mut |s| {
-                    progress_callback(format!("[Init LS {}] {}", i + 1, s))
-                });
-            let cost = sol.calculate_cost(instance);
+        let mut pop: Vec<(Solution, Cost)> = Vec::with_capacity(self.params.pop_size);
+        let mut seeds = self
+            .elites
+            .as_ref()
+            .map_or(Vec::new(), |elites| elites.elites());
+        seeds.truncate(self.params.pop_size / 2);
+        let mut seeds = seeds.into_iter();
+        let mut initial_ls_runs = 0;
+        // On a timed budget the initial population gets at most half of the time, so some
+        // generations always run; later members then skip their local search.
+        let init_deadline = budget
+            .remaining()
+            .map(|remaining| budget.elapsed() + remaining / 2);
+        let init_time_left = || init_deadline.is_none_or(|deadline| budget.elapsed() < deadline);
+        for i in 0..self.params.pop_size {
+            let sol = if let Some(solution) = self.initial_solution.as_ref().filter(|_| i == 0) {
+                progress_callback(format!("[Init {}] Improving the given solution", i + 1));
+                let mut sol = Solution::clone(solution);
+                self.base_local_search
+                    .improve_within(instance, &mut sol, budget, rng, &mut |s| {
+                        progress_callback(nested(&s))
+                    });
+                initial_ls_runs += 1;
+                sol
+            } else if let Some(elite) = seeds.next() {
+                progress_callback(format!(
+                    "[Init {}] Seeded with an elite of {} (cost {})",
+                    i + 1,
+                    elite.source,
+                    elite.cost
+                ));
+                elite.solution
+            } else {
+                let mut member_rng = init_streams.child(i as u64).rng();
+                let mut sol = self
+                    .base_local_search
+                    .generate_initial_solution(instance, &mut member_rng);
+                if init_time_left() {
+                    progress_callback(format!("[Init {}] Generating initial LS", i + 1));
+                    self.base_local_search.improve_within(
+                        instance,
+                        &mut sol,
+                        budget,
+                        &mut member_rng,
+                        &mut |s| progress_callback(nested(&s)),
+                    );
+                    initial_ls_runs += 1;
+                } else {
+                    progress_callback(format!(
+                        "[Init {}] Out of initial population time, skipping the LS",
+                        i + 1
+                    ));
+                }
+                budget.count_solution();
+                sol
+            };
+            let cost = sol.calculate_objective(instance, self.base_local_search.objective());
+            if let Some(archive) = archive.as_deref_mut() {
+                archive.offer(&sol, instance);
+            }
             pop.push((sol, cost));
         }
+        let mut hashes = MemberHashes::default();
+        for (member, _) in &pop {
+            hashes.insert(member.canonical_hash());
+        }
 
         // Determine initial best
         let mut best_idx = 0;
@@ -75,107 +435,516 @@ impl Hae {
             }
         }
         let mut best_sol = pop[best_idx].0.clone();
+        let mut child = best_sol.clone();
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.record(budget.elapsed(), best_cost);
+        }
 
+        // Iterations count children, so both replacement modes report the same unit.
         let mut iterations = 0;
-        while start_time.elapsed() < time_limit {
+        let mut duplicates = 0;
+        while !budget.exhausted() {
+            if let Some(telemetry) = telemetry.as_deref_mut()
+                && telemetry.is_due(iterations)
+            {
+                progress_callback(
+                    telemetry
+                        .record(&pop, budget.elapsed(), iterations)
+                        .summary(),
+                );
+            }
+            if let Replacement::MuPlusLambda { children } = self.params.replacement {
+                let (built, accepted, rejected) = self.next_generation(
+                    children,
+                    &mut pop,
+                    instance,
+                    budget,
+                    rng,
+                    archive.as_deref_mut(),
+                );
+                iterations += built;
+                duplicates += rejected;
+                if let Some(telemetry) = telemetry.as_deref_mut() {
+                    telemetry.count_children(built, accepted);
+                    telemetry.count_duplicates(rejected);
+                }
+                // The population is sorted, so its first member is the generation's best.
+                if pop[0].1 < best_cost {
+                    best_sol.clone_from(&pop[0].0);
+                    best_cost = pop[0].1;
+                    if let Some(trace) = trace.as_deref_mut() {
+                        trace.record(budget.elapsed(), best_cost);
+                    }
+                    progress_callback(format!(
+                        "[Iter {}] New global best: {}",
+                        iterations, best_cost
+                    ));
+                }
+                continue;
+            }
             iterations += 1;
 
-            // Select two distinct parents uniformly
-            let i1 = rng.gen_range(0..self.pop_size);
-            let mut i2 = rng.gen_range(0..self.pop_size);
-            while i2 == i1 {
-                i2 = rng.gen_range(0..self.pop_size);
-            }
+            // Select two distinct parents
+            let (i1, i2) = self.params.selection.select_parents(&pop, rng);
             let parent1 = &pop[i1].0;
             let parent2 = &pop[i2].0;
 
-            // Recombination
-            let mut child = self.recombine(parent1, parent2, instance, &mut rng);
+            // Recombination into the reusable child buffer
+            self.recombine_into(parent1, parent2, instance, rng, &mut child, budget);
+            budget.count_solution();
 
             // Optional local search after recombination
-            if self.with_local {
-                child = self
-                    .base_local_search
-                    .solve_with_feedback(instance, &mut |s| {
-                        progress_callback(format!("[Iter {}] LS on child: {}", iterations, s))
-                    });
+            if self.params.with_local {
+                self.base_local_search.improve_within(
+                    instance,
+                    &mut child,
+                    budget,
+                    rng,
+                    &mut |s| progress_callback(nested(&s)),
+                );
             }
 
-            let child_cost = child.calculate_cost(instance);
+            let child_cost =
+                child.calculate_objective(instance, self.base_local_search.objective());
             progress_callback(format!("[Iter {}] Child cost: {}", iterations, child_cost));
+            if let Some(archive) = archive.as_deref_mut() {
+                archive.offer(&child, instance);
+            }
+
+            // An exact copy of a member adds nothing to the population
+            let child_hash = child.canonical_hash();
+            if hashes.contains(child_hash) {
+                duplicates += 1;
+                if let Some(telemetry) = telemetry.as_deref_mut() {
+                    telemetry.count_children(1, 0);
+                    telemetry.count_duplicates(1);
+                }
+                continue;
+            }
 
-            // Check similarity
-            let too_similar = pop.iter().any(|(_, cost)| (child_cost - *cost).abs() < self.min_diff);
+            // Pick the member the child competes with
+            let (victim_idx, accepted) = if self.params.replacement == Replacement::Crowding {
+                let closest_idx = closest_member(&pop, &child);
+                (closest_idx, child_cost < pop[closest_idx].1)
+            } else {
+                // Check similarity
+                let too_similar = pop
+                    .iter()
+                    .any(|(_, cost)| (child_cost - *cost).abs() < self.params.min_diff);
 
-            // Find worst solution index
-            let mut worst_idx = 0;
-            let mut worst_cost = pop[0].1;
-            for (idx, (_, cost)) in pop.iter().enumerate().skip(1) {
-                if *cost > worst_cost {
-                    worst_idx = idx;
-                    worst_cost = *cost;
+                // Find worst solution index
+                let mut worst_idx = 0;
+                let mut worst_cost = pop[0].1;
+                for (idx, (_, cost)) in pop.iter().enumerate().skip(1) {
+                    if *cost > worst_cost {
+                        worst_idx = idx;
+                        worst_cost = *cost;
+                    }
                 }
+                let accepted = child_cost < best_cost || (child_cost < worst_cost && !too_similar);
+                (worst_idx, accepted)
+            };
+            if let Some(telemetry) = telemetry.as_deref_mut() {
+                telemetry.count_children(1, usize::from(accepted));
             }
 
             // Replacement
+            if !accepted {
+                continue;
+            }
             if child_cost < best_cost {
-                // replace worst
-                pop[worst_idx] = (child.clone(), child_cost);
+                best_sol.clone_from(&child);
                 best_cost = child_cost;
-                best_sol = child;
-                progress_callback(format!("[Iter {}] New global best: {}", iterations, best_cost));
-            } else if child_cost < worst_cost && !too_similar {
-                pop[worst_idx] = (child, child_cost);
-                progress_callback(format!("[Iter {}] Replaced worst: idx={}, cost={}", iterations, worst_idx, child_cost));
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record(budget.elapsed(), best_cost);
+                }
+                progress_callback(format!(
+                    "[Iter {}] New global best: {}",
+                    iterations, best_cost
+                ));
+            } else {
+                progress_callback(format!(
+                    "[Iter {}] Replaced member: idx={}, cost={}",
+                    iterations, victim_idx, child_cost
+                ));
             }
+            hashes.remove(pop[victim_idx].0.canonical_hash());
+            hashes.insert(child_hash);
+            // The evicted member's allocation becomes the next child buffer.
+            std::mem::swap(&mut pop[victim_idx].0, &mut child);
+            pop[victim_idx].1 = child_cost;
         }
 
-        (best_sol, iterations)
+        if let Some(trace) = trace {
+            trace.finish(budget.elapsed());
+        }
+        progress_callback(format!(
+            "[Iter {}] Rejected {} children as exact duplicates of a member",
+            iterations, duplicates
+        ));
+        if let Some(telemetry) = telemetry
+            && telemetry
+                .snapshots()
+                .last()
+                .is_none_or(|last| last.iteration < iterations)
+        {
+            progress_callback(
+                telemetry
+                    .record(&pop, budget.elapsed(), iterations)
+                    .summary(),
+            );
+        }
+        if let Some(elites) = &self.elites {
+            elites.offer(&best_sol, instance, &self.name_str);
+        }
+        let info = IterationInfo {
+            unit: IterationUnit::Children,
+            outer: iterations,
+            inner_ls_runs: initial_ls_runs
+                + if self.params.with_local {
+                    iterations
+                } else {
+                    0
+                },
+            evaluations: budget.evaluations_since_start(),
+        };
+        (best_sol, info)
     }
 
-    fn recombine<R: Rng + ?Sized>(
+    /// One (μ+λ) generation: builds `children` children in parallel, then keeps the best
+    /// diverse `pop_size` of `pop` and the children in `pop`, sorted by cost. Exact duplicates
+    /// of a better or equal member only survive to fill the population. Returns the number of
+    /// children built, the number that survived and the number that were duplicates.
+    fn next_generation(
         &self,
-        p1: &Solution,
-        p2: &Solution,
+        children: usize,
+        pop: &mut Vec<(Solution, Cost)>,
         instance: &TsplibInstance,
-        rng: &mut R,
-    ) -> Solution {
-        // Start from parent1
-        let mut child = p1.clone();
-        let mut destroyed: HashSet<usize> = HashSet::new();
-
-        // Remove edges not in parent2
-        for &cycle_id in &[CycleId::Cycle1, CycleId::Cycle2] {
-            let cycle = child.get_cycle(cycle_id);
-            let n = cycle.len();
-            for i in 0..n {
-                let a = cycle[i];
-                let b = cycle[(i + 1) % n];
-                if p2.has_edge(a, b).is_none() {
-                    destroyed.insert(a);
-                    destroyed.insert(b);
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        archive: Option<&mut ParetoArchive>,
+    ) -> (usize, usize, usize) {
+        // Parents are drawn up front and child `k` draws from stream `k`, so the threads share
+        // no RNG.
+        let streams = SeedStream::from_rng(rng);
+        let jobs: Vec<(usize, usize, SeedStream)> = (0..children)
+            .map(|k| {
+                let (i1, i2) = self.params.selection.select_parents(pop, rng);
+                (i1, i2, streams.child(k as u64))
+            })
+            .collect();
+        let parents = &*pop;
+        let offspring: Vec<(Solution, Cost)> = jobs
+            .into_par_iter()
+            .map(|(i1, i2, stream)| {
+                let mut child_rng = stream.rng();
+                let mut child = Solution::new(Vec::new(), Vec::new());
+                self.recombine_into(
+                    &parents[i1].0,
+                    &parents[i2].0,
+                    instance,
+                    &mut child_rng,
+                    &mut child,
+                    budget,
+                );
+                if self.params.with_local {
+                    self.base_local_search.improve_within(
+                        instance,
+                        &mut child,
+                        budget,
+                        &mut child_rng,
+                        &mut |_| {},
+                    );
                 }
+                let cost = child.calculate_objective(instance, self.base_local_search.objective());
+                (child, cost)
+            })
+            .collect();
+        let built = offspring.len();
+        for _ in 0..built {
+            budget.count_solution();
+        }
+        if let Some(archive) = archive {
+            for (child, _) in &offspring {
+                archive.offer(child, instance);
             }
         }
 
-        // Optional random deletion for diversification (20% probability)
-        for &node in child
-            .cycle1
+        // (solution, cost, is a child). Stable sort: on equal cost, parents rank first.
+        let mut pool: Vec<(Solution, Cost, bool)> = pop
+            .drain(..)
+            .map(|(solution, cost)| (solution, cost, false))
+            .chain(
+                offspring
+                    .into_iter()
+                    .map(|(solution, cost)| (solution, cost, true)),
+            )
+            .collect();
+        pool.sort_by_key(|&(_, cost, _)| Ordered(cost));
+        let mut survivors: Vec<(Solution, Cost, bool)> = Vec::with_capacity(self.params.pop_size);
+        let mut skipped = Vec::new();
+        let mut seen: FxHashSet<u64> = FxHashSet::default();
+        let mut copies = Vec::new();
+        for member in pool {
+            if !seen.insert(member.0.canonical_hash()) {
+                copies.push(member);
+                continue;
+            }
+            let too_similar = survivors
+                .iter()
+                .any(|(_, cost, _)| (member.1 - *cost).abs() < self.params.min_diff);
+            if survivors.len() < self.params.pop_size && !too_similar {
+                survivors.push(member);
+            } else {
+                skipped.push(member);
+            }
+        }
+        let rejected = copies.iter().filter(|&&(_, _, is_child)| is_child).count();
+        let missing = self.params.pop_size - survivors.len();
+        survivors.extend(skipped.into_iter().chain(copies).take(missing));
+        survivors.sort_by_key(|&(_, cost, _)| Ordered(cost));
+        let accepted = survivors
             .iter()
-            .chain(child.cycle2.iter())
+            .filter(|&&(_, _, is_child)| is_child)
+            .count();
+        pop.extend(
+            survivors
+                .into_iter()
+                .map(|(solution, cost, _)| (solution, cost)),
+        );
+        (built, accepted, rejected)
+    }
+
+    /// Builds the offspring of `p1` and `p2` into `child`, reusing its allocations.
+    fn recombine_into(
+        &self,
+        p1: &Solution,
+        p2: &Solution,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        child: &mut Solution,
+        budget: &Budget,
+    ) {
+        let destroyed = match self.params.crossover {
+            Crossover::ForeignEdgeRemoval => remove_foreign_edges(p1, p2, rng, child),
+            Crossover::CommonEdges => keep_common_edges(p1, p2, child),
+        };
+
+        // Repair using regret insertion
+        repair(child, instance, destroyed, budget, rng);
+
+        if let (Some(operator), Some(mutation)) = (&self.mutation, self.params.mutation)
+            && rng.random_bool(mutation.probability)
         {
-            if rng.gen_bool(0.2) {
-                destroyed.insert(node);
+            operator.perturb_within(child, instance, rng, budget);
+        }
+
+        if self.audit_children {
+            audit_child(
+                child,
+                instance,
+                self.base_local_search.size_slack(),
+                budget,
+                rng,
+            );
+        }
+    }
+}
+
+/// Logs the rule violations of `child` and repairs them: out-of-range and repeated vertices
+/// and pinned vertices outside their cycle are taken out, cycles beyond their balanced size
+/// are trimmed from the end, and [`repair`] reinserts the vertices left out.
+fn audit_child<R: Rng + ?Sized>(
+    child: &mut Solution,
+    instance: &TsplibInstance,
+    size_slack: usize,
+    budget: &Budget,
+    rng: &mut R,
+) {
+    let errors = child.validation_errors(instance, size_slack);
+    if errors.is_empty() {
+        return;
+    }
+    eprintln!(
+        "[WARN] Repairing an invalid HAE child: {}",
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    );
+
+    let n = instance.size();
+    let mut visited = vec![false; n];
+    for cycle_id in [CycleId::Cycle1, CycleId::Cycle2] {
+        child.get_cycle_mut(cycle_id).retain(|&v| {
+            let keep =
+                v < n && !visited[v] && instance.pinned_cycle(v).is_none_or(|pin| pin == cycle_id);
+            if keep {
+                visited[v] = true;
             }
+            keep
+        });
+    }
+    let (len1, len2) = (child.cycle1.len(), child.cycle2.len());
+    let targets = if instance.is_single_cycle() {
+        [n, 0]
+    } else if instance.is_prize_collecting() {
+        // Only the visited vertices need to split evenly.
+        [len2 + 1, len1]
+    } else {
+        [n.div_ceil(2), n - n.div_ceil(2)]
+    };
+    for (cycle_id, target) in [CycleId::Cycle1, CycleId::Cycle2].into_iter().zip(targets) {
+        let cycle = child.get_cycle_mut(cycle_id);
+        while cycle.len() > target {
+            let Some(pos) = cycle.iter().rposition(|&v| !instance.is_pinned(v)) else {
+                break;
+            };
+            visited[cycle.remove(pos)] = false;
         }
+    }
+    repair(
+        child,
+        instance,
+        (0..n).filter(|&v| !visited[v]).collect(),
+        budget,
+        rng,
+    );
 
-        // Remove destroyed nodes
-        child.cycle1.retain(|v| !destroyed.contains(v));
-        child.cycle2.retain(|v| !destroyed.contains(v));
+    if let Err(error) = child.validate(instance, size_slack) {
+        eprintln!("[WARN] HAE child still invalid after the repair: {}", error);
+    }
+}
 
-        // Repair using regret insertion
-        repair(&mut child, instance, destroyed);
+/// Canonical hashes of the population members, counted since the initial population may
+/// hold a solution twice, so a child duplicating a member is found in O(1).
+#[derive(Debug, Default)]
+struct MemberHashes(FxHashMap<u64, usize>);
+
+impl MemberHashes {
+    fn contains(&self, hash: u64) -> bool {
+        self.0.contains_key(&hash)
+    }
+
+    fn insert(&mut self, hash: u64) {
+        *self.0.entry(hash).or_insert(0) += 1;
+    }
 
-        child
+    fn remove(&mut self, hash: u64) {
+        if let Some(count) = self.0.get_mut(&hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.0.remove(&hash);
+            }
+        }
     }
-} 
\ No newline at end of file
+}
+
+/// Index of the member of `pop` sharing the most edges with `child`; the first on ties.
+fn closest_member(pop: &[(Solution, Cost)], child: &Solution) -> usize {
+    let mut closest_idx = 0;
+    let mut closest_similarity = f64::NEG_INFINITY;
+    for (idx, (member, _)) in pop.iter().enumerate() {
+        let similarity = common_edges(child, member);
+        if similarity > closest_similarity {
+            closest_idx = idx;
+            closest_similarity = similarity;
+        }
+    }
+    closest_idx
+}
+
+/// Two distinct population indices drawn uniformly.
+fn select_parents<R: Rng + ?Sized>(pop_size: usize, rng: &mut R) -> (usize, usize) {
+    let i1 = rng.random_range(0..pop_size);
+    let mut i2 = rng.random_range(0..pop_size);
+    while i2 == i1 {
+        i2 = rng.random_range(0..pop_size);
+    }
+    (i1, i2)
+}
+
+/// Index other than `excluded` drawn with probability proportional to `weights`, or
+/// uniformly when all those weights underflowed to 0.
+fn roulette<R: Rng + ?Sized>(weights: &[f64], excluded: Option<usize>, rng: &mut R) -> usize {
+    let candidates: Vec<usize> = (0..weights.len())
+        .filter(|&index| Some(index) != excluded)
+        .collect();
+    let total: f64 = candidates.iter().map(|&index| weights[index]).sum();
+    if total <= 0.0 {
+        return candidates[rng.random_range(0..candidates.len())];
+    }
+    let mut draw = rng.random_range(0.0..total);
+    for &index in &candidates {
+        if draw < weights[index] {
+            return index;
+        }
+        draw -= weights[index];
+    }
+    candidates[candidates.len() - 1]
+}
+
+/// [`Crossover::ForeignEdgeRemoval`] into `child`; returns the removed vertices.
+fn remove_foreign_edges<R: Rng + ?Sized>(
+    p1: &Solution,
+    p2: &Solution,
+    rng: &mut R,
+    child: &mut Solution,
+) -> HashSet<usize> {
+    // Start from parent1
+    child.clone_from(p1);
+    let mut destroyed: HashSet<usize> = HashSet::new();
+    let p2_index = SolutionIndex::new(p2);
+
+    // Remove edges not in parent2
+    for &cycle_id in &[CycleId::Cycle1, CycleId::Cycle2] {
+        let cycle = child.get_cycle(cycle_id);
+        let n = cycle.len();
+        for i in 0..n {
+            let a = cycle[i];
+            let b = cycle[(i + 1) % n];
+            if p2_index.has_edge(a, b).is_none() {
+                destroyed.insert(a);
+                destroyed.insert(b);
+            }
+        }
+    }
+
+    // Optional random deletion for diversification (20% probability)
+    for &node in child.cycle1.iter().chain(child.cycle2.iter()) {
+        if rng.random_bool(0.2) {
+            destroyed.insert(node);
+        }
+    }
+
+    // Remove destroyed nodes
+    child.cycle1.retain(|v| !destroyed.contains(v));
+    child.cycle2.retain(|v| !destroyed.contains(v));
+    destroyed
+}
+
+/// [`Crossover::CommonEdges`] into `child`; returns the removed vertices. Filtering parent 1's
+/// cycles keeps the endpoints of each common edge adjacent, so the fragments survive the
+/// removal; the repair may still insert vertices into them.
+fn keep_common_edges(p1: &Solution, p2: &Solution, child: &mut Solution) -> HashSet<usize> {
+    child.clone_from(p1);
+    let p2_edges = p2.to_edge_set();
+    let mut on_common_edge: HashSet<usize> = HashSet::new();
+    for (u, v) in p1.to_edge_set() {
+        if p2_edges.contains(&(u, v)) {
+            on_common_edge.insert(u);
+            on_common_edge.insert(v);
+        }
+    }
+    let destroyed: HashSet<usize> = p1
+        .cycle1
+        .iter()
+        .chain(&p1.cycle2)
+        .copied()
+        .filter(|v| !on_common_edge.contains(v))
+        .collect();
+    child.cycle1.retain(|v| on_common_edge.contains(v));
+    child.cycle2.retain(|v| on_common_edge.contains(v));
+    destroyed
+}
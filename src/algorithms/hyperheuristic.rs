@@ -0,0 +1,297 @@
+use crate::algorithm::{IterationInfo, IterationUnit, ProgressCallback, TspAlgorithm};
+use crate::algorithms::local_search::base::{
+    InitialSolutionType, LocalSearch, NeighborhoodType, SearchVariant,
+};
+use crate::algorithms::perturbation::Perturbation;
+use crate::budget::Budget;
+use crate::convergence::ConvergenceTrace;
+use crate::seed::SeedStream;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::utils::generate_random_solution;
+use crate::weight::{Cost, Weight};
+use rand::{Rng, RngCore};
+use std::time::Instant;
+
+/// Weight of the latest outcome in a configuration's running improvement rate.
+const RATE_REACTION: f64 = 0.2;
+/// Smallest selection weight relative to the best rate, so a configuration that stopped
+/// paying off is still tried now and then.
+const MIN_RELATIVE_WEIGHT: f64 = 0.05;
+
+/// How one local search configuration fared in a [`Hyperheuristic`] run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigurationUsage {
+    pub name: String,
+    pub uses: usize,
+    /// Uses after which the best solution improved.
+    pub improvements: usize,
+    /// Time spent in the configuration, in milliseconds.
+    pub time_ms: f64,
+    /// Recent cost reduction per millisecond of search, weighting the latest use by
+    /// `RATE_REACTION`; `None` before the first use.
+    pub rate: Option<f64>,
+}
+
+impl ConfigurationUsage {
+    fn record(&mut self, reduction: Cost, time_ms: f64, improved: bool) {
+        self.uses += 1;
+        if improved {
+            self.improvements += 1;
+        }
+        self.time_ms += time_ms;
+        // A floor of a microsecond keeps instant no-op searches from dividing by zero.
+        let rate = reduction.to_f64().max(0.0) / time_ms.max(1e-3);
+        self.rate = Some(match self.rate {
+            Some(previous) => (1.0 - RATE_REACTION) * previous + RATE_REACTION * rate,
+            None => rate,
+        });
+    }
+
+    /// Share of the run's iterations this configuration got.
+    pub fn share(&self, iterations: usize) -> f64 {
+        if iterations == 0 {
+            0.0
+        } else {
+            self.uses as f64 / iterations as f64
+        }
+    }
+}
+
+/// Iterated local search that switches between inner local search configurations during the
+/// run: every iteration perturbs the best solution and improves it with a configuration drawn
+/// in proportion to its recent cost reduction per millisecond, so the search settles on the
+/// configurations that pay off on the instance at hand and phase of the run.
+pub struct Hyperheuristic {
+    configurations: Vec<LocalSearch>,
+    perturbation: Box<dyn Perturbation>,
+    name_str: String,
+}
+
+impl Hyperheuristic {
+    pub fn new(configurations: Vec<LocalSearch>, perturbation: Box<dyn Perturbation>) -> Self {
+        assert!(
+            !configurations.is_empty(),
+            "A hyperheuristic needs at least one local search configuration"
+        );
+        let name_str = format!(
+            "Hyperheuristic ({} LS configurations, Perturb: {})",
+            configurations.len(),
+            perturbation.name()
+        );
+        Self {
+            configurations,
+            perturbation,
+            name_str,
+        }
+    }
+
+    /// Steepest and greedy search over both neighbourhoods, plus candidate search with
+    /// k = 5, 10 and 20 (which needs the instance's nearest neighbours precomputed).
+    pub fn standard_configurations() -> Vec<LocalSearch> {
+        let mut configurations = Vec::new();
+        for neighborhood in [
+            NeighborhoodType::EdgeExchange,
+            NeighborhoodType::VertexExchange,
+        ] {
+            for variant in [SearchVariant::Steepest, SearchVariant::Greedy] {
+                configurations.push(LocalSearch::new(
+                    variant,
+                    neighborhood,
+                    InitialSolutionType::Random,
+                ));
+            }
+        }
+        for k in [5, 10, 20] {
+            configurations.push(LocalSearch::new(
+                SearchVariant::CandidateSteepest(k),
+                NeighborhoodType::EdgeExchange,
+                InitialSolutionType::Random,
+            ));
+        }
+        configurations
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name_str
+    }
+
+    /// Runs until `budget` is exhausted and returns the best solution with its cost over
+    /// time.
+    pub fn solve_traced(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo, ConvergenceTrace) {
+        let (solution, iterations, trace, _) =
+            self.solve_traced_with_usage(instance, budget, rng, progress_callback);
+        (solution, iterations, trace)
+    }
+
+    /// [`Self::solve_traced`] that also returns how each configuration fared.
+    pub fn solve_traced_with_usage(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (
+        Solution,
+        IterationInfo,
+        ConvergenceTrace,
+        Vec<ConfigurationUsage>,
+    ) {
+        let mut trace = ConvergenceTrace::new();
+        let (solution, iterations, usage) =
+            self.run(instance, budget, rng, progress_callback, Some(&mut trace));
+        (solution, iterations, trace, usage)
+    }
+
+    fn run(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        mut trace: Option<&mut ConvergenceTrace>,
+    ) -> (Solution, IterationInfo, Vec<ConfigurationUsage>) {
+        let mut usage: Vec<ConfigurationUsage> = self
+            .configurations
+            .iter()
+            .map(|ls| ConfigurationUsage {
+                name: ls.name().to_string(),
+                uses: 0,
+                improvements: 0,
+                time_ms: 0.0,
+                rate: None,
+            })
+            .collect();
+
+        let mut best_solution = generate_random_solution(instance, rng);
+        budget.count_solution();
+        let mut best_cost = best_solution.calculate_cost(instance);
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.record(budget.elapsed(), best_cost);
+        }
+        let mut current_solution = best_solution.clone();
+
+        // The perturbation of iteration `i` draws from stream `i`.
+        let perturbation_streams = SeedStream::from_rng(rng);
+        let mut iterations = 0;
+        while !budget.exhausted() {
+            iterations += 1;
+            // The first iteration improves the initial solution as it is.
+            current_solution.clone_from(&best_solution);
+            if iterations > 1 {
+                self.perturbation.perturb_within(
+                    &mut current_solution,
+                    instance,
+                    &mut perturbation_streams.child(iterations as u64).rng(),
+                    budget,
+                );
+                budget.count_solution();
+            }
+            let start_cost = current_solution.calculate_cost(instance);
+
+            let configuration = select_configuration(&usage, rng);
+            let ls_start = Instant::now();
+            let current_cost = self.configurations[configuration].improve_within(
+                instance,
+                &mut current_solution,
+                budget,
+                rng,
+                &mut |_| {},
+            );
+            let improved = current_cost < best_cost;
+            usage[configuration].record(
+                start_cost - current_cost,
+                ls_start.elapsed().as_secs_f64() * 1000.0,
+                improved,
+            );
+
+            if improved {
+                std::mem::swap(&mut best_solution, &mut current_solution);
+                best_cost = current_cost;
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record(budget.elapsed(), best_cost);
+                }
+                progress_callback(format!(
+                    "[Iter {}] New best solution found with {}: {}",
+                    iterations, usage[configuration].name, best_cost
+                ));
+            }
+        }
+
+        if let Some(trace) = trace {
+            trace.finish(budget.elapsed());
+        }
+        progress_callback(format!(
+            "Hyperheuristic finished. Total iterations: {}, Best cost: {}",
+            iterations, best_cost
+        ));
+        for configuration in &usage {
+            progress_callback(format!(
+                "  {}: {:.1}% of iterations, {} improvements, {:.1} ms",
+                configuration.name,
+                configuration.share(iterations) * 100.0,
+                configuration.improvements,
+                configuration.time_ms
+            ));
+        }
+        let info = IterationInfo {
+            unit: IterationUnit::Perturbations,
+            outer: iterations,
+            inner_ls_runs: iterations,
+            evaluations: budget.evaluations_since_start(),
+        };
+        (best_solution, info, usage)
+    }
+}
+
+/// Share of the iterations of `runs` (the usage of every run, configurations in the same
+/// order) each configuration got, by name.
+pub fn usage_distribution(runs: &[Vec<ConfigurationUsage>]) -> Vec<(String, f64)> {
+    let Some(first) = runs.first() else {
+        return Vec::new();
+    };
+    let total: usize = runs.iter().flatten().map(|u| u.uses).sum();
+    first
+        .iter()
+        .enumerate()
+        .map(|(index, configuration)| {
+            let uses: usize = runs.iter().map(|run| run[index].uses).sum();
+            let share = if total == 0 {
+                0.0
+            } else {
+                uses as f64 / total as f64
+            };
+            (configuration.name.clone(), share)
+        })
+        .collect()
+}
+
+/// Draws an untried configuration if there is one, otherwise one with probability
+/// proportional to its rate, floored at `MIN_RELATIVE_WEIGHT` of the best rate.
+fn select_configuration<R: Rng + ?Sized>(usage: &[ConfigurationUsage], rng: &mut R) -> usize {
+    if let Some(untried) = usage.iter().position(|u| u.rate.is_none()) {
+        return untried;
+    }
+    let best_rate = usage.iter().filter_map(|u| u.rate).fold(0.0_f64, f64::max);
+    if best_rate <= 0.0 {
+        return rng.random_range(0..usage.len());
+    }
+    let weights: Vec<f64> = usage
+        .iter()
+        .map(|u| u.rate.unwrap_or(0.0).max(MIN_RELATIVE_WEIGHT * best_rate))
+        .collect();
+    let total: f64 = weights.iter().sum();
+    let mut draw = rng.random_range(0.0..total);
+    for (index, &weight) in weights.iter().enumerate() {
+        if draw < weight {
+            return index;
+        }
+        draw -= weight;
+    }
+    usage.len() - 1
+}
@@ -1,34 +1,106 @@
-use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithm::{IterationInfo, IterationUnit, ProgressCallback, TspAlgorithm};
 use crate::algorithms::local_search::base::LocalSearch;
 use crate::algorithms::perturbation::Perturbation;
+use crate::budget::Budget;
+use crate::convergence::ConvergenceTrace;
+use crate::elite::EliteArchive;
+use crate::pareto::ParetoArchive;
+use crate::progress::nested;
+use crate::seed::SeedStream;
 use crate::tsplib::{Solution, TsplibInstance};
 use crate::utils::generate_random_solution;
-use rand::{Rng, thread_rng};
-use std::marker::PhantomData;
-use std::time::{Duration, Instant};
+use rand::{Rng, RngCore};
+use std::sync::Arc;
+use std::time::Instant;
 
-// Make Ils generic over the perturbation type P
-pub struct Ils<P: Perturbation + Send + Sync> {
+/// Weight of the latest outcome in an operator's running success rate.
+const OPERATOR_REACTION: f64 = 0.1;
+/// Smallest selection weight, so an operator that stopped paying off is still tried.
+const MIN_OPERATOR_WEIGHT: f64 = 0.05;
+
+pub struct Ils {
     base_local_search: LocalSearch,
-    perturbation: P,
+    /// Portfolio the perturbation of every iteration is drawn from.
+    perturbations: Vec<Box<dyn Perturbation>>,
+    elites: Option<Arc<EliteArchive>>,
+    initial_solution: Option<Arc<Solution>>,
     name_str: String,
-    _marker: PhantomData<P>, // Use PhantomData if P is not used directly in struct fields
 }
 
-// Update impl block to include the generic parameter P
-impl<P: Perturbation + Send + Sync> Ils<P> {
-    pub fn new(base_local_search: LocalSearch, perturbation: P) -> Self {
-        let name_str = format!(
-            "ILS (Base: {}, Perturb: {})",
-            base_local_search.name(),
-            perturbation.name()
-        );
-        Self {
+/// How one perturbation operator of the portfolio fared in a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorUsage {
+    pub name: String,
+    pub uses: usize,
+    /// Uses after which the local search improved the best solution.
+    pub improvements: usize,
+    /// Selection weight at the end of the run: the recent success rate, at least
+    /// `MIN_OPERATOR_WEIGHT`. Every operator starts at 1.
+    pub weight: f64,
+}
+
+impl OperatorUsage {
+    /// Counts one use and moves the weight towards 1 after an improvement, towards 0 otherwise.
+    fn record(&mut self, improved: bool) {
+        self.uses += 1;
+        let outcome = if improved {
+            self.improvements += 1;
+            1.0
+        } else {
+            0.0
+        };
+        self.weight = ((1.0 - OPERATOR_REACTION) * self.weight + OPERATOR_REACTION * outcome)
+            .max(MIN_OPERATOR_WEIGHT);
+    }
+}
+
+impl Ils {
+    pub fn new(base_local_search: LocalSearch, perturbation: Box<dyn Perturbation>) -> Self {
+        let mut ils = Self {
             base_local_search,
-            perturbation,
-            name_str,
-            _marker: PhantomData,
-        }
+            perturbations: vec![perturbation],
+            elites: None,
+            initial_solution: None,
+            name_str: String::new(),
+        };
+        ils.name_str = ils.build_name();
+        ils
+    }
+
+    /// Adds an operator to the portfolio. Each iteration draws one with probability
+    /// proportional to its recent success rate, i.e. how often it led to a new best solution.
+    pub fn with_perturbation(mut self, perturbation: Box<dyn Perturbation>) -> Self {
+        self.perturbations.push(perturbation);
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Starts every run from a random elite of `elites` instead of a random solution (while
+    /// the archive is empty, from a random solution) and offers the final best back to it.
+    pub fn with_elites(mut self, elites: Arc<EliteArchive>) -> Self {
+        self.elites = Some(elites);
+        self
+    }
+
+    /// Starts every run from a copy of `solution` (ahead of any elite), e.g. to keep
+    /// improving a solution saved by an earlier run.
+    pub fn with_initial_solution(mut self, solution: Solution) -> Self {
+        self.initial_solution = Some(Arc::new(solution));
+        self
+    }
+
+    fn build_name(&self) -> String {
+        let names: Vec<String> = self.perturbations.iter().map(|p| p.name()).collect();
+        let perturb_info = if names.len() == 1 {
+            names[0].clone()
+        } else {
+            format!("adaptive[{}]", names.join(" | "))
+        };
+        format!(
+            "ILS (Base: {}, Perturb: {})",
+            self.base_local_search.name(),
+            perturb_info
+        )
     }
 
     // Add public name accessor
@@ -36,61 +108,171 @@ impl<P: Perturbation + Send + Sync> Ils<P> {
         &self.name_str
     }
 
-    // solve_timed remains largely the same, but can now call perturbation.perturb directly
-    pub fn solve_timed(
+    /// Runs until `budget` is exhausted, which also bounds the inner local search and repair
+    /// steps, and returns the best solution with its cost over time.
+    pub fn solve_traced(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo, ConvergenceTrace) {
+        let (solution, iterations, trace, _) =
+            self.solve_traced_with_usage(instance, budget, rng, progress_callback);
+        (solution, iterations, trace)
+    }
+
+    /// [`Self::solve_traced`] that also returns how each perturbation operator fared.
+    pub fn solve_traced_with_usage(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (
+        Solution,
+        IterationInfo,
+        ConvergenceTrace,
+        Vec<OperatorUsage>,
+    ) {
+        let mut trace = ConvergenceTrace::new();
+        let (solution, iterations, usage) = self.run(
+            instance,
+            budget,
+            rng,
+            progress_callback,
+            None,
+            Some(&mut trace),
+        );
+        (solution, iterations, trace, usage)
+    }
+
+    /// [`Self::solve_traced`] that also offers every locally optimised solution to `archive`,
+    /// collecting the cost/balance trade-offs visited along the way.
+    pub fn solve_with_archive(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        archive: &mut ParetoArchive,
+    ) -> (Solution, IterationInfo) {
+        let (solution, iterations, _) = self.run(
+            instance,
+            budget,
+            rng,
+            progress_callback,
+            Some(archive),
+            None,
+        );
+        (solution, iterations)
+    }
+
+    fn run(
         &self,
         instance: &TsplibInstance,
-        time_limit: Duration,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
-    ) -> (Solution, usize) {
-        // Return iterations count as well
-        let start_time = Instant::now();
-        let mut rng = thread_rng();
+        mut archive: Option<&mut ParetoArchive>,
+        mut trace: Option<&mut ConvergenceTrace>,
+    ) -> (Solution, IterationInfo, Vec<OperatorUsage>) {
+        let mut usage: Vec<OperatorUsage> = self
+            .perturbations
+            .iter()
+            .map(|p| OperatorUsage {
+                name: p.name(),
+                uses: 0,
+                improvements: 0,
+                weight: 1.0,
+            })
+            .collect();
 
         // 1. Generate Initial Solution
-        progress_callback("Generating initial random solution...".to_string());
-        let initial_solution = generate_random_solution(instance);
+        let mut best_solution = if let Some(solution) = &self.initial_solution {
+            progress_callback("Starting from the given solution...".to_string());
+            Solution::clone(solution)
+        } else if let Some(elite) = self.elites.as_ref().and_then(|e| e.warm_start(rng)) {
+            progress_callback(format!(
+                "Starting from an elite of {} (cost {})",
+                elite.source, elite.cost
+            ));
+            elite.solution
+        } else {
+            progress_callback("Generating initial random solution...".to_string());
+            generate_random_solution(instance, rng)
+        };
+        budget.count_solution();
 
         // 2. Apply Local Search to Initial Solution
         progress_callback("Running initial Local Search...".to_string());
-        let mut best_solution = self
-            .base_local_search
-            .solve_with_feedback(instance, &mut |s| {
-                progress_callback(format!("Initial LS: {}", s))
-            });
-        let mut best_cost = best_solution.calculate_cost(instance);
+        let mut best_cost = self.base_local_search.improve_within(
+            instance,
+            &mut best_solution,
+            budget,
+            rng,
+            &mut |s| progress_callback(nested(&s)),
+        );
         progress_callback(format!("Initial LS finished. Cost: {}", best_cost));
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.record(budget.elapsed(), best_cost);
+        }
+        if let Some(archive) = archive.as_deref_mut() {
+            archive.offer(&best_solution, instance);
+        }
+
+        // Working buffer reused every iteration; it is swapped with the best on improvement.
+        let mut current_solution = best_solution.clone();
 
+        let objective = self.base_local_search.objective();
+        // The perturbation of iteration `i` draws from stream `i`.
+        let perturbation_streams = SeedStream::from_rng(rng);
         let mut iterations = 0;
-        while start_time.elapsed() < time_limit {
+        while !budget.exhausted() {
             iterations += 1;
             let loop_start_time = Instant::now();
 
             // 3. Perturbation
-            let mut current_solution = best_solution.clone();
-            // Now we can call perturb directly
-            self.perturbation
-                .perturb(&mut current_solution, instance, &mut rng);
-            progress_callback(format!("[Iter {}] Perturbed solution.", iterations));
+            current_solution.clone_from(&best_solution);
+            let operator = select_operator(&usage, rng);
+            self.perturbations[operator].perturb_within(
+                &mut current_solution,
+                instance,
+                &mut perturbation_streams.child(iterations as u64).rng(),
+                budget,
+            );
+            budget.count_solution();
+            let perturbed_cost = objective.value(current_solution.cycle_costs(instance));
+            progress_callback(format!(
+                "[Iter {}] Perturbed solution with {} (delta {:+}).",
+                iterations,
+                usage[operator].name,
+                perturbed_cost - best_cost
+            ));
 
             // 4. Local Search on Perturbed Solution
-            let mut ls_callback = |s: String| {
-                progress_callback(format!(
-                    "[Iter {}] LS on perturbed: {} (Time left: {:?})",
-                    iterations,
-                    s,
-                    time_limit.saturating_sub(start_time.elapsed())
-                ));
-            };
-            current_solution = self
-                .base_local_search
-                .solve_with_feedback(instance, &mut ls_callback);
-            let current_cost = current_solution.calculate_cost(instance);
+            let mut ls_callback = |s: String| progress_callback(nested(&s));
+            let current_cost = self.base_local_search.improve_within(
+                instance,
+                &mut current_solution,
+                budget,
+                rng,
+                &mut ls_callback,
+            );
+            if let Some(archive) = archive.as_deref_mut() {
+                archive.offer(&current_solution, instance);
+            }
+
+            let improved = current_cost < best_cost;
+            usage[operator].record(improved);
 
             // 5. Acceptance Criterion (Accept if better)
-            if current_cost < best_cost {
-                best_solution = current_solution;
+            if improved {
+                std::mem::swap(&mut best_solution, &mut current_solution);
                 best_cost = current_cost;
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record(budget.elapsed(), best_cost);
+                }
                 progress_callback(format!(
                     "[Iter {}] New best solution found: {}. Loop time: {:?}",
                     iterations,
@@ -106,42 +288,66 @@ impl<P: Perturbation + Send + Sync> Ils<P> {
                     loop_start_time.elapsed()
                 ));
             }
-
-            // Check time limit again before next iteration
-            if start_time.elapsed() >= time_limit {
-                progress_callback(format!("[Iter {}] Time limit reached.", iterations));
-                break;
-            }
         }
 
+        if let Some(trace) = trace {
+            trace.finish(budget.elapsed());
+        }
         progress_callback(format!(
             "ILS finished. Total iterations: {}, Best cost: {}, Total time: {:?}",
             iterations,
             best_cost,
-            start_time.elapsed()
+            budget.elapsed()
         ));
-        (best_solution, iterations)
+        if let Some(elites) = &self.elites {
+            elites.offer(&best_solution, instance, &self.name_str);
+        }
+        for operator in &usage {
+            progress_callback(format!(
+                "  {}: {} uses, {} improvements, final weight {:.3}",
+                operator.name, operator.uses, operator.improvements, operator.weight
+            ));
+        }
+        let info = IterationInfo {
+            unit: IterationUnit::Perturbations,
+            outer: iterations,
+            inner_ls_runs: iterations + 1,
+            evaluations: budget.evaluations_since_start(),
+        };
+        (best_solution, info, usage)
     }
 }
 
-// We might need a way to run ILS with a time limit based on MSLS average time.
-// TspAlgorithm trait expects solve_with_feedback which doesn't naturally take a time limit.
-// For now, we'll add a separate solve_timed method.
-// Alternatively, we could adapt the TspAlgorithm trait or ExperimentRunner later.
+/// Uses and improvements of each operator summed over `runs` (the usage of every run,
+/// operators in the same order), with the final weights averaged.
+pub fn operator_totals(runs: &[Vec<OperatorUsage>]) -> Vec<OperatorUsage> {
+    let Some(first) = runs.first() else {
+        return Vec::new();
+    };
+    first
+        .iter()
+        .enumerate()
+        .map(|(index, operator)| OperatorUsage {
+            name: operator.name.clone(),
+            uses: runs.iter().map(|run| run[index].uses).sum(),
+            improvements: runs.iter().map(|run| run[index].improvements).sum(),
+            weight: runs.iter().map(|run| run[index].weight).sum::<f64>() / runs.len() as f64,
+        })
+        .collect()
+}
 
-/*
-impl TspAlgorithm for Ils {
-    fn name(&self) -> &str {
-        &self.name_str
+/// Draws an operator index with probability proportional to its weight.
+fn select_operator<R: Rng + ?Sized>(usage: &[OperatorUsage], rng: &mut R) -> usize {
+    if usage.len() == 1 {
+        return 0;
     }
-
-    fn solve_with_feedback(
-        &self,
-        instance: &TsplibInstance,
-        progress_callback: ProgressCallback,
-    ) -> Solution {
-        // This would need a default time limit or another way to determine it.
-        unimplemented!("ILS solve_with_feedback needs time limit handling.");
+    let total: f64 = usage.iter().map(|u| u.weight).sum();
+    let mut draw = rng.random_range(0.0..total);
+    for (index, operator) in usage.iter().enumerate() {
+        if draw < operator.weight {
+            return index;
+        }
+        draw -= operator.weight;
     }
+    usage.len() - 1
 }
-*/
@@ -1,55 +1,130 @@
-use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithm::{IterationInfo, IterationUnit, ProgressCallback, TspAlgorithm};
+use crate::algorithms::acceptance::Acceptance;
 use crate::algorithms::local_search::base::LocalSearch;
 use crate::algorithms::perturbation::Perturbation;
+use crate::budget::Budget;
+use crate::convergence::ConvergenceTrace;
+use crate::elite::EliteArchive;
+use crate::progress::nested;
+use crate::seed::SeedStream;
 use crate::tsplib::{Solution, TsplibInstance};
 use crate::utils::generate_random_solution;
-use rand::{Rng, thread_rng};
-use std::marker::PhantomData;
-use std::time::{Duration, Instant};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
 
-// Make Lns generic over the perturbation type P
-pub struct Lns<P: Perturbation + Send + Sync> {
+/// Parameters of [`Lns`] apart from its base local search and destroy/repair operator.
+/// Config files may leave out any of them, which then keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LnsParams {
+    pub apply_ls_after_repair: bool,
+    pub apply_ls_to_initial: bool,
+    /// When a repaired solution replaces the current one the next destroy starts from.
+    pub acceptance: Acceptance,
+}
+
+impl Default for LnsParams {
+    fn default() -> Self {
+        Self {
+            apply_ls_after_repair: true,
+            apply_ls_to_initial: true,
+            acceptance: Acceptance::BetterOnly,
+        }
+    }
+}
+
+pub struct Lns {
     base_local_search: LocalSearch,
-    perturbation: P, // Should be a Destroy/Repair type
-    apply_ls_after_repair: bool,
-    apply_ls_to_initial: bool,
+    perturbation: Box<dyn Perturbation>, // Should be a Destroy/Repair type
+    params: LnsParams,
+    elites: Option<Arc<EliteArchive>>,
+    initial_solution: Option<Arc<Solution>>,
     name_str: String,
-    _marker: PhantomData<P>,
 }
 
-// Update impl block to include the generic parameter P
-impl<P: Perturbation + Send + Sync> Lns<P> {
+impl Lns {
     pub fn new(
         base_local_search: LocalSearch,
-        perturbation: P,
+        perturbation: Box<dyn Perturbation>,
         apply_ls_after_repair: bool,
         apply_ls_to_initial: bool, // LNSa variant check
     ) -> Self {
-        let variant = if apply_ls_after_repair {
+        Self::from_params(
+            base_local_search,
+            perturbation,
+            LnsParams {
+                apply_ls_after_repair,
+                apply_ls_to_initial,
+                ..LnsParams::default()
+            },
+        )
+    }
+
+    pub fn from_params(
+        base_local_search: LocalSearch,
+        perturbation: Box<dyn Perturbation>,
+        params: LnsParams,
+    ) -> Self {
+        let mut lns = Self {
+            base_local_search,
+            perturbation,
+            params,
+            elites: None,
+            initial_solution: None,
+            name_str: String::new(),
+        };
+        lns.name_str = lns.build_name();
+        lns
+    }
+
+    /// Replaces the default better-only acceptance, letting the destroy/repair chain move
+    /// away from the best solution found; that one is still kept and returned.
+    pub fn with_acceptance(mut self, acceptance: Acceptance) -> Self {
+        self.params.acceptance = acceptance;
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Starts every run from a random elite of `elites` instead of a random solution (while
+    /// the archive is empty, from a random solution) and offers the final best back to it.
+    pub fn with_elites(mut self, elites: Arc<EliteArchive>) -> Self {
+        self.elites = Some(elites);
+        self
+    }
+
+    /// Starts every run from a copy of `solution` (ahead of any elite), e.g. to keep
+    /// improving a solution saved by an earlier run.
+    pub fn with_initial_solution(mut self, solution: Solution) -> Self {
+        self.initial_solution = Some(Arc::new(solution));
+        self
+    }
+
+    fn build_name(&self) -> String {
+        let variant = if self.params.apply_ls_after_repair {
             "LNS"
         } else {
             "LNSa (no LS after repair)"
         };
-        let initial_ls_info = if apply_ls_to_initial {
+        let initial_ls_info = if self.params.apply_ls_to_initial {
             " (LS on Initial)"
         } else {
             ""
         };
-        let name_str = format!(
-            "{} (Base: {}, Perturb: {}){}",
+        let acceptance_info = if self.params.acceptance == Acceptance::BetterOnly {
+            String::new()
+        } else {
+            format!(", Accept: {}", self.params.acceptance.name())
+        };
+        format!(
+            "{} (Base: {}, Perturb: {}{}){}",
             variant,
-            base_local_search.name(),
-            perturbation.name(),
+            self.base_local_search.name(),
+            self.perturbation.name(),
+            acceptance_info,
             initial_ls_info
-        );
-        Self {
-            base_local_search,
-            perturbation,
-            apply_ls_after_repair,
-            apply_ls_to_initial,
-            name_str,
-            _marker: PhantomData,
-        }
+        )
     }
 
     // Add public name accessor
@@ -57,71 +132,134 @@ impl<P: Perturbation + Send + Sync> Lns<P> {
         &self.name_str
     }
 
-    // solve_timed remains largely the same, but can now call perturbation.perturb directly
-    pub fn solve_timed(
+    /// Runs until `budget` is exhausted, which also bounds the inner local search and repair
+    /// steps.
+    pub fn solve_within(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo) {
+        self.run(instance, budget, rng, progress_callback, None)
+    }
+
+    /// [`Self::solve_within`] that also returns the best cost over time.
+    pub fn solve_traced(
         &self,
         instance: &TsplibInstance,
-        time_limit: Duration,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
-    ) -> (Solution, usize) {
-        // Return iterations count as well
-        let start_time = Instant::now();
-        let mut rng = thread_rng();
+    ) -> (Solution, IterationInfo, ConvergenceTrace) {
+        let mut trace = ConvergenceTrace::new();
+        let (solution, iterations) =
+            self.run(instance, budget, rng, progress_callback, Some(&mut trace));
+        (solution, iterations, trace)
+    }
 
+    fn run(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        mut trace: Option<&mut ConvergenceTrace>,
+    ) -> (Solution, IterationInfo) {
         // 1. Generate Initial Solution
-        progress_callback("Generating initial random solution...".to_string());
-        let mut best_solution = generate_random_solution(instance);
+        let mut best_solution = if let Some(solution) = &self.initial_solution {
+            progress_callback("Starting from the given solution...".to_string());
+            Solution::clone(solution)
+        } else if let Some(elite) = self.elites.as_ref().and_then(|e| e.warm_start(rng)) {
+            progress_callback(format!(
+                "Starting from an elite of {} (cost {})",
+                elite.source, elite.cost
+            ));
+            elite.solution
+        } else {
+            progress_callback("Generating initial random solution...".to_string());
+            generate_random_solution(instance, rng)
+        };
+        budget.count_solution();
+        best_solution.is_closed = !self.base_local_search.open_paths();
 
         // 2. Apply Local Search to Initial Solution (Optional)
-        if self.apply_ls_to_initial {
+        if self.params.apply_ls_to_initial {
             progress_callback("Running initial Local Search...".to_string());
-            best_solution = self
-                .base_local_search
-                .solve_with_feedback(instance, &mut |s| {
-                    progress_callback(format!("Initial LS: {}", s))
-                });
-            progress_callback(format!(
-                "Initial LS finished. Cost: {}",
-                best_solution.calculate_cost(instance)
-            ));
+            let initial_cost = self.base_local_search.improve_within(
+                instance,
+                &mut best_solution,
+                budget,
+                rng,
+                &mut |s| progress_callback(nested(&s)),
+            );
+            progress_callback(format!("Initial LS finished. Cost: {}", initial_cost));
         }
-        let mut best_cost = best_solution.calculate_cost(instance);
+        let mut best_cost =
+            best_solution.calculate_objective(instance, self.base_local_search.objective());
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.record(budget.elapsed(), best_cost);
+        }
+
+        // The accepted solution the next destroy starts from, and a working buffer reused
+        // every iteration that is swapped with it on acceptance.
+        let mut accepted_solution = best_solution.clone();
+        let mut accepted_cost = best_cost;
+        let mut current_solution = best_solution.clone();
 
+        // The destroy-repair of iteration `i` draws from stream `i`.
+        let perturbation_streams = SeedStream::from_rng(rng);
         let mut iterations = 0;
-        while start_time.elapsed() < time_limit {
+        while !budget.exhausted() {
             iterations += 1;
             let loop_start_time = Instant::now();
 
             // 3. Perturbation (Destroy + Repair)
-            let mut current_solution = best_solution.clone();
-            // Now we can call perturb directly
-            self.perturbation
-                .perturb(&mut current_solution, instance, &mut rng);
+            current_solution.clone_from(&accepted_solution);
+            self.perturbation.perturb_within(
+                &mut current_solution,
+                instance,
+                &mut perturbation_streams.child(iterations as u64).rng(),
+                budget,
+            );
+            budget.count_solution();
             progress_callback(format!(
                 "[Iter {}] Perturbed (Destroy/Repair) solution.",
                 iterations
             ));
 
             // 4. Local Search on Repaired Solution (Optional)
-            if self.apply_ls_after_repair {
-                let mut ls_callback = |s: String| {
-                    progress_callback(format!(
-                        "[Iter {}] LS on repaired: {} (Time left: {:?})",
-                        iterations,
-                        s,
-                        time_limit.saturating_sub(start_time.elapsed())
-                    ));
-                };
-                current_solution = self
-                    .base_local_search
-                    .solve_with_feedback(instance, &mut ls_callback);
+            if self.params.apply_ls_after_repair {
+                let mut ls_callback = |s: String| progress_callback(nested(&s));
+                self.base_local_search.improve_within(
+                    instance,
+                    &mut current_solution,
+                    budget,
+                    rng,
+                    &mut ls_callback,
+                );
             }
-            let current_cost = current_solution.calculate_cost(instance);
+            let current_cost =
+                current_solution.calculate_objective(instance, self.base_local_search.objective());
 
-            // 5. Acceptance Criterion (Accept if better)
-            if current_cost < best_cost {
-                best_solution = current_solution;
-                best_cost = current_cost;
+            // 5. Acceptance Criterion
+            let accepted = self.params.acceptance.accepts(
+                current_cost,
+                accepted_cost,
+                best_cost,
+                iterations,
+                rng,
+            );
+            if accepted {
+                std::mem::swap(&mut accepted_solution, &mut current_solution);
+                accepted_cost = current_cost;
+            }
+            if accepted_cost < best_cost {
+                best_solution.clone_from(&accepted_solution);
+                best_cost = accepted_cost;
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record(budget.elapsed(), best_cost);
+                }
                 progress_callback(format!(
                     "[Iter {}] New best solution found: {}. Loop time: {:?}",
                     iterations,
@@ -130,44 +268,39 @@ impl<P: Perturbation + Send + Sync> Lns<P> {
                 ));
             } else {
                 progress_callback(format!(
-                    "[Iter {}] Solution not improved ({} >= {}). Loop time: {:?}",
+                    "[Iter {}] Solution not improved ({} >= {}, {}). Loop time: {:?}",
                     iterations,
                     current_cost,
                     best_cost,
+                    if accepted { "accepted" } else { "rejected" },
                     loop_start_time.elapsed()
                 ));
             }
-
-            // Check time limit again before next iteration
-            if start_time.elapsed() >= time_limit {
-                progress_callback(format!("[Iter {}] Time limit reached.", iterations));
-                break;
-            }
         }
 
+        if let Some(trace) = trace {
+            trace.finish(budget.elapsed());
+        }
         progress_callback(format!(
             "LNS finished. Total iterations: {}, Best cost: {}, Total time: {:?}",
             iterations,
             best_cost,
-            start_time.elapsed()
+            budget.elapsed()
         ));
-        (best_solution, iterations)
-    }
-}
-
-// Similar time limit considerations as ILS apply here.
-/*
-impl TspAlgorithm for Lns {
-    fn name(&self) -> &str {
-        &self.name_str
-    }
-
-    fn solve_with_feedback(
-        &self,
-        instance: &TsplibInstance,
-        progress_callback: ProgressCallback,
-    ) -> Solution {
-        unimplemented!("LNS solve_with_feedback needs time limit handling.");
+        if let Some(elites) = &self.elites {
+            elites.offer(&best_solution, instance, &self.name_str);
+        }
+        let info = IterationInfo {
+            unit: IterationUnit::DestroyRepairs,
+            outer: iterations,
+            inner_ls_runs: usize::from(self.params.apply_ls_to_initial)
+                + if self.params.apply_ls_after_repair {
+                    iterations
+                } else {
+                    0
+                },
+            evaluations: budget.evaluations_since_start(),
+        };
+        (best_solution, info)
     }
 }
-*/
@@ -1,17 +1,39 @@
-use crate::algorithm::ProgressCallback;
-use crate::algorithm::TspAlgorithm;
+use crate::algorithm::{IterationInfo, IterationUnit, ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::convex_hull_insertion::ConvexHullInsertion;
+use crate::algorithms::constructive::greedy_edge::GreedyEdge;
+use crate::algorithms::constructive::hilbert_curve::HilbertCurve;
+use crate::algorithms::constructive::nearest_neighbor_insertion::NearestNeighborInsertion;
+use crate::algorithms::constructive::prize_collecting_insertion::PrizeCollectingInsertion;
 use crate::algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
-use crate::moves::inter_route::evaluate_inter_route_exchange;
+use crate::algorithms::local_search::incidents::{INCIDENT_DIR, record_cost_mismatch};
+use crate::algorithms::local_search::lin_kernighan::LinKernighan;
+use crate::algorithms::local_search::oracle;
+use crate::algorithms::local_search::report::{LocalSearchReport, MoveCounts};
+use crate::algorithms::local_search::sampling::{neighborhood_size, sample_move};
+use crate::algorithms::local_search::trajectory::TrajectoryRecorder;
+use crate::budget::Budget;
+use crate::convergence::ConvergenceTrace;
+use crate::moves::evaluate::evaluate_move_indexed;
+use crate::moves::inter_route::{evaluate_inter_route_exchange, evaluate_inter_route_transfer};
 use crate::moves::intra_route::{
-    evaluate_candidate_intra_route_edge_exchange, evaluate_intra_route_edge_exchange,
+    evaluate_candidate_intra_route_edge_exchange,
+    evaluate_candidate_intra_route_edge_exchange_backward, evaluate_intra_route_edge_exchange,
     evaluate_intra_route_vertex_exchange,
 };
-use crate::moves::types::{CycleId, EvaluatedMove, Move};
-use crate::tsplib::{Solution, TsplibInstance};
+use crate::moves::types::{CycleId, EvaluatedMove, Move, MoveKind};
+use crate::progress::nested;
+use crate::seed::SeedStream;
+use crate::tsplib::{Objective, Solution, SolutionIndex, TsplibInstance};
 use crate::utils::generate_random_solution;
+use crate::weight::{Cost, Weight};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::collections::{BinaryHeap, HashSet};
+use rand::{Rng, RngCore};
+use rayon::prelude::*;
+use rustc_hash::FxHashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SearchVariant {
@@ -19,6 +41,31 @@ pub enum SearchVariant {
     Greedy,
     CandidateSteepest(usize),
     MoveListSteepest,
+    /// Steepest over a uniform random sample of this fraction of the neighbourhood.
+    SampledSteepest(f64),
+}
+
+impl SearchVariant {
+    /// `steepest`, `greedy`, `candidate` (10 candidates), `move-list` or
+    /// `sampled[:<fraction>]` (a quarter of the neighbourhood by default).
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.split_once(':') {
+            None => match text {
+                "steepest" => Some(Self::Steepest),
+                "greedy" => Some(Self::Greedy),
+                "candidate" => Some(Self::CandidateSteepest(10)),
+                "move-list" => Some(Self::MoveListSteepest),
+                "sampled" => Some(Self::SampledSteepest(0.25)),
+                _ => None,
+            },
+            Some(("sampled", fraction)) => fraction
+                .parse()
+                .ok()
+                .filter(|&fraction| fraction > 0.0 && fraction <= 1.0)
+                .map(Self::SampledSteepest),
+            Some(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -27,15 +74,57 @@ pub enum NeighborhoodType {
     EdgeExchange,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum InitialSolutionType {
+    #[default]
     Random,
     Heuristic(HeuristicAlgorithm),
 }
 
+impl InitialSolutionType {
+    /// `random` or the name of a constructive heuristic, see [`HeuristicAlgorithm::as_str`].
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "random" => Some(Self::Random),
+            _ => HeuristicAlgorithm::parse(text).map(Self::Heuristic),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum HeuristicAlgorithm {
     WeightedRegret,
+    NearestNeighborInsertion,
+    ConvexHullInsertion,
+    HilbertCurve,
+    GreedyEdge,
+    PrizeCollectingInsertion,
+}
+
+impl HeuristicAlgorithm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WeightedRegret => "weighted-regret",
+            Self::NearestNeighborInsertion => "nn-insertion",
+            Self::ConvexHullInsertion => "convex-hull",
+            Self::HilbertCurve => "hilbert",
+            Self::GreedyEdge => "greedy-edge",
+            Self::PrizeCollectingInsertion => "prize-collecting",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        [
+            Self::WeightedRegret,
+            Self::NearestNeighborInsertion,
+            Self::ConvexHullInsertion,
+            Self::HilbertCurve,
+            Self::GreedyEdge,
+            Self::PrizeCollectingInsertion,
+        ]
+        .into_iter()
+        .find(|heuristic| heuristic.as_str() == text)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -43,6 +132,30 @@ pub struct LocalSearch {
     variant: SearchVariant,
     neighborhood: NeighborhoodType,
     initial_solution_type: InitialSolutionType,
+    /// Solution every run starts from instead of one of `initial_solution_type`.
+    initial_solution: Option<Arc<Solution>>,
+    /// Maximum number of consecutive zero-delta (sideways) moves; 0 disables plateau moves.
+    max_sideways: usize,
+    /// Whether candidate search confirms an empty candidate scan with a full-neighbourhood
+    /// scan before stopping.
+    full_fallback: bool,
+    /// Largest candidate list prefix adaptive candidate search grows to, starting from the
+    /// variant's k; `None` scans the whole precomputed lists.
+    adaptive_max_k: Option<usize>,
+    /// Optional variable-depth pass run at each local optimum.
+    lk_pass: Option<LinKernighan>,
+    /// How far cycle 1 may deviate from `ceil(n/2)` vertices; 0 keeps the cycles balanced
+    /// and disables vertex transfers.
+    size_slack: usize,
+    /// What the search minimises; anything but the plain sum rescores each move from the
+    /// current cycle lengths.
+    objective: Objective,
+    /// Whether the search works on two open paths instead of two cycles.
+    open_paths: bool,
+    /// File the applied moves are recorded to, for replaying them later.
+    trajectory: Option<PathBuf>,
+    /// Whether every step is cross-checked against the brute-force oracle.
+    oracle_check: bool,
     name_str: String,
 }
 
@@ -52,64 +165,475 @@ impl LocalSearch {
         neighborhood: NeighborhoodType,
         initial_solution_type: InitialSolutionType,
     ) -> Self {
-        let name_str = match variant {
-            SearchVariant::CandidateSteepest(k) => format!(
-                "Local Search (Candidate k={}, {:?}, Init: {:?})",
-                k, neighborhood, initial_solution_type
-            ),
-            SearchVariant::MoveListSteepest => format!(
-                "Local Search (MoveListSteepest, {:?}, Init: {:?})",
-                neighborhood, initial_solution_type
-            ),
-            _ => format!(
-                "Local Search ({:?}, {:?}, Init: {:?})",
-                variant, neighborhood, initial_solution_type
-            ),
-        };
-        Self {
+        if let SearchVariant::SampledSteepest(fraction) = variant {
+            assert!(
+                fraction > 0.0 && fraction <= 1.0,
+                "Sample fraction must be in (0, 1], got {}",
+                fraction
+            );
+        }
+        let mut local_search = Self {
             variant,
             neighborhood,
             initial_solution_type,
-            name_str,
+            initial_solution: None,
+            max_sideways: 0,
+            full_fallback: false,
+            adaptive_max_k: None,
+            lk_pass: None,
+            size_slack: 0,
+            objective: Objective::SumOfCycles,
+            open_paths: false,
+            trajectory: None,
+            oracle_check: false,
+            name_str: String::new(),
+        };
+        local_search.name_str = local_search.build_name();
+        local_search
+    }
+
+    /// Lets the search take up to `max_sideways` consecutive zero-delta moves when no
+    /// improving move exists, to cross plateaus of equal cost. The counter resets after every
+    /// improving move. Not supported by `MoveListSteepest`, whose list keeps improving moves only.
+    pub fn with_sideways(mut self, max_sideways: usize) -> Self {
+        self.max_sideways = max_sideways;
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Makes candidate search scan the full neighbourhood once whenever the candidate lists
+    /// have no improving move left, and continue if that finds one, so it stops at the same
+    /// kind of local optimum as plain steepest search while most steps stay cheap.
+    pub fn with_full_fallback(mut self) -> Self {
+        assert!(
+            matches!(self.variant, SearchVariant::CandidateSteepest(_)),
+            "The full-neighbourhood fallback only applies to candidate search"
+        );
+        self.full_fallback = true;
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Makes candidate search start from the `k` nearest candidates of the variant and double
+    /// the scanned prefix of the precomputed (distance-sorted) lists, up to `max_k`, whenever
+    /// it finds no improving move, so small lists do the cheap early descent and larger ones
+    /// only pay off near the local optimum. The lists must be precomputed with at least
+    /// `max_k` entries to make full use of it.
+    pub fn with_adaptive_k(mut self, max_k: usize) -> Self {
+        let SearchVariant::CandidateSteepest(k) = self.variant else {
+            panic!("Adaptive k only applies to candidate search");
+        };
+        assert!(
+            k >= 1 && max_k >= k,
+            "Adaptive k needs 1 <= k <= max_k, got k={} and max_k={}",
+            k,
+            max_k
+        );
+        self.adaptive_max_k = Some(max_k);
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Runs a Lin–Kernighan style pass of depth `max_depth` on both cycles whenever the
+    /// neighbourhood has no improving move left; the search resumes if the pass improved.
+    pub fn with_lk_pass(mut self, max_depth: usize) -> Self {
+        self.lk_pass = Some(LinKernighan::new(max_depth));
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Allows cycle sizes to deviate by up to `size_slack` vertices and adds single-vertex
+    /// transfers between the cycles to the steepest, greedy and candidate neighbourhoods.
+    pub fn with_size_slack(mut self, size_slack: usize) -> Self {
+        self.size_slack = size_slack;
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Minimises `objective` instead of the total length. Not supported by
+    /// `MoveListSteepest`, whose stored deltas would go stale as the cycle lengths change.
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        debug_assert!(
+            objective.is_separable() || self.variant != SearchVariant::MoveListSteepest,
+            "MoveListSteepest only supports the sum objective"
+        );
+        self.objective = objective;
+        self.name_str = self.build_name();
+        self
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    pub fn size_slack(&self) -> usize {
+        self.size_slack
+    }
+
+    /// Solves the two-path variant: the closing link of each cycle is free. Solutions passed
+    /// to [`Self::improve`] are switched to this mode. Not supported by `MoveListSteepest`,
+    /// whose stored deltas depend on where the paths end.
+    pub fn with_open_paths(mut self) -> Self {
+        assert!(
+            self.variant != SearchVariant::MoveListSteepest,
+            "MoveListSteepest only supports closed cycles"
+        );
+        self.open_paths = true;
+        self.name_str = self.build_name();
+        self
+    }
+
+    pub fn open_paths(&self) -> bool {
+        self.open_paths
+    }
+
+    /// Starts every run from a copy of `solution` instead of constructing one, e.g. to keep
+    /// improving a solution saved by an earlier run.
+    pub fn with_initial_solution(mut self, solution: Solution) -> Self {
+        self.initial_solution = Some(Arc::new(solution));
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Records every run's start solution, applied moves and LK checkpoints to `path` as
+    /// JSON lines, overwriting the file at the start of each run. Meant for single runs whose
+    /// cost mismatches need debugging; see [`crate::algorithms::local_search::trajectory`].
+    pub fn with_trajectory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.trajectory = Some(path.into());
+        self
+    }
+
+    /// Cross-checks every step against [`oracle::best_improving_move`], which scans the full
+    /// neighbourhood by brute force. A step misses when the search stops while an improving
+    /// move exists or, for `Steepest` and `MoveListSteepest`, takes a move worse than the
+    /// best one; misses are printed as warnings and counted in the report. Candidate search
+    /// misses by design when its lists omit the improving moves. Very slow; meant for small
+    /// instances.
+    pub fn with_oracle_check(mut self) -> Self {
+        self.oracle_check = true;
+        self.name_str = self.build_name();
+        self
+    }
+
+    /// Whether moving one vertex out of cycle `from` keeps the sizes within the slack.
+    fn transfer_allowed(&self, solution: &Solution, from: CycleId) -> bool {
+        if self.size_slack == 0 {
+            return false;
         }
+        let n = solution.cycle1.len() + solution.cycle2.len();
+        let new_len1 = match from {
+            CycleId::Cycle1 => solution.cycle1.len() as isize - 1,
+            CycleId::Cycle2 => solution.cycle1.len() as isize + 1,
+        };
+        (new_len1 - n.div_ceil(2) as isize).unsigned_abs() <= self.size_slack
+    }
+
+    fn build_name(&self) -> String {
+        let mut extras = String::new();
+        if self.objective != Objective::SumOfCycles {
+            extras.push_str(&format!(", Objective: {:?}", self.objective));
+        }
+        if self.open_paths {
+            extras.push_str(", Open paths");
+        }
+        if self.size_slack > 0 {
+            extras.push_str(&format!(", Slack: {}", self.size_slack));
+        }
+        if self.max_sideways > 0 {
+            extras.push_str(&format!(", Sideways: {}", self.max_sideways));
+        }
+        if self.full_fallback {
+            extras.push_str(", Full fallback");
+        }
+        if let Some(lk) = self.lk_pass {
+            extras.push_str(&format!(", LK depth: {}", lk.max_depth));
+        }
+        if self.oracle_check {
+            extras.push_str(", Oracle check");
+        }
+        let neighborhood = self.neighborhood;
+        let init = match self.initial_solution {
+            Some(_) => "Given".to_string(),
+            None => format!("{:?}", self.initial_solution_type),
+        };
+        match self.variant {
+            SearchVariant::CandidateSteepest(k) => match self.adaptive_max_k {
+                Some(max_k) => format!(
+                    "Local Search (Candidate k={}..{}, {:?}, Init: {}{})",
+                    k, max_k, neighborhood, init, extras
+                ),
+                None => format!(
+                    "Local Search (Candidate k={}, {:?}, Init: {}{})",
+                    k, neighborhood, init, extras
+                ),
+            },
+            SearchVariant::MoveListSteepest => format!(
+                "Local Search (MoveListSteepest, {:?}, Init: {}{})",
+                neighborhood, init, extras
+            ),
+            variant => format!(
+                "Local Search ({:?}, {:?}, Init: {}{})",
+                variant, neighborhood, init, extras
+            ),
+        }
+    }
+
+    /// Rescores `evaluated` under the search objective for the current `cycle_costs` and keeps
+    /// it if worth collecting: improving moves always, zero-delta moves only when sideways
+    /// steps are enabled.
+    #[inline]
+    fn score_move(
+        &self,
+        mut evaluated: EvaluatedMove,
+        cycle_costs: (Cost, Cost),
+    ) -> Option<EvaluatedMove> {
+        evaluated.delta = self.objective.delta(cycle_costs, evaluated.cycle_deltas);
+        let delta = evaluated.delta;
+        (delta.is_improvement() || (delta.approx_eq(Cost::ZERO) && self.max_sideways > 0))
+            .then_some(evaluated)
     }
 
-    fn generate_initial_solution(&self, instance: &TsplibInstance) -> Solution {
+    /// The configured starting solution, before any improvement, drawing a random start or
+    /// the start vertices of a construction from `rng`.
+    pub fn generate_initial_solution(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+    ) -> Solution {
+        if let Some(solution) = &self.initial_solution {
+            return Solution::clone(solution);
+        }
         match self.initial_solution_type {
-            InitialSolutionType::Random => generate_random_solution(instance),
+            InitialSolutionType::Random => generate_random_solution(instance, rng),
             InitialSolutionType::Heuristic(heuristic) => match heuristic {
                 HeuristicAlgorithm::WeightedRegret => {
                     let constructive_algo = WeightedRegretCycle::default();
                     let mut dummy_callback = |_: String| {};
-                    constructive_algo.solve_with_feedback(instance, &mut dummy_callback)
+                    constructive_algo.solve_with_feedback(instance, rng, &mut dummy_callback)
+                }
+                HeuristicAlgorithm::NearestNeighborInsertion => {
+                    let constructive_algo = NearestNeighborInsertion::default();
+                    let mut dummy_callback = |_: String| {};
+                    constructive_algo.solve_with_feedback(instance, rng, &mut dummy_callback)
+                }
+                HeuristicAlgorithm::ConvexHullInsertion => {
+                    let constructive_algo = ConvexHullInsertion::default();
+                    let mut dummy_callback = |_: String| {};
+                    constructive_algo.solve_with_feedback(instance, rng, &mut dummy_callback)
+                }
+                HeuristicAlgorithm::HilbertCurve => {
+                    let constructive_algo = HilbertCurve::default();
+                    let mut dummy_callback = |_: String| {};
+                    constructive_algo.solve_with_feedback(instance, rng, &mut dummy_callback)
+                }
+                HeuristicAlgorithm::GreedyEdge => {
+                    let constructive_algo = GreedyEdge::new();
+                    let mut dummy_callback = |_: String| {};
+                    constructive_algo.solve_with_feedback(instance, rng, &mut dummy_callback)
+                }
+                HeuristicAlgorithm::PrizeCollectingInsertion => {
+                    let constructive_algo = PrizeCollectingInsertion::default();
+                    let mut dummy_callback = |_: String| {};
+                    constructive_algo.solve_with_feedback(instance, rng, &mut dummy_callback)
                 }
             },
         }
     }
-}
 
-impl TspAlgorithm for LocalSearch {
-    fn name(&self) -> &str {
-        &self.name_str
+    /// Runs the local search in place starting from `current_solution` and returns its
+    /// final cost. Metaheuristics use this to improve perturbed solutions without
+    /// constructing (or cloning) a fresh starting point.
+    pub fn improve<R: Rng + ?Sized>(
+        &self,
+        instance: &TsplibInstance,
+        current_solution: &mut Solution,
+        rng: &mut R,
+        progress_callback: ProgressCallback,
+    ) -> Cost {
+        self.improve_within(
+            instance,
+            current_solution,
+            &Budget::unlimited(),
+            rng,
+            progress_callback,
+        )
     }
 
-    fn solve_with_feedback(
+    /// [`Self::improve`] that stops early, short of a local optimum, once `budget` runs out.
+    pub fn improve_within<R: Rng + ?Sized>(
+        &self,
+        instance: &TsplibInstance,
+        current_solution: &mut Solution,
+        budget: &Budget,
+        rng: &mut R,
+        progress_callback: ProgressCallback,
+    ) -> Cost {
+        self.improve_with_report(instance, current_solution, budget, rng, progress_callback)
+            .final_cost
+    }
+
+    /// [`Self::solve_with_feedback`] from a fresh initial solution, stopping early once
+    /// `budget` runs out.
+    pub fn solve_within(
         &self,
         instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
     ) -> Solution {
-        let mut current_solution = self.generate_initial_solution(instance);
-        let mut current_cost = current_solution.calculate_cost(instance);
-        let mut rng = thread_rng();
+        let mut current_solution = self.generate_initial_solution(instance, rng);
+        self.improve_within(
+            instance,
+            &mut current_solution,
+            budget,
+            rng,
+            progress_callback,
+        );
+        current_solution
+    }
+
+    /// Restarts the local search from fresh initial solutions until `budget` runs out and
+    /// returns the best local optimum with the best cost over time, so the search compares
+    /// with the metaheuristics on the same budget. The budget also cuts the last run short,
+    /// but there is always at least one.
+    pub fn solve_traced(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo, ConvergenceTrace) {
+        let mut trace = ConvergenceTrace::new();
+        let (solution, info) = self.restart(instance, budget, rng, progress_callback, &mut trace);
+        (solution, info, trace)
+    }
+
+    fn restart(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        trace: &mut ConvergenceTrace,
+    ) -> (Solution, IterationInfo) {
+        let mut best: Option<(Solution, Cost)> = None;
+        let mut restarts = 0;
+        let mut evaluations = 0;
+        while best.is_none() || !budget.exhausted() {
+            restarts += 1;
+            let mut solution = self.generate_initial_solution(instance, rng);
+            budget.count_solution();
+            let report = self.improve_with_report(instance, &mut solution, budget, rng, &mut |s| {
+                progress_callback(nested(&s))
+            });
+            evaluations += report.evaluated.total();
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_cost)| report.final_cost < *best_cost)
+            {
+                trace.record(budget.elapsed(), report.final_cost);
+                progress_callback(format!(
+                    "[Restart {}] New best local optimum: {}",
+                    restarts, report.final_cost
+                ));
+                best = Some((solution, report.final_cost));
+            }
+        }
+
+        trace.finish(budget.elapsed());
+        let (solution, best_cost) = best.expect("at least one run");
+        progress_callback(format!(
+            "Restarted local search finished. Restarts: {}, Best cost: {}",
+            restarts, best_cost
+        ));
+        let info = IterationInfo {
+            unit: IterationUnit::Restarts,
+            outer: restarts,
+            inner_ls_runs: restarts,
+            evaluations: Some(evaluations),
+        };
+        (solution, info)
+    }
+
+    /// Like [`Self::solve_with_feedback`], also returning the run's move statistics.
+    pub fn solve_with_report(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, LocalSearchReport) {
+        let mut current_solution = self.generate_initial_solution(instance, rng);
+        let report = self.improve_with_report(
+            instance,
+            &mut current_solution,
+            &Budget::unlimited(),
+            rng,
+            progress_callback,
+        );
+        (current_solution, report)
+    }
+
+    /// [`Self::improve_within`] with per-move-type statistics of the run.
+    pub fn improve_with_report<R: Rng + ?Sized>(
+        &self,
+        instance: &TsplibInstance,
+        current_solution: &mut Solution,
+        budget: &Budget,
+        rng: &mut R,
+        progress_callback: ProgressCallback,
+    ) -> LocalSearchReport {
+        let start_time = Instant::now();
+        let mut report = LocalSearchReport {
+            runs: 1,
+            ..Default::default()
+        };
+        current_solution.is_closed = !self.open_paths;
+        let mut cycle_costs = current_solution.cycle_costs(instance);
+        let mut current_cost = self.objective.value(cycle_costs);
         let mut iteration = 0;
+        let mut recorder = self.trajectory.as_ref().and_then(|path| {
+            TrajectoryRecorder::create(path, instance, current_solution)
+                .map_err(|e| eprintln!("[WARN] Cannot record trajectory: {}", e))
+                .ok()
+        });
 
         let mut move_list: Vec<EvaluatedMove> = Vec::new();
+        // Vertices are looked up, and move-list moves checked against the current edges,
+        // through an index of the solution that each applied move updates, instead of by
+        // scanning the cycles.
+        let mut solution_index = SolutionIndex::new(current_solution);
         if self.variant == SearchVariant::MoveListSteepest {
-            move_list = self.generate_all_improving_moves(instance, &current_solution);
-            move_list.sort_unstable_by_key(|m| m.delta);
+            move_list = self.generate_all_improving_moves(
+                instance,
+                current_solution,
+                cycle_costs,
+                &mut report.evaluated,
+                budget,
+            );
+            report.improving_found += move_list.len() as u64;
+            move_list.sort_unstable_by(EvaluatedMove::steepest_order);
         }
 
+        // Reused across iterations so the neighbourhood scan does not reallocate every step.
+        let mut current_improving_moves: Vec<EvaluatedMove> = Vec::new();
+        let mut sideways_steps = 0;
+        // Candidate list prefix scanned; without adaptive k, the whole precomputed lists.
+        let (mut candidate_k, max_candidate_k) = match (self.variant, self.adaptive_max_k) {
+            (SearchVariant::CandidateSteepest(k), Some(max_k)) => (k, max_k),
+            _ => (usize::MAX, usize::MAX),
+        };
+        // Copy of the solution before the latest move, for filing cost-mismatch incidents.
+        let mut solution_before_apply = current_solution.clone();
+
         loop {
+            if budget.exhausted() {
+                report.truncated += 1;
+                progress_callback(format!(
+                    "[Truncated] Budget exhausted. Final Cost: {}",
+                    current_cost
+                ));
+                break;
+            }
             iteration += 1;
             let cost_before_iter = current_cost;
             progress_callback(format!("[Iter: {}] Cost: {}", iteration, current_cost));
@@ -118,28 +642,103 @@ impl TspAlgorithm for LocalSearch {
             let mut found_improving_move = false;
             let mut best_move_index_in_list: Option<usize> = None;
 
-            let mut current_improving_moves: Vec<EvaluatedMove> = Vec::new();
+            current_improving_moves.clear();
 
             match self.variant {
                 SearchVariant::Steepest | SearchVariant::Greedy => {
-                    current_improving_moves =
-                        self.generate_all_improving_moves(instance, &current_solution);
+                    self.collect_all_improving_moves(
+                        instance,
+                        current_solution,
+                        cycle_costs,
+                        &mut current_improving_moves,
+                        &mut report.evaluated,
+                        budget,
+                    );
                 }
-                SearchVariant::CandidateSteepest(k) => {
-                    current_improving_moves =
-                        self.generate_candidate_moves(instance, &current_solution, k);
+                SearchVariant::CandidateSteepest(_) => {
+                    loop {
+                        self.collect_candidate_moves(
+                            instance,
+                            current_solution,
+                            &solution_index,
+                            cycle_costs,
+                            candidate_k,
+                            &mut current_improving_moves,
+                            &mut report.evaluated,
+                        );
+                        if candidate_k >= max_candidate_k
+                            || current_improving_moves
+                                .iter()
+                                .any(|m| m.delta.is_improvement())
+                        {
+                            break;
+                        }
+                        candidate_k = (candidate_k * 2).min(max_candidate_k);
+                        progress_callback(format!(
+                            "[Iter: {}] Enlarging candidate lists to k={}",
+                            iteration, candidate_k
+                        ));
+                        current_improving_moves.clear();
+                    }
+                    // Zero-delta candidates (sideways steps) do not count as improving.
+                    if self.full_fallback
+                        && !current_improving_moves
+                            .iter()
+                            .any(|m| m.delta.is_improvement())
+                    {
+                        report.fallback_scans += 1;
+                        self.collect_all_improving_moves(
+                            instance,
+                            current_solution,
+                            cycle_costs,
+                            &mut current_improving_moves,
+                            &mut report.evaluated,
+                            budget,
+                        );
+                        if current_improving_moves
+                            .iter()
+                            .any(|m| m.delta.is_improvement())
+                        {
+                            report.fallback_hits += 1;
+                        }
+                    }
+                }
+                SearchVariant::SampledSteepest(fraction) => {
+                    self.collect_sampled_moves(
+                        instance,
+                        current_solution,
+                        cycle_costs,
+                        fraction,
+                        &mut current_improving_moves,
+                        &mut report.evaluated,
+                        rng,
+                    );
+                    // An empty sample does not prove a local optimum; confirm with a full scan.
+                    if current_improving_moves.is_empty() {
+                        self.collect_all_improving_moves(
+                            instance,
+                            current_solution,
+                            cycle_costs,
+                            &mut current_improving_moves,
+                            &mut report.evaluated,
+                            budget,
+                        );
+                    }
                 }
                 SearchVariant::MoveListSteepest => {}
             }
-
-            best_evaluated_move = None;
-            found_improving_move = false;
+            report.improving_found += current_improving_moves
+                .iter()
+                .filter(|m| m.delta.is_improvement())
+                .count() as u64;
 
             match self.variant {
-                SearchVariant::Steepest | SearchVariant::CandidateSteepest(_) => {
+                SearchVariant::Steepest
+                | SearchVariant::CandidateSteepest(_)
+                | SearchVariant::SampledSteepest(_) => {
                     best_evaluated_move = current_improving_moves
                         .iter()
-                        .min_by_key(|m| m.delta)
+                        .min_by(|a, b| a.steepest_order(b))
                         .cloned();
 
                     if best_evaluated_move.is_some() {
@@ -147,18 +746,36 @@ impl TspAlgorithm for LocalSearch {
                     }
                 }
                 SearchVariant::Greedy => {
-                    current_improving_moves.shuffle(&mut rng);
-                    if let Some(first_move) = current_improving_moves.into_iter().next() {
-                        best_evaluated_move = Some(first_move);
+                    current_improving_moves.shuffle(rng);
+                    // Sideways candidates are only taken when nothing improves.
+                    if let Some(first_move) = current_improving_moves
+                        .iter()
+                        .find(|m| m.delta.is_improvement())
+                        .or_else(|| current_improving_moves.first())
+                    {
+                        best_evaluated_move = Some(first_move.clone());
                         found_improving_move = true;
                     }
                 }
                 SearchVariant::MoveListSteepest => {
-                    for (index, evaluated_move) in move_list.iter().enumerate() {
-                        if evaluated_move.delta < 0
-                            && self.is_move_valid(&current_solution, &evaluated_move.move_type)
+                    // Stored moves are rescored at their vertices' current positions, so a
+                    // move whose edges survived but whose neighbours changed is not applied
+                    // with a stale delta.
+                    for (index, stored) in move_list.iter().enumerate() {
+                        if !stored.delta.is_improvement() {
+                            continue;
+                        }
+                        report.evaluated.add(stored.move_type.kind(), 1);
+                        if let Some(evaluated) = evaluate_move_indexed(
+                            current_solution,
+                            &solution_index,
+                            instance,
+                            &stored.move_type,
+                        )
+                        .and_then(|evaluated| self.score_move(evaluated, cycle_costs))
+                        .filter(|evaluated| evaluated.delta.is_improvement())
                         {
-                            best_evaluated_move = Some(evaluated_move.clone());
+                            best_evaluated_move = Some(evaluated);
                             found_improving_move = true;
                             best_move_index_in_list = Some(index);
                             break;
@@ -167,46 +784,119 @@ impl TspAlgorithm for LocalSearch {
                 }
             }
 
+            if self.oracle_check {
+                self.check_against_oracle(
+                    instance,
+                    current_solution,
+                    iteration,
+                    best_evaluated_move.as_ref(),
+                    &mut report,
+                );
+            }
+
+            if found_improving_move
+                && let Some(evaluated) = &best_evaluated_move
+                && !evaluated.delta.is_improvement()
+            {
+                if sideways_steps >= self.max_sideways {
+                    found_improving_move = false;
+                } else {
+                    sideways_steps += 1;
+                }
+            } else if found_improving_move {
+                sideways_steps = 0;
+            }
+
             if found_improving_move {
                 let applied_move = best_evaluated_move.unwrap();
+                report.applied.add(applied_move.move_type.kind(), 1);
                 let cost_before_apply = current_cost;
-                applied_move.move_type.apply(&mut current_solution);
+                if let Some(rec) = &mut recorder
+                    && let Err(e) =
+                        rec.record_move(iteration, &applied_move, cycle_costs.0 + cycle_costs.1)
+                {
+                    eprintln!("[WARN] Stopped recording trajectory: {}", e);
+                    recorder = None;
+                }
+                solution_before_apply.clone_from(current_solution);
+                applied_move
+                    .move_type
+                    .apply_indexed(current_solution, &mut solution_index);
                 current_cost += applied_move.delta;
+                cycle_costs.0 += applied_move.cycle_deltas.0;
+                cycle_costs.1 += applied_move.cycle_deltas.1;
 
-                let real_cost_after_apply = current_solution.calculate_cost(instance);
-                if real_cost_after_apply != current_cost {
-                    eprintln!(
-                        "[WARN] Cost mismatch after apply! Iter: {}, Move: {:?}, Delta: {}, Cost before: {}, Incremental cost: {}, Real cost: {}",
-                        iteration,
-                        applied_move.move_type,
-                        applied_move.delta,
-                        cost_before_apply,
-                        current_cost,
-                        real_cost_after_apply
+                let real_cycle_costs = current_solution.cycle_costs(instance);
+                let real_cost_after_apply = self.objective.value(real_cycle_costs);
+                if !real_cost_after_apply.approx_eq(current_cost)
+                    || !real_cycle_costs.0.approx_eq(cycle_costs.0)
+                    || !real_cycle_costs.1.approx_eq(cycle_costs.1)
+                {
+                    let real_cycle_costs_before = solution_before_apply.cycle_costs(instance);
+                    let actual_deltas = (
+                        real_cycle_costs.0 - real_cycle_costs_before.0,
+                        real_cycle_costs.1 - real_cycle_costs_before.1,
                     );
+                    if let Some(rec) = &mut recorder
+                        && let Err(e) =
+                            rec.record_mismatch(iteration, applied_move.cycle_deltas, actual_deltas)
+                    {
+                        eprintln!("[WARN] Stopped recording trajectory: {}", e);
+                        recorder = None;
+                    }
+                    match record_cost_mismatch(
+                        Path::new(INCIDENT_DIR),
+                        instance,
+                        &solution_before_apply,
+                        iteration,
+                        &applied_move,
+                        actual_deltas,
+                    ) {
+                        Ok(path) => eprintln!(
+                            "[WARN] Cost mismatch after apply, incident saved to {}",
+                            path.display()
+                        ),
+                        Err(e) => eprintln!(
+                            "[WARN] Cost mismatch after apply! Iter: {}, Move: {:?}, Delta: {}, Cost before: {}, Incremental cost: {}, Real cost: {} (cannot save the incident: {})",
+                            iteration,
+                            applied_move.move_type,
+                            applied_move.delta,
+                            cost_before_apply,
+                            current_cost,
+                            real_cost_after_apply,
+                            e
+                        ),
+                    }
                     current_cost = real_cost_after_apply;
+                    cycle_costs = real_cycle_costs;
                 }
 
                 if self.variant == SearchVariant::MoveListSteepest {
                     if let Some(applied_index) = best_move_index_in_list {
                         move_list.remove(applied_index);
 
-                        let affected_nodes = self
-                            .identify_affected_nodes(&applied_move.move_type, &current_solution);
+                        let affected_nodes = self.identify_affected_nodes(
+                            &applied_move.move_type,
+                            current_solution,
+                            &solution_index,
+                        );
 
                         move_list
                             .retain(|m| !self.move_involves_nodes(&m.move_type, &affected_nodes));
 
                         let new_potential_moves = self.generate_moves_around_nodes(
                             instance,
-                            &current_solution,
+                            current_solution,
+                            &solution_index,
                             &affected_nodes,
+                            &mut report.evaluated,
                         );
+                        report.improving_found += new_potential_moves.len() as u64;
 
-                        let mut existing_moves_set: HashSet<Move> =
+                        let mut existing_moves_set: FxHashSet<Move> =
                             move_list.iter().map(|em| em.move_type.clone()).collect();
                         for new_move in new_potential_moves {
-                            if new_move.delta < 0
+                            if new_move.delta.is_improvement()
                                 && !existing_moves_set.contains(&new_move.move_type)
                             {
                                 move_list.push(new_move);
@@ -215,12 +905,15 @@ impl TspAlgorithm for LocalSearch {
                             }
                         }
 
-                        move_list.sort_unstable_by_key(|m| m.delta);
+                        move_list.sort_unstable_by(EvaluatedMove::steepest_order);
                     } else {
                         eprintln!("[WARN] MoveListSteepest applied a move but had no index?");
                     }
                 }
-                if current_cost >= cost_before_iter {
+                let change = current_cost - cost_before_iter;
+                if (-change).is_improvement()
+                    || (!change.is_improvement() && applied_move.delta.is_improvement())
+                {
                     progress_callback(format!(
                         "[Finished] No significant cost improvement. Final Cost: {}",
                         current_cost
@@ -228,6 +921,45 @@ impl TspAlgorithm for LocalSearch {
                     break;
                 }
             } else {
+                // A scan cut short at the deadline proves no local optimum.
+                if budget.past_deadline() {
+                    report.truncated += 1;
+                    progress_callback(format!(
+                        "[Truncated] Budget exhausted. Final Cost: {}",
+                        current_cost
+                    ));
+                    break;
+                }
+                if let Some(lk) = &self.lk_pass {
+                    let gain = lk.improve_within(instance, current_solution, budget);
+                    if gain > Cost::ZERO {
+                        // LK only shortens cycles, so monotone objectives cannot get worse.
+                        cycle_costs = current_solution.cycle_costs(instance);
+                        current_cost = self.objective.value(cycle_costs);
+                        if let Some(rec) = &mut recorder
+                            && let Err(e) = rec.record_checkpoint(iteration, current_solution)
+                        {
+                            eprintln!("[WARN] Stopped recording trajectory: {}", e);
+                            recorder = None;
+                        }
+                        progress_callback(format!(
+                            "[Iter: {}] LK pass gained {}. Cost: {}",
+                            iteration, gain, current_cost
+                        ));
+                        solution_index = SolutionIndex::new(current_solution);
+                        if self.variant == SearchVariant::MoveListSteepest {
+                            move_list = self.generate_all_improving_moves(
+                                instance,
+                                current_solution,
+                                cycle_costs,
+                                &mut report.evaluated,
+                                budget,
+                            );
+                            move_list.sort_unstable_by(EvaluatedMove::steepest_order);
+                        }
+                        continue;
+                    }
+                }
                 progress_callback(format!(
                     "[Finished] Local optimum found or no improving moves. Final Cost: {}",
                     current_cost
@@ -236,13 +968,75 @@ impl TspAlgorithm for LocalSearch {
             }
         }
 
-        current_solution
+        report.iterations = iteration;
+        report.final_cost = current_cost;
+        report.elapsed = start_time.elapsed();
+        report
+    }
+}
+
+impl TspAlgorithm for LocalSearch {
+    fn name(&self) -> &str {
+        &self.name_str
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        self.solve_within(instance, &Budget::unlimited(), rng, progress_callback)
     }
 }
 
 impl LocalSearch {
-    fn get_neighbors(&self, solution: &Solution, node: usize) -> (Option<usize>, Option<usize>) {
-        if let Some((cycle_id, pos)) = solution.find_node(node) {
+    /// Compares `taken`, the move the search picked at `iteration` (`None` when it stops),
+    /// with the oracle's best move, and warns and counts in `report` if the search missed.
+    fn check_against_oracle(
+        &self,
+        instance: &TsplibInstance,
+        solution: &Solution,
+        iteration: u64,
+        taken: Option<&EvaluatedMove>,
+        report: &mut LocalSearchReport,
+    ) {
+        let Some(best) = oracle::best_improving_move(
+            solution,
+            instance,
+            self.neighborhood,
+            self.objective,
+            self.size_slack,
+        ) else {
+            return;
+        };
+        let exhaustive = matches!(
+            self.variant,
+            SearchVariant::Steepest | SearchVariant::MoveListSteepest
+        );
+        let missed = match taken.filter(|m| m.delta.is_improvement()) {
+            Some(taken) => exhaustive && !taken.delta.approx_eq(best.delta),
+            None => true,
+        };
+        if missed {
+            report.oracle_misses += 1;
+            eprintln!(
+                "[WARN] Oracle check at iter {}: the search took {:?}, but {:?} has delta {}",
+                iteration,
+                taken.map(|m| (&m.move_type, m.delta)),
+                best.move_type,
+                best.delta
+            );
+        }
+    }
+
+    fn get_neighbors(
+        &self,
+        solution: &Solution,
+        index: &SolutionIndex,
+        node: usize,
+    ) -> (Option<usize>, Option<usize>) {
+        if let Some((cycle_id, pos)) = index.find_node(node) {
             let cycle = solution.get_cycle(cycle_id);
             let n = cycle.len();
             if n <= 1 {
@@ -261,163 +1055,302 @@ impl LocalSearch {
         &self,
         instance: &TsplibInstance,
         solution: &Solution,
+        cycle_costs: (Cost, Cost),
+        evaluated: &mut MoveCounts,
+        budget: &Budget,
     ) -> Vec<EvaluatedMove> {
         let mut moves = Vec::new();
+        self.collect_all_improving_moves(
+            instance,
+            solution,
+            cycle_costs,
+            &mut moves,
+            evaluated,
+            budget,
+        );
+        moves
+    }
 
-        for pos1 in 0..solution.cycle1.len() {
-            for pos2 in 0..solution.cycle2.len() {
-                if let Some(m) = evaluate_inter_route_exchange(solution, instance, pos1, pos2) {
-                    if m.delta < 0 {
-                        moves.push(m);
-                    }
-                }
-            }
-        }
+    /// Appends every improving move of the full neighbourhood to `moves`.
+    /// Evaluation is split across the rayon pool by the first position of each move, and the
+    /// rows of positions not yet started are skipped once the deadline of `budget` passes, so
+    /// one scan of a large instance cannot run far past it. `evaluated` counts the moves of
+    /// the rows scanned.
+    fn collect_all_improving_moves(
+        &self,
+        instance: &TsplibInstance,
+        solution: &Solution,
+        cycle_costs: (Cost, Cost),
+        moves: &mut Vec<EvaluatedMove>,
+        evaluated: &mut MoveCounts,
+        budget: &Budget,
+    ) {
+        let n1 = solution.cycle1.len();
+        let n2 = solution.cycle2.len();
+        let scanned = AtomicU64::new(0);
+        moves.par_extend(
+            (0..n1)
+                .into_par_iter()
+                .filter(|_| scan_row(budget, &scanned, n2))
+                .flat_map_iter(|pos1| {
+                    (0..n2)
+                        .filter_map(move |pos2| {
+                            evaluate_inter_route_exchange(solution, instance, pos1, pos2)
+                        })
+                        .filter_map(move |m| self.score_move(m, cycle_costs))
+                }),
+        );
+        evaluated.add(MoveKind::InterRouteExchange, scanned.into_inner());
 
         for cycle_id in [CycleId::Cycle1, CycleId::Cycle2] {
-            let cycle_vec = solution.get_cycle(cycle_id);
-            let n = cycle_vec.len();
+            let n = solution.get_cycle(cycle_id).len();
+            let scanned = AtomicU64::new(0);
             match self.neighborhood {
                 NeighborhoodType::VertexExchange => {
                     if n >= 2 {
-                        for pos1 in 0..n {
-                            for pos2 in pos1 + 1..n {
-                                if let Some(m) = evaluate_intra_route_vertex_exchange(
-                                    solution, instance, cycle_id, pos1, pos2,
-                                ) {
-                                    if m.delta < 0 {
-                                        moves.push(m);
-                                    }
-                                }
-                            }
-                        }
+                        moves.par_extend(
+                            (0..n)
+                                .into_par_iter()
+                                .filter(|&pos1| scan_row(budget, &scanned, n - 1 - pos1))
+                                .flat_map_iter(|pos1| {
+                                    (pos1 + 1..n)
+                                        .filter_map(move |pos2| {
+                                            evaluate_intra_route_vertex_exchange(
+                                                solution, instance, cycle_id, pos1, pos2,
+                                            )
+                                        })
+                                        .filter_map(move |m| self.score_move(m, cycle_costs))
+                                }),
+                        );
+                        evaluated.add(MoveKind::IntraRouteVertexExchange, scanned.into_inner());
                     }
                 }
                 NeighborhoodType::EdgeExchange => {
                     if n >= 3 {
-                        for pos1 in 0..n {
-                            for pos2_offset in 2..n {
-                                let pos2 = (pos1 + pos2_offset) % n;
-                                if pos1 < pos2 || (pos2 == 0 && pos1 == n - 1) {
-                                    if !(pos1 == 0 && pos2 == n - 1) {
-                                        if let Some(m) = evaluate_intra_route_edge_exchange(
-                                            solution, instance, cycle_id, pos1, pos2,
-                                        ) {
-                                            if m.delta < 0 {
-                                                moves.push(m);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                        // Row `pos1` pairs edge (pos1, pos1+1) with the later edges sharing no
+                        // vertex with it; the first row also skips the closing edge.
+                        let row_len =
+                            |pos1: usize| (n - 2).saturating_sub(pos1) - usize::from(pos1 == 0);
+                        moves.par_extend(
+                            (0..n)
+                                .into_par_iter()
+                                .filter(|&pos1| scan_row(budget, &scanned, row_len(pos1)))
+                                .flat_map_iter(|pos1| {
+                                    (2..n)
+                                        .map(move |pos2_offset| (pos1 + pos2_offset) % n)
+                                        .filter(move |&pos2| {
+                                            (pos1 < pos2 || (pos2 == 0 && pos1 == n - 1))
+                                                && !(pos1 == 0 && pos2 == n - 1)
+                                        })
+                                        .filter_map(move |pos2| {
+                                            evaluate_intra_route_edge_exchange(
+                                                solution, instance, cycle_id, pos1, pos2,
+                                            )
+                                        })
+                                        .filter_map(move |m| self.score_move(m, cycle_costs))
+                                }),
+                        );
+                        evaluated.add(MoveKind::IntraRouteEdgeExchange, scanned.into_inner());
                     }
                 }
             }
         }
-        moves
+
+        for from in [CycleId::Cycle1, CycleId::Cycle2] {
+            if !self.transfer_allowed(solution, from) {
+                continue;
+            }
+            let ns = solution.get_cycle(from).len();
+            let nt = n1 + n2 - ns;
+            let scanned = AtomicU64::new(0);
+            moves.par_extend(
+                (0..ns)
+                    .into_par_iter()
+                    .filter(|_| scan_row(budget, &scanned, nt))
+                    .flat_map_iter(|pos| {
+                        (0..nt)
+                            .filter_map(move |after_pos| {
+                                evaluate_inter_route_transfer(
+                                    solution, instance, from, pos, after_pos,
+                                )
+                            })
+                            .filter_map(move |m| self.score_move(m, cycle_costs))
+                    }),
+            );
+            evaluated.add(MoveKind::InterRouteTransfer, scanned.into_inner());
+        }
     }
 
-    fn generate_candidate_moves(
+    /// Appends every improving candidate move (nearest-neighbour restricted, to the first `k`
+    /// entries of every list) to `moves`. Each source node's candidate list is evaluated on
+    /// the rayon pool, with the vertices looked up in `index`.
+    #[allow(clippy::too_many_arguments)]
+    fn collect_candidate_moves(
         &self,
         instance: &TsplibInstance,
         solution: &Solution,
+        index: &SolutionIndex,
+        cycle_costs: (Cost, Cost),
         k: usize,
-    ) -> Vec<EvaluatedMove> {
-        let mut moves = Vec::new();
-        for node_a in 0..instance.dimension {
-            let neighbors = instance.get_nearest_neighbors(node_a);
-            let node_a_info_opt = solution.find_node(node_a);
-            if node_a_info_opt.is_none() {
-                continue;
-            }
-            let (cycle_a, pos_a) = node_a_info_opt.unwrap();
-
-            for &node_b in neighbors {
-                if node_a == node_b {
-                    continue;
-                }
-                let node_b_info_opt = solution.find_node(node_b);
-                if node_b_info_opt.is_none() {
-                    continue;
-                }
-                let (cycle_b, pos_b) = node_b_info_opt.unwrap();
+        moves: &mut Vec<EvaluatedMove>,
+        evaluated: &mut MoveCounts,
+    ) {
+        let counters: [AtomicU64; MoveKind::ALL.len()] = Default::default();
+        moves.par_extend(
+            (0..instance.dimension)
+                .into_par_iter()
+                .filter_map(|node_a| index.find_node(node_a).map(|info| (node_a, info)))
+                .flat_map_iter(|(node_a, (cycle_a, pos_a))| {
+                    instance
+                        .get_nearest_neighbors(node_a)
+                        .iter()
+                        .take(k)
+                        .flat_map(move |&node_b| {
+                            self.evaluate_candidate_pair(
+                                instance, solution, index, node_a, cycle_a, pos_a, node_b, k,
+                            )
+                        })
+                        .flatten()
+                        .inspect(|m| {
+                            counters[m.move_type.kind().index()].fetch_add(1, Ordering::Relaxed);
+                        })
+                        .filter_map(move |m| self.score_move(m, cycle_costs))
+                }),
+        );
+        for kind in MoveKind::ALL {
+            evaluated.add(kind, counters[kind.index()].load(Ordering::Relaxed));
+        }
+    }
 
-                if cycle_a != cycle_b {
-                    let (actual_pos_a, actual_pos_b) = if cycle_a == CycleId::Cycle1 {
-                        (pos_a, pos_b)
-                    } else {
-                        (pos_b, pos_a)
-                    };
-                    if let Some(m) = evaluate_inter_route_exchange(
-                        solution,
-                        instance,
-                        actual_pos_a,
-                        actual_pos_b,
-                    ) {
-                        if m.delta < 0 {
-                            moves.push(m);
-                        }
-                    }
-                } else {
-                    match self.neighborhood {
-                        NeighborhoodType::EdgeExchange => {
-                            if let Some(m) = evaluate_candidate_intra_route_edge_exchange(
-                                solution, instance, cycle_a, pos_a, pos_b,
-                            ) {
-                                if m.delta < 0 {
-                                    moves.push(m);
-                                }
-                            }
-                        }
-                        NeighborhoodType::VertexExchange => {
-                            if let Some(m) = evaluate_intra_route_vertex_exchange(
-                                solution, instance, cycle_a, pos_a, pos_b,
-                            ) {
-                                if m.delta < 0 {
-                                    moves.push(m);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    /// Appends the acceptable moves among `ceil(fraction * |N|)` moves drawn uniformly (with
+    /// replacement) from the full neighbourhood `N`. Sampling runs on the rayon pool, each
+    /// sample drawn from its own child of a stream seeded from `rng`.
+    #[allow(clippy::too_many_arguments)]
+    fn collect_sampled_moves<R: Rng + ?Sized>(
+        &self,
+        instance: &TsplibInstance,
+        solution: &Solution,
+        cycle_costs: (Cost, Cost),
+        fraction: f64,
+        moves: &mut Vec<EvaluatedMove>,
+        evaluated: &mut MoveCounts,
+        rng: &mut R,
+    ) {
+        let size = neighborhood_size(solution, self.neighborhood);
+        let samples = ((size as f64 * fraction).ceil() as usize).clamp(1, size.max(1));
+        let counters: [AtomicU64; MoveKind::ALL.len()] = Default::default();
+        let streams = SeedStream::from_rng(rng);
+        moves.par_extend(
+            (0..samples)
+                .into_par_iter()
+                .map(|sample| {
+                    let mut rng = streams.child(sample as u64).rng();
+                    sample_move(solution, instance, self.neighborhood, &mut rng)
+                })
+                .flatten()
+                .inspect(|m| {
+                    counters[m.move_type.kind().index()].fetch_add(1, Ordering::Relaxed);
+                })
+                .filter_map(move |m| self.score_move(m, cycle_costs)),
+        );
+        for kind in MoveKind::ALL {
+            evaluated.add(kind, counters[kind.index()].load(Ordering::Relaxed));
         }
-        moves
     }
 
-    fn is_move_valid(&self, solution: &Solution, move_type: &Move) -> bool {
-        match move_type {
-            Move::InterRouteExchange { v1, v2 } => {
-                let info1 = solution.find_node(*v1);
-                let info2 = solution.find_node(*v2);
-                match (info1, info2) {
-                    (Some((c1, _)), Some((c2, _))) => c1 != c2,
-                    _ => false,
+    /// Evaluates the moves a candidate pair (a, b) stands for, i.e. moves introducing the
+    /// edge (a, b). In the same cycle with edge exchange these are the 2-opt moves replacing
+    /// the successor edges of a and b or their predecessor edges; across cycles, the
+    /// exchanges putting b in place of a's successor or predecessor. `k` is the prefix of
+    /// the candidate lists being scanned.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate_candidate_pair(
+        &self,
+        instance: &TsplibInstance,
+        solution: &Solution,
+        index: &SolutionIndex,
+        node_a: usize,
+        cycle_a: CycleId,
+        pos_a: usize,
+        node_b: usize,
+        k: usize,
+    ) -> [Option<EvaluatedMove>; 4] {
+        if node_a == node_b {
+            return [None, None, None, None];
+        }
+        let Some((cycle_b, pos_b)) = index.find_node(node_b) else {
+            return [None, None, None, None];
+        };
+
+        if cycle_a != cycle_b {
+            // Exchanging b with a's successor or predecessor moves b next to a, creating the
+            // candidate edge (a, b) while both cycles keep their sizes.
+            let n_a = solution.get_cycle(cycle_a).len();
+            let exchange_with = |pos_in_a: usize| {
+                if cycle_a == CycleId::Cycle1 {
+                    evaluate_inter_route_exchange(solution, instance, pos_in_a, pos_b)
+                } else {
+                    evaluate_inter_route_exchange(solution, instance, pos_b, pos_in_a)
                 }
+            };
+            if n_a < 2 {
+                return [None, None, None, None];
             }
-            Move::IntraRouteVertexExchange { v1, v2, cycle } => {
-                let info1 = solution.find_node(*v1);
-                let info2 = solution.find_node(*v2);
-                match (info1, info2) {
-                    (Some((c1, _)), Some((c2, _))) => c1 == *cycle && c2 == *cycle,
-                    _ => false,
-                }
+            // With size slack, b can also move next to a on its own.
+            let transfer_after = |after_pos: usize| {
+                self.transfer_allowed(solution, cycle_b)
+                    .then(|| {
+                        evaluate_inter_route_transfer(solution, instance, cycle_b, pos_b, after_pos)
+                    })
+                    .flatten()
+            };
+            [
+                exchange_with((pos_a + 1) % n_a),
+                exchange_with((pos_a + n_a - 1) % n_a),
+                transfer_after(pos_a),
+                transfer_after((pos_a + n_a - 1) % n_a),
+            ]
+        } else {
+            // Within a cycle, the pair (b, a) stands for the same moves; when b's scanned
+            // prefix lists a too, only the smaller vertex evaluates them.
+            if node_b < node_a
+                && instance.is_neighbor(node_b, node_a)
+                && instance.get_nearest_neighbors(node_b).len() <= k
+            {
+                return [None, None, None, None];
             }
-            Move::IntraRouteEdgeExchange { a, b, c, d, cycle } => {
-                let edge1_check = solution.check_edge_in_cycle(solution.get_cycle(*cycle), *a, *b);
-                let edge2_check = solution.check_edge_in_cycle(solution.get_cycle(*cycle), *c, *d);
-                edge1_check == Some(1) && edge2_check == Some(1)
+            match self.neighborhood {
+                NeighborhoodType::EdgeExchange => [
+                    evaluate_candidate_intra_route_edge_exchange(
+                        solution, instance, cycle_a, pos_a, pos_b,
+                    ),
+                    evaluate_candidate_intra_route_edge_exchange_backward(
+                        solution, instance, cycle_a, pos_a, pos_b,
+                    ),
+                    None,
+                    None,
+                ],
+                NeighborhoodType::VertexExchange => [
+                    evaluate_intra_route_vertex_exchange(solution, instance, cycle_a, pos_a, pos_b),
+                    None,
+                    None,
+                    None,
+                ],
             }
         }
     }
 
-    fn identify_affected_nodes(&self, applied_move: &Move, solution: &Solution) -> HashSet<usize> {
-        let mut affected = HashSet::new();
+    fn identify_affected_nodes(
+        &self,
+        applied_move: &Move,
+        solution: &Solution,
+        index: &SolutionIndex,
+    ) -> FxHashSet<usize> {
+        let mut affected = FxHashSet::default();
 
-        let mut add_node_and_neighbors = |node: usize, affected: &mut HashSet<usize>| {
+        let add_node_and_neighbors = |node: usize, affected: &mut FxHashSet<usize>| {
             let _newly_inserted = affected.insert(node);
-            if let (Some(pred), Some(succ)) = self.get_neighbors(solution, node) {
+            if let (Some(pred), Some(succ)) = self.get_neighbors(solution, index, node) {
                 affected.insert(pred);
                 affected.insert(succ);
             }
@@ -438,11 +1371,15 @@ impl LocalSearch {
                 add_node_and_neighbors(*c, &mut affected);
                 add_node_and_neighbors(*d, &mut affected);
             }
+            Move::InterRouteTransfer { v, after, .. } => {
+                add_node_and_neighbors(*v, &mut affected);
+                add_node_and_neighbors(*after, &mut affected);
+            }
         }
         affected
     }
 
-    fn move_involves_nodes(&self, move_type: &Move, affected_nodes: &HashSet<usize>) -> bool {
+    fn move_involves_nodes(&self, move_type: &Move, affected_nodes: &FxHashSet<usize>) -> bool {
         if affected_nodes.is_empty() {
             return false;
         }
@@ -459,6 +1396,9 @@ impl LocalSearch {
                     || affected_nodes.contains(c)
                     || affected_nodes.contains(d)
             }
+            Move::InterRouteTransfer { v, after, .. } => {
+                affected_nodes.contains(v) || affected_nodes.contains(after)
+            }
         }
     }
 
@@ -466,26 +1406,27 @@ impl LocalSearch {
         &self,
         instance: &TsplibInstance,
         solution: &Solution,
-        affected_nodes: &HashSet<usize>,
+        index: &SolutionIndex,
+        affected_nodes: &FxHashSet<usize>,
+        evaluated: &mut MoveCounts,
     ) -> Vec<EvaluatedMove> {
         let mut new_moves = Vec::new();
         if affected_nodes.is_empty() {
             return new_moves;
         }
 
-        let mut considered_vertex_pairs = HashSet::new();
-        let mut considered_inter_pairs = HashSet::new();
+        let mut considered_vertex_pairs = FxHashSet::default();
+        let mut considered_inter_pairs = FxHashSet::default();
 
         for &node_a in affected_nodes {
-            if let Some((cycle_id_a, pos_a)) = solution.find_node(node_a) {
+            if let Some((cycle_id_a, pos_a)) = index.find_node(node_a) {
                 let other_cycle_id = if cycle_id_a == CycleId::Cycle1 {
                     CycleId::Cycle2
                 } else {
                     CycleId::Cycle1
                 };
                 let other_cycle = solution.get_cycle(other_cycle_id);
-                for pos_b in 0..other_cycle.len() {
-                    let node_b = other_cycle[pos_b];
+                for (pos_b, &node_b) in other_cycle.iter().enumerate() {
                     let pair = if node_a < node_b {
                         (node_a, node_b)
                     } else {
@@ -500,7 +1441,8 @@ impl LocalSearch {
                         if let Some(m) =
                             evaluate_inter_route_exchange(solution, instance, eval_pos1, eval_pos2)
                         {
-                            if m.delta < 0 {
+                            evaluated.add(m.move_type.kind(), 1);
+                            if m.delta.is_improvement() {
                                 new_moves.push(m);
                             }
                         }
@@ -509,8 +1451,7 @@ impl LocalSearch {
 
                 let same_cycle = solution.get_cycle(cycle_id_a);
                 let n = same_cycle.len();
-                for pos_b in 0..n {
-                    let node_b = same_cycle[pos_b];
+                for (pos_b, &node_b) in same_cycle.iter().enumerate() {
                     if node_a == node_b {
                         continue;
                     }
@@ -522,25 +1463,29 @@ impl LocalSearch {
                             } else {
                                 (node_b, node_a)
                             };
-                            if considered_vertex_pairs.insert(pair) {
-                                if let Some(m) = evaluate_intra_route_vertex_exchange(
+                            if considered_vertex_pairs.insert(pair)
+                                && let Some(m) = evaluate_intra_route_vertex_exchange(
                                     solution, instance, cycle_id_a, pos_a, pos_b,
-                                ) {
-                                    if m.delta < 0 {
-                                        new_moves.push(m);
-                                    }
+                                )
+                            {
+                                evaluated.add(m.move_type.kind(), 1);
+                                if m.delta.is_improvement() {
+                                    new_moves.push(m);
                                 }
                             }
                         }
                         NeighborhoodType::EdgeExchange => {
                             let diff = (pos_a as isize - pos_b as isize).abs();
-                            if n >= 3 && diff != 1 && diff != (n - 1) as isize {
-                                if let Some(m) = evaluate_intra_route_edge_exchange(
+                            if n >= 3
+                                && diff != 1
+                                && diff != (n - 1) as isize
+                                && let Some(m) = evaluate_intra_route_edge_exchange(
                                     solution, instance, cycle_id_a, pos_a, pos_b,
-                                ) {
-                                    if m.delta < 0 {
-                                        new_moves.push(m);
-                                    }
+                                )
+                            {
+                                evaluated.add(m.move_type.kind(), 1);
+                                if m.delta.is_improvement() {
+                                    new_moves.push(m);
                                 }
                             }
                         }
@@ -552,3 +1497,13 @@ impl LocalSearch {
         new_moves
     }
 }
+
+/// Whether a row of `row_len` moves of a full scan should run: it should unless the deadline
+/// of `budget` has passed. Adds the moves of a row that runs to `scanned`.
+fn scan_row(budget: &Budget, scanned: &AtomicU64, row_len: usize) -> bool {
+    if budget.past_deadline() {
+        return false;
+    }
+    scanned.fetch_add(row_len as u64, Ordering::Relaxed);
+    true
+}
@@ -0,0 +1,63 @@
+use crate::algorithms::local_search::trajectory::TrajectoryRecorder;
+use crate::moves::types::EvaluatedMove;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::utils::safe_file_name;
+use crate::weight::Cost;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where the local search files cost-mismatch incidents.
+pub const INCIDENT_DIR: &str = "output/incidents";
+
+/// Tells apart incidents filed in the same millisecond.
+static INCIDENT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Files a cost mismatch of the local search: the move `evaluated`, applied at `step` to
+/// `solution_before`, changed the cycle lengths by `actual` instead of its predicted deltas.
+/// The incident is a two-move trajectory (start, move, mismatch) in its own file under `dir`,
+/// so `replay` or the `incidents` subcommand reproduce it until the delta bug is fixed.
+/// Returns the file's path.
+pub fn record_cost_mismatch(
+    dir: &Path,
+    instance: &TsplibInstance,
+    solution_before: &Solution,
+    step: u64,
+    evaluated: &EvaluatedMove,
+    actual: (Cost, Cost),
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    let path = dir.join(format!(
+        "{}-{}-{}.jsonl",
+        safe_file_name(&instance.name),
+        millis,
+        INCIDENT_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let (cost1, cost2) = solution_before.cycle_costs(instance);
+    let mut recorder = TrajectoryRecorder::create(&path, instance, solution_before)?;
+    recorder.record_move(step, evaluated, cost1 + cost2)?;
+    recorder.record_mismatch(step, evaluated.cycle_deltas, actual)?;
+    recorder.finish()?;
+    Ok(path)
+}
+
+/// The incident files in `dir`, sorted by name; none if it does not exist.
+pub fn incident_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .filter(|path| {
+            path.as_ref()
+                .map_or(true, |p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        })
+        .collect::<io::Result<_>>()?;
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,208 @@
+use crate::budget::Budget;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered, Weight};
+use rustc_hash::FxHashSet;
+
+const NOT_IN_CYCLE: usize = usize::MAX;
+
+/// Bounded-depth Lin–Kernighan style improvement within each cycle, built from chained
+/// 2-opt steps. From a base vertex t1 the edge (t1, t2) is broken; each level adds an edge
+/// (t2, t3) to a candidate neighbour, breaks (t4, t3) with t4 the predecessor of t3 and
+/// closes the tour with (t1, t4), which becomes the edge broken on the next level. Levels
+/// continue while the cumulative gain stays positive; the best closed tour of the chain is
+/// kept and deeper levels are undone. Added edges may not be broken again and broken edges
+/// may not be re-added.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinKernighan {
+    pub max_depth: usize,
+}
+
+/// An undirected edge in canonical order.
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// One cycle with a vertex-to-position map, supporting in-place segment reversal.
+struct CycleState<'a> {
+    order: &'a mut Vec<usize>,
+    pos: Vec<usize>,
+}
+
+impl<'a> CycleState<'a> {
+    fn new(order: &'a mut Vec<usize>, dimension: usize) -> Self {
+        let mut pos = vec![NOT_IN_CYCLE; dimension];
+        for (i, &v) in order.iter().enumerate() {
+            pos[v] = i;
+        }
+        Self { order, pos }
+    }
+
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    fn succ(&self, v: usize) -> usize {
+        self.order[(self.pos[v] + 1) % self.len()]
+    }
+
+    fn pred(&self, v: usize) -> usize {
+        self.order[(self.pos[v] + self.len() - 1) % self.len()]
+    }
+
+    fn contains(&self, v: usize) -> bool {
+        self.pos[v] != NOT_IN_CYCLE
+    }
+
+    /// Reverses the segment running forward from position `from` to position `to`
+    /// (inclusive, possibly wrapping).
+    fn reverse(&mut self, from: usize, to: usize) {
+        let n = self.len();
+        let span = (to + n - from) % n + 1;
+        for k in 0..span / 2 {
+            let i = (from + k) % n;
+            let j = (to + n - k) % n;
+            self.order.swap(i, j);
+            self.pos[self.order[i]] = i;
+            self.pos[self.order[j]] = j;
+        }
+    }
+}
+
+impl LinKernighan {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth: max_depth.max(1),
+        }
+    }
+
+    /// Improves both cycles of `solution` in place until no chain improves them or the
+    /// deadline of `budget` has passed, and returns the total gain. Uses the instance's
+    /// candidate lists when precomputed, otherwise every vertex of the cycle is a candidate.
+    /// Open paths are left unchanged: the chains assume every link is paid for.
+    pub fn improve_within(
+        &self,
+        instance: &TsplibInstance,
+        solution: &mut Solution,
+        budget: &Budget,
+    ) -> Cost {
+        if !solution.is_closed {
+            return Cost::ZERO;
+        }
+        let mut total_gain = Cost::ZERO;
+        for cycle in [&mut solution.cycle1, &mut solution.cycle2] {
+            if cycle.len() < 5 {
+                continue;
+            }
+            let mut state = CycleState::new(cycle, instance.size());
+            loop {
+                let mut pass_gain = Cost::ZERO;
+                // The second sweep runs on the reversed cycle, i.e. breaks predecessor edges.
+                for _ in 0..2 {
+                    for i in 0..state.len() {
+                        if budget.past_deadline() {
+                            return total_gain + pass_gain;
+                        }
+                        let t1 = state.order[i];
+                        pass_gain += self.improve_from(instance, &mut state, t1);
+                    }
+                    state.order.reverse();
+                    for (i, &v) in state.order.iter().enumerate() {
+                        state.pos[v] = i;
+                    }
+                }
+                total_gain += pass_gain;
+                if pass_gain == Cost::ZERO {
+                    break;
+                }
+            }
+        }
+        total_gain
+    }
+
+    /// Runs one chain from base vertex `t1`, keeps the best prefix and returns its gain.
+    fn improve_from(&self, instance: &TsplibInstance, state: &mut CycleState, t1: usize) -> Cost {
+        let mut t2 = state.succ(t1);
+        let mut gain = instance.distance(t1, t2);
+        let mut added: FxHashSet<(usize, usize)> = FxHashSet::default();
+        let mut broken: FxHashSet<(usize, usize)> = FxHashSet::default();
+        broken.insert(edge(t1, t2));
+
+        let mut applied: Vec<(usize, usize)> = Vec::with_capacity(self.max_depth);
+        let mut best_gain = Cost::ZERO;
+        let mut best_depth = 0;
+
+        for _ in 0..self.max_depth {
+            let Some((t3, t4, next_gain)) =
+                self.best_step(instance, state, t1, t2, gain, &added, &broken)
+            else {
+                break;
+            };
+
+            // Remove (t1, t2) and (t4, t3), add (t2, t3) and (t1, t4): reversing t2..t4 makes
+            // t4 the successor of t1 and t3 the successor of t2.
+            let (from, to) = (state.pos[t2], state.pos[t4]);
+            state.reverse(from, to);
+            applied.push((from, to));
+            added.insert(edge(t2, t3));
+            broken.insert(edge(t4, t3));
+
+            gain = next_gain;
+            let closed_gain = gain - instance.distance(t1, t4);
+            // Gains within rounding noise would let float costs cycle forever.
+            if (best_gain - closed_gain).is_improvement() {
+                best_gain = closed_gain;
+                best_depth = applied.len();
+            }
+            t2 = t4;
+        }
+
+        while applied.len() > best_depth {
+            let (from, to) = applied.pop().expect("length checked above");
+            state.reverse(from, to);
+        }
+        best_gain
+    }
+
+    /// The next level with the largest `d(t4, t3) - d(t2, t3)` among candidate t3 that keep
+    /// the partial gain positive, as `(t3, t4, partial gain after the level)`.
+    #[allow(clippy::too_many_arguments)]
+    fn best_step(
+        &self,
+        instance: &TsplibInstance,
+        state: &CycleState,
+        t1: usize,
+        t2: usize,
+        gain: Cost,
+        added: &FxHashSet<(usize, usize)>,
+        broken: &FxHashSet<(usize, usize)>,
+    ) -> Option<(usize, usize, Cost)> {
+        let evaluate = |t3: usize| -> Option<(usize, usize, Cost)> {
+            if t3 == t1 || t3 == t2 || !state.contains(t3) || t3 == state.succ(t2) {
+                return None;
+            }
+            let partial = gain - instance.distance(t2, t3);
+            if partial <= Cost::ZERO || broken.contains(&edge(t2, t3)) {
+                return None;
+            }
+            let t4 = state.pred(t3);
+            if t4 == t1 || added.contains(&edge(t4, t3)) {
+                return None;
+            }
+            Some((t3, t4, partial + instance.distance(t4, t3)))
+        };
+
+        if instance.candidate_source().is_some() {
+            instance
+                .get_nearest_neighbors(t2)
+                .iter()
+                .filter_map(|&t3| evaluate(t3))
+                .max_by_key(|&(_, _, g)| Ordered(g))
+        } else {
+            state
+                .order
+                .iter()
+                .filter_map(|&t3| evaluate(t3))
+                .max_by_key(|&(_, _, g)| Ordered(g))
+        }
+    }
+}
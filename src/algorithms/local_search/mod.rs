@@ -1 +1,7 @@
 pub mod base;
+pub mod incidents;
+pub mod lin_kernighan;
+pub mod oracle;
+pub mod report;
+pub mod sampling;
+pub mod trajectory;
@@ -0,0 +1,126 @@
+//! Brute-force reference for the move generators. It enumerates the whole neighbourhood and
+//! scores every move by applying it to a copy and recomputing the cycle lengths, so it shares
+//! no code with the incremental evaluators it checks. Slow; meant for small instances.
+
+use crate::algorithms::local_search::base::NeighborhoodType;
+use crate::moves::types::{CycleId, EvaluatedMove, Move};
+use crate::tsplib::{Objective, Solution, TsplibInstance};
+use crate::weight::Weight;
+use std::cmp::Ordering;
+
+/// Every move of the neighbourhood of `solution`: inter-route exchanges, the intra-route
+/// moves of `neighborhood` in both cycles and, with a nonzero `size_slack`, the transfers
+/// that keep cycle 1 within `size_slack` of `ceil(n/2)` vertices. Moves that would take a
+/// pinned vertex out of its cycle are left out.
+pub fn enumerate_moves(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    neighborhood: NeighborhoodType,
+    size_slack: usize,
+) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for &v1 in &solution.cycle1 {
+        for &v2 in &solution.cycle2 {
+            if !instance.is_pinned(v1) && !instance.is_pinned(v2) {
+                moves.push(Move::InterRouteExchange { v1, v2 });
+            }
+        }
+    }
+
+    for cycle in [CycleId::Cycle1, CycleId::Cycle2] {
+        let vertices = solution.get_cycle(cycle);
+        let n = vertices.len();
+        for i in 0..n {
+            match neighborhood {
+                NeighborhoodType::VertexExchange => {
+                    for j in i + 1..n {
+                        moves.push(Move::IntraRouteVertexExchange {
+                            v1: vertices[i],
+                            v2: vertices[j],
+                            cycle,
+                        });
+                    }
+                }
+                NeighborhoodType::EdgeExchange => {
+                    // Pairs of edges (i, i+1) and (j, j+1) that share no vertex.
+                    for j in i + 2..n {
+                        if i == 0 && j == n - 1 {
+                            continue;
+                        }
+                        moves.push(Move::IntraRouteEdgeExchange {
+                            a: vertices[i],
+                            b: vertices[i + 1],
+                            c: vertices[j],
+                            d: vertices[(j + 1) % n],
+                            cycle,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    if size_slack > 0 {
+        let n = solution.cycle1.len() + solution.cycle2.len();
+        for (from, to) in [
+            (CycleId::Cycle1, CycleId::Cycle2),
+            (CycleId::Cycle2, CycleId::Cycle1),
+        ] {
+            let source = solution.get_cycle(from);
+            let target = solution.get_cycle(to);
+            if source.len() < 2 || target.is_empty() {
+                continue;
+            }
+            let len1_after = match from {
+                CycleId::Cycle1 => solution.cycle1.len() - 1,
+                CycleId::Cycle2 => solution.cycle1.len() + 1,
+            };
+            if len1_after.abs_diff(n.div_ceil(2)) > size_slack {
+                continue;
+            }
+            for &v in source.iter().filter(|&&v| !instance.is_pinned(v)) {
+                for &after in target {
+                    moves.push(Move::InterRouteTransfer { v, from, after });
+                }
+            }
+        }
+    }
+    moves
+}
+
+/// The move of [`enumerate_moves`] that improves `objective` the most, with its true delta
+/// and cycle deltas, or `None` at a local optimum. Ties are broken as in steepest search, by
+/// [`EvaluatedMove::steepest_order`].
+pub fn best_improving_move(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    neighborhood: NeighborhoodType,
+    objective: Objective,
+    size_slack: usize,
+) -> Option<EvaluatedMove> {
+    let cycle_costs = solution.cycle_costs(instance);
+    let cost = objective.value(cycle_costs);
+    let mut scratch = solution.clone();
+    let mut best: Option<EvaluatedMove> = None;
+    for m in enumerate_moves(solution, instance, neighborhood, size_slack) {
+        scratch.clone_from(solution);
+        m.apply(&mut scratch);
+        let costs_after = scratch.cycle_costs(instance);
+        let delta = objective.value(costs_after) - cost;
+        if !delta.is_improvement() {
+            continue;
+        }
+        let mut evaluated = EvaluatedMove::new(
+            m,
+            (costs_after.0 - cycle_costs.0, costs_after.1 - cycle_costs.1),
+        );
+        evaluated.delta = delta;
+        if best
+            .as_ref()
+            .is_none_or(|b| evaluated.steepest_order(b) == Ordering::Less)
+        {
+            best = Some(evaluated);
+        }
+    }
+    best
+}
@@ -0,0 +1,126 @@
+use crate::moves::types::MoveKind;
+use crate::weight::Cost;
+use std::time::Duration;
+
+/// Per-move-type counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MoveCounts([u64; MoveKind::ALL.len()]);
+
+impl MoveCounts {
+    pub fn add(&mut self, kind: MoveKind, count: u64) {
+        self.0[kind.index()] += count;
+    }
+
+    pub fn get(&self, kind: MoveKind) -> u64 {
+        self.0[kind.index()]
+    }
+
+    pub fn total(&self) -> u64 {
+        self.0.iter().sum()
+    }
+
+    pub fn merge(&mut self, other: &MoveCounts) {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a += b;
+        }
+    }
+}
+
+/// What a local search run did, returned next to the solution by
+/// `LocalSearch::improve_with_report` / `solve_with_report`.
+#[derive(Debug, Clone, Default)]
+pub struct LocalSearchReport {
+    /// Main-loop iterations, including the final one that found no move.
+    pub iterations: u64,
+    /// Moves whose delta was computed, per type.
+    pub evaluated: MoveCounts,
+    /// Moves actually applied, per type.
+    pub applied: MoveCounts,
+    /// Evaluated moves that turned out improving (delta < 0).
+    pub improving_found: u64,
+    pub elapsed: Duration,
+    pub final_cost: Cost,
+    /// Number of runs merged into this report (1 for a single run).
+    pub runs: u64,
+    /// Runs stopped by their budget before reaching a local optimum.
+    pub truncated: u64,
+    /// Full-neighbourhood fallback scans of candidate search, and how many of them found an
+    /// improving move the candidate lists had missed.
+    pub fallback_scans: u64,
+    pub fallback_hits: u64,
+    /// Steps where the oracle check found the search missing a better move; see
+    /// `LocalSearch::with_oracle_check`.
+    pub oracle_misses: u64,
+}
+
+impl LocalSearchReport {
+    /// Share of evaluated moves that were improving; for candidate search this is the hit
+    /// rate of the candidate lists.
+    pub fn improving_hit_rate(&self) -> f64 {
+        let evaluated = self.evaluated.total();
+        if evaluated == 0 {
+            0.0
+        } else {
+            self.improving_found as f64 / evaluated as f64
+        }
+    }
+
+    /// Adds another run's counters to this one (`final_cost` keeps the best).
+    pub fn merge(&mut self, other: &LocalSearchReport) {
+        self.final_cost = if self.runs == 0 {
+            other.final_cost
+        } else {
+            self.final_cost.min(other.final_cost)
+        };
+        self.iterations += other.iterations;
+        self.evaluated.merge(&other.evaluated);
+        self.applied.merge(&other.applied);
+        self.improving_found += other.improving_found;
+        self.elapsed += other.elapsed;
+        self.runs += other.runs;
+        self.truncated += other.truncated;
+        self.fallback_scans += other.fallback_scans;
+        self.fallback_hits += other.fallback_hits;
+        self.oracle_misses += other.oracle_misses;
+    }
+
+    /// One-line summary with per-run averages.
+    pub fn format_summary(&self) -> String {
+        let runs = self.runs.max(1) as f64;
+        let per_kind = MoveKind::ALL
+            .iter()
+            .map(|&kind| {
+                format!(
+                    "{:?}: {:.0} eval / {:.1} applied",
+                    kind,
+                    self.evaluated.get(kind) as f64 / runs,
+                    self.applied.get(kind) as f64 / runs
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let fallback = if self.fallback_scans > 0 {
+            format!(
+                ", fallback {:.1} scans / {:.1} hits",
+                self.fallback_scans as f64 / runs,
+                self.fallback_hits as f64 / runs
+            )
+        } else {
+            String::new()
+        };
+        let oracle = if self.oracle_misses > 0 {
+            format!(", {} oracle misses", self.oracle_misses)
+        } else {
+            String::new()
+        };
+        format!(
+            "iters {:.1}, hit rate {:.4}, {:.2} ms/run{}{} | {}",
+            self.iterations as f64 / runs,
+            self.improving_hit_rate(),
+            self.elapsed.as_secs_f64() * 1000.0 / runs,
+            fallback,
+            oracle,
+            per_kind
+        )
+    }
+}
@@ -0,0 +1,67 @@
+use crate::algorithms::local_search::base::NeighborhoodType;
+use crate::moves::inter_route::evaluate_inter_route_exchange;
+use crate::moves::intra_route::{
+    evaluate_intra_route_edge_exchange, evaluate_intra_route_vertex_exchange,
+};
+use crate::moves::types::{CycleId, EvaluatedMove};
+use crate::tsplib::{Solution, TsplibInstance};
+use rand::Rng;
+
+/// Number of intra-route moves of type `neighborhood` in a cycle of `n` vertices.
+pub fn intra_move_count(neighborhood: NeighborhoodType, n: usize) -> usize {
+    match neighborhood {
+        NeighborhoodType::VertexExchange if n >= 2 => n * (n - 1) / 2,
+        NeighborhoodType::EdgeExchange if n >= 4 => n * (n - 3) / 2,
+        _ => 0,
+    }
+}
+
+/// Size of the full neighbourhood: inter-route exchanges plus the intra-route moves of both
+/// cycles.
+pub fn neighborhood_size(solution: &Solution, neighborhood: NeighborhoodType) -> usize {
+    let n1 = solution.cycle1.len();
+    let n2 = solution.cycle2.len();
+    n1 * n2 + intra_move_count(neighborhood, n1) + intra_move_count(neighborhood, n2)
+}
+
+/// Draws a move uniformly from the whole neighbourhood and evaluates its delta.
+pub fn sample_move<R: Rng + ?Sized>(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    neighborhood: NeighborhoodType,
+    rng: &mut R,
+) -> Option<EvaluatedMove> {
+    let n1 = solution.cycle1.len();
+    let n2 = solution.cycle2.len();
+    let inter = n1 * n2;
+    let intra1 = intra_move_count(neighborhood, n1);
+    let total = neighborhood_size(solution, neighborhood);
+    if total == 0 {
+        return None;
+    }
+
+    let r = rng.random_range(0..total);
+    if r < inter {
+        let pos1 = rng.random_range(0..n1);
+        let pos2 = rng.random_range(0..n2);
+        return evaluate_inter_route_exchange(solution, instance, pos1, pos2);
+    }
+
+    let (cycle, n) = if r < inter + intra1 {
+        (CycleId::Cycle1, n1)
+    } else {
+        (CycleId::Cycle2, n2)
+    };
+    let pos1 = rng.random_range(0..n);
+    match neighborhood {
+        NeighborhoodType::VertexExchange => {
+            let pos2 = (pos1 + rng.random_range(1..n)) % n;
+            evaluate_intra_route_vertex_exchange(solution, instance, cycle, pos1, pos2)
+        }
+        NeighborhoodType::EdgeExchange => {
+            // Any edge other than the first one and its two neighbours.
+            let pos2 = (pos1 + rng.random_range(2..n - 1)) % n;
+            evaluate_intra_route_edge_exchange(solution, instance, cycle, pos1, pos2)
+        }
+    }
+}
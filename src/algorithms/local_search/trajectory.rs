@@ -0,0 +1,418 @@
+use crate::flat_json::{FlatObject, join_list, plain_string};
+use crate::moves::types::{CycleId, EvaluatedMove, Move};
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Weight};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// One line of a recorded local search trajectory.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrajectoryEvent {
+    /// The solution the search started from.
+    Start {
+        instance: String,
+        closed: bool,
+        cycle1: Vec<usize>,
+        cycle2: Vec<usize>,
+    },
+    /// An applied move with the cycle length changes the evaluator predicted and the total
+    /// length the search believed the solution had before it.
+    Move {
+        step: u64,
+        move_type: Move,
+        cycle_deltas: (Cost, Cost),
+        cost_before: Cost,
+    },
+    /// The solution after a step that is not a recorded move (a Lin–Kernighan pass).
+    Checkpoint {
+        step: u64,
+        cycle1: Vec<usize>,
+        cycle2: Vec<usize>,
+    },
+    /// The search noticed that the move of `step` changed the cycle lengths by `actual`
+    /// instead of the `expected` deltas. Informational; replays check the move itself.
+    Mismatch {
+        step: u64,
+        expected: (Cost, Cost),
+        actual: (Cost, Cost),
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum TrajectoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("The trajectory does not begin with a start event")]
+    MissingStart,
+}
+
+/// Writes the moves of one local search run as JSON lines: a start event with the initial
+/// cycles, then one event per applied move or checkpoint. Replay with [`replay`].
+pub struct TrajectoryRecorder {
+    writer: BufWriter<File>,
+}
+
+impl TrajectoryRecorder {
+    pub fn create(path: &Path, instance: &TsplibInstance, solution: &Solution) -> io::Result<Self> {
+        let mut recorder = Self {
+            writer: BufWriter::new(File::create(path)?),
+        };
+        recorder.write(&TrajectoryEvent::Start {
+            instance: instance.name.clone(),
+            closed: solution.is_closed,
+            cycle1: solution.cycle1.clone(),
+            cycle2: solution.cycle2.clone(),
+        })?;
+        Ok(recorder)
+    }
+
+    pub fn record_move(
+        &mut self,
+        step: u64,
+        evaluated: &EvaluatedMove,
+        cost_before: Cost,
+    ) -> io::Result<()> {
+        self.write(&TrajectoryEvent::Move {
+            step,
+            move_type: evaluated.move_type.clone(),
+            cycle_deltas: evaluated.cycle_deltas,
+            cost_before,
+        })
+    }
+
+    pub fn record_checkpoint(&mut self, step: u64, solution: &Solution) -> io::Result<()> {
+        self.write(&TrajectoryEvent::Checkpoint {
+            step,
+            cycle1: solution.cycle1.clone(),
+            cycle2: solution.cycle2.clone(),
+        })
+    }
+
+    pub fn record_mismatch(
+        &mut self,
+        step: u64,
+        expected: (Cost, Cost),
+        actual: (Cost, Cost),
+    ) -> io::Result<()> {
+        self.write(&TrajectoryEvent::Mismatch {
+            step,
+            expected,
+            actual,
+        })
+    }
+
+    /// Flushes the file, reporting errors that dropping the recorder would swallow.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write(&mut self, event: &TrajectoryEvent) -> io::Result<()> {
+        writeln!(self.writer, "{}", event)
+    }
+}
+
+fn cycle_number(cycle: CycleId) -> usize {
+    match cycle {
+        CycleId::Cycle1 => 1,
+        CycleId::Cycle2 => 2,
+    }
+}
+
+impl fmt::Display for TrajectoryEvent {
+    /// Flat JSON object on one line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrajectoryEvent::Start {
+                instance,
+                closed,
+                cycle1,
+                cycle2,
+            } => write!(
+                f,
+                r#"{{"event":"start","instance":"{}","closed":{},"cycle1":[{}],"cycle2":[{}]}}"#,
+                plain_string(instance),
+                closed,
+                join_list(cycle1),
+                join_list(cycle2)
+            ),
+            TrajectoryEvent::Move {
+                step,
+                move_type,
+                cycle_deltas,
+                cost_before,
+            } => {
+                write!(
+                    f,
+                    r#"{{"event":"move","step":{},"kind":"{:?}","#,
+                    step,
+                    move_type.kind()
+                )?;
+                match move_type {
+                    Move::InterRouteExchange { v1, v2 } => {
+                        write!(f, r#""v1":{},"v2":{},"#, v1, v2)?
+                    }
+                    Move::IntraRouteVertexExchange { v1, v2, cycle } => write!(
+                        f,
+                        r#""v1":{},"v2":{},"cycle":{},"#,
+                        v1,
+                        v2,
+                        cycle_number(*cycle)
+                    )?,
+                    Move::IntraRouteEdgeExchange { a, b, c, d, cycle } => write!(
+                        f,
+                        r#""a":{},"b":{},"c":{},"d":{},"cycle":{},"#,
+                        a,
+                        b,
+                        c,
+                        d,
+                        cycle_number(*cycle)
+                    )?,
+                    Move::InterRouteTransfer { v, from, after } => write!(
+                        f,
+                        r#""v":{},"from":{},"after":{},"#,
+                        v,
+                        cycle_number(*from),
+                        after
+                    )?,
+                }
+                write!(
+                    f,
+                    r#""delta1":{},"delta2":{},"cost_before":{}}}"#,
+                    cycle_deltas.0, cycle_deltas.1, cost_before
+                )
+            }
+            TrajectoryEvent::Checkpoint {
+                step,
+                cycle1,
+                cycle2,
+            } => write!(
+                f,
+                r#"{{"event":"checkpoint","step":{},"cycle1":[{}],"cycle2":[{}]}}"#,
+                step,
+                join_list(cycle1),
+                join_list(cycle2)
+            ),
+            TrajectoryEvent::Mismatch {
+                step,
+                expected,
+                actual,
+            } => write!(
+                f,
+                r#"{{"event":"mismatch","step":{},"expected1":{},"expected2":{},"actual1":{},"actual2":{}}}"#,
+                step, expected.0, expected.1, actual.0, actual.1
+            ),
+        }
+    }
+}
+
+fn parse_event(fields: &FlatObject) -> Result<TrajectoryEvent, String> {
+    let cycle_id = |key: &str| match fields.int(key)? {
+        1 => Ok(CycleId::Cycle1),
+        2 => Ok(CycleId::Cycle2),
+        other => Err(format!("invalid cycle {} in '{}'", other, key)),
+    };
+
+    match fields.str("event")? {
+        "start" => Ok(TrajectoryEvent::Start {
+            instance: fields.str("instance")?.to_string(),
+            closed: fields.bool("closed")?,
+            cycle1: fields.vertices("cycle1")?,
+            cycle2: fields.vertices("cycle2")?,
+        }),
+        "checkpoint" => Ok(TrajectoryEvent::Checkpoint {
+            step: fields.u64("step")?,
+            cycle1: fields.vertices("cycle1")?,
+            cycle2: fields.vertices("cycle2")?,
+        }),
+        "mismatch" => Ok(TrajectoryEvent::Mismatch {
+            step: fields.u64("step")?,
+            expected: (fields.cost("expected1")?, fields.cost("expected2")?),
+            actual: (fields.cost("actual1")?, fields.cost("actual2")?),
+        }),
+        "move" => {
+            let move_type = match fields.str("kind")? {
+                "InterRouteExchange" => Move::InterRouteExchange {
+                    v1: fields.usize("v1")?,
+                    v2: fields.usize("v2")?,
+                },
+                "IntraRouteVertexExchange" => Move::IntraRouteVertexExchange {
+                    v1: fields.usize("v1")?,
+                    v2: fields.usize("v2")?,
+                    cycle: cycle_id("cycle")?,
+                },
+                "IntraRouteEdgeExchange" => Move::IntraRouteEdgeExchange {
+                    a: fields.usize("a")?,
+                    b: fields.usize("b")?,
+                    c: fields.usize("c")?,
+                    d: fields.usize("d")?,
+                    cycle: cycle_id("cycle")?,
+                },
+                "InterRouteTransfer" => Move::InterRouteTransfer {
+                    v: fields.usize("v")?,
+                    from: cycle_id("from")?,
+                    after: fields.usize("after")?,
+                },
+                other => return Err(format!("unknown move kind '{}'", other)),
+            };
+            Ok(TrajectoryEvent::Move {
+                step: fields.u64("step")?,
+                move_type,
+                cycle_deltas: (fields.cost("delta1")?, fields.cost("delta2")?),
+                cost_before: fields.cost("cost_before")?,
+            })
+        }
+        other => Err(format!("unknown event '{}'", other)),
+    }
+}
+
+/// Reads a trajectory written by [`TrajectoryRecorder`]. Blank lines are skipped.
+pub fn read_trajectory(path: &Path) -> Result<Vec<TrajectoryEvent>, TrajectoryError> {
+    let text = fs::read_to_string(path)?;
+    let mut events = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = FlatObject::parse(line)
+            .and_then(|fields| parse_event(&fields))
+            .map_err(|message| TrajectoryError::Parse {
+                line: idx + 1,
+                message,
+            })?;
+        events.push(event);
+    }
+    if !matches!(events.first(), Some(TrajectoryEvent::Start { .. })) {
+        return Err(TrajectoryError::MissingStart);
+    }
+    Ok(events)
+}
+
+/// Why a replayed step disagrees with the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DivergenceKind {
+    /// The move's vertices are not where the move expects them.
+    NotApplicable,
+    /// The solution length before the move differs from what the search believed.
+    CostDrift { recorded: Cost, actual: Cost },
+    /// Applying the move changed the cycle lengths by other amounts than predicted.
+    DeltaMismatch {
+        recorded: (Cost, Cost),
+        actual: (Cost, Cost),
+    },
+}
+
+/// First step at which a replay disagrees with the recording, with the solution just
+/// before that step.
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    /// 1-based index of the event in the trajectory.
+    pub event: usize,
+    pub step: u64,
+    pub move_type: Move,
+    pub kind: DivergenceKind,
+    pub solution_before: Solution,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Event {} (iteration {}), {:?}: ",
+            self.event, self.step, self.move_type
+        )?;
+        match &self.kind {
+            DivergenceKind::NotApplicable => {
+                write!(f, "the move does not fit the current solution")
+            }
+            DivergenceKind::CostDrift { recorded, actual } => write!(
+                f,
+                "recorded cost before the move {} but the solution costs {} (off by {})",
+                recorded,
+                actual,
+                recorded - actual
+            ),
+            DivergenceKind::DeltaMismatch { recorded, actual } => write!(
+                f,
+                "predicted cycle deltas {:?} but applying it gives {:?} (errors {} / {})",
+                recorded,
+                actual,
+                recorded.0 - actual.0,
+                recorded.1 - actual.1
+            ),
+        }
+    }
+}
+
+/// Re-applies `events` (which must begin with a start event, as checked by
+/// [`read_trajectory`]) on `instance`, checking every recorded cost and delta. Returns the
+/// final solution, or the first divergence.
+pub fn replay(
+    instance: &TsplibInstance,
+    events: &[TrajectoryEvent],
+) -> Result<Solution, Box<Divergence>> {
+    let Some(TrajectoryEvent::Start {
+        closed,
+        cycle1,
+        cycle2,
+        ..
+    }) = events.first()
+    else {
+        panic!("The trajectory must begin with a start event");
+    };
+    let mut solution = Solution::new(cycle1.clone(), cycle2.clone());
+    solution.is_closed = *closed;
+
+    for (idx, event) in events.iter().enumerate().skip(1) {
+        match event {
+            TrajectoryEvent::Start { .. } | TrajectoryEvent::Mismatch { .. } => {}
+            TrajectoryEvent::Checkpoint { cycle1, cycle2, .. } => {
+                solution.cycle1.clone_from(cycle1);
+                solution.cycle2.clone_from(cycle2);
+            }
+            TrajectoryEvent::Move {
+                step,
+                move_type,
+                cycle_deltas,
+                cost_before,
+            } => {
+                let diverged = |kind| {
+                    Box::new(Divergence {
+                        event: idx + 1,
+                        step: *step,
+                        move_type: move_type.clone(),
+                        kind,
+                        solution_before: solution.clone(),
+                    })
+                };
+                let before = solution.cycle_costs(instance);
+                let actual_before = before.0 + before.1;
+                if !actual_before.approx_eq(*cost_before) {
+                    return Err(diverged(DivergenceKind::CostDrift {
+                        recorded: *cost_before,
+                        actual: actual_before,
+                    }));
+                }
+                if !move_type.is_applicable(&solution) {
+                    return Err(diverged(DivergenceKind::NotApplicable));
+                }
+                let mut next = solution.clone();
+                move_type.apply(&mut next);
+                let after = next.cycle_costs(instance);
+                let actual = (after.0 - before.0, after.1 - before.1);
+                if !actual.0.approx_eq(cycle_deltas.0) || !actual.1.approx_eq(cycle_deltas.1) {
+                    return Err(diverged(DivergenceKind::DeltaMismatch {
+                        recorded: *cycle_deltas,
+                        actual,
+                    }));
+                }
+                solution = next;
+            }
+        }
+    }
+    Ok(solution)
+}
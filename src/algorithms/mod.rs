@@ -1,8 +1,12 @@
+pub mod acceptance;
 pub mod constructive;
+pub mod exact;
+pub mod hae;
+pub mod hyperheuristic;
 pub mod ils;
 pub mod lns;
 pub mod local_search;
 pub mod msls;
 pub mod perturbation;
+pub mod population;
 pub mod random_walk;
-pub mod hae;
@@ -1,99 +1,311 @@
 use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::StartStrategy;
+use crate::algorithms::constructive::nearest_neighbor_insertion::NearestNeighborInsertion;
+use crate::algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
 use crate::algorithms::local_search::base::LocalSearch;
+use crate::progress::nested;
+use crate::seed::SeedStream;
 use crate::tsplib::{Solution, TsplibInstance};
 use crate::utils::generate_random_solution;
-use std::time::Instant;
+use crate::weight::{Cost, Ordered};
+use rand::{Rng, RngCore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How an MSLS iteration builds the solution its local search starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Starter {
+    /// Whatever initial solution the base local search is configured with.
+    BaseLocalSearch,
+    Random,
+    /// Weighted 2-regret construction seeded with a random vertex pair.
+    RandomizedRegret,
+    /// Nearest-neighbour insertion seeded with a random vertex pair.
+    NearestNeighbor,
+}
+
+impl Starter {
+    fn start(
+        self,
+        local_search: &LocalSearch,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+    ) -> Solution {
+        let mut quiet = |_: String| {};
+        match self {
+            Starter::BaseLocalSearch => local_search.generate_initial_solution(instance, rng),
+            Starter::Random => generate_random_solution(instance, rng),
+            Starter::RandomizedRegret => WeightedRegretCycle::default()
+                .with_start_strategy(StartStrategy::RandomPair)
+                .solve_with_feedback(instance, rng, &mut quiet),
+            Starter::NearestNeighbor => NearestNeighborInsertion::new(StartStrategy::RandomPair)
+                .solve_with_feedback(instance, rng, &mut quiet),
+        }
+    }
+}
+
+/// Which of the configured starters an iteration uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StarterSelection {
+    /// Cycle through the starters in order.
+    Alternate,
+    /// Draw one uniformly at random every iteration.
+    Random,
+}
+
+/// Parameters of [`Msls`] apart from its base local search. Config files may leave out any
+/// of them, which then keep their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MslsParams {
+    pub iterations: usize,
+    pub starters: Vec<Starter>,
+    pub selection: StarterSelection,
+    /// Whether the iterations run on the rayon thread pool.
+    pub parallel: bool,
+}
+
+impl Default for MslsParams {
+    fn default() -> Self {
+        Self {
+            iterations: 200,
+            starters: vec![Starter::BaseLocalSearch],
+            selection: StarterSelection::Alternate,
+            parallel: false,
+        }
+    }
+}
 
 pub struct Msls {
     base_local_search: LocalSearch,
-    iterations: usize,
+    params: MslsParams,
     name_str: String,
 }
 
+/// Result of one MSLS run with its timing.
+#[derive(Debug, Clone)]
+pub struct MslsOutcome {
+    pub solution: Solution,
+    /// Starter of the iteration that found `solution`.
+    pub starter: Starter,
+    /// Time of all iterations summed; equal to the wall time up to overhead when sequential.
+    pub cpu_time: Duration,
+}
+
+/// One finished iteration.
+struct IterationResult {
+    index: usize,
+    solution: Solution,
+    cost: Cost,
+    starter: Starter,
+    elapsed: Duration,
+}
+
 impl Msls {
     pub fn new(base_local_search: LocalSearch, iterations: usize) -> Self {
-        let name_str = format!(
-            "MSLS (Base: {}, Iterations: {})",
-            base_local_search.name(),
-            iterations
+        // Iterations start from the base LS's own initial solution unless `with_starters`
+        // picks other starters, so it should be configured for random starts.
+        Self::from_params(
+            base_local_search,
+            MslsParams {
+                iterations,
+                ..MslsParams::default()
+            },
+        )
+    }
+
+    pub fn from_params(base_local_search: LocalSearch, params: MslsParams) -> Self {
+        assert!(
+            !params.starters.is_empty(),
+            "MSLS needs at least one starter"
         );
-        // Ensure the base LS starts randomly for MSLS
-        // We might need to adjust LocalSearch or clone+modify it here if it doesn't always start randomly.
-        // Assuming the passed base_local_search is configured for random starts.
-        Self {
+        let mut msls = Self {
             base_local_search,
-            iterations,
-            name_str,
-        }
+            params,
+            name_str: String::new(),
+        };
+        msls.name_str = msls.build_name();
+        msls
     }
-}
 
-impl TspAlgorithm for Msls {
-    fn name(&self) -> &str {
-        &self.name_str
+    /// Starts the iterations from `starters`, chosen by `selection`, instead of the base
+    /// local search's own initial solution.
+    pub fn with_starters(mut self, starters: Vec<Starter>, selection: StarterSelection) -> Self {
+        assert!(!starters.is_empty(), "MSLS needs at least one starter");
+        self.params.starters = starters;
+        self.params.selection = selection;
+        self.name_str = self.build_name();
+        self
     }
 
-    fn solve_with_feedback(
+    /// Runs the independent iterations in parallel. Per-iteration progress is not reported
+    /// then, and [`MslsOutcome::cpu_time`] rather than the wall time measures the work done.
+    pub fn with_parallel(mut self) -> Self {
+        self.params.parallel = true;
+        self.name_str = self.build_name();
+        self
+    }
+
+    fn build_name(&self) -> String {
+        let mut name = format!(
+            "MSLS (Base: {}, Iterations: {}",
+            self.base_local_search.name(),
+            self.params.iterations
+        );
+        if self.params.starters != [Starter::BaseLocalSearch] {
+            let names: Vec<String> = self
+                .params
+                .starters
+                .iter()
+                .map(|s| format!("{:?}", s))
+                .collect();
+            name += &format!(
+                ", Starters: {:?} {}",
+                self.params.selection,
+                names.join("/")
+            );
+        }
+        if self.params.parallel {
+            name += ", parallel";
+        }
+        name + ")"
+    }
+
+    /// [`TspAlgorithm::solve_with_feedback`] that also returns the starter of the best run.
+    pub fn solve_with_starter(
         &self,
         instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
-    ) -> Solution {
-        let mut best_solution: Option<Solution> = None;
-        let mut best_cost = i32::MAX;
+    ) -> (Solution, Starter) {
+        let outcome = self.solve_with_outcome(instance, rng, progress_callback);
+        (outcome.solution, outcome.starter)
+    }
 
+    /// Runs all iterations and returns the best solution with its starter and the run's
+    /// wall and summed iteration time.
+    pub fn solve_with_outcome(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> MslsOutcome {
         let start_time = Instant::now();
 
-        for i in 0..self.iterations {
-            // Generate a new random solution for each iteration
-            // Note: The base LocalSearch also generates an initial solution.
-            // We rely on the base_local_search being configured with InitialSolutionType::Random.
-            // If not, we would need to generate random here and pass it to a modified LS::solve.
-
-            let iter_start_time = Instant::now();
-            let mut iter_callback = |status: String| {
-                progress_callback(format!(
-                    "[MSLS Iter {}/{}] BaseLS: {}",
-                    i + 1,
-                    self.iterations,
-                    status
-                ));
-            };
-
-            // Run the base local search
-            let current_solution = self
-                .base_local_search
-                .solve_with_feedback(instance, &mut iter_callback);
-
-            let current_cost = current_solution.calculate_cost(instance);
-            let iter_elapsed = iter_start_time.elapsed();
+        // Starters are drawn up front and every iteration draws from its own stream, so the
+        // parallel iterations need no shared RNG and find what the sequential ones do.
+        let starters: Vec<Starter> = (0..self.params.iterations)
+            .map(|i| match self.params.selection {
+                StarterSelection::Alternate => self.params.starters[i % self.params.starters.len()],
+                StarterSelection::Random => {
+                    self.params.starters[rng.random_range(0..self.params.starters.len())]
+                }
+            })
+            .collect();
+        let streams = SeedStream::from_rng(rng);
 
+        let results: Vec<IterationResult> = if self.params.parallel {
             progress_callback(format!(
-                "[MSLS Iter {}/{}] Finished. Cost: {}, Time: {:?}. Best: {}",
-                i + 1,
-                self.iterations,
-                current_cost,
-                iter_elapsed,
-                best_cost
+                "[MSLS] Running {} iterations in parallel",
+                self.params.iterations
             ));
-
-            if current_cost < best_cost {
-                best_cost = current_cost;
-                best_solution = Some(current_solution);
+            starters
+                .par_iter()
+                .enumerate()
+                .map(|(i, &starter)| {
+                    let mut rng = streams.child(i as u64).rng();
+                    self.run_iteration(instance, i, starter, &mut rng, &mut |_| {})
+                })
+                .collect()
+        } else {
+            let mut results = Vec::with_capacity(self.params.iterations);
+            let mut best_cost = Cost::MAX;
+            for (i, &starter) in starters.iter().enumerate() {
+                let mut iter_callback = |status: String| progress_callback(nested(&status));
+                let mut rng = streams.child(i as u64).rng();
+                let result = self.run_iteration(instance, i, starter, &mut rng, &mut iter_callback);
                 progress_callback(format!(
-                    "[MSLS Iter {}/{}] New best solution found: {}",
+                    "[MSLS Iter {}/{}] Finished ({:?}). Cost: {}, Time: {:?}. Best: {}",
                     i + 1,
-                    self.iterations,
+                    self.params.iterations,
+                    starter,
+                    result.cost,
+                    result.elapsed,
                     best_cost
                 ));
+                if result.cost < best_cost {
+                    best_cost = result.cost;
+                    progress_callback(format!(
+                        "[MSLS Iter {}/{}] New best solution found: {} ({:?})",
+                        i + 1,
+                        self.params.iterations,
+                        best_cost,
+                        starter
+                    ));
+                }
+                results.push(result);
             }
-        }
+            results
+        };
+        let cpu_time: Duration = results.iter().map(|r| r.elapsed).sum();
+        // Ties go to the earliest iteration, as in a sequential run.
+        let best = results
+            .into_iter()
+            .min_by_key(|r| (Ordered(r.cost), r.index))
+            .expect("MSLS should find at least one solution");
 
-        let total_elapsed = start_time.elapsed();
         progress_callback(format!(
-            "[MSLS Finished] Total time: {:?}, Best cost: {}",
-            total_elapsed, best_cost
+            "[MSLS Finished] Total time: {:?} (summed iterations: {:?}), Best cost: {}",
+            start_time.elapsed(),
+            cpu_time,
+            best.cost
         ));
 
-        best_solution.expect("MSLS should find at least one solution")
+        MslsOutcome {
+            solution: best.solution,
+            starter: best.starter,
+            cpu_time,
+        }
+    }
+
+    /// Builds the starter's solution and improves it with the base local search.
+    fn run_iteration(
+        &self,
+        instance: &TsplibInstance,
+        index: usize,
+        starter: Starter,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> IterationResult {
+        let iter_start_time = Instant::now();
+        let mut solution = starter.start(&self.base_local_search, instance, rng);
+        let cost = self
+            .base_local_search
+            .improve(instance, &mut solution, rng, progress_callback);
+        IterationResult {
+            index,
+            solution,
+            cost,
+            starter,
+            elapsed: iter_start_time.elapsed(),
+        }
+    }
+}
+
+impl TspAlgorithm for Msls {
+    fn name(&self) -> &str {
+        &self.name_str
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        self.solve_with_starter(instance, rng, progress_callback).0
     }
 }
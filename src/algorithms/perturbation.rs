@@ -1,109 +1,397 @@
-use crate::algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
-use crate::moves::types::{CycleId, Move};
+use crate::budget::Budget;
+use crate::moves::types::{CycleId, Move, MoveKind};
+use crate::seed::SeedStream;
 use crate::tsplib::{Solution, TsplibInstance};
-use rand::Rng;
-use rand::seq::{IndexedMutRandom, SliceRandom};
+use crate::weight::{Cost, Ordered, Weight};
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, RngCore};
+use rayon::prelude::*;
 use std::collections::HashSet;
 
-pub trait Perturbation {
+/// Changes a solution between two local searches, either with a kick of random moves (ILS)
+/// or by destroying and repairing part of it (LNS). The trait is object safe, so ILS and LNS
+/// take any perturbation as a `Box<dyn Perturbation>`.
+pub trait Perturbation: Send + Sync {
     fn name(&self) -> String;
-    fn perturb<R: Rng + ?Sized>(
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore);
+
+    /// [`Self::perturb`] for perturbations whose repair step can be cut short once `budget`
+    /// runs out; the result is still a complete solution.
+    fn perturb_within(
         &self,
         solution: &mut Solution,
         instance: &TsplibInstance,
-        rng: &mut R,
-    );
+        rng: &mut dyn RngCore,
+        _budget: &Budget,
+    ) {
+        self.perturb(solution, instance, rng);
+    }
+}
+
+// --- ILS kicks ---
+
+/// The ILS perturbations the experiment can be run with, by `--ils-kick`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kick {
+    /// [`SmallPerturbation`] of `num_moves` moves, drawn from the default [`MoveMix`] or,
+    /// with an `inter_route_bias`, from [`MoveMix::inter_route_biased`].
+    Small {
+        num_moves: usize,
+        inter_route_bias: Option<f64>,
+    },
+    /// [`SegmentReversalPerturbation`] of `min_len..=max_len` vertices.
+    SegmentReversal { min_len: usize, max_len: usize },
+    /// [`SegmentSwapPerturbation`] of `min_len..=max_len` vertices.
+    SegmentSwap { min_len: usize, max_len: usize },
+}
+
+impl Default for Kick {
+    fn default() -> Self {
+        Kick::Small {
+            num_moves: 10,
+            inter_route_bias: None,
+        }
+    }
+}
+
+impl Kick {
+    /// Parses `small:<moves>[,<inter-route bias>]`, `reversal:<min len>,<max len>` or
+    /// `swap:<min len>,<max len>`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (kind, parameters) = text.split_once(':')?;
+        match kind {
+            "small" => {
+                let (num_moves, bias) = match parameters.split_once(',') {
+                    Some((num_moves, bias)) => (num_moves, Some(bias.parse::<f64>().ok()?)),
+                    None => (parameters, None),
+                };
+                let num_moves: usize = num_moves.parse().ok()?;
+                (num_moves >= 1 && bias.is_none_or(|bias| (0.0..=1.0).contains(&bias))).then_some(
+                    Kick::Small {
+                        num_moves,
+                        inter_route_bias: bias,
+                    },
+                )
+            }
+            "reversal" | "swap" => {
+                let (min_len, max_len) = parameters.split_once(',')?;
+                let min_len: usize = min_len.parse().ok()?;
+                let max_len: usize = max_len.parse().ok()?;
+                if kind == "reversal" {
+                    (min_len >= 2 && min_len <= max_len)
+                        .then_some(Kick::SegmentReversal { min_len, max_len })
+                } else {
+                    (min_len >= 1 && min_len <= max_len)
+                        .then_some(Kick::SegmentSwap { min_len, max_len })
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn build(self) -> Box<dyn Perturbation> {
+        match self {
+            Kick::Small {
+                num_moves,
+                inter_route_bias,
+            } => {
+                let perturbation = SmallPerturbation::new(num_moves);
+                Box::new(match inter_route_bias {
+                    Some(bias) => perturbation.with_move_mix(MoveMix::inter_route_biased(bias)),
+                    None => perturbation,
+                })
+            }
+            Kick::SegmentReversal { min_len, max_len } => {
+                Box::new(SegmentReversalPerturbation::new(min_len, max_len))
+            }
+            Kick::SegmentSwap { min_len, max_len } => {
+                Box::new(SegmentSwapPerturbation::new(min_len, max_len))
+            }
+        }
+    }
+}
+
+// --- LNS destroy operators ---
+
+/// The LNS destroy operators the experiment can be run with, by `--lns-destroy`. All of them
+/// repair by weighted regret.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Destroy {
+    /// [`LargePerturbation`] removing `fraction` of the vertices.
+    Random { fraction: f64 },
+    /// [`ClusterPerturbation`] removing a cluster of `size` around a random vertex.
+    Cluster(ClusterSize),
+    /// [`WorstPerturbation`] removing `fraction` of the vertices, drawn with `determinism`.
+    Worst { fraction: f64, determinism: f64 },
+    /// [`SegmentPerturbation`] removing `min_len..=max_len` vertices from each cycle.
+    Segment { min_len: usize, max_len: usize },
+}
+
+impl Default for Destroy {
+    fn default() -> Self {
+        Destroy::Random { fraction: 0.2 }
+    }
+}
+
+impl Destroy {
+    /// Parses `random:<fraction>`, `nearest:<vertices>`, `radius:<distance>`,
+    /// `worst:<fraction>,<determinism>` or `segment:<min len>,<max len>`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (kind, parameters) = text.split_once(':')?;
+        let fraction = |text: &str| {
+            text.parse::<f64>()
+                .ok()
+                .filter(|fraction| *fraction > 0.0 && *fraction < 1.0)
+        };
+        match kind {
+            "random" => Some(Destroy::Random {
+                fraction: fraction(parameters)?,
+            }),
+            "nearest" => {
+                let count: usize = parameters.parse().ok()?;
+                (count > 0).then_some(Destroy::Cluster(ClusterSize::Nearest(count)))
+            }
+            "radius" => {
+                let radius: Cost = parameters.parse().ok()?;
+                (radius >= Cost::ZERO).then_some(Destroy::Cluster(ClusterSize::Radius(radius)))
+            }
+            "worst" => {
+                let (destroyed, determinism) = parameters.split_once(',')?;
+                let determinism: f64 = determinism.parse().ok()?;
+                (determinism >= 1.0).then_some(Destroy::Worst {
+                    fraction: fraction(destroyed)?,
+                    determinism,
+                })
+            }
+            "segment" => {
+                let (min_len, max_len) = parameters.split_once(',')?;
+                let min_len: usize = min_len.parse().ok()?;
+                let max_len: usize = max_len.parse().ok()?;
+                (min_len >= 1 && min_len <= max_len)
+                    .then_some(Destroy::Segment { min_len, max_len })
+            }
+            _ => None,
+        }
+    }
+
+    /// The operator, repairing with insertion costs perturbed by up to `repair_noise` (see
+    /// [`RegretRepair::with_noise`]).
+    pub fn build(self, repair_noise: f64) -> Box<dyn Perturbation> {
+        match self {
+            Destroy::Random { fraction } => {
+                Box::new(LargePerturbation::new(fraction).with_repair_noise(repair_noise))
+            }
+            Destroy::Cluster(size) => {
+                Box::new(ClusterPerturbation::new(size).with_repair_noise(repair_noise))
+            }
+            Destroy::Worst {
+                fraction,
+                determinism,
+            } => Box::new(
+                WorstPerturbation::new(fraction, determinism).with_repair_noise(repair_noise),
+            ),
+            Destroy::Segment { min_len, max_len } => {
+                Box::new(SegmentPerturbation::new(min_len, max_len).with_repair_noise(repair_noise))
+            }
+        }
+    }
 }
 
 // --- Small Perturbation (for ILS) ---
 
+/// Relative frequencies of the move types [`SmallPerturbation`] draws. Only the moves that
+/// keep the cycle sizes are drawn, so a perturbed solution stays balanced. An intra-route
+/// move picks either cycle with equal probability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveMix {
+    pub inter_route_exchange: f64,
+    pub vertex_exchange: f64,
+    pub edge_exchange: f64,
+}
+
+impl Default for MoveMix {
+    /// One inter-route exchange for every two vertex and two edge exchanges: each of the
+    /// five (move, cycle) combinations equally often.
+    fn default() -> Self {
+        Self {
+            inter_route_exchange: 1.0,
+            vertex_exchange: 2.0,
+            edge_exchange: 2.0,
+        }
+    }
+}
+
+impl MoveMix {
+    /// A fraction `bias` of inter-route exchanges, the rest split evenly between vertex and
+    /// edge exchanges.
+    pub fn inter_route_biased(bias: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&bias),
+            "Inter-route bias must be between 0 and 1"
+        );
+        Self {
+            inter_route_exchange: bias,
+            vertex_exchange: (1.0 - bias) / 2.0,
+            edge_exchange: (1.0 - bias) / 2.0,
+        }
+    }
+
+    fn weight(&self, kind: MoveKind) -> f64 {
+        match kind {
+            MoveKind::InterRouteExchange => self.inter_route_exchange,
+            MoveKind::IntraRouteVertexExchange => self.vertex_exchange,
+            MoveKind::IntraRouteEdgeExchange => self.edge_exchange,
+            MoveKind::InterRouteTransfer => 0.0,
+        }
+    }
+
+    /// A move type drawn by weight among those `solution` has room for.
+    fn choose<R: Rng + ?Sized>(&self, solution: &Solution, rng: &mut R) -> Option<MoveKind> {
+        let (n1, n2) = (solution.cycle1.len(), solution.cycle2.len());
+        let feasible = |kind: MoveKind| match kind {
+            MoveKind::InterRouteExchange => n1 >= 1 && n2 >= 1,
+            MoveKind::IntraRouteVertexExchange => n1 >= 2 || n2 >= 2,
+            MoveKind::IntraRouteEdgeExchange => n1 >= 4 || n2 >= 4,
+            MoveKind::InterRouteTransfer => false,
+        };
+        let weighted: Vec<(MoveKind, f64)> = MoveKind::ALL
+            .into_iter()
+            .filter(|&kind| feasible(kind))
+            .map(|kind| (kind, self.weight(kind)))
+            .filter(|&(_, weight)| weight > 0.0)
+            .collect();
+        let total: f64 = weighted.iter().map(|&(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = rng.random::<f64>() * total;
+        for &(kind, weight) in &weighted {
+            if target < weight {
+                return Some(kind);
+            }
+            target -= weight;
+        }
+        weighted.last().map(|&(kind, _)| kind)
+    }
+}
+
+/// Draws before [`SmallPerturbation`] gives up, per move it is asked for, when moves keep
+/// coming out invalid (e.g. every vertex of a cycle is pinned).
+const MAX_DRAWS_PER_MOVE: usize = 10;
+
+/// Applies `num_moves` random moves, drawn by type from a [`MoveMix`]. Every move is checked
+/// with [`Move::is_applicable`] before it is applied, and only applied moves count, so the
+/// strength is exactly `num_moves` structural changes.
 #[derive(Debug, Clone)]
 pub struct SmallPerturbation {
     num_moves: usize,
+    mix: MoveMix,
 }
 
 impl SmallPerturbation {
     pub fn new(num_moves: usize) -> Self {
-        Self { num_moves }
+        Self {
+            num_moves,
+            mix: MoveMix::default(),
+        }
+    }
+
+    pub fn with_move_mix(mut self, mix: MoveMix) -> Self {
+        assert!(
+            [
+                mix.inter_route_exchange,
+                mix.vertex_exchange,
+                mix.edge_exchange
+            ]
+            .iter()
+            .all(|&weight| weight >= 0.0)
+                && mix.inter_route_exchange + mix.vertex_exchange + mix.edge_exchange > 0.0,
+            "Move mix weights must be non-negative and not all zero"
+        );
+        self.mix = mix;
+        self
     }
 }
 
 impl Perturbation for SmallPerturbation {
     fn name(&self) -> String {
-        format!("SmallPerturbation(n_moves={})", self.num_moves)
+        if self.mix == MoveMix::default() {
+            format!("SmallPerturbation(n_moves={})", self.num_moves)
+        } else {
+            format!(
+                "SmallPerturbation(n_moves={}, mix={}/{}/{})",
+                self.num_moves,
+                self.mix.inter_route_exchange,
+                self.mix.vertex_exchange,
+                self.mix.edge_exchange
+            )
+        }
     }
 
-    fn perturb<R: Rng + ?Sized>(
-        &self,
-        solution: &mut Solution,
-        _instance: &TsplibInstance,
-        rng: &mut R,
-    ) {
-        for _ in 0..self.num_moves {
-            if let Some(random_move) = generate_random_move(solution, rng) {
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        let mut applied = 0;
+        for _ in 0..self.num_moves * MAX_DRAWS_PER_MOVE {
+            if applied == self.num_moves {
+                break;
+            }
+            let Some(kind) = self.mix.choose(solution, rng) else {
+                // The cycles are too small for any move of the mix.
+                break;
+            };
+            if let Some(random_move) = generate_random_move(kind, solution, instance, rng)
+                && random_move.is_applicable(solution)
+            {
                 // Apply the move directly without checking delta
                 random_move.apply(solution);
-            } else {
-                // Could happen if cycles are too small for any moves
-                break;
+                applied += 1;
             }
         }
     }
 }
 
-fn generate_random_move<R: Rng + ?Sized>(solution: &Solution, rng: &mut R) -> Option<Move> {
-    let n1 = solution.cycle1.len();
-    let n2 = solution.cycle2.len();
-
-    // Available move types depend on cycle sizes
-    let mut possible_move_types = Vec::new();
-    if n1 >= 2 && n2 >= 2 {
-        possible_move_types.push(0);
-    } // Inter-route exchange
-    if n1 >= 2 {
-        possible_move_types.push(1);
-    } // Intra-vertex C1
-    if n2 >= 2 {
-        possible_move_types.push(2);
-    } // Intra-vertex C2
-    if n1 >= 4 {
-        possible_move_types.push(3);
-    } // Intra-edge C1
-    if n2 >= 4 {
-        possible_move_types.push(4);
-    } // Intra-edge C2
-
-    if possible_move_types.is_empty() {
-        return None; // No possible moves
-    }
-
-    // Choose a random move type and generate it
-    let choice = *possible_move_types.choose_mut(rng).unwrap();
-    match choice {
-        0 => generate_random_inter_route_exchange(solution, rng),
-        1 => generate_random_intra_vertex_exchange(solution, rng, CycleId::Cycle1),
-        2 => generate_random_intra_vertex_exchange(solution, rng, CycleId::Cycle2),
-        3 => generate_random_intra_edge_exchange(solution, rng, CycleId::Cycle1),
-        4 => generate_random_intra_edge_exchange(solution, rng, CycleId::Cycle2),
-        _ => unreachable!(),
+/// A random move of type `kind`, in a random cycle with room for it for intra-route moves.
+fn generate_random_move<R: Rng + ?Sized>(
+    kind: MoveKind,
+    solution: &Solution,
+    instance: &TsplibInstance,
+    rng: &mut R,
+) -> Option<Move> {
+    let min_len = match kind {
+        MoveKind::InterRouteExchange => {
+            return generate_random_inter_route_exchange(solution, instance, rng);
+        }
+        MoveKind::IntraRouteVertexExchange => 2,
+        MoveKind::IntraRouteEdgeExchange => 4,
+        MoveKind::InterRouteTransfer => return None,
+    };
+    let cycle = [CycleId::Cycle1, CycleId::Cycle2]
+        .into_iter()
+        .filter(|&cycle| solution.get_cycle(cycle).len() >= min_len)
+        .choose(rng)?;
+    match kind {
+        MoveKind::IntraRouteVertexExchange => {
+            generate_random_intra_vertex_exchange(solution, rng, cycle)
+        }
+        _ => generate_random_intra_edge_exchange(solution, rng, cycle),
     }
 }
 
+/// Swaps two random vertices between the cycles; pinned vertices are never picked.
 fn generate_random_inter_route_exchange<R: Rng + ?Sized>(
     solution: &Solution,
+    instance: &TsplibInstance,
     rng: &mut R,
 ) -> Option<Move> {
-    let n1 = solution.cycle1.len();
-    let n2 = solution.cycle2.len();
-    if n1 == 0 || n2 == 0 {
-        return None;
-    }
-    let pos1 = rng.gen_range(0..n1);
-    let pos2 = rng.gen_range(0..n2);
-    Some(Move::InterRouteExchange {
-        v1: solution.cycle1[pos1],
-        v2: solution.cycle2[pos2],
-    })
+    let unpinned = |cycle: &[usize], rng: &mut R| {
+        cycle
+            .iter()
+            .copied()
+            .filter(|&v| !instance.is_pinned(v))
+            .choose(rng)
+    };
+    let v1 = unpinned(&solution.cycle1, rng)?;
+    let v2 = unpinned(&solution.cycle2, rng)?;
+    Some(Move::InterRouteExchange { v1, v2 })
 }
 
 fn generate_random_intra_vertex_exchange<R: Rng + ?Sized>(
@@ -116,10 +404,10 @@ fn generate_random_intra_vertex_exchange<R: Rng + ?Sized>(
     if n < 2 {
         return None;
     }
-    let pos1 = rng.gen_range(0..n);
-    let mut pos2 = rng.gen_range(0..n);
+    let pos1 = rng.random_range(0..n);
+    let mut pos2 = rng.random_range(0..n);
     while pos1 == pos2 {
-        pos2 = rng.gen_range(0..n);
+        pos2 = rng.random_range(0..n);
     }
     Some(Move::IntraRouteVertexExchange {
         v1: cycle[pos1],
@@ -141,15 +429,15 @@ fn generate_random_intra_edge_exchange<R: Rng + ?Sized>(
     }
 
     // Pick first edge (a, b)
-    let pos1 = rng.gen_range(0..n);
+    let pos1 = rng.random_range(0..n);
     let a = cycle[pos1];
     let b = cycle[(pos1 + 1) % n];
 
     // Pick second edge (c, d), ensuring it's not adjacent to the first
-    let mut pos2 = rng.gen_range(0..n);
+    let mut pos2 = rng.random_range(0..n);
     // Avoid picking the same edge or adjacent edges
     while pos2 == pos1 || pos2 == (pos1 + 1) % n || pos2 == (pos1 + n - 1) % n {
-        pos2 = rng.gen_range(0..n);
+        pos2 = rng.random_range(0..n);
     }
     let c = cycle[pos2];
     let d = cycle[(pos2 + 1) % n];
@@ -168,7 +456,7 @@ fn generate_random_intra_edge_exchange<R: Rng + ?Sized>(
 #[derive(Debug, Clone)]
 pub struct LargePerturbation {
     destroy_fraction: f64, // e.g., 0.2 for 20%
-                           // We'll use WeightedRegretCycle for repair implicitly for now
+    repair: RegretRepair,
 }
 
 impl LargePerturbation {
@@ -177,20 +465,39 @@ impl LargePerturbation {
             destroy_fraction > 0.0 && destroy_fraction < 1.0,
             "Destroy fraction must be between 0 and 1"
         );
-        Self { destroy_fraction }
+        Self {
+            destroy_fraction,
+            repair: RegretRepair::default(),
+        }
+    }
+
+    /// Perturbs the repair's insertion costs by up to `noise` (a fraction), so repeated
+    /// repairs of the same destroyed solution diversify.
+    pub fn with_repair_noise(mut self, noise: f64) -> Self {
+        self.repair = RegretRepair::with_noise(noise);
+        self
     }
 }
 
 impl Perturbation for LargePerturbation {
     fn name(&self) -> String {
-        format!("LargePerturbation(destroy={:.2})", self.destroy_fraction)
+        format!(
+            "LargePerturbation(destroy={:.2}{})",
+            self.destroy_fraction,
+            self.repair.name_suffix()
+        )
+    }
+
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        self.perturb_within(solution, instance, rng, &Budget::unlimited());
     }
 
-    fn perturb<R: Rng + ?Sized>(
+    fn perturb_within(
         &self,
         solution: &mut Solution,
         instance: &TsplibInstance,
-        rng: &mut R,
+        rng: &mut dyn RngCore,
+        budget: &Budget,
     ) {
         let nodes_to_remove_count =
             ((instance.dimension as f64 * self.destroy_fraction) / 2.0).round() as usize * 2;
@@ -199,10 +506,426 @@ impl Perturbation for LargePerturbation {
         }
 
         let destroyed_nodes = destroy(solution, nodes_to_remove_count, rng);
-        repair(solution, instance, destroyed_nodes);
+        self.repair
+            .repair(solution, instance, destroyed_nodes, budget, rng);
+    }
+}
+
+// --- Cluster Perturbation (for LNS) ---
+
+/// Which vertices around the seed a [`ClusterPerturbation`] removes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClusterSize {
+    /// Every vertex at most this far from the seed.
+    Radius(Cost),
+    /// The seed and this many of its nearest vertices.
+    Nearest(usize),
+}
+
+/// Destroy/repair that removes a geographic cluster around a random seed vertex, from both
+/// cycles, and reinserts it by weighted regret. Removing neighbouring vertices together lets
+/// the repair redraw a whole region, which suits clustered instances better than uniform
+/// removal.
+#[derive(Debug, Clone)]
+pub struct ClusterPerturbation {
+    size: ClusterSize,
+    repair: RegretRepair,
+}
+
+impl ClusterPerturbation {
+    pub fn new(size: ClusterSize) -> Self {
+        match size {
+            ClusterSize::Radius(radius) => {
+                assert!(radius >= Cost::ZERO, "Cluster radius must not be negative")
+            }
+            ClusterSize::Nearest(count) => assert!(count > 0, "Cluster must not be empty"),
+        }
+        Self {
+            size,
+            repair: RegretRepair::default(),
+        }
+    }
+
+    /// Perturbs the repair's insertion costs by up to `noise` (a fraction), so repeated
+    /// repairs of the same destroyed solution diversify.
+    pub fn with_repair_noise(mut self, noise: f64) -> Self {
+        self.repair = RegretRepair::with_noise(noise);
+        self
     }
 }
 
+impl Perturbation for ClusterPerturbation {
+    fn name(&self) -> String {
+        match self.size {
+            ClusterSize::Radius(radius) => format!(
+                "ClusterPerturbation(radius={}{})",
+                radius,
+                self.repair.name_suffix()
+            ),
+            ClusterSize::Nearest(count) => format!(
+                "ClusterPerturbation(nearest={}{})",
+                count,
+                self.repair.name_suffix()
+            ),
+        }
+    }
+
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        self.perturb_within(solution, instance, rng, &Budget::unlimited());
+    }
+
+    fn perturb_within(
+        &self,
+        solution: &mut Solution,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        budget: &Budget,
+    ) {
+        let mut visited: Vec<usize> = solution
+            .cycle1
+            .iter()
+            .chain(solution.cycle2.iter())
+            .cloned()
+            .collect();
+        if visited.is_empty() {
+            return;
+        }
+        let seed = visited[rng.random_range(0..visited.len())];
+
+        let nodes_to_remove: HashSet<usize> = match self.size {
+            ClusterSize::Radius(radius) => visited
+                .into_iter()
+                .filter(|&v| instance.distance(seed, v) <= radius)
+                .collect(),
+            ClusterSize::Nearest(count) => {
+                // The key puts the seed first, even among coincident vertices.
+                let take = (count + 1).min(visited.len());
+                visited.select_nth_unstable_by_key(take - 1, |&v| {
+                    (v != seed, Ordered(instance.distance(seed, v)))
+                });
+                visited.truncate(take);
+                visited.into_iter().collect()
+            }
+        };
+
+        solution
+            .cycle1
+            .retain(|node| !nodes_to_remove.contains(node));
+        solution
+            .cycle2
+            .retain(|node| !nodes_to_remove.contains(node));
+        self.repair
+            .repair(solution, instance, nodes_to_remove, budget, rng);
+    }
+}
+
+// --- Worst-Contribution Perturbation (for LNS) ---
+
+/// Destroy/repair that removes the vertices whose removal saves the most length (their
+/// detour), one at a time with the savings updated after each removal, and reinserts them
+/// by weighted regret. The `k`-th largest saving is picked with `k = floor(y^p * m)` for
+/// uniform `y` in `[0, 1)`, `m` candidates and `p = determinism`, so higher values remove
+/// the worst vertices more reliably; equal savings are ordered randomly.
+#[derive(Debug, Clone)]
+pub struct WorstPerturbation {
+    destroy_fraction: f64,
+    determinism: f64,
+    repair: RegretRepair,
+}
+
+impl WorstPerturbation {
+    pub fn new(destroy_fraction: f64, determinism: f64) -> Self {
+        assert!(
+            destroy_fraction > 0.0 && destroy_fraction < 1.0,
+            "Destroy fraction must be between 0 and 1"
+        );
+        assert!(determinism >= 1.0, "Determinism must be at least 1");
+        Self {
+            destroy_fraction,
+            determinism,
+            repair: RegretRepair::default(),
+        }
+    }
+
+    /// Perturbs the repair's insertion costs by up to `noise` (a fraction), so repeated
+    /// repairs of the same destroyed solution diversify.
+    pub fn with_repair_noise(mut self, noise: f64) -> Self {
+        self.repair = RegretRepair::with_noise(noise);
+        self
+    }
+
+    /// Length saved by removing `cycle[i]` and joining its neighbours.
+    fn saving(instance: &TsplibInstance, cycle: &[usize], i: usize, closed: bool) -> Cost {
+        let n = cycle.len();
+        if n < 2 {
+            return Cost::ZERO;
+        }
+        let v = cycle[i];
+        let prev = (i > 0 || closed).then(|| cycle[(i + n - 1) % n]);
+        let next = (i + 1 < n || closed).then(|| cycle[(i + 1) % n]);
+        match (prev, next) {
+            (Some(p), Some(q)) => {
+                instance.distance(p, v) + instance.distance(v, q) - instance.distance(p, q)
+            }
+            (Some(p), None) => instance.distance(p, v),
+            (None, Some(q)) => instance.distance(v, q),
+            (None, None) => Cost::ZERO,
+        }
+    }
+}
+
+impl Perturbation for WorstPerturbation {
+    fn name(&self) -> String {
+        format!(
+            "WorstPerturbation(destroy={:.2}, p={}{})",
+            self.destroy_fraction,
+            self.determinism,
+            self.repair.name_suffix()
+        )
+    }
+
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        self.perturb_within(solution, instance, rng, &Budget::unlimited());
+    }
+
+    fn perturb_within(
+        &self,
+        solution: &mut Solution,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        budget: &Budget,
+    ) {
+        let nodes_to_remove_count =
+            ((instance.dimension as f64 * self.destroy_fraction) / 2.0).round() as usize * 2;
+        let mut removed = HashSet::with_capacity(nodes_to_remove_count);
+        // (saving, random tie-breaker, cycle, position)
+        let mut candidates: Vec<(Cost, u32, CycleId, usize)> = Vec::new();
+        for _ in 0..nodes_to_remove_count {
+            candidates.clear();
+            for cycle_id in [CycleId::Cycle1, CycleId::Cycle2] {
+                let cycle = solution.get_cycle(cycle_id);
+                for i in 0..cycle.len() {
+                    let saving = Self::saving(instance, cycle, i, solution.is_closed);
+                    candidates.push((saving, rng.random(), cycle_id, i));
+                }
+            }
+            if candidates.is_empty() {
+                break;
+            }
+            candidates.sort_unstable_by(|a, b| b.0.total_order(&a.0).then(a.1.cmp(&b.1)));
+            let y: f64 = rng.random();
+            let k = ((y.powf(self.determinism) * candidates.len() as f64) as usize)
+                .min(candidates.len() - 1);
+            let (_, _, cycle_id, position) = candidates[k];
+            removed.insert(solution.get_cycle_mut(cycle_id).remove(position));
+        }
+        if removed.is_empty() {
+            return;
+        }
+        self.repair.repair(solution, instance, removed, budget, rng);
+    }
+}
+
+// --- Segment Perturbation (for LNS) ---
+
+/// Destroy/repair that cuts one contiguous segment out of each cycle, with lengths drawn
+/// uniformly from `min_len..=max_len`, and reinserts the vertices by weighted regret. The
+/// rest of each cycle keeps its order, unlike with scattered removal.
+#[derive(Debug, Clone)]
+pub struct SegmentPerturbation {
+    min_len: usize,
+    max_len: usize,
+    repair: RegretRepair,
+}
+
+impl SegmentPerturbation {
+    pub fn new(min_len: usize, max_len: usize) -> Self {
+        assert!(
+            min_len >= 1 && min_len <= max_len,
+            "Segment lengths need 1 <= min_len <= max_len"
+        );
+        Self {
+            min_len,
+            max_len,
+            repair: RegretRepair::default(),
+        }
+    }
+
+    /// Perturbs the repair's insertion costs by up to `noise` (a fraction), so repeated
+    /// repairs of the same destroyed solution diversify.
+    pub fn with_repair_noise(mut self, noise: f64) -> Self {
+        self.repair = RegretRepair::with_noise(noise);
+        self
+    }
+}
+
+impl Perturbation for SegmentPerturbation {
+    fn name(&self) -> String {
+        format!(
+            "SegmentPerturbation(len={}..={}{})",
+            self.min_len,
+            self.max_len,
+            self.repair.name_suffix()
+        )
+    }
+
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        self.perturb_within(solution, instance, rng, &Budget::unlimited());
+    }
+
+    fn perturb_within(
+        &self,
+        solution: &mut Solution,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        budget: &Budget,
+    ) {
+        let closed = solution.is_closed;
+        let mut removed = HashSet::new();
+        for cycle_id in [CycleId::Cycle1, CycleId::Cycle2] {
+            let cycle = solution.get_cycle_mut(cycle_id);
+            let n = cycle.len();
+            if n == 0 {
+                continue;
+            }
+            let len = rng.random_range(self.min_len..=self.max_len).min(n);
+            // Segments of a closed cycle may wrap around its end; an open path is only cut
+            // where the segment fits.
+            let start = if closed {
+                rng.random_range(0..n)
+            } else {
+                rng.random_range(0..=n - len)
+            };
+            if start + len <= n {
+                removed.extend(cycle.drain(start..start + len));
+            } else {
+                removed.extend(cycle.drain(start..));
+                removed.extend(cycle.drain(..start + len - n));
+            }
+        }
+        if removed.is_empty() {
+            return;
+        }
+        self.repair.repair(solution, instance, removed, budget, rng);
+    }
+}
+
+// --- Segment Reversal Perturbation (for ILS) ---
+
+/// Reverses one contiguous segment of a random cycle, with its length drawn uniformly from
+/// `min_len..=max_len` and capped at the cycle length. A long reversal is a single 2-opt
+/// move the local search rarely undoes in one step.
+#[derive(Debug, Clone)]
+pub struct SegmentReversalPerturbation {
+    min_len: usize,
+    max_len: usize,
+}
+
+impl SegmentReversalPerturbation {
+    pub fn new(min_len: usize, max_len: usize) -> Self {
+        assert!(
+            min_len >= 2 && min_len <= max_len,
+            "Segment lengths need 2 <= min_len <= max_len"
+        );
+        Self { min_len, max_len }
+    }
+}
+
+impl Perturbation for SegmentReversalPerturbation {
+    fn name(&self) -> String {
+        format!(
+            "SegmentReversalPerturbation(len={}..={})",
+            self.min_len, self.max_len
+        )
+    }
+
+    fn perturb(&self, solution: &mut Solution, _instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        let closed = solution.is_closed;
+        let Some(cycle_id) = [CycleId::Cycle1, CycleId::Cycle2]
+            .into_iter()
+            .filter(|&cycle| solution.get_cycle(cycle).len() >= 2)
+            .choose(rng)
+        else {
+            return;
+        };
+        let cycle = solution.get_cycle_mut(cycle_id);
+        let len = rng
+            .random_range(self.min_len..=self.max_len)
+            .min(cycle.len());
+        let positions = random_segment(cycle.len(), len, closed, rng);
+        for k in 0..len / 2 {
+            cycle.swap(positions[k], positions[len - 1 - k]);
+        }
+    }
+}
+
+// --- Segment Swap Perturbation (for ILS) ---
+
+/// Swaps two segments of the same length between the cycles, with the length drawn uniformly
+/// from `min_len..=max_len` and capped at the shorter cycle. Each segment takes the place of
+/// the other in the same order, so the cycle sizes do not change. Segments holding a pinned
+/// vertex are redrawn; the solution is left as it is if none is found.
+#[derive(Debug, Clone)]
+pub struct SegmentSwapPerturbation {
+    min_len: usize,
+    max_len: usize,
+}
+
+impl SegmentSwapPerturbation {
+    pub fn new(min_len: usize, max_len: usize) -> Self {
+        assert!(
+            min_len >= 1 && min_len <= max_len,
+            "Segment lengths need 1 <= min_len <= max_len"
+        );
+        Self { min_len, max_len }
+    }
+}
+
+impl Perturbation for SegmentSwapPerturbation {
+    fn name(&self) -> String {
+        format!(
+            "SegmentSwapPerturbation(len={}..={})",
+            self.min_len, self.max_len
+        )
+    }
+
+    fn perturb(&self, solution: &mut Solution, instance: &TsplibInstance, rng: &mut dyn RngCore) {
+        let closed = solution.is_closed;
+        let (n1, n2) = (solution.cycle1.len(), solution.cycle2.len());
+        if n1 == 0 || n2 == 0 {
+            return;
+        }
+        let len = rng
+            .random_range(self.min_len..=self.max_len)
+            .min(n1)
+            .min(n2);
+        let movable = |cycle: &[usize], positions: &[usize]| {
+            positions.iter().all(|&i| !instance.is_pinned(cycle[i]))
+        };
+        for _ in 0..MAX_DRAWS_PER_MOVE {
+            let positions1 = random_segment(n1, len, closed, rng);
+            let positions2 = random_segment(n2, len, closed, rng);
+            if movable(&solution.cycle1, &positions1) && movable(&solution.cycle2, &positions2) {
+                for (&i, &j) in positions1.iter().zip(&positions2) {
+                    std::mem::swap(&mut solution.cycle1[i], &mut solution.cycle2[j]);
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// Positions of a random segment of `len` consecutive vertices of a cycle of `n`, in order.
+/// Segments of a closed cycle may wrap around its end; on an open path they fit inside it.
+fn random_segment<R: Rng + ?Sized>(n: usize, len: usize, closed: bool, rng: &mut R) -> Vec<usize> {
+    let start = if closed {
+        rng.random_range(0..n)
+    } else {
+        rng.random_range(0..=n - len)
+    };
+    (start..start + len).map(|i| i % n).collect()
+}
+
 fn destroy<R: Rng + ?Sized>(
     solution: &mut Solution,
     nodes_to_remove_count: usize,
@@ -229,78 +952,275 @@ fn destroy<R: Rng + ?Sized>(
     nodes_to_remove
 }
 
-pub(crate) fn repair(solution: &mut Solution, instance: &TsplibInstance, destroyed_nodes: HashSet<usize>) {
-    // Compute target sizes for two cycles to enforce balance
-    let total_size = instance.size();
-    let target1 = (total_size + 1) / 2;
-    let target2 = total_size - target1;
-    let mut remaining_nodes: Vec<usize> = destroyed_nodes.into_iter().collect();
+/// Weighted-regret reinsertion used by the destroy/repair perturbations.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RegretRepair {
+    /// Each insertion cost is multiplied by a factor drawn uniformly from
+    /// `[1 - noise, 1 + noise]` before the choice, so repeated repairs of the same partial
+    /// solution differ; 0 repairs deterministically.
+    pub noise: f64,
+}
 
-    // Implementation based on `solve_regret_init` from python_reference.py
-    while !remaining_nodes.is_empty() {
-        let mut best_node_idx = 0;
-        let mut best_insertion: Option<(usize, CycleId)> = None; // (insert_pos, cycle_id)
-        let mut max_weighted_regret = -f64::INFINITY;
+impl RegretRepair {
+    pub fn with_noise(noise: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&noise),
+            "Repair noise must be between 0 and 1"
+        );
+        Self { noise }
+    }
 
-        for (node_idx, &node_to_insert) in remaining_nodes.iter().enumerate() {
-            let mut insertion_costs: Vec<(i32, usize, CycleId)> = Vec::new(); // (cost_delta, insert_pos, cycle_id)
+    /// Suffix for the names of the perturbations using this repair.
+    fn name_suffix(&self) -> String {
+        if self.noise > 0.0 {
+            format!(", noise={:.2}", self.noise)
+        } else {
+            String::new()
+        }
+    }
 
-            // Evaluate insertion only into cycles that haven't reached target size
-            for cycle_id in [CycleId::Cycle1, CycleId::Cycle2] {
-                let cycle = solution.get_cycle(cycle_id);
-                let n = cycle.len();
-                // Determine capacity for this cycle
-                let cap = if cycle_id == CycleId::Cycle1 { target1 } else { target2 };
-                if n >= cap {
-                    // Skip insertion into a full cycle
-                    continue;
-                }
-                if n == 0 {
-                    // Inserting into an empty cycle: delta is 0 for the first node
-                    insertion_costs.push((0, 0, cycle_id));
-                    continue;
-                }
-                for i in 0..=n {
-                    let prev_node = cycle[if i == 0 { n - 1 } else { i - 1 }];
-                    let next_node = cycle[i % n];
-                    let delta = instance.distance(prev_node, node_to_insert)
-                        + instance.distance(node_to_insert, next_node)
-                        - instance.distance(prev_node, next_node);
-                    insertion_costs.push((delta, i, cycle_id));
+    /// Reinserts `destroyed_nodes` into `solution`; see [`repair`].
+    pub fn repair<R: Rng + ?Sized>(
+        &self,
+        solution: &mut Solution,
+        instance: &TsplibInstance,
+        destroyed_nodes: HashSet<usize>,
+        budget: &Budget,
+        rng: &mut R,
+    ) {
+        regret_repair(solution, instance, destroyed_nodes, budget, self.noise, rng);
+    }
+}
+
+/// Weighted-regret reinsertion of `destroyed_nodes`. Once `budget` runs out, each remaining
+/// vertex is inserted in turn at its cheapest position instead, which skips the regret scan
+/// over all of them.
+pub(crate) fn repair<R: Rng + ?Sized>(
+    solution: &mut Solution,
+    instance: &TsplibInstance,
+    destroyed_nodes: HashSet<usize>,
+    budget: &Budget,
+    rng: &mut R,
+) {
+    regret_repair(solution, instance, destroyed_nodes, budget, 0.0, rng);
+}
+
+/// Below this many vertices to insert, repair evaluates them on the calling thread.
+const PARALLEL_REPAIR_MIN_VERTICES: usize = 16;
+
+/// What one repair step evaluates every remaining vertex against.
+struct RepairContext<'a> {
+    solution: &'a Solution,
+    instance: &'a TsplibInstance,
+    targets: [usize; 2],
+    reserved: [usize; 2],
+    prize_collecting: bool,
+    noise: f64,
+}
+
+impl RepairContext<'_> {
+    /// Weighted regret and best `(insert_pos, cycle_id)` of `node_to_insert`, or `None` if
+    /// it cannot or (with prizes) should not be inserted now.
+    fn evaluate<R: Rng + ?Sized>(
+        &self,
+        node_to_insert: usize,
+        rng: &mut R,
+    ) -> Option<(f64, (usize, CycleId))> {
+        let RepairContext {
+            solution,
+            instance,
+            prize_collecting,
+            noise,
+            ..
+        } = *self;
+        let pin = instance.pinned_cycle(node_to_insert);
+        let mut insertion_costs: Vec<(Cost, usize, CycleId)> = Vec::new(); // (cost_delta, insert_pos, cycle_id)
+
+        // Evaluate insertion only into cycles that haven't reached target size
+        for cycle_id in [CycleId::Cycle1, CycleId::Cycle2] {
+            if pin.is_some_and(|pinned| pinned != cycle_id) {
+                continue;
+            }
+            let cycle = solution.get_cycle(cycle_id);
+            let n = cycle.len();
+            // Determine capacity for this cycle; unpinned vertices leave the reserved room
+            let (cap, reserved) = if cycle_id == CycleId::Cycle1 {
+                (self.targets[0], self.reserved[0])
+            } else {
+                (self.targets[1], self.reserved[1])
+            };
+            let full = if pin.is_some() {
+                // Pinned vertices must be visited, even at the cost of balance with prizes.
+                !prize_collecting && n >= cap
+            } else if prize_collecting {
+                let (n1, n2) = (solution.cycle1.len(), solution.cycle2.len());
+                match cycle_id {
+                    CycleId::Cycle1 => n1 > n2,
+                    CycleId::Cycle2 => n2 >= n1,
                 }
+            } else {
+                n + reserved >= cap
+            };
+            if full {
+                // Skip insertion into a full cycle
+                continue;
             }
-
-            if insertion_costs.is_empty() {
-                // Should not happen if instance has nodes
+            if n == 0 {
+                // Inserting into an empty cycle: delta is 0 for the first node
+                insertion_costs.push((Cost::ZERO, 0, cycle_id));
                 continue;
             }
+            for i in 0..=n {
+                let prev_node = cycle[if i == 0 { n - 1 } else { i - 1 }];
+                let next_node = cycle[i % n];
+                let delta = if !solution.is_closed && i == 0 {
+                    // New start of an open path
+                    instance.distance(node_to_insert, next_node)
+                } else if !solution.is_closed && i == n {
+                    // New end of an open path
+                    instance.distance(prev_node, node_to_insert)
+                } else {
+                    instance.distance(prev_node, node_to_insert)
+                        + instance.distance(node_to_insert, next_node)
+                        - instance.distance(prev_node, next_node)
+                };
+                let delta = if noise > 0.0 {
+                    Cost::round_from(delta.to_f64() * rng.random_range(1.0 - noise..=1.0 + noise))
+                } else {
+                    delta
+                };
+                insertion_costs.push((delta, i, cycle_id));
+            }
+        }
 
-            // Sort by cost delta to find best and second best
-            insertion_costs.sort_unstable_by_key(|k| k.0);
+        if insertion_costs.is_empty() {
+            // Should not happen if instance has nodes
+            return None;
+        }
 
-            let best_cost = insertion_costs[0].0;
-            let current_best_insertion = (insertion_costs[0].1, insertion_costs[0].2);
+        // Partially select so that [0] is the best and [1] the second best insertion
+        if insertion_costs.len() > 1 {
+            insertion_costs.select_nth_unstable_by_key(1, |k| Ordered(k.0));
+        }
 
-            // Calculate regret (Python: np.diff(np.partition(scores, 1)[:, :2]))
-            let regret = if insertion_costs.len() > 1 {
-                (insertion_costs[1].0 - best_cost) as f64
-            } else {
-                0.0 // No regret if only one possible insertion spot
-            };
+        let best_cost = insertion_costs[0].0;
+        if prize_collecting && pin.is_none() && instance.prize(node_to_insert) <= best_cost {
+            // Not worth visiting (yet); the vertex stays out unless the cycles change.
+            return None;
+        }
+        let current_best_insertion = (insertion_costs[0].1, insertion_costs[0].2);
 
-            // Weighted Regret (Python: weight = regret - 0.37 * np.min(scores, axis=1))
-            let weight_factor = 0.37; // Same as in the Python reference
-            let weighted_regret = regret - weight_factor * (best_cost as f64);
+        // Calculate regret (Python: np.diff(np.partition(scores, 1)[:, :2]))
+        let regret = if insertion_costs.len() > 1 {
+            (insertion_costs[1].0 - best_cost).to_f64()
+        } else {
+            0.0 // No regret if only one possible insertion spot
+        };
+
+        // Weighted Regret (Python: weight = regret - 0.37 * np.min(scores, axis=1))
+        let weight_factor = 0.37; // Same as in the Python reference
+        let weighted_regret = regret - weight_factor * best_cost.to_f64();
+        Some((weighted_regret, current_best_insertion))
+    }
+}
 
-            if weighted_regret > max_weighted_regret {
-                max_weighted_regret = weighted_regret;
-                best_node_idx = node_idx;
-                best_insertion = Some(current_best_insertion);
+/// [`repair`] with insertion costs scaled by random factors in `[1 - noise, 1 + noise]`.
+fn regret_repair<R: Rng + ?Sized>(
+    solution: &mut Solution,
+    instance: &TsplibInstance,
+    destroyed_nodes: HashSet<usize>,
+    budget: &Budget,
+    noise: f64,
+    rng: &mut R,
+) {
+    // Compute target sizes for two cycles to enforce balance
+    let total_size = instance.size();
+    let target1 = if instance.is_single_cycle() {
+        total_size
+    } else {
+        total_size.div_ceil(2)
+    };
+    let target2 = total_size - target1;
+    // With prizes, every unvisited vertex (not only the destroyed ones) is a candidate, but
+    // it is only inserted while that pays off. The visited vertices stay balanced by always
+    // growing the shorter cycle (cycle 1 on ties).
+    let prize_collecting = instance.is_prize_collecting();
+    let mut remaining_nodes: Vec<usize> = if prize_collecting {
+        let mut visited = vec![false; total_size];
+        for &v in solution.cycle1.iter().chain(&solution.cycle2) {
+            visited[v] = true;
+        }
+        (0..total_size).filter(|&v| !visited[v]).collect()
+    } else {
+        // In vertex order, not the hash set's, so ties and the noise streams of the vertices
+        // come out the same for the same seed.
+        let mut nodes: Vec<usize> = destroyed_nodes.into_iter().collect();
+        nodes.sort_unstable();
+        nodes
+    };
+
+    // Implementation based on `solve_regret_init` from python_reference.py
+    let mut hurried = false;
+    while !remaining_nodes.is_empty() {
+        hurried = hurried || budget.exhausted();
+        // Room kept free in each cycle for the pinned vertices still to be inserted.
+        let mut reserved = [0; 2];
+        for &v in &remaining_nodes {
+            match instance.pinned_cycle(v) {
+                Some(CycleId::Cycle1) => reserved[0] += 1,
+                Some(CycleId::Cycle2) => reserved[1] += 1,
+                None => {}
             }
         }
+        let context = RepairContext {
+            solution,
+            instance,
+            targets: [target1, target2],
+            reserved,
+            prize_collecting,
+            noise,
+        };
+
+        // (weighted regret, index into remaining_nodes, (insert_pos, cycle_id))
+        let best = if hurried {
+            remaining_nodes
+                .iter()
+                .enumerate()
+                .find_map(|(idx, &v)| context.evaluate(v, rng).map(|(w, ins)| (w, idx, ins)))
+        } else if remaining_nodes.len() >= PARALLEL_REPAIR_MIN_VERTICES {
+            // Noise comes from the stream of each vertex, so the threads share no RNG.
+            let streams = SeedStream::from_rng(rng);
+            remaining_nodes
+                .par_iter()
+                .enumerate()
+                .filter_map(|(idx, &v)| {
+                    let mut node_rng = streams.child(v as u64).rng();
+                    context
+                        .evaluate(v, &mut node_rng)
+                        .map(|(w, ins)| (w, idx, ins))
+                })
+                // Ties go to the earlier vertex, as in the sequential scan.
+                .reduce_with(|a, b| {
+                    if b.0 > a.0 || (b.0 == a.0 && b.1 < a.1) {
+                        b
+                    } else {
+                        a
+                    }
+                })
+        } else {
+            let mut best: Option<(f64, usize, (usize, CycleId))> = None;
+            for (idx, &v) in remaining_nodes.iter().enumerate() {
+                if let Some((w, ins)) = context.evaluate(v, rng)
+                    && best.is_none_or(|(best_w, _, _)| w > best_w)
+                {
+                    best = Some((w, idx, ins));
+                }
+            }
+            best
+        };
 
         // Perform the best insertion found based on weighted regret
-        if let Some((insert_pos, cycle_id)) = best_insertion {
+        if let Some((_, best_node_idx, (insert_pos, cycle_id))) = best {
             let node_to_insert = remaining_nodes.remove(best_node_idx);
             let cycle = solution.get_cycle_mut(cycle_id);
             // Ensure insertion position is valid for the current cycle length
@@ -308,7 +1228,7 @@ pub(crate) fn repair(solution: &mut Solution, instance: &TsplibInstance, destroy
             cycle.insert(actual_insert_pos, node_to_insert);
         } else {
             // This might happen if remaining_nodes was empty initially or no valid insertions found
-            if !remaining_nodes.is_empty() {
+            if !remaining_nodes.is_empty() && !prize_collecting {
                 eprintln!(
                     "[WARN] Repair phase could not find best insertion for remaining nodes. Aborting."
                 );
@@ -317,7 +1237,7 @@ pub(crate) fn repair(solution: &mut Solution, instance: &TsplibInstance, destroy
         }
     }
 
-    if !remaining_nodes.is_empty() {
+    if !remaining_nodes.is_empty() && !prize_collecting {
         eprintln!(
             "[WARN] Repair phase finished with {} un-inserted nodes.",
             remaining_nodes.len()
@@ -0,0 +1,162 @@
+use crate::analysis::common_edges;
+use crate::tsplib::Solution;
+use crate::weight::{Cost, Ordered, Weight};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// State of an evolving population at one point of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PopulationSnapshot {
+    pub elapsed: Duration,
+    /// Children built so far.
+    pub iteration: usize,
+    pub best_cost: Cost,
+    pub mean_cost: f64,
+    pub worst_cost: Cost,
+    /// Mean fraction of edges one member does not share with another, over all pairs; it
+    /// falls towards 0 as the population converges.
+    pub avg_edge_distance: f64,
+    /// Share of the children built since the previous snapshot that entered the population.
+    pub acceptance_rate: f64,
+    /// Children built since the previous snapshot that were exact duplicates of a member.
+    pub duplicates: usize,
+}
+
+impl PopulationSnapshot {
+    /// One-line summary for progress output.
+    pub fn summary(&self) -> String {
+        format!(
+            "[Population @ {}] cost {}/{:.1}/{} (best/mean/worst), edge distance {:.3}, acceptance {:.1}%, {} duplicates",
+            self.iteration,
+            self.best_cost,
+            self.mean_cost,
+            self.worst_cost,
+            self.avg_edge_distance,
+            self.acceptance_rate * 100.0,
+            self.duplicates
+        )
+    }
+}
+
+/// Population snapshots of one run, taken every `every` children, so premature convergence
+/// shows up in the data.
+#[derive(Debug, Clone)]
+pub struct PopulationTelemetry {
+    every: usize,
+    snapshots: Vec<PopulationSnapshot>,
+    /// Children built, accepted and rejected as duplicates since the last snapshot.
+    built: usize,
+    accepted: usize,
+    duplicates: usize,
+}
+
+impl PopulationTelemetry {
+    pub fn new(every: usize) -> Self {
+        assert!(
+            every >= 1,
+            "Snapshots need an interval of at least one child"
+        );
+        Self {
+            every,
+            snapshots: Vec::new(),
+            built: 0,
+            accepted: 0,
+            duplicates: 0,
+        }
+    }
+
+    pub fn snapshots(&self) -> &[PopulationSnapshot] {
+        &self.snapshots
+    }
+
+    /// Counts `built` new children, `accepted` of which entered the population.
+    pub fn count_children(&mut self, built: usize, accepted: usize) {
+        self.built += built;
+        self.accepted += accepted;
+    }
+
+    /// Counts `duplicates` of the built children as exact duplicates of a member.
+    pub fn count_duplicates(&mut self, duplicates: usize) {
+        self.duplicates += duplicates;
+    }
+
+    /// Whether a snapshot is due at `iteration` children.
+    pub fn is_due(&self, iteration: usize) -> bool {
+        self.snapshots
+            .last()
+            .is_none_or(|last| iteration >= last.iteration + self.every)
+    }
+
+    /// Records the state of `population` (members with their costs) and returns it.
+    pub fn record(
+        &mut self,
+        population: &[(Solution, Cost)],
+        elapsed: Duration,
+        iteration: usize,
+    ) -> &PopulationSnapshot {
+        let costs = population.iter().map(|&(_, cost)| cost);
+        let mut pairs = 0usize;
+        let mut distance_sum = 0.0;
+        for (i, (a, _)) in population.iter().enumerate() {
+            for (b, _) in &population[i + 1..] {
+                pairs += 1;
+                distance_sum += 1.0 - common_edges(a, b);
+            }
+        }
+        let acceptance_rate = if self.built == 0 {
+            0.0
+        } else {
+            self.accepted as f64 / self.built as f64
+        };
+        self.snapshots.push(PopulationSnapshot {
+            elapsed,
+            iteration,
+            best_cost: costs
+                .clone()
+                .min_by_key(|&cost| Ordered(cost))
+                .unwrap_or(Cost::ZERO),
+            mean_cost: costs.clone().map(Cost::to_f64).sum::<f64>()
+                / population.len().max(1) as f64,
+            worst_cost: costs
+                .max_by_key(|&cost| Ordered(cost))
+                .unwrap_or(Cost::ZERO),
+            avg_edge_distance: if pairs == 0 {
+                0.0
+            } else {
+                distance_sum / pairs as f64
+            },
+            acceptance_rate,
+            duplicates: self.duplicates,
+        });
+        self.built = 0;
+        self.accepted = 0;
+        self.duplicates = 0;
+        self.snapshots.last().expect("just pushed")
+    }
+
+    /// Writes the snapshots as CSV with columns
+    /// `elapsed_ms,iteration,best,mean,worst,avg_edge_distance,acceptance_rate,duplicates`.
+    pub fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = String::from(
+            "elapsed_ms,iteration,best,mean,worst,avg_edge_distance,acceptance_rate,duplicates\n",
+        );
+        for s in &self.snapshots {
+            writeln!(
+                out,
+                "{:.3},{},{},{:.2},{},{:.4},{:.4},{}",
+                s.elapsed.as_secs_f64() * 1000.0,
+                s.iteration,
+                s.best_cost,
+                s.mean_cost,
+                s.worst_cost,
+                s.avg_edge_distance,
+                s.acceptance_rate,
+                s.duplicates
+            )
+            .expect("writing to a String cannot fail");
+        }
+        fs::write(path, out)
+    }
+}
@@ -1,156 +1,144 @@
-use crate::algorithm::ProgressCallback;
-use crate::algorithm::TspAlgorithm;
-use crate::moves::types::{CycleId, Move};
+use crate::algorithm::{IterationInfo, IterationUnit, ProgressCallback, TspAlgorithm};
+use crate::algorithms::local_search::base::NeighborhoodType;
+use crate::algorithms::local_search::sampling::sample_move;
+use crate::budget::Budget;
+use crate::convergence::ConvergenceTrace;
 use crate::tsplib::{Solution, TsplibInstance};
 use crate::utils::generate_random_solution;
-use rand::{Rng, thread_rng};
-use std::time::{Duration, Instant};
+use rand::RngCore;
+use std::time::Duration;
 
+/// Baseline that applies uniformly random moves from the full neighbourhood regardless of
+/// their delta, remembering the best solution visited, until the time budget runs out.
+/// `time_limit` is the budget of [`TspAlgorithm::solve_with_feedback`]; the timed solvers
+/// take theirs as an argument.
 #[derive(Debug, Clone)]
 pub struct RandomWalk {
-    max_iterations: usize,
+    time_limit: Duration,
+    neighborhood: NeighborhoodType,
+    name_str: String,
 }
 
 impl Default for RandomWalk {
     fn default() -> Self {
-        Self {
-            max_iterations: 10000,
-        }
+        Self::new(Duration::from_millis(1000), NeighborhoodType::EdgeExchange)
     }
 }
 
 impl RandomWalk {
-    pub fn new(max_iterations: usize) -> Self {
-        Self { max_iterations }
+    pub fn new(time_limit: Duration, neighborhood: NeighborhoodType) -> Self {
+        let name_str = format!("Random Walk ({:?}, {:?})", neighborhood, time_limit);
+        Self {
+            time_limit,
+            neighborhood,
+            name_str,
+        }
     }
 
-    fn generate_random_move(&self, solution: &Solution, rng: &mut impl Rng) -> Option<Move> {
-        let n1 = solution.cycle1.len();
-        let n2 = solution.cycle2.len();
+    pub fn solve_timed(
+        &self,
+        instance: &TsplibInstance,
+        time_limit: Duration,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo) {
+        self.run(
+            instance,
+            &Budget::timed(time_limit),
+            rng,
+            progress_callback,
+            None,
+        )
+    }
 
-        if n1 + n2 < 3 {
-            return None;
+    /// [`Self::solve_timed`] under a caller-provided budget, so the walk can run alongside
+    /// the metaheuristics on the same deadline or evaluation limit, that also returns the
+    /// best cost over time.
+    pub fn solve_traced(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> (Solution, IterationInfo, ConvergenceTrace) {
+        let mut trace = ConvergenceTrace::new();
+        let (solution, info) = self.run(instance, budget, rng, progress_callback, Some(&mut trace));
+        (solution, info, trace)
+    }
+
+    fn run(
+        &self,
+        instance: &TsplibInstance,
+        budget: &Budget,
+        rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+        mut trace: Option<&mut ConvergenceTrace>,
+    ) -> (Solution, IterationInfo) {
+        let mut current_solution = generate_random_solution(instance, rng);
+        budget.count_solution();
+        let mut current_cost = current_solution.calculate_cost(instance);
+        let mut best_solution = current_solution.clone();
+        let mut best_cost = current_cost;
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.record(budget.elapsed(), best_cost);
         }
 
-        let move_type_choice = rng.gen_range(0..=2);
+        let mut steps = 0;
+        while !budget.exhausted() {
+            let Some(evaluated) = sample_move(&current_solution, instance, self.neighborhood, rng)
+            else {
+                break;
+            };
+            evaluated.move_type.apply(&mut current_solution);
+            current_cost += evaluated.delta;
+            steps += 1;
 
-        match move_type_choice {
-            0 if n1 > 0 && n2 > 0 => {
-                let pos1 = rng.gen_range(0..n1);
-                let pos2 = rng.gen_range(0..n2);
-                let v1 = solution.cycle1[pos1];
-                let v2 = solution.cycle2[pos2];
-                Some(Move::InterRouteExchange { v1, v2 })
-            }
-            1 => {
-                let cycle_choice = if n1 >= 2 && (n2 < 2 || rng.gen_bool(0.5)) {
-                    CycleId::Cycle1
-                } else if n2 >= 2 {
-                    CycleId::Cycle2
-                } else {
-                    return None;
-                };
-                let n = if cycle_choice == CycleId::Cycle1 {
-                    n1
-                } else {
-                    n2
-                };
-                if n < 2 {
-                    return None;
+            if current_cost < best_cost {
+                best_cost = current_cost;
+                best_solution.clone_from(&current_solution);
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record(budget.elapsed(), best_cost);
                 }
-                let pos1 = rng.gen_range(0..n);
-                let mut pos2 = rng.gen_range(0..n);
-                while pos1 == pos2 {
-                    pos2 = rng.gen_range(0..n);
-                }
-                let cycle_vec = solution.get_cycle(cycle_choice);
-                let v1 = cycle_vec[pos1];
-                let v2 = cycle_vec[pos2];
-                Some(Move::IntraRouteVertexExchange {
-                    v1,
-                    v2,
-                    cycle: cycle_choice,
-                })
             }
-            2 => {
-                let cycle_choice = if n1 >= 3 && (n2 < 3 || rng.gen_bool(0.5)) {
-                    CycleId::Cycle1
-                } else if n2 >= 3 {
-                    CycleId::Cycle2
-                } else {
-                    return None;
-                };
-                let n = if cycle_choice == CycleId::Cycle1 {
-                    n1
-                } else {
-                    n2
-                };
-                if n < 3 {
-                    return None;
-                }
-
-                let pos1 = rng.gen_range(0..n);
-                let mut pos2 = rng.gen_range(0..n);
-                while pos1 == pos2 || (pos1 + 1) % n == pos2 || (pos2 + 1) % n == pos1 {
-                    pos2 = rng.gen_range(0..n);
-                }
-
-                let cycle_vec = solution.get_cycle(cycle_choice);
-                let a = cycle_vec[pos1];
-                let b = cycle_vec[(pos1 + 1) % n];
-                let c = cycle_vec[pos2];
-                let d = cycle_vec[(pos2 + 1) % n];
 
-                Some(Move::IntraRouteEdgeExchange {
-                    a,
-                    b,
-                    c,
-                    d,
-                    cycle: cycle_choice,
-                })
+            if steps % 1000 == 0 {
+                progress_callback(format!(
+                    "[Step {}] Current: {}, Best: {}",
+                    steps, current_cost, best_cost
+                ));
             }
-            _ => None,
         }
+
+        if let Some(trace) = trace {
+            trace.finish(budget.elapsed());
+        }
+        progress_callback(format!(
+            "[Finished] Steps: {}, Final Best Cost: {}",
+            steps, best_cost
+        ));
+        // Every step evaluates exactly the move it applies.
+        let info = IterationInfo {
+            unit: IterationUnit::RandomMoves,
+            outer: steps,
+            inner_ls_runs: 0,
+            evaluations: Some(steps as u64),
+        };
+        (best_solution, info)
     }
 }
 
 impl TspAlgorithm for RandomWalk {
     fn name(&self) -> &str {
-        "Random Walk"
+        &self.name_str
     }
 
     fn solve_with_feedback(
         &self,
         instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
         progress_callback: ProgressCallback,
     ) -> Solution {
-        let mut current_solution = generate_random_solution(instance);
-        let mut best_solution = current_solution.clone();
-        let mut best_cost = best_solution.calculate_cost(instance);
-        let mut rng = thread_rng();
-
-        for i in 0..self.max_iterations {
-            if i % 100 == 0 || i == self.max_iterations - 1 {
-                progress_callback(format!(
-                    "[Iter: {}/{}] Best Cost: {}",
-                    i + 1,
-                    self.max_iterations,
-                    best_cost
-                ));
-            }
-
-            if let Some(random_move) = self.generate_random_move(&current_solution, &mut rng) {
-                random_move.apply(&mut current_solution);
-                let current_cost = current_solution.calculate_cost(instance);
-                if current_cost < best_cost {
-                    best_cost = current_cost;
-                    best_solution = current_solution.clone();
-                }
-            } else {
-                break;
-            }
-        }
-        progress_callback(format!("[Finished] Final Best Cost: {}", best_cost));
-        best_solution
+        self.solve_timed(instance, self.time_limit, rng, progress_callback)
+            .0
     }
 }
@@ -0,0 +1,135 @@
+use crate::algorithm::RunResult;
+use crate::tsplib::{Edge, Solution};
+use crate::weight::{Cost, Ordered};
+use rustc_hash::FxHashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Number of solutions each edge appears in.
+pub fn edge_frequencies<'a>(
+    solutions: impl IntoIterator<Item = &'a Solution>,
+) -> FxHashMap<Edge, usize> {
+    let mut frequencies = FxHashMap::default();
+    for solution in solutions {
+        for edge in solution.to_edge_set() {
+            *frequencies.entry(edge).or_insert(0) += 1;
+        }
+    }
+    frequencies
+}
+
+/// Fraction of the edges of `a` that `b` also uses.
+pub fn common_edges(a: &Solution, b: &Solution) -> f64 {
+    let (edges_a, edges_b) = (a.to_edge_set(), b.to_edge_set());
+    if edges_a.is_empty() {
+        return 1.0;
+    }
+    edges_a.intersection(&edges_b).count() as f64 / edges_a.len() as f64
+}
+
+/// Fraction of the vertex pairs sharing a cycle in `a` that also share one in `b`.
+pub fn common_pairs(a: &Solution, b: &Solution) -> f64 {
+    let mut cycle_in_b: FxHashMap<usize, u8> = FxHashMap::default();
+    cycle_in_b.extend(b.cycle1.iter().map(|&v| (v, 1)));
+    cycle_in_b.extend(b.cycle2.iter().map(|&v| (v, 2)));
+
+    let (mut shared_in_a, mut shared_in_both) = (0u64, 0u64);
+    for cycle in [&a.cycle1, &a.cycle2] {
+        for (i, u) in cycle.iter().enumerate() {
+            for v in &cycle[i + 1..] {
+                shared_in_a += 1;
+                if let (Some(cu), Some(cv)) = (cycle_in_b.get(u), cycle_in_b.get(v))
+                    && cu == cv
+                {
+                    shared_in_both += 1;
+                }
+            }
+        }
+    }
+    if shared_in_a == 0 {
+        return 1.0;
+    }
+    shared_in_both as f64 / shared_in_a as f64
+}
+
+/// Similarity of one run's solution to the best run and, on average, to the other runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityPoint {
+    pub cost: Cost,
+    pub edges_to_best: f64,
+    pub pairs_to_best: f64,
+    pub edges_to_others: f64,
+    pub pairs_to_others: f64,
+}
+
+/// Cost/similarity points of every run except the best one, sorted by cost, for
+/// global-convexity plots.
+pub fn similarity_analysis(runs: &[RunResult]) -> Vec<SimilarityPoint> {
+    let Some((best_index, best)) = runs
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, run)| Ordered(run.cost))
+    else {
+        return Vec::new();
+    };
+    let mut points: Vec<SimilarityPoint> = runs
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != best_index)
+        .map(|(i, run)| {
+            let others: Vec<&RunResult> = runs
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, other)| other)
+                .collect();
+            let average = |measure: fn(&Solution, &Solution) -> f64| {
+                others
+                    .iter()
+                    .map(|other| measure(&run.solution, &other.solution))
+                    .sum::<f64>()
+                    / others.len() as f64
+            };
+            SimilarityPoint {
+                cost: run.cost,
+                edges_to_best: common_edges(&run.solution, &best.solution),
+                pairs_to_best: common_pairs(&run.solution, &best.solution),
+                edges_to_others: average(common_edges),
+                pairs_to_others: average(common_pairs),
+            }
+        })
+        .collect();
+    points.sort_by_key(|p| Ordered(p.cost));
+    points
+}
+
+/// Writes the points as CSV with columns
+/// `cost,edges_to_best,pairs_to_best,edges_to_others,pairs_to_others`.
+pub fn write_similarity_csv(points: &[SimilarityPoint], path: &Path) -> std::io::Result<()> {
+    let mut out =
+        String::from("cost,edges_to_best,pairs_to_best,edges_to_others,pairs_to_others\n");
+    for p in points {
+        writeln!(
+            out,
+            "{},{:.4},{:.4},{:.4},{:.4}",
+            p.cost, p.edges_to_best, p.pairs_to_best, p.edges_to_others, p.pairs_to_others
+        )
+        .expect("writing to a String cannot fail");
+    }
+    fs::write(path, out)
+}
+
+/// Writes edge frequencies as CSV with columns `u,v,count`, most frequent first.
+pub fn write_edge_frequency_csv(
+    frequencies: &FxHashMap<Edge, usize>,
+    path: &Path,
+) -> std::io::Result<()> {
+    let mut sorted: Vec<(&Edge, &usize)> = frequencies.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let mut out = String::from("u,v,count\n");
+    for (&(u, v), count) in sorted {
+        writeln!(out, "{},{},{}", u, v, count).expect("writing to a String cannot fail");
+    }
+    fs::write(path, out)
+}
@@ -0,0 +1,182 @@
+use crate::algorithm::TspAlgorithm;
+use crate::algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
+use crate::algorithms::exact::HeldKarp;
+use crate::seed::SeedStream;
+use crate::tsplib::TsplibInstance;
+use crate::weight::{Cost, Weight};
+
+/// Instances up to this size are solved exactly instead of bounded.
+const EXACT_BOUND_VERTICES: usize = 12;
+
+/// Lower bound on the optimal cost of `instance` under its current mode, from `iterations`
+/// steps of subgradient ascent: the 1-tree bound in single-cycle mode and the
+/// [`two_cycle_bound`] otherwise. Tiny instances get their exact optimum. Pins and the
+/// cycle-size rule are relaxed, so the bound stays valid with them. Returns `None` on
+/// prize-collecting instances, whose cost can be negative.
+pub fn lower_bound(instance: &TsplibInstance, iterations: usize) -> Option<Cost> {
+    if instance.is_prize_collecting() {
+        return None;
+    }
+    if instance.size() <= EXACT_BOUND_VERTICES {
+        let mut quiet = |_: String| {};
+        let optimum = HeldKarp::new().solve_with_feedback(
+            instance,
+            &mut SeedStream::default().rng(),
+            &mut quiet,
+        );
+        return Some(optimum.calculate_cost(instance));
+    }
+    Some(if instance.is_single_cycle() {
+        one_tree_bound(instance, iterations)
+    } else {
+        two_cycle_bound(instance, iterations)
+    })
+}
+
+/// Held–Karp 1-tree bound for a single tour over all vertices (at least 3): a minimum
+/// spanning tree of vertices `1..n` plus the two cheapest edges of vertex 0, under degree
+/// penalties tuned by subgradient ascent.
+pub fn one_tree_bound(instance: &TsplibInstance, iterations: usize) -> Cost {
+    subgradient_ascent(instance, iterations, Relaxation::OneTree)
+}
+
+/// Bound for two disjoint cycles of at least 3 vertices each. Dropping the two edges of
+/// vertex 0 and any edge of the other cycle leaves a spanning forest of vertices `1..n`
+/// with two components, so a solution costs at least the cheapest such forest (a minimum
+/// spanning tree without its heaviest edge), the two cheapest edges of vertex 0 and the
+/// cheapest edge among vertices `1..n`. Degree penalties are tuned as for the 1-tree.
+pub fn two_cycle_bound(instance: &TsplibInstance, iterations: usize) -> Cost {
+    subgradient_ascent(instance, iterations, Relaxation::TwoForest)
+}
+
+/// Optimality gap of `cost` above `bound`, in percent.
+pub fn gap_percent(cost: Cost, bound: Cost) -> f64 {
+    if bound <= Cost::ZERO {
+        return 0.0;
+    }
+    100.0 * (cost - bound).to_f64() / bound.to_f64()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Relaxation {
+    OneTree,
+    TwoForest,
+}
+
+/// Value of the relaxation under penalties `pi` (excluding the `-2 * sum(pi)` term) and the
+/// degree of every vertex in it.
+fn relaxed_structure(
+    instance: &TsplibInstance,
+    pi: &[f64],
+    relaxation: Relaxation,
+) -> (f64, Vec<usize>) {
+    let n = instance.size();
+    let cost = |i: usize, j: usize| instance.distance(i, j).to_f64() + pi[i] + pi[j];
+    let mut degree = vec![0; n];
+
+    // Prim's algorithm over vertices 1..n.
+    let mut in_tree = vec![false; n];
+    let mut best_edge: Vec<(f64, usize)> = (0..n).map(|v| (cost(1, v), 1)).collect();
+    in_tree[1] = true;
+    let mut tree_edges = Vec::with_capacity(n - 2);
+    for _ in 2..n {
+        let v = (2..n)
+            .filter(|&v| !in_tree[v])
+            .min_by(|&a, &b| best_edge[a].0.total_cmp(&best_edge[b].0))
+            .expect("a vertex is still outside the tree");
+        in_tree[v] = true;
+        tree_edges.push((best_edge[v].0, best_edge[v].1, v));
+        for u in (2..n).filter(|&u| !in_tree[u]) {
+            let c = cost(v, u);
+            if c < best_edge[u].0 {
+                best_edge[u] = (c, v);
+            }
+        }
+    }
+    if relaxation == Relaxation::TwoForest
+        && let Some(idx) =
+            (0..tree_edges.len()).max_by(|&a, &b| tree_edges[a].0.total_cmp(&tree_edges[b].0))
+    {
+        tree_edges.swap_remove(idx);
+    }
+    let mut value = 0.0;
+    for &(c, u, v) in &tree_edges {
+        value += c;
+        degree[u] += 1;
+        degree[v] += 1;
+    }
+
+    // The two cheapest edges of vertex 0.
+    let mut edges0: Vec<(f64, usize)> = (1..n).map(|v| (cost(0, v), v)).collect();
+    edges0.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for &(c, v) in edges0.iter().take(2) {
+        value += c;
+        degree[0] += 1;
+        degree[v] += 1;
+    }
+
+    if relaxation == Relaxation::TwoForest {
+        let (c, u, v) = (1..n)
+            .flat_map(|u| (u + 1..n).map(move |v| (u, v)))
+            .map(|(u, v)| (cost(u, v), u, v))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .expect("at least two vertices besides vertex 0");
+        value += c;
+        degree[u] += 1;
+        degree[v] += 1;
+    }
+    (value, degree)
+}
+
+/// Maximises the Lagrangian bound over the degree penalties with the classic Held–Karp step
+/// `t = lambda * (upper - bound) / |g|^2`, halving `lambda` whenever the bound has not
+/// improved for a while. The upper bound comes from a constructive heuristic.
+fn subgradient_ascent(
+    instance: &TsplibInstance,
+    iterations: usize,
+    relaxation: Relaxation,
+) -> Cost {
+    let n = instance.size();
+    assert!(
+        n >= 6,
+        "The relaxations need at least 6 vertices, got {}",
+        n
+    );
+    // A fixed stream keeps the bound the same from run to run.
+    let mut quiet = |_: String| {};
+    let upper = WeightedRegretCycle::default()
+        .solve_with_feedback(instance, &mut SeedStream::default().rng(), &mut quiet)
+        .calculate_cost(instance)
+        .to_f64();
+
+    let mut pi = vec![0.0; n];
+    let mut best = f64::MIN;
+    let mut lambda = 2.0;
+    let patience = (n / 4).max(10);
+    let mut since_improvement = 0;
+    for _ in 0..iterations.max(1) {
+        let (value, degree) = relaxed_structure(instance, &pi, relaxation);
+        let bound = value - 2.0 * pi.iter().sum::<f64>();
+        if bound > best {
+            best = bound;
+            since_improvement = 0;
+        } else {
+            since_improvement += 1;
+            if since_improvement >= patience {
+                lambda /= 2.0;
+                since_improvement = 0;
+            }
+        }
+
+        let norm: i64 = degree.iter().map(|&d| (d as i64 - 2).pow(2)).sum();
+        if norm == 0 || lambda < 1e-6 {
+            break;
+        }
+        let step = lambda * (upper - bound).max(0.0) / norm as f64;
+        for (p, &d) in pi.iter_mut().zip(&degree) {
+            *p += step * (d as f64 - 2.0);
+        }
+    }
+    // Integer tour costs let the bound round up; the epsilon absorbs float noise.
+    Cost::ceil_from(best - 1e-6)
+}
@@ -0,0 +1,119 @@
+use crate::counters;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Run budget shared by a metaheuristic and the components it calls, so inner work can stop
+/// at the outer deadline instead of overshooting it. Bounded by any combination of a
+/// deadline, a number of move evaluations and a number of constructed solutions; the last
+/// two make runs comparable across machines.
+#[derive(Debug)]
+pub struct Budget {
+    start: Instant,
+    deadline: Option<Instant>,
+    /// `(move evaluations counted when the limit was set, allowed evaluations)`.
+    evaluations: Option<(u64, u64)>,
+    max_solutions: Option<u64>,
+    solutions: AtomicU64,
+    /// Move evaluations counted when the budget was created, with the `op-counters` feature.
+    evaluations_at_start: Option<u64>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self {
+            start: Instant::now(),
+            deadline: None,
+            evaluations: None,
+            max_solutions: None,
+            solutions: AtomicU64::new(0),
+            evaluations_at_start: counted_evaluations(),
+        }
+    }
+
+    /// Budget ending `limit` from now.
+    pub fn timed(limit: Duration) -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            deadline: Some(start + limit),
+            evaluations: None,
+            max_solutions: None,
+            solutions: AtomicU64::new(0),
+            evaluations_at_start: counted_evaluations(),
+        }
+    }
+
+    /// Additionally ends the budget after `evaluations` move evaluations, as counted by the
+    /// operation counters. Those are process-wide, so the budget also pays for evaluations
+    /// of concurrent runs.
+    ///
+    /// # Panics
+    ///
+    /// Without the `op-counters` feature, which compiles the counters out.
+    pub fn with_max_evaluations(mut self, evaluations: u64) -> Self {
+        let counted = counters::snapshot()
+            .expect("Evaluation budgets need the op-counters feature")
+            .move_evaluations;
+        self.evaluations = Some((counted, evaluations));
+        self
+    }
+
+    /// Additionally ends the budget once [`Self::count_solution`] has been called
+    /// `solutions` times.
+    pub fn with_max_solutions(mut self, solutions: u64) -> Self {
+        self.max_solutions = Some(solutions);
+        self
+    }
+
+    /// Charges one constructed solution (an initial solution, perturbed solution or child).
+    pub fn count_solution(&self) {
+        self.solutions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Move evaluations charged to the budget, or `None` without an evaluation limit.
+    pub fn evaluations_used(&self) -> Option<u64> {
+        let (counted, _) = self.evaluations?;
+        let now = counters::snapshot().map_or(counted, |ops| ops.move_evaluations);
+        Some(now - counted)
+    }
+
+    /// Move evaluations since the budget was created, or `None` without the `op-counters`
+    /// feature. Process-wide like the evaluation limit.
+    pub fn evaluations_since_start(&self) -> Option<u64> {
+        Some(counted_evaluations()? - self.evaluations_at_start?)
+    }
+
+    /// Whether the budget has run out.
+    pub fn exhausted(&self) -> bool {
+        let out_of_evaluations = self
+            .evaluations
+            .zip(self.evaluations_used())
+            .is_some_and(|((_, limit), used)| used >= limit);
+        let out_of_solutions = self
+            .max_solutions
+            .is_some_and(|limit| self.solutions.load(Ordering::Relaxed) >= limit);
+        out_of_evaluations || out_of_solutions || self.past_deadline()
+    }
+
+    /// Whether the deadline has passed. Cheaper than [`Self::exhausted`], so inner loops can
+    /// poll it as often as they like, from any thread, to cut a long step short.
+    pub fn past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Time since the budget was created; the clock all timed components should report.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Time left until the deadline, or `None` without one.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+fn counted_evaluations() -> Option<u64> {
+    counters::snapshot().map(|ops| ops.move_evaluations)
+}
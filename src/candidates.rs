@@ -0,0 +1,239 @@
+use crate::weight::{Cost, Ordered};
+use rustc_hash::FxHashMap;
+
+/// Where the per-vertex candidate lists used by candidate moves come from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CandidateSource {
+    /// The k nearest vertices.
+    NearestNeighbors(usize),
+    /// Neighbours in the Delaunay triangulation of the coordinates.
+    Delaunay,
+    /// Delaunay neighbours unioned with the k nearest vertices.
+    DelaunayWithNearest(usize),
+    /// k vertices spread over the four quadrants around each vertex.
+    Quadrant(usize),
+}
+
+impl CandidateSource {
+    /// Name of the source on the command line, without its list size.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NearestNeighbors(_) => "nearest",
+            Self::Delaunay => "delaunay",
+            Self::DelaunayWithNearest(_) => "delaunay-nearest",
+            Self::Quadrant(_) => "quadrant",
+        }
+    }
+
+    /// The source named `text`, with `k` vertices per list where it takes a size.
+    pub fn parse(text: &str, k: usize) -> Option<Self> {
+        [
+            Self::NearestNeighbors(k),
+            Self::Delaunay,
+            Self::DelaunayWithNearest(k),
+            Self::Quadrant(k),
+        ]
+        .into_iter()
+        .find(|source| source.as_str() == text)
+    }
+
+    /// The same source with `k` vertices per list where it takes a size.
+    pub fn with_list_size(self, k: usize) -> Self {
+        match self {
+            Self::NearestNeighbors(_) => Self::NearestNeighbors(k),
+            Self::Delaunay => Self::Delaunay,
+            Self::DelaunayWithNearest(_) => Self::DelaunayWithNearest(k),
+            Self::Quadrant(_) => Self::Quadrant(k),
+        }
+    }
+}
+
+impl Default for CandidateSource {
+    /// The 10 nearest vertices, which the candidate moves of the base local search look at.
+    fn default() -> Self {
+        Self::NearestNeighbors(10)
+    }
+}
+
+/// A triangle of the triangulation with its circumcircle (center and squared radius).
+struct Triangle {
+    vertices: [usize; 3],
+    center: (f64, f64),
+    radius_sq: f64,
+}
+
+impl Triangle {
+    fn new(vertices: [usize; 3], points: &[(f64, f64)]) -> Self {
+        let [(ax, ay), (bx, by), (cx, cy)] = vertices.map(|v| points[v]);
+        let d = 2.0 * (ax * (by - cy) + bx * (cy - ay) + cx * (ay - by));
+        let (a2, b2, c2) = (ax * ax + ay * ay, bx * bx + by * by, cx * cx + cy * cy);
+        let center = if d.abs() < f64::EPSILON {
+            // Collinear triple: an infinite circumcircle contains every later point, so the
+            // triangle is always replaced.
+            (f64::NAN, f64::NAN)
+        } else {
+            (
+                (a2 * (by - cy) + b2 * (cy - ay) + c2 * (ay - by)) / d,
+                (a2 * (cx - bx) + b2 * (ax - cx) + c2 * (bx - ax)) / d,
+            )
+        };
+        let radius_sq = (ax - center.0).powi(2) + (ay - center.1).powi(2);
+        Self {
+            vertices,
+            center,
+            radius_sq,
+        }
+    }
+
+    fn circumcircle_contains(&self, (x, y): (f64, f64)) -> bool {
+        if self.center.0.is_nan() {
+            return true;
+        }
+        (x - self.center.0).powi(2) + (y - self.center.1).powi(2) < self.radius_sq
+    }
+}
+
+/// Delaunay neighbour lists of `points` (Bowyer–Watson, O(n^2) worst case). Coincident
+/// points are triangulated once and then share their representative's neighbours.
+pub fn delaunay_neighbors(points: &[(f64, f64)]) -> Vec<Vec<usize>> {
+    let n = points.len();
+    let mut neighbors = vec![Vec::new(); n];
+    if n < 2 {
+        return neighbors;
+    }
+
+    // Map coincident points to one representative.
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| points[a].partial_cmp(&points[b]).unwrap());
+    let mut representative: Vec<usize> = (0..n).collect();
+    for w in order.windows(2) {
+        if points[w[0]] == points[w[1]] {
+            representative[w[1]] = representative[w[0]];
+        }
+    }
+    let unique: Vec<usize> = order
+        .iter()
+        .copied()
+        .filter(|&v| representative[v] == v)
+        .collect();
+
+    // Working point set: the unique points followed by a super-triangle enclosing them all.
+    let mut work: Vec<(f64, f64)> = unique.iter().map(|&v| points[v]).collect();
+    let (min_x, max_x, min_y, max_y) = work.iter().fold(
+        (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(a, b, c, d), &(x, y)| (a.min(x), b.max(x), c.min(y), d.max(y)),
+    );
+    let extent = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let (mid_x, mid_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    let m = work.len();
+    work.push((mid_x - 100.0 * extent, mid_y - 100.0 * extent));
+    work.push((mid_x + 100.0 * extent, mid_y - 100.0 * extent));
+    work.push((mid_x, mid_y + 100.0 * extent));
+
+    let mut triangles = vec![Triangle::new([m, m + 1, m + 2], &work)];
+    let mut boundary: FxHashMap<(usize, usize), u32> = FxHashMap::default();
+    for p in 0..m {
+        boundary.clear();
+        triangles.retain(|t| {
+            if !t.circumcircle_contains(work[p]) {
+                return true;
+            }
+            let [a, b, c] = t.vertices;
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *boundary.entry((u.min(v), u.max(v))).or_insert(0) += 1;
+            }
+            false
+        });
+        for (&(u, v), &count) in &boundary {
+            if count == 1 {
+                triangles.push(Triangle::new([u, v, p], &work));
+            }
+        }
+    }
+
+    let mut add_edge = |a: usize, b: usize| {
+        if !neighbors[a].contains(&b) {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+    };
+    for t in &triangles {
+        let [a, b, c] = t.vertices;
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            if u < m && v < m {
+                add_edge(unique[u], unique[v]);
+            }
+        }
+    }
+
+    // Duplicates inherit their representative's neighbours (symmetrically) and are adjacent to
+    // it and to each other.
+    for v in 0..n {
+        let r = representative[v];
+        if r != v {
+            let inherited = neighbors[r].clone();
+            for &u in &inherited {
+                neighbors[u].push(v);
+            }
+            neighbors[v] = inherited;
+            neighbors[v].push(r);
+            neighbors[r].push(v);
+        }
+    }
+    neighbors
+}
+
+/// Quadrant (0..4) of `to` around `from`; coincident points fall in quadrant 0.
+fn quadrant(from: (f64, f64), to: (f64, f64)) -> usize {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    match (dx >= 0.0, dy >= 0.0) {
+        (true, true) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+        (true, false) => 3,
+    }
+}
+
+/// For every vertex, `k` candidates drawn from the four quadrants around it in rounds: each
+/// round takes the next nearest vertex of every non-exhausted quadrant (nearest first), so
+/// the list is not dominated by one nearby cluster and empty quadrants leave their share to
+/// the others.
+pub fn quadrant_neighbors(
+    points: &[(f64, f64)],
+    k: usize,
+    distance: impl Fn(usize, usize) -> Cost,
+) -> Vec<Vec<usize>> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let mut quadrants: [Vec<usize>; 4] = Default::default();
+            for j in (0..n).filter(|&j| j != i) {
+                quadrants[quadrant(points[i], points[j])].push(j);
+            }
+            for q in &mut quadrants {
+                q.sort_unstable_by_key(|&j| Ordered(distance(i, j)));
+            }
+
+            let mut list = Vec::with_capacity(k);
+            let mut round = 0;
+            while list.len() < k {
+                let mut picks: Vec<usize> = quadrants
+                    .iter()
+                    .filter_map(|q| q.get(round).copied())
+                    .collect();
+                if picks.is_empty() {
+                    break;
+                }
+                picks.sort_unstable_by_key(|&j| Ordered(distance(i, j)));
+                list.extend(picks.into_iter().take(k - list.len()));
+                round += 1;
+            }
+            list
+        })
+        .collect()
+}
@@ -0,0 +1,1200 @@
+use crate::algorithms::acceptance::Acceptance;
+use crate::algorithms::hae::{Hae, HaeParams};
+use crate::algorithms::lns::{Lns, LnsParams};
+use crate::algorithms::local_search::base::{
+    InitialSolutionType, LocalSearch, NeighborhoodType, SearchVariant,
+};
+use crate::algorithms::local_search::incidents::{INCIDENT_DIR, incident_files};
+use crate::algorithms::local_search::trajectory::{TrajectoryEvent, read_trajectory, replay};
+use crate::algorithms::msls::{Msls, MslsParams};
+use crate::algorithms::perturbation::{Destroy, Kick, LargePerturbation};
+use crate::external::{ExternalSolver, ExternalSolverKind};
+use crate::fetch::{DEFAULT_MIRROR, FetchOutcome, Fetcher};
+use crate::moves::types::{CycleId, MoveKind};
+use crate::seed::SeedStream;
+use crate::time_limits::{TIME_LIMITS_FILE, TimeLimits, TimeLimitsError, read_time_limits};
+use crate::tsplib::{
+    CandidateSource, DistanceLayout, Objective, Solution, SolutionError, TsplibInstance, is_gzipped,
+};
+use crate::tuning::race::{AlgorithmFactory, Race, TimedAlgorithm, read_config, write_config};
+use crate::tuning::sweep::{sweep_candidate_k, sweep_regret_weights};
+use crate::visualization;
+use crate::weight::Cost;
+use regex::Regex;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const VALIDATE_USAGE: &str = "Usage: imo validate --instance <file.tsp> --tour <file.tour> \
+     [--plot <out.png>] [--slack <n>] [--single-cycle]";
+
+const REPLAY_USAGE: &str = "Usage: imo replay --instance <file.tsp> --trajectory <file.jsonl> \
+     [--dump <before.tour>] [--against <file.tour>]";
+
+const INCIDENTS_USAGE: &str = "Usage: imo incidents [--dir <incident dir>]";
+
+const SWEEP_USAGE: &str = "Usage: imo sweep --instance <file.tsp> \
+     (--regret-weights <w,...> --greedy-weights <w,...> [--k-regret <k>] | --candidate-k <k,...>) \
+     [--runs <n>] [--seed <n>]";
+
+const TUNE_USAGE: &str = "Usage: imo tune <msls|lns|hae> --config <file.json>... \
+     [--instances <file.tsp|dir|pattern>]... [--time-limit <ms>] [--min-blocks <n>] \
+     [--max-blocks <n>] [--best <file.json>] [--seed <n>]";
+
+const FETCH_USAGE: &str = "Usage: imo fetch <instance>... [--mirror <url>] [--dir <dir>]\n\
+     Checksums only catch a file that changes after its first download (trust on first use, \
+     non-cryptographic FNV-1a); the default mirror is plain http.";
+
+const EXPERIMENT_USAGE: &str = "Usage: imo [--instances <file.tsp|dir|pattern>]... \
+     [--initial-solution <file.json|file.tour|random|weighted-regret|nn-insertion|convex-hull|\
+     hilbert|greedy-edge|prize-collecting>] \
+     [--time-limits <file.json> | --reuse-time-limits] [--time-limit <instance>=<ms>]... \
+     [--fetch-missing] [--mirror <url>] [--dry-run] \
+     [--distance-layout <full|full-u16|triangular|triangular-u16>] \
+     [--candidates <nearest|delaunay|delaunay-nearest|quadrant>] \
+     [--local-search <steepest|greedy|candidate|move-list|sampled[:<fraction>]>] \
+     [--objective <sum|max|weighted:<sum weight>,<max weight>>] [--pareto] [--single-cycle] \
+     [--stop-by-evaluations] \
+     [--lns-acceptance <better-only|always|annealing:<temperature>,<cooling>|rrt:<deviation>>] \
+     [--lns-destroy <random:<fraction>|nearest:<n>|radius:<distance>|\
+     worst:<fraction>,<determinism>|segment:<min>,<max>>] [--lns-repair-noise <fraction>] \
+     [--ils-kick <small:<moves>[,<inter-route bias>]|reversal:<min>,<max>|swap:<min>,<max>>]... \
+     [--sideways <n>] [--full-fallback] [--adaptive-k <max k>] \
+     [--lk-pass <depth>] [--size-slack <n>] [--open-paths] [--oracle-check] [--trajectory] \
+     [--audit-children] [--max-solutions <n>] [--prizes <file>] \
+     [--pin <vertex>=<1|2>]... \
+     [--share-elites] [--hae-telemetry] [--hyperheuristic] [--timed-baselines] \
+     [--instance-cache <dir>] \
+     [--concorde <binary> | --lkh <binary>] [--seed <n>]";
+
+/// Instances the experiment runs without `--instances`.
+const DEFAULT_INSTANCES: [&str; 2] = ["tsplib/kroa200.tsp", "tsplib/krob200.tsp"];
+
+/// Options of the default experiment run.
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentArgs {
+    /// Solution ILS, LNS and HAE start from, on the instances it is valid for.
+    pub initial_solution: Option<PathBuf>,
+    /// How the base local search builds its starting solutions when `--initial-solution`
+    /// names a construction instead of a file.
+    pub initial_solution_type: InitialSolutionType,
+    /// File of time limits for ILS, LNS and HAE (see [`read_time_limits`]); with
+    /// `--reuse-time-limits`, the ones the previous experiment saved.
+    pub time_limits_file: Option<PathBuf>,
+    /// Time limits given on the command line; they take priority over the file's.
+    pub time_limits: TimeLimits,
+    /// Print what the experiment would run, with a runtime estimate, instead of running it.
+    pub dry_run: bool,
+    /// `--instances` values: `.tsp` or `.tsp.gz` files, directories of them, or file name
+    /// patterns with
+    /// `*` and `?` such as `tsplib/kro*.tsp`.
+    pub instances: Vec<String>,
+    /// Download instance files that do not exist, by file name, into their directory (see
+    /// [`Fetcher`], whose checksums are only trusted on first use).
+    pub fetch_missing: bool,
+    /// Mirror `--fetch-missing` downloads from, instead of the TSPLIB95 distribution.
+    pub mirror: Option<String>,
+    /// How the distance matrices are stored; instances whose distances do not fit a compact
+    /// layout keep the full one.
+    pub distance_layout: DistanceLayout,
+    /// Where the candidate lists come from; list sizes are capped on instances too small
+    /// for them.
+    pub candidates: CandidateSource,
+    /// Variant of the local search inside MSLS, ILS, LNS and HAE; `None` keeps candidate
+    /// steepest search.
+    pub local_search: Option<SearchVariant>,
+    /// What the local search minimises and the results report.
+    pub objective: Objective,
+    /// Collect the cost/balance Pareto front ILS and HAE visit, over extra runs of both.
+    pub pareto: bool,
+    /// Solve the classic single-tour TSP; gaps are then against the known TSPLIB optimum
+    /// where there is one.
+    pub single_cycle: bool,
+    /// Stop ILS, LNS and HAE after as many move evaluations as an average MSLS run instead
+    /// of after its time, which does not depend on the machine. Needs the `op-counters`
+    /// feature, and MSLS runs, so no pinned time limits.
+    pub stop_by_evaluations: bool,
+    /// When LNS and LNSa move on to a repaired solution.
+    pub lns_acceptance: Acceptance,
+    /// How LNS and LNSa destroy the solution before repairing it.
+    pub lns_destroy: Destroy,
+    /// Up to what fraction the LNS repair perturbs its insertion costs; 0 repairs greedily.
+    pub lns_repair_noise: f64,
+    /// How ILS perturbs the solution between local searches; with more than one kick, ILS
+    /// draws from them as a portfolio and reports how often each one paid off. Empty keeps
+    /// the default kick.
+    pub ils_kicks: Vec<Kick>,
+    /// Let the local search take up to this many consecutive zero-delta moves.
+    pub sideways: usize,
+    /// Make candidate search confirm each local optimum with a full-neighbourhood scan.
+    pub full_fallback: bool,
+    /// Let candidate search grow its list prefix up to this k when it gets stuck.
+    pub adaptive_k: Option<usize>,
+    /// Depth of the Lin-Kernighan style pass run at each local optimum.
+    pub lk_pass: Option<usize>,
+    /// How many vertices the cycle sizes may deviate from an even split.
+    pub size_slack: usize,
+    /// Solve the two-path variant, whose closing links are free.
+    pub open_paths: bool,
+    /// Cross-check every local search step against the brute-force oracle; very slow.
+    pub oracle_check: bool,
+    /// Record the moves of one extra base local search run per instance to trajectory.jsonl
+    /// in its directory, for `imo replay`.
+    pub trajectory: bool,
+    /// Validate and repair every HAE child after recombination.
+    pub audit_children: bool,
+    /// Stop ILS, LNS and HAE after constructing this many solutions, on top of their limit.
+    pub max_solutions: Option<u64>,
+    /// File of vertex prizes, one per vertex in order, which makes every instance
+    /// prize-collecting.
+    pub prizes: Option<PathBuf>,
+    /// Vertices forced into cycle 1 or 2.
+    pub pins: Vec<(usize, CycleId)>,
+    /// Offer every run to one elite archive per instance, and warm-start ILS, LNS and HAE
+    /// from the elites of the algorithms run before them.
+    pub share_elites: bool,
+    /// Record population snapshots of one extra HAE run per instance to population.csv in
+    /// the HAE directory of the instance, to diagnose premature convergence.
+    pub hae_telemetry: bool,
+    /// Run a hyperheuristic over local search configurations after ILS, and print the share
+    /// of iterations it gave each configuration.
+    pub hyperheuristic: bool,
+    /// Run the base local search restarted from random solutions and a random walk after
+    /// HAE on the same budget, to show what the metaheuristics add.
+    pub timed_baselines: bool,
+    /// Directory the distance matrices and candidate lists are stored in and read back from
+    /// by later runs on the same files, instead of being recomputed.
+    pub instance_cache: Option<PathBuf>,
+    /// Concorde or LKH binary whose tours the gaps are measured against instead of the lower
+    /// bound.
+    pub external_solver: Option<ExternalSolver>,
+    /// Master seed of the experiment's random numbers; drawn from the OS when not given.
+    pub seed: Option<u64>,
+}
+
+impl ExperimentArgs {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}\n{}", flag, EXPERIMENT_USAGE))
+            };
+            match flag.as_str() {
+                "--initial-solution" => {
+                    let value = value()?;
+                    match InitialSolutionType::parse(value) {
+                        Some(initial) => parsed.initial_solution_type = initial,
+                        None => parsed.initial_solution = Some(PathBuf::from(value)),
+                    }
+                }
+                "--time-limits" => parsed.time_limits_file = Some(PathBuf::from(value()?)),
+                "--dry-run" => parsed.dry_run = true,
+                "--fetch-missing" => parsed.fetch_missing = true,
+                "--mirror" => parsed.mirror = Some(value()?.clone()),
+                "--pareto" => parsed.pareto = true,
+                "--single-cycle" => parsed.single_cycle = true,
+                "--stop-by-evaluations" => parsed.stop_by_evaluations = true,
+                "--share-elites" => parsed.share_elites = true,
+                "--hae-telemetry" => parsed.hae_telemetry = true,
+                "--audit-children" => parsed.audit_children = true,
+                "--open-paths" => parsed.open_paths = true,
+                "--oracle-check" => parsed.oracle_check = true,
+                "--trajectory" => parsed.trajectory = true,
+                "--full-fallback" => parsed.full_fallback = true,
+                "--prizes" => parsed.prizes = Some(PathBuf::from(value()?)),
+                "--hyperheuristic" => parsed.hyperheuristic = true,
+                "--timed-baselines" => parsed.timed_baselines = true,
+                "--instances" => parsed.instances.push(value()?.clone()),
+                "--instance-cache" => parsed.instance_cache = Some(PathBuf::from(value()?)),
+                "--concorde" | "--lkh" => {
+                    if parsed.external_solver.is_some() {
+                        return Err("Give one of --concorde and --lkh".to_string());
+                    }
+                    let kind = if flag == "--concorde" {
+                        ExternalSolverKind::Concorde
+                    } else {
+                        ExternalSolverKind::Lkh
+                    };
+                    parsed.external_solver = Some(ExternalSolver::new(kind, value()?));
+                }
+                "--distance-layout" => {
+                    let value = value()?;
+                    parsed.distance_layout = DistanceLayout::parse(value)
+                        .ok_or_else(|| format!("Invalid --distance-layout value: {}", value))?;
+                }
+                "--candidates" => {
+                    let value = value()?;
+                    parsed.candidates = CandidateSource::parse(value, 10)
+                        .ok_or_else(|| format!("Invalid --candidates value: {}", value))?;
+                }
+                "--local-search" => {
+                    let value = value()?;
+                    parsed.local_search = Some(
+                        SearchVariant::parse(value)
+                            .ok_or_else(|| format!("Invalid --local-search value: {}", value))?,
+                    );
+                }
+                "--lns-acceptance" => {
+                    let value = value()?;
+                    parsed.lns_acceptance = Acceptance::parse(value)
+                        .ok_or_else(|| format!("Invalid --lns-acceptance value: {}", value))?;
+                }
+                "--seed" => {
+                    let value = value()?;
+                    parsed.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid --seed value: {}", value))?,
+                    );
+                }
+                "--lns-destroy" => {
+                    let value = value()?;
+                    parsed.lns_destroy = Destroy::parse(value)
+                        .ok_or_else(|| format!("Invalid --lns-destroy value: {}", value))?;
+                }
+                "--lns-repair-noise" => {
+                    let value = value()?;
+                    parsed.lns_repair_noise = value
+                        .parse()
+                        .ok()
+                        .filter(|noise| (0.0..=1.0).contains(noise))
+                        .ok_or_else(|| format!("Invalid --lns-repair-noise value: {}", value))?;
+                }
+                "--sideways" | "--size-slack" => {
+                    let value = value()?;
+                    let count = value
+                        .parse()
+                        .map_err(|_| format!("Invalid {} value: {}", flag, value))?;
+                    if flag == "--sideways" {
+                        parsed.sideways = count;
+                    } else {
+                        parsed.size_slack = count;
+                    }
+                }
+                "--adaptive-k" | "--lk-pass" => {
+                    let value = value()?;
+                    let count = value
+                        .parse()
+                        .ok()
+                        .filter(|&count: &usize| count > 0)
+                        .ok_or_else(|| format!("Invalid {} value: {}", flag, value))?;
+                    if flag == "--adaptive-k" {
+                        parsed.adaptive_k = Some(count);
+                    } else {
+                        parsed.lk_pass = Some(count);
+                    }
+                }
+                "--max-solutions" => {
+                    let value = value()?;
+                    parsed.max_solutions = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&solutions: &u64| solutions > 0)
+                            .ok_or_else(|| format!("Invalid --max-solutions value: {}", value))?,
+                    );
+                }
+                "--pin" => {
+                    let value = value()?;
+                    let pin = value
+                        .split_once('=')
+                        .and_then(|(vertex, cycle)| {
+                            let cycle = match cycle {
+                                "1" => CycleId::Cycle1,
+                                "2" => CycleId::Cycle2,
+                                _ => return None,
+                            };
+                            Some((vertex.parse().ok()?, cycle))
+                        })
+                        .ok_or_else(|| format!("Invalid --pin value: {}", value))?;
+                    parsed.pins.push(pin);
+                }
+                "--ils-kick" => {
+                    let value = value()?;
+                    parsed.ils_kicks.push(
+                        Kick::parse(value)
+                            .ok_or_else(|| format!("Invalid --ils-kick value: {}", value))?,
+                    );
+                }
+                "--objective" => {
+                    let value = value()?;
+                    parsed.objective = Objective::parse(value)
+                        .ok_or_else(|| format!("Invalid --objective value: {}", value))?;
+                }
+                "--reuse-time-limits" => {
+                    parsed.time_limits_file = Some(PathBuf::from(TIME_LIMITS_FILE))
+                }
+                "--time-limit" => {
+                    let value = value()?;
+                    let (instance, ms) = value
+                        .split_once('=')
+                        .and_then(|(instance, ms)| Some((instance, ms.parse().ok()?)))
+                        .ok_or_else(|| format!("Invalid --time-limit value: {}", value))?;
+                    parsed
+                        .time_limits
+                        .insert(instance.to_string(), Duration::from_millis(ms));
+                }
+                _ => return Err(format!("Unknown argument: {}\n{}", flag, EXPERIMENT_USAGE)),
+            }
+        }
+        if parsed.stop_by_evaluations
+            && (parsed.time_limits_file.is_some() || !parsed.time_limits.is_empty())
+        {
+            return Err(
+                "--stop-by-evaluations takes its limits from MSLS, which pinned time \
+                 limits skip; drop --time-limits, --reuse-time-limits and --time-limit"
+                    .to_string(),
+            );
+        }
+        let candidate_k = match parsed.local_search {
+            None => Some(10),
+            Some(SearchVariant::CandidateSteepest(k)) => Some(k),
+            Some(_) => None,
+        };
+        match (candidate_k, parsed.adaptive_k) {
+            (None, _) if parsed.full_fallback || parsed.adaptive_k.is_some() => {
+                return Err(
+                    "--full-fallback and --adaptive-k only apply to candidate search".to_string(),
+                );
+            }
+            (Some(k), Some(max_k)) if max_k < k => {
+                return Err(format!(
+                    "--adaptive-k must be at least the candidate list size {}",
+                    k
+                ));
+            }
+            _ => {}
+        }
+        if parsed.mirror.is_some() && !parsed.fetch_missing {
+            return Err("--mirror only applies with --fetch-missing".to_string());
+        }
+        if parsed.local_search == Some(SearchVariant::MoveListSteepest)
+            && (parsed.sideways > 0 || parsed.size_slack > 0 || parsed.open_paths)
+        {
+            return Err(
+                "--sideways, --size-slack and --open-paths do not apply to move-list search"
+                    .to_string(),
+            );
+        }
+        if parsed.local_search == Some(SearchVariant::MoveListSteepest)
+            && !parsed.objective.is_separable()
+        {
+            return Err(
+                "Move-list search only supports --objective sum: its stored deltas go stale \
+                 when the objective depends on the cycle lengths"
+                    .to_string(),
+            );
+        }
+        Ok(parsed)
+    }
+
+    /// The pinned time limits: those of the file, overridden by the command line's.
+    pub fn pinned_time_limits(&self) -> Result<TimeLimits, TimeLimitsError> {
+        let mut limits = match &self.time_limits_file {
+            Some(path) => read_time_limits(path)?,
+            None => TimeLimits::new(),
+        };
+        limits.extend(self.time_limits.clone());
+        Ok(limits)
+    }
+
+    /// The `--prizes` file's prizes, whitespace-separated in vertex order.
+    pub fn read_prizes(&self) -> Result<Option<Vec<Cost>>, Box<dyn Error>> {
+        let Some(path) = &self.prizes else {
+            return Ok(None);
+        };
+        let prizes = fs::read_to_string(path)?
+            .split_whitespace()
+            .map(|prize| {
+                prize
+                    .parse()
+                    .map_err(|_| format!("Invalid prize in {}: {}", path.display(), prize))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Some(prizes))
+    }
+
+    /// The instance files to run, in order and without repeats: those `--instances` names,
+    /// or kroA200 and kroB200 by default. Fails on a directory or pattern without any
+    /// `.tsp` file, and on two files that would share an instance name. With
+    /// `--fetch-missing`, files that do not exist are downloaded first.
+    pub fn instance_paths(&self) -> Result<Vec<PathBuf>, String> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        if self.instances.is_empty() {
+            paths.extend(DEFAULT_INSTANCES.iter().map(PathBuf::from));
+        }
+        for pattern in &self.instances {
+            for path in expand_instance_pattern(pattern)? {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        let mut names = HashSet::new();
+        for path in &paths {
+            if !names.insert(instance_name(path)) {
+                return Err(format!(
+                    "Two instance files are named {}; pass only one of them",
+                    instance_name(path)
+                ));
+            }
+        }
+        if self.fetch_missing {
+            let mirror = self.mirror.as_deref().unwrap_or(DEFAULT_MIRROR);
+            for path in paths.iter().filter(|path| !path.exists()) {
+                let dir = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+                let outcome = Fetcher::new(mirror, dir)
+                    .fetch(&instance_name(path))
+                    .map_err(|e| e.to_string())?;
+                print_fetch_outcome(&outcome);
+            }
+        }
+        Ok(paths)
+    }
+}
+
+/// Name of the instance in `path`, its file name without the extension (both of them for a
+/// `.tsp.gz` file).
+pub fn instance_name(path: &Path) -> String {
+    let path = match path.file_stem() {
+        Some(stem) if is_gzipped(path) => Path::new(stem),
+        _ => path,
+    };
+    path.file_stem().map_or_else(
+        || path.display().to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    )
+}
+
+/// The `.tsp` and `.tsp.gz` files of a directory, the files matching a pattern with `*` and
+/// `?` in its file name, sorted, or else `pattern` as a file path.
+fn expand_instance_pattern(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let path = Path::new(pattern);
+    let (dir, matcher) = if path.is_dir() {
+        (path, None)
+    } else {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        if !file_name.contains(['*', '?']) {
+            return Ok(vec![path.to_path_buf()]);
+        }
+        let glob = regex::escape(&file_name)
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".");
+        let matcher = Regex::new(&format!("^{}$", glob)).map_err(|e| e.to_string())?;
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        (dir, Some(matcher))
+    };
+    let entries = fs::read_dir(dir).map_err(|e| format!("Cannot list {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| match &matcher {
+            Some(matcher) => path
+                .file_name()
+                .is_some_and(|name| matcher.is_match(&name.to_string_lossy())),
+            None => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.ends_with(".tsp") || name.ends_with(".tsp.gz")
+            }
+        })
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("No instance files match {}", pattern));
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Options of the `validate` subcommand.
+#[derive(Debug, Clone, Default)]
+struct ValidateArgs {
+    instance: Option<PathBuf>,
+    tour: Option<PathBuf>,
+    plot: Option<PathBuf>,
+    size_slack: usize,
+    single_cycle: bool,
+}
+
+impl ValidateArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}\n{}", flag, VALIDATE_USAGE))
+            };
+            match flag.as_str() {
+                "--instance" => parsed.instance = Some(PathBuf::from(value()?)),
+                "--tour" => parsed.tour = Some(PathBuf::from(value()?)),
+                "--plot" => parsed.plot = Some(PathBuf::from(value()?)),
+                "--slack" => {
+                    let value = value()?;
+                    parsed.size_slack = value
+                        .parse()
+                        .map_err(|_| format!("Invalid --slack value: {}", value))?;
+                }
+                "--single-cycle" => parsed.single_cycle = true,
+                _ => return Err(format!("Unknown argument: {}\n{}", flag, VALIDATE_USAGE)),
+            }
+        }
+        if parsed.instance.is_none() || parsed.tour.is_none() {
+            return Err(VALIDATE_USAGE.to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// Options of the `replay` subcommand.
+#[derive(Debug, Clone, Default)]
+struct ReplayArgs {
+    instance: Option<PathBuf>,
+    trajectory: Option<PathBuf>,
+    dump: Option<PathBuf>,
+    /// Tour the final solution is diffed against.
+    against: Option<PathBuf>,
+}
+
+impl ReplayArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}\n{}", flag, REPLAY_USAGE))?;
+            match flag.as_str() {
+                "--instance" => parsed.instance = Some(PathBuf::from(value)),
+                "--trajectory" => parsed.trajectory = Some(PathBuf::from(value)),
+                "--dump" => parsed.dump = Some(PathBuf::from(value)),
+                "--against" => parsed.against = Some(PathBuf::from(value)),
+                _ => return Err(format!("Unknown argument: {}\n{}", flag, REPLAY_USAGE)),
+            }
+        }
+        if parsed.instance.is_none() || parsed.trajectory.is_none() {
+            return Err(REPLAY_USAGE.to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// Options of the `incidents` subcommand.
+#[derive(Debug, Clone)]
+struct IncidentsArgs {
+    dir: PathBuf,
+}
+
+impl IncidentsArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self {
+            dir: PathBuf::from(INCIDENT_DIR),
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}\n{}", flag, INCIDENTS_USAGE))
+            };
+            match flag.as_str() {
+                "--dir" => parsed.dir = PathBuf::from(value()?),
+                _ => return Err(format!("Unknown argument: {}\n{}", flag, INCIDENTS_USAGE)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Options of the `sweep` subcommand.
+#[derive(Debug, Clone)]
+struct SweepArgs {
+    instance: Option<PathBuf>,
+    regret_weights: Vec<f64>,
+    greedy_weights: Vec<f64>,
+    k_regret: usize,
+    candidate_ks: Vec<usize>,
+    runs: usize,
+    /// Master seed of the runs; drawn from the OS when not given.
+    seed: Option<u64>,
+}
+
+impl SweepArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self {
+            instance: None,
+            regret_weights: Vec::new(),
+            greedy_weights: Vec::new(),
+            k_regret: 2,
+            candidate_ks: Vec::new(),
+            runs: 20,
+            seed: None,
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}\n{}", flag, SWEEP_USAGE))?;
+            match flag.as_str() {
+                "--instance" => parsed.instance = Some(PathBuf::from(value)),
+                "--regret-weights" => parsed.regret_weights = parse_list(flag, value)?,
+                "--greedy-weights" => parsed.greedy_weights = parse_list(flag, value)?,
+                "--candidate-k" => parsed.candidate_ks = parse_list(flag, value)?,
+                "--k-regret" => {
+                    parsed.k_regret = value
+                        .parse()
+                        .ok()
+                        .filter(|&k| k >= 1)
+                        .ok_or_else(|| format!("Invalid --k-regret value: {}", value))?;
+                }
+                "--runs" => {
+                    parsed.runs = value
+                        .parse()
+                        .map_err(|_| format!("Invalid --runs value: {}", value))?;
+                }
+                "--seed" => {
+                    parsed.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid --seed value: {}", value))?,
+                    )
+                }
+                _ => return Err(format!("Unknown argument: {}\n{}", flag, SWEEP_USAGE)),
+            }
+        }
+        // Exactly one of the two sweeps.
+        let weights = !parsed.regret_weights.is_empty() && !parsed.greedy_weights.is_empty();
+        let candidate_ks = !parsed.candidate_ks.is_empty();
+        if parsed.instance.is_none() || weights == candidate_ks {
+            return Err(SWEEP_USAGE.to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// Options of the `tune` subcommand.
+#[derive(Debug, Clone)]
+struct TuneArgs {
+    algorithm: String,
+    configs: Vec<PathBuf>,
+    instances: Vec<String>,
+    /// Time per run of LNS and HAE; MSLS runs its configured iterations.
+    time_limit: Duration,
+    min_blocks: usize,
+    max_blocks: usize,
+    /// File the winning configuration is written to.
+    best: PathBuf,
+    /// Master seed of the race; drawn from the OS when not given.
+    seed: Option<u64>,
+}
+
+impl TuneArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let (algorithm, args) = args.split_first().ok_or(TUNE_USAGE)?;
+        if !["msls", "lns", "hae"].contains(&algorithm.as_str()) {
+            return Err(format!(
+                "Unknown algorithm to tune: {}\n{}",
+                algorithm, TUNE_USAGE
+            ));
+        }
+        let mut parsed = Self {
+            algorithm: algorithm.clone(),
+            configs: Vec::new(),
+            instances: Vec::new(),
+            time_limit: Duration::from_secs(1),
+            min_blocks: 5,
+            max_blocks: 50,
+            best: PathBuf::from("tuned.json"),
+            seed: None,
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}\n{}", flag, TUNE_USAGE))?;
+            let count = || {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid {} value: {}", flag, value))
+            };
+            match flag.as_str() {
+                "--config" => parsed.configs.push(PathBuf::from(value)),
+                "--instances" => parsed.instances.push(value.clone()),
+                "--time-limit" => parsed.time_limit = Duration::from_millis(count()? as u64),
+                "--min-blocks" => parsed.min_blocks = count()?,
+                "--max-blocks" => parsed.max_blocks = count()?,
+                "--best" => parsed.best = PathBuf::from(value),
+                "--seed" => {
+                    parsed.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid --seed value: {}", value))?,
+                    )
+                }
+                _ => return Err(format!("Unknown argument: {}\n{}", flag, TUNE_USAGE)),
+            }
+        }
+        if parsed.configs.len() < 2 {
+            return Err(format!(
+                "Racing needs at least two --config files\n{}",
+                TUNE_USAGE
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// A comma-separated `--flag` value such as `0.5,1,2`.
+fn parse_list<T: std::str::FromStr>(flag: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| item.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid {} value: {}", flag, value))
+}
+
+/// Options of the `fetch` subcommand.
+#[derive(Debug, Clone, Default)]
+struct FetchArgs {
+    names: Vec<String>,
+    /// The mirror given with `--mirror`, else [`DEFAULT_MIRROR`].
+    mirror: String,
+    dir: PathBuf,
+}
+
+impl FetchArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self {
+            mirror: DEFAULT_MIRROR.to_string(),
+            dir: PathBuf::from("tsplib"),
+            ..Self::default()
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}\n{}", arg, FETCH_USAGE))
+            };
+            match arg.as_str() {
+                "--mirror" => parsed.mirror = value()?.clone(),
+                "--dir" => parsed.dir = PathBuf::from(value()?),
+                flag if flag.starts_with("--") => {
+                    return Err(format!("Unknown argument: {}\n{}", flag, FETCH_USAGE));
+                }
+                name => parsed.names.push(instance_name(Path::new(name))),
+            }
+        }
+        if parsed.names.is_empty() {
+            return Err(FETCH_USAGE.to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// A bare instance name such as `kroa200.tsp` is looked up in `tsplib/` when it does not
+/// exist as given, and then as `kroa200.tsp.gz` in both places.
+fn resolve_instance_path(path: &Path) -> PathBuf {
+    let in_tsplib = Path::new("tsplib").join(path);
+    let gzipped = |path: &Path| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".gz");
+        PathBuf::from(name)
+    };
+    [
+        path.to_path_buf(),
+        in_tsplib.clone(),
+        gzipped(path),
+        gzipped(&in_tsplib),
+    ]
+    .into_iter()
+    .find(|candidate| candidate.exists())
+    .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// `validate` subcommand: loads an instance and a tour file, lists every rule the tour
+/// breaks, prints the total and per-cycle cost and optionally plots it. Fails if the tour is
+/// invalid, so scripts can rely on the exit code.
+pub fn run_validate(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = ValidateArgs::parse(args)?;
+    let instance_path = resolve_instance_path(args.instance.as_deref().expect("checked by parse"));
+    let tour_path = args.tour.as_deref().expect("checked by parse");
+
+    let mut instance = TsplibInstance::from_file(&instance_path)?;
+    instance.set_single_cycle(args.single_cycle);
+    let solution = Solution::read_tour(tour_path)?;
+    println!(
+        "Instance: {} ({} vertices), tour: {}",
+        instance.name,
+        instance.size(),
+        tour_path.display()
+    );
+
+    let errors = solution.validation_errors(&instance, args.size_slack);
+    if errors.is_empty() {
+        println!("Valid");
+    } else {
+        println!("Invalid ({} problems):", errors.len());
+        for error in &errors {
+            println!("  - {}", error);
+        }
+    }
+
+    // Costs are only meaningful when every vertex id is in range.
+    if errors
+        .iter()
+        .all(|e| !matches!(e, SolutionError::VertexOutOfRange(_)))
+    {
+        let (cost1, cost2) = solution.cycle_costs(&instance);
+        println!("Total cost: {}", cost1 + cost2);
+        println!(
+            "Cycle 1: {} vertices, cost {}",
+            solution.cycle1.len(),
+            cost1
+        );
+        println!(
+            "Cycle 2: {} vertices, cost {}",
+            solution.cycle2.len(),
+            cost2
+        );
+
+        if let Some(plot) = &args.plot {
+            let title = format!("{} - {}", instance.name, tour_path.display());
+            visualization::plot_solution(&instance, &solution, &title, plot)?;
+            println!("Plot saved to {}", plot.display());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err("The tour is invalid".into())
+    }
+}
+
+/// `replay` subcommand: re-applies a trajectory recorded with
+/// `LocalSearch::with_trajectory`, verifying the cost before and the cycle deltas of every
+/// move, and stops at the first divergence. `--dump` writes the solution just before it as a
+/// tour file for `validate` or further debugging; `--against` counts the moves that still
+/// separate the final solution from another tour.
+pub fn run_replay(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = ReplayArgs::parse(args)?;
+    let instance_path = resolve_instance_path(args.instance.as_deref().expect("checked by parse"));
+    let trajectory_path = args.trajectory.as_deref().expect("checked by parse");
+
+    let instance = TsplibInstance::from_file(&instance_path)?;
+    let events = read_trajectory(trajectory_path)?;
+    if let Some(TrajectoryEvent::Start { instance: name, .. }) = events.first()
+        && *name != instance.name
+    {
+        println!(
+            "[WARN] Trajectory was recorded on {}, replaying on {}",
+            name, instance.name
+        );
+    }
+    let moves = events
+        .iter()
+        .filter(|e| matches!(e, TrajectoryEvent::Move { .. }))
+        .count();
+    println!(
+        "Replaying {} events ({} moves) on {}",
+        events.len(),
+        moves,
+        instance.name
+    );
+
+    match replay(&instance, &events) {
+        Ok(solution) => {
+            println!(
+                "All moves verified. Final cost: {}",
+                solution.calculate_cost(&instance)
+            );
+            if let Some(against) = &args.against {
+                let other = Solution::read_tour(against)?;
+                let moves = solution
+                    .diff(&other)
+                    .map_err(|e| format!("Cannot compare with {}: {}", against.display(), e))?;
+                println!(
+                    "{} moves to {} (cost {}):",
+                    moves.len(),
+                    against.display(),
+                    other.calculate_cost(&instance)
+                );
+                for kind in MoveKind::ALL {
+                    let count = moves.iter().filter(|m| m.kind() == kind).count();
+                    if count > 0 {
+                        println!("  {:?}: {}", kind, count);
+                    }
+                }
+            }
+            Ok(())
+        }
+        Err(divergence) => {
+            println!("Divergence: {}", divergence);
+            println!(
+                "Cost before the step: {}",
+                divergence.solution_before.calculate_cost(&instance)
+            );
+            if let Some(dump) = &args.dump {
+                divergence
+                    .solution_before
+                    .write_tour(dump, &instance.name)?;
+                println!("Solution before the step saved to {}", dump.display());
+            }
+            Err("The trajectory diverges".into())
+        }
+    }
+}
+
+/// `sweep` subcommand: runs the weighted k-regret construction over the grid of regret and
+/// greedy weights, or candidate steepest local search over candidate list sizes, on one
+/// instance and prints every configuration's results and the best one.
+pub fn run_sweep(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = SweepArgs::parse(args)?;
+    let instance_path = resolve_instance_path(args.instance.as_deref().expect("checked by parse"));
+    let instance = TsplibInstance::from_file(&instance_path)?;
+    let seeds = SeedStream::master(args.seed);
+    println!("Seed {}", seeds.seed());
+    let report = if args.candidate_ks.is_empty() {
+        sweep_regret_weights(
+            &instance,
+            args.k_regret,
+            &args.regret_weights,
+            &args.greedy_weights,
+            args.runs,
+            seeds,
+        )
+    } else {
+        sweep_candidate_k(
+            &instance,
+            &args.candidate_ks,
+            NeighborhoodType::EdgeExchange,
+            InitialSolutionType::Random,
+            args.runs,
+            seeds,
+        )
+    };
+    print!("{}", report.format());
+    Ok(())
+}
+
+/// `tune` subcommand: races the MSLS, LNS or HAE parameter configurations of the `--config`
+/// files, JSON of [`MslsParams`], [`LnsParams`] or [`HaeParams`] (see [`read_config`]), on
+/// the instances with [`Race`], prints the survivors and writes the winner to `--best`, from
+/// where it can be edited and raced again.
+pub fn run_tune(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = TuneArgs::parse(args)?;
+    let instance_args = ExperimentArgs {
+        instances: args.instances.clone(),
+        ..ExperimentArgs::default()
+    };
+    let mut instances = Vec::new();
+    for path in instance_args.instance_paths()? {
+        let mut instance = TsplibInstance::from_file(&path)?;
+        instance.precompute_nearest_neighbors(10);
+        instances.push(instance);
+    }
+
+    let base_ls = LocalSearch::new(
+        SearchVariant::CandidateSteepest(10),
+        NeighborhoodType::EdgeExchange,
+        InitialSolutionType::Random,
+    );
+    let time_limit = args.time_limit;
+    match args.algorithm.as_str() {
+        "msls" => race_configs::<MslsParams>(
+            &args,
+            &instances,
+            Box::new(move |params| Box::new(Msls::from_params(base_ls.clone(), params.clone()))),
+        ),
+        "lns" => race_configs::<LnsParams>(
+            &args,
+            &instances,
+            Box::new(move |&params| {
+                let lns = Lns::from_params(
+                    base_ls.clone(),
+                    Box::new(LargePerturbation::new(0.2)),
+                    params,
+                );
+                Box::new(TimedAlgorithm::new(
+                    lns.name().to_string(),
+                    time_limit,
+                    move |instance, budget, rng| {
+                        lns.solve_within(instance, budget, rng, &mut |_| {}).0
+                    },
+                ))
+            }),
+        ),
+        _ => race_configs::<HaeParams>(
+            &args,
+            &instances,
+            Box::new(move |&params| {
+                let hae = Hae::from_params(base_ls.clone(), params);
+                Box::new(TimedAlgorithm::new(
+                    hae.name().to_string(),
+                    time_limit,
+                    move |instance, budget, rng| {
+                        hae.solve_within(instance, budget, rng, &mut |_| {}).0
+                    },
+                ))
+            }),
+        ),
+    }
+}
+
+/// Reads the `--config` files of `args` as `C`, races them on `instances` with the
+/// algorithms `factory` builds and writes the winner to `--best`.
+fn race_configs<C: Clone + Serialize + DeserializeOwned>(
+    args: &TuneArgs,
+    instances: &[TsplibInstance],
+    factory: AlgorithmFactory<C>,
+) -> Result<(), Box<dyn Error>> {
+    let configs = args
+        .configs
+        .iter()
+        .map(|path| read_config(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e)))
+        .collect::<Result<Vec<C>, _>>()?;
+    let seeds = SeedStream::master(args.seed);
+    println!(
+        "Racing {} {} configurations on {} instances (seed {})",
+        configs.len(),
+        args.algorithm,
+        instances.len(),
+        seeds.seed()
+    );
+    let result =
+        Race::new(args.min_blocks, args.max_blocks, seeds).run(configs, &factory, instances);
+    println!(
+        "{} survivors after {} blocks:",
+        result.survivors.len(),
+        result.blocks_run
+    );
+    for entry in &result.survivors {
+        println!(
+            "  mean rank {:>5.2}, mean cost {:>10.2}  {}",
+            entry.mean_rank, entry.mean_cost, entry.name
+        );
+    }
+    let best = result.best().expect("at least one configuration survives");
+    write_config(&args.best, &best.config)?;
+    println!("Best configuration written to {}", args.best.display());
+    Ok(())
+}
+
+/// `fetch` subcommand: downloads the named instances into `tsplib/` (see [`Fetcher`]), from
+/// `--mirror` or the TSPLIB95 distribution. Instances already there are only checked against
+/// their checksums. Fails if any instance could not be fetched.
+pub fn run_fetch(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = FetchArgs::parse(args)?;
+    let fetcher = Fetcher::new(args.mirror.as_str(), &args.dir);
+    let mut failed = 0;
+    for name in &args.names {
+        match fetcher.fetch(name) {
+            Ok(outcome) => print_fetch_outcome(&outcome),
+            Err(e) => {
+                failed += 1;
+                println!("FAILED  {}: {}", name, e);
+            }
+        }
+    }
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} instances could not be fetched",
+            failed,
+            args.names.len()
+        )
+        .into())
+    }
+}
+
+fn print_fetch_outcome(outcome: &FetchOutcome) {
+    match outcome {
+        FetchOutcome::Present(path) => println!("present {}", path.display()),
+        FetchOutcome::Verified(path) => println!("fetched {} (checksum verified)", path.display()),
+        FetchOutcome::Recorded(path, checksum) => println!(
+            "fetched {} (new checksum fnv1a64:{:016x} recorded)",
+            path.display(),
+            checksum
+        ),
+    }
+}
+
+/// `incidents` subcommand: replays every cost-mismatch incident the local search filed (see
+/// [`crate::algorithms::local_search::incidents`]) on its instance from `tsplib/`, like a test
+/// suite: an incident that still diverges fails, one that replays cleanly has been fixed and
+/// can be deleted. Fails if any incident still reproduces.
+pub fn run_incidents(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = IncidentsArgs::parse(args)?;
+    let files = incident_files(&args.dir)?;
+    if files.is_empty() {
+        println!("No incidents in {}", args.dir.display());
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for file in &files {
+        let outcome = read_trajectory(file)
+            .map_err(|e| e.to_string())
+            .and_then(|events| {
+                let Some(TrajectoryEvent::Start { instance, .. }) = events.first() else {
+                    unreachable!("read_trajectory checks the start event");
+                };
+                let path = resolve_instance_path(Path::new(&format!("{}.tsp", instance)));
+                let instance = TsplibInstance::from_file(&path).map_err(|e| e.to_string())?;
+                replay(&instance, &events).map_err(|divergence| divergence.to_string())
+            });
+        match outcome {
+            Ok(_) => println!("fixed   {}", file.display()),
+            Err(message) => {
+                failed += 1;
+                println!("FAILED  {}: {}", file.display(), message);
+            }
+        }
+    }
+    println!(
+        "{} incidents: {} still failing, {} fixed",
+        files.len(),
+        failed,
+        files.len() - failed
+    );
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} incidents still reproduce", failed).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<ExperimentArgs, String> {
+        ExperimentArgs::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    /// Move-list search with an objective other than the sum is an argument error rather than
+    /// a panic in `LocalSearch::with_objective`.
+    #[test]
+    fn move_list_rejects_non_separable_objectives() {
+        for objective in ["max", "weighted:1,1"] {
+            let result = parse(&["--local-search", "move-list", "--objective", objective]);
+            assert!(result.is_err(), "--objective {} was accepted", objective);
+        }
+        let args = parse(&["--local-search", "move-list", "--objective", "sum"]).unwrap();
+        assert_eq!(args.local_search, Some(SearchVariant::MoveListSteepest));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::weight::{Cost, Weight};
+use std::time::Duration;
+
+/// Best-so-far cost of one run over time, for anytime-quality metrics.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConvergenceTrace {
+    /// `(time since start, best cost)` at every improvement, in time order.
+    samples: Vec<(Duration, Cost)>,
+    /// When the run stopped.
+    end: Duration,
+}
+
+impl ConvergenceTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `cost` reached `elapsed` after the start, if it beats the last sample.
+    pub fn record(&mut self, elapsed: Duration, cost: Cost) {
+        if self.samples.last().is_none_or(|&(_, best)| cost < best) {
+            self.samples.push((elapsed, cost));
+        }
+        self.end = self.end.max(elapsed);
+    }
+
+    /// Marks the end of the run, which closes the last step of the curve.
+    pub fn finish(&mut self, elapsed: Duration) {
+        self.end = self.end.max(elapsed);
+    }
+
+    pub fn best_cost(&self) -> Option<Cost> {
+        self.samples.last().map(|&(_, cost)| cost)
+    }
+
+    /// Primal integral normalised by the run length: the time-average of the primal gap
+    /// against `reference` over `[0, end]`, counting 1 before the first solution. It lies in
+    /// `[0, 1]` and is lower for runs that get close to the reference early.
+    pub fn primal_integral(&self, reference: Cost) -> f64 {
+        let end = self.end.as_secs_f64();
+        if end <= 0.0 {
+            return self.best_cost().map_or(1.0, |c| primal_gap(c, reference));
+        }
+        let mut area = 0.0;
+        let mut last_time = 0.0;
+        let mut last_gap = 1.0;
+        for &(time, cost) in &self.samples {
+            let time = time.as_secs_f64();
+            area += last_gap * (time - last_time);
+            last_time = time;
+            last_gap = primal_gap(cost, reference);
+        }
+        area += last_gap * (end - last_time);
+        area / end
+    }
+}
+
+/// Primal gap `|cost - reference| / max(|cost|, |reference|)`, in `[0, 1]` for costs of the
+/// same sign; 0 when both are 0.
+pub fn primal_gap(cost: Cost, reference: Cost) -> f64 {
+    let scale = cost.abs().max(reference.abs());
+    if scale == Cost::ZERO {
+        return 0.0;
+    }
+    ((cost - reference).abs().to_f64() / scale.to_f64()).min(1.0)
+}
@@ -0,0 +1,77 @@
+//! Machine-independent operation counters, compiled in only with the `op-counters` feature.
+//! Without it the `count_*` hooks are empty and [`snapshot`] returns `None`. The counters are
+//! process-wide, so experiments running concurrently share them.
+
+#[cfg(feature = "op-counters")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "op-counters")]
+static DISTANCE_LOOKUPS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "op-counters")]
+static MOVE_EVALUATIONS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "op-counters")]
+static SOLUTION_COPIES: AtomicU64 = AtomicU64::new(0);
+
+/// Operation totals, as a snapshot or as the difference of two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpCounts {
+    pub distance_lookups: u64,
+    pub move_evaluations: u64,
+    pub solution_copies: u64,
+}
+
+impl OpCounts {
+    /// Operations performed between `earlier` and `self`.
+    pub fn since(self, earlier: OpCounts) -> OpCounts {
+        OpCounts {
+            distance_lookups: self.distance_lookups - earlier.distance_lookups,
+            move_evaluations: self.move_evaluations - earlier.move_evaluations,
+            solution_copies: self.solution_copies - earlier.solution_copies,
+        }
+    }
+
+    /// One-line summary with the counts divided by `runs`.
+    pub fn format_per_run(&self, runs: usize) -> String {
+        let per_run = |count: u64| count as f64 / runs.max(1) as f64;
+        format!(
+            "Per run: {:.0} distance lookups, {:.0} move evaluations, {:.1} solution copies",
+            per_run(self.distance_lookups),
+            per_run(self.move_evaluations),
+            per_run(self.solution_copies)
+        )
+    }
+}
+
+/// Current totals, or `None` when the counters are compiled out.
+pub fn snapshot() -> Option<OpCounts> {
+    #[cfg(feature = "op-counters")]
+    {
+        Some(OpCounts {
+            distance_lookups: DISTANCE_LOOKUPS.load(Ordering::Relaxed),
+            move_evaluations: MOVE_EVALUATIONS.load(Ordering::Relaxed),
+            solution_copies: SOLUTION_COPIES.load(Ordering::Relaxed),
+        })
+    }
+    #[cfg(not(feature = "op-counters"))]
+    {
+        None
+    }
+}
+
+#[inline(always)]
+pub fn count_distance_lookup() {
+    #[cfg(feature = "op-counters")]
+    DISTANCE_LOOKUPS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub fn count_move_evaluation() {
+    #[cfg(feature = "op-counters")]
+    MOVE_EVALUATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub fn count_solution_copy() {
+    #[cfg(feature = "op-counters")]
+    SOLUTION_COPIES.fetch_add(1, Ordering::Relaxed);
+}
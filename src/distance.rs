@@ -0,0 +1,207 @@
+//! Batched Euclidean distance kernels used when building distance matrices and nearest
+//! neighbour lists, including those of generated instances (which are built from their
+//! coordinates through the same path).
+//!
+//! Coordinates are taken in structure-of-arrays form so the chunked loop below can be
+//! auto-vectorized; a plain scalar loop handles the remainder (and is the fallback for
+//! targets where the compiler does not vectorize).
+
+use crate::weight::{Cost, Weight};
+
+const LANES: usize = 8;
+
+/// Writes the Euclidean distance from `(x0, y0)` to every point `(xs[j], ys[j])` into
+/// `out[j]`, rounded as [`Weight::from_length`] does (TSPLIB `nint` for integer costs).
+pub fn euclidean_row(x0: f64, y0: f64, xs: &[f64], ys: &[f64], out: &mut [Cost]) {
+    assert!(
+        xs.len() == ys.len() && xs.len() == out.len(),
+        "Coordinate and output slices must have equal length"
+    );
+
+    let chunks = xs.len() / LANES;
+    for c in 0..chunks {
+        let base = c * LANES;
+        let xs = &xs[base..base + LANES];
+        let ys = &ys[base..base + LANES];
+        let mut lane = [0.0f64; LANES];
+        for l in 0..LANES {
+            let dx = xs[l] - x0;
+            let dy = ys[l] - y0;
+            lane[l] = (dx * dx + dy * dy).sqrt();
+        }
+        for (slot, dist) in out[base..base + LANES].iter_mut().zip(lane) {
+            *slot = Cost::from_length(dist);
+        }
+    }
+
+    for j in chunks * LANES..xs.len() {
+        out[j] = euclidean(x0, y0, xs[j], ys[j]);
+    }
+}
+
+/// Scalar Euclidean distance, identical to one lane of `euclidean_row`.
+pub fn euclidean(x1: f64, y1: f64, x2: f64, y2: f64) -> Cost {
+    let dx = x2 - x1;
+    let dy = y2 - y1;
+    Cost::from_length((dx * dx + dy * dy).sqrt())
+}
+
+/// Storage layout of an instance's distance matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceLayout {
+    /// Full `n x n` matrix of [`Cost`].
+    #[default]
+    Full,
+    /// Full `n x n` matrix of `u16`; every distance must lie in `0..=u16::MAX`.
+    FullU16,
+    /// Strict lower triangle of `u32`, valid for symmetric instances (half the memory).
+    Triangular,
+    /// Strict lower triangle of `u16`.
+    TriangularU16,
+}
+
+impl DistanceLayout {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::FullU16 => "full-u16",
+            Self::Triangular => "triangular",
+            Self::TriangularU16 => "triangular-u16",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        [
+            Self::Full,
+            Self::FullU16,
+            Self::Triangular,
+            Self::TriangularU16,
+        ]
+        .into_iter()
+        .find(|layout| layout.as_str() == text)
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("distance {value} between {i} and {j} does not fit the {layout:?} layout")]
+pub struct DistanceOverflow {
+    pub value: Cost,
+    pub i: usize,
+    pub j: usize,
+    pub layout: DistanceLayout,
+}
+
+#[derive(Debug, Clone)]
+pub enum DistanceMatrix {
+    Full { n: usize, data: Vec<Cost> },
+    FullU16 { n: usize, data: Vec<u16> },
+    Triangular { data: Vec<u32> },
+    TriangularU16 { data: Vec<u16> },
+}
+
+impl DistanceMatrix {
+    /// Builds an `n x n` matrix in `layout`, asking `fill_row(i, row)` for each full row.
+    /// Triangular layouts keep only `j < i` and assume the matrix is symmetric.
+    pub fn build(
+        layout: DistanceLayout,
+        n: usize,
+        mut fill_row: impl FnMut(usize, &mut [Cost]),
+    ) -> Result<Self, DistanceOverflow> {
+        let mut row = vec![Cost::ZERO; n];
+        // The compact layouts store integers, so fractional distances do not fit either.
+        fn compact<T: TryFrom<i64>>(
+            value: Cost,
+            i: usize,
+            j: usize,
+            layout: DistanceLayout,
+        ) -> Result<T, DistanceOverflow> {
+            value
+                .to_i64()
+                .and_then(|value| T::try_from(value).ok())
+                .ok_or(DistanceOverflow {
+                    value,
+                    i,
+                    j,
+                    layout,
+                })
+        }
+
+        Ok(match layout {
+            DistanceLayout::Full => {
+                let mut data = Vec::with_capacity(n * n);
+                for i in 0..n {
+                    fill_row(i, &mut row);
+                    data.extend_from_slice(&row);
+                }
+                DistanceMatrix::Full { n, data }
+            }
+            DistanceLayout::FullU16 => {
+                let mut data = Vec::with_capacity(n * n);
+                for i in 0..n {
+                    fill_row(i, &mut row);
+                    for (j, &d) in row.iter().enumerate() {
+                        data.push(compact(d, i, j, layout)?);
+                    }
+                }
+                DistanceMatrix::FullU16 { n, data }
+            }
+            DistanceLayout::Triangular => {
+                let mut data = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+                for i in 0..n {
+                    fill_row(i, &mut row);
+                    for (j, &d) in row[..i].iter().enumerate() {
+                        data.push(compact(d, i, j, layout)?);
+                    }
+                }
+                DistanceMatrix::Triangular { data }
+            }
+            DistanceLayout::TriangularU16 => {
+                let mut data = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+                for i in 0..n {
+                    fill_row(i, &mut row);
+                    for (j, &d) in row[..i].iter().enumerate() {
+                        data.push(compact(d, i, j, layout)?);
+                    }
+                }
+                DistanceMatrix::TriangularU16 { data }
+            }
+        })
+    }
+
+    pub fn layout(&self) -> DistanceLayout {
+        match self {
+            DistanceMatrix::Full { .. } => DistanceLayout::Full,
+            DistanceMatrix::FullU16 { .. } => DistanceLayout::FullU16,
+            DistanceMatrix::Triangular { .. } => DistanceLayout::Triangular,
+            DistanceMatrix::TriangularU16 { .. } => DistanceLayout::TriangularU16,
+        }
+    }
+
+    #[inline]
+    fn triangular_index(i: usize, j: usize) -> usize {
+        let (hi, lo) = if i > j { (i, j) } else { (j, i) };
+        hi * (hi - 1) / 2 + lo
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize, j: usize) -> Cost {
+        match self {
+            DistanceMatrix::Full { n, data } => data[i * n + j],
+            DistanceMatrix::FullU16 { n, data } => Cost::from_i32(data[i * n + j] as i32),
+            DistanceMatrix::Triangular { data } => {
+                if i == j {
+                    Cost::ZERO
+                } else {
+                    Cost::from_i32(data[Self::triangular_index(i, j)] as i32)
+                }
+            }
+            DistanceMatrix::TriangularU16 { data } => {
+                if i == j {
+                    Cost::ZERO
+                } else {
+                    Cost::from_i32(data[Self::triangular_index(i, j)] as i32)
+                }
+            }
+        }
+    }
+}
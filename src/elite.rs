@@ -0,0 +1,127 @@
+use crate::analysis::common_edges;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::Cost;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use rustc_hash::FxHashSet;
+use std::sync::Mutex;
+
+/// A solution in the [`EliteArchive`].
+#[derive(Debug, Clone)]
+pub struct Elite {
+    pub solution: Solution,
+    pub cost: Cost,
+    /// Name of the algorithm that found it.
+    pub source: String,
+}
+
+/// The best solutions found so far, bounded in size and kept diverse, that algorithms push
+/// their results to and pull warm starts from. Elites stay at least `min_distance` apart,
+/// measured as the fraction of edges one does not share with the other. Shared by reference
+/// between algorithms and threads. Exact duplicates of an elite are turned away before the
+/// distance check and counted.
+#[derive(Debug)]
+pub struct EliteArchive {
+    capacity: usize,
+    min_distance: f64,
+    state: Mutex<EliteState>,
+}
+
+#[derive(Debug)]
+struct EliteState {
+    /// Sorted by increasing cost.
+    elites: Vec<Elite>,
+    /// [`Solution::canonical_hash`] of every elite.
+    hashes: FxHashSet<u64>,
+    /// Offers rejected as exact duplicates of an elite.
+    duplicates: usize,
+}
+
+impl EliteArchive {
+    pub fn new(capacity: usize, min_distance: f64) -> Self {
+        assert!(
+            capacity >= 1,
+            "An elite archive needs room for one solution"
+        );
+        Self {
+            capacity,
+            min_distance,
+            state: Mutex::new(EliteState {
+                elites: Vec::with_capacity(capacity),
+                hashes: FxHashSet::default(),
+                duplicates: 0,
+            }),
+        }
+    }
+
+    /// Adds `solution` if it is better than the elites closer to it than `min_distance`,
+    /// which it then replaces, and, when the archive is full, better than the worst elite,
+    /// which it then evicts. Returns whether it was added.
+    pub fn offer(&self, solution: &Solution, instance: &TsplibInstance, source: &str) -> bool {
+        let cost = solution.calculate_cost(instance);
+        let hash = solution.canonical_hash();
+        let mut state = self.state.lock().expect("Elite archive lock poisoned");
+        if state.hashes.contains(&hash) {
+            state.duplicates += 1;
+            return false;
+        }
+        let EliteState { elites, hashes, .. } = &mut *state;
+        let close: Vec<usize> = elites
+            .iter()
+            .enumerate()
+            .filter(|(_, elite)| 1.0 - common_edges(solution, &elite.solution) < self.min_distance)
+            .map(|(index, _)| index)
+            .collect();
+        if close.iter().any(|&index| elites[index].cost <= cost) {
+            return false;
+        }
+        if close.is_empty()
+            && elites.len() >= self.capacity
+            && elites.last().is_some_and(|worst| worst.cost <= cost)
+        {
+            return false;
+        }
+        for &index in close.iter().rev() {
+            hashes.remove(&elites.remove(index).solution.canonical_hash());
+        }
+        if elites.len() >= self.capacity {
+            for evicted in elites.drain(self.capacity - 1..) {
+                hashes.remove(&evicted.solution.canonical_hash());
+            }
+        }
+        hashes.insert(hash);
+        let at = elites.partition_point(|elite| elite.cost <= cost);
+        elites.insert(
+            at,
+            Elite {
+                solution: solution.clone(),
+                cost,
+                source: source.to_string(),
+            },
+        );
+        true
+    }
+
+    /// A uniformly drawn elite to start from, or `None` while the archive is empty.
+    pub fn warm_start<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Elite> {
+        let state = self.state.lock().expect("Elite archive lock poisoned");
+        state.elites.choose(rng).cloned()
+    }
+
+    /// Copy of the elites, best first.
+    pub fn elites(&self) -> Vec<Elite> {
+        self.state
+            .lock()
+            .expect("Elite archive lock poisoned")
+            .elites
+            .clone()
+    }
+
+    /// Offers rejected so far as exact duplicates of an elite.
+    pub fn duplicates(&self) -> usize {
+        self.state
+            .lock()
+            .expect("Elite archive lock poisoned")
+            .duplicates
+    }
+}
@@ -0,0 +1,267 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::algorithms::constructive::common::finish_construction;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered};
+use rand::RngCore;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use thiserror::Error;
+
+/// Distinguishes the scratch files of concurrent runs within this process.
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Error)]
+pub enum ExternalError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Solver failed ({status}): {stderr}")]
+    SolverFailed { status: String, stderr: String },
+    #[error("Cannot parse solver output: {0}")]
+    Parse(String),
+    #[error("Solver returned an invalid tour: {0}")]
+    InvalidTour(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalSolverKind {
+    Concorde,
+    Lkh,
+}
+
+/// Wrapper around a locally installed Concorde or LKH binary. The instance is written as an
+/// explicit TSPLIB distance matrix, so the solver sees exactly this crate's distances. Both
+/// solvers return one tour; outside single-cycle mode it is cut into the two cycles at the
+/// cheapest rotation, giving a strong reference solution rather than a proven optimum.
+#[derive(Debug, Clone)]
+pub struct ExternalSolver {
+    kind: ExternalSolverKind,
+    binary: PathBuf,
+    work_dir: PathBuf,
+    name_str: String,
+}
+
+impl ExternalSolver {
+    pub fn new(kind: ExternalSolverKind, binary: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            binary: binary.into(),
+            work_dir: std::env::temp_dir().join("imo-external"),
+            name_str: format!("External ({:?})", kind),
+        }
+    }
+
+    /// Runs the solver and returns its tour over all vertices.
+    pub fn solve_tour(&self, instance: &TsplibInstance) -> Result<Vec<usize>, ExternalError> {
+        fs::create_dir_all(&self.work_dir)?;
+        let stem = format!(
+            "{}_{}_{}",
+            instance.name,
+            std::process::id(),
+            RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let problem = self.work_dir.join(format!("{}.tsp", stem));
+        let tour_file = self.work_dir.join(format!("{}.tour", stem));
+        fs::write(&problem, tsplib_matrix(instance))?;
+
+        let mut command = Command::new(&self.binary);
+        command.current_dir(&self.work_dir);
+        let mut scratch = vec![problem.clone(), tour_file.clone()];
+        match self.kind {
+            ExternalSolverKind::Concorde => {
+                command.arg("-x").arg("-o").arg(&tour_file).arg(&problem);
+            }
+            ExternalSolverKind::Lkh => {
+                let params = self.work_dir.join(format!("{}.par", stem));
+                fs::write(
+                    &params,
+                    format!(
+                        "PROBLEM_FILE = {}\nOUTPUT_TOUR_FILE = {}\nRUNS = 1\nTRACE_LEVEL = 0\n",
+                        problem.display(),
+                        tour_file.display()
+                    ),
+                )?;
+                command.arg(&params);
+                scratch.push(params);
+            }
+        }
+
+        let result = run_and_parse(command, self.kind, &tour_file);
+        for file in scratch {
+            let _ = fs::remove_file(file);
+        }
+        let tour = result?;
+        check_tour(&tour, instance.size())?;
+        Ok(tour)
+    }
+
+    /// Cost of the solver's solution, for use as the reference in gap columns.
+    pub fn reference_cost(&self, instance: &TsplibInstance) -> Result<Cost, ExternalError> {
+        Ok(self.solve(instance)?.calculate_cost(instance))
+    }
+
+    /// The solver's tour turned into a solution for the instance's mode.
+    pub fn solve(&self, instance: &TsplibInstance) -> Result<Solution, ExternalError> {
+        let tour = self.solve_tour(instance)?;
+        let mut solution = if instance.is_single_cycle() {
+            Solution::new(tour, vec![])
+        } else {
+            split_tour(&tour, instance)
+        };
+        finish_construction(&mut solution, instance);
+        Ok(solution)
+    }
+}
+
+impl TspAlgorithm for ExternalSolver {
+    fn name(&self) -> &str {
+        &self.name_str
+    }
+
+    /// Panics if the solver cannot be run; use [`ExternalSolver::solve`] to handle errors.
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        _rng: &mut dyn RngCore,
+        progress_callback: ProgressCallback,
+    ) -> Solution {
+        progress_callback(format!("[Init] Running {}", self.binary.display()));
+        let solution = self
+            .solve(instance)
+            .unwrap_or_else(|e| panic!("{} failed: {}", self.name_str, e));
+        progress_callback(format!(
+            "[Finished] Cost: {}",
+            solution.calculate_cost(instance)
+        ));
+        solution
+    }
+}
+
+fn run_and_parse(
+    mut command: Command,
+    kind: ExternalSolverKind,
+    tour_file: &Path,
+) -> Result<Vec<usize>, ExternalError> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(ExternalError::SolverFailed {
+            status: output.status.to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let text = fs::read_to_string(tour_file)?;
+    match kind {
+        ExternalSolverKind::Concorde => parse_concorde_tour(&text),
+        ExternalSolverKind::Lkh => parse_tsplib_tour(&text),
+    }
+}
+
+/// The instance as a TSPLIB file with an explicit full distance matrix.
+fn tsplib_matrix(instance: &TsplibInstance) -> String {
+    let n = instance.size();
+    let mut out = format!(
+        "NAME : {}\nTYPE : TSP\nDIMENSION : {}\nEDGE_WEIGHT_TYPE : EXPLICIT\n\
+         EDGE_WEIGHT_FORMAT : FULL_MATRIX\nEDGE_WEIGHT_SECTION\n",
+        instance.name, n
+    );
+    for i in 0..n {
+        let row: Vec<String> = (0..n)
+            .map(|j| instance.distance(i, j).to_string())
+            .collect();
+        out.push_str(&row.join(" "));
+        out.push('\n');
+    }
+    out.push_str("EOF\n");
+    out
+}
+
+/// Concorde's `.sol` format: the vertex count followed by 0-based vertex ids.
+fn parse_concorde_tour(text: &str) -> Result<Vec<usize>, ExternalError> {
+    let mut numbers = text.split_whitespace().map(|token| {
+        token
+            .parse::<usize>()
+            .map_err(|_| ExternalError::Parse(format!("Unexpected token '{}'", token)))
+    });
+    let n = numbers
+        .next()
+        .ok_or_else(|| ExternalError::Parse("Empty tour file".to_string()))??;
+    let tour = numbers.collect::<Result<Vec<_>, _>>()?;
+    if tour.len() != n {
+        return Err(ExternalError::Parse(format!(
+            "Header announces {} vertices, found {}",
+            n,
+            tour.len()
+        )));
+    }
+    Ok(tour)
+}
+
+/// TSPLIB `TOUR_SECTION` with 1-based vertex ids terminated by `-1`, as written by LKH.
+fn parse_tsplib_tour(text: &str) -> Result<Vec<usize>, ExternalError> {
+    let section = text
+        .split_once("TOUR_SECTION")
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| ExternalError::Parse("Missing TOUR_SECTION".to_string()))?;
+    let mut tour = Vec::new();
+    for token in section.split_whitespace() {
+        if token == "-1" || token == "EOF" {
+            break;
+        }
+        let id: usize = token
+            .parse()
+            .map_err(|_| ExternalError::Parse(format!("Unexpected token '{}'", token)))?;
+        if id == 0 {
+            return Err(ExternalError::Parse("Vertex ids are 1-based".to_string()));
+        }
+        tour.push(id - 1);
+    }
+    Ok(tour)
+}
+
+fn check_tour(tour: &[usize], n: usize) -> Result<(), ExternalError> {
+    if tour.len() != n {
+        return Err(ExternalError::InvalidTour(format!(
+            "{} vertices instead of {}",
+            tour.len(),
+            n
+        )));
+    }
+    let mut seen = vec![false; n];
+    for &v in tour {
+        if v >= n || std::mem::replace(&mut seen[v], true) {
+            return Err(ExternalError::InvalidTour(format!(
+                "vertex {} is out of range or repeated",
+                v
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Cuts a tour into cycle 1 (`ceil(n/2)` consecutive vertices) and cycle 2 (the rest), each
+/// closed by a new link, at the rotation where the two new links replace the two removed
+/// ones most cheaply.
+fn split_tour(tour: &[usize], instance: &TsplibInstance) -> Solution {
+    let n = tour.len();
+    let size1 = n.div_ceil(2);
+    if size1 == n {
+        return Solution::new(tour.to_vec(), vec![]);
+    }
+    let at = |i: usize| tour[i % n];
+    let best_start = (0..n)
+        .min_by_key(|&k| {
+            let (first1, last1) = (at(k), at(k + size1 - 1));
+            let (first2, last2) = (at(k + size1), at(k + n - 1));
+            Ordered(
+                instance.distance(first1, last1) + instance.distance(first2, last2)
+                    - instance.distance(last1, first2)
+                    - instance.distance(last2, first1),
+            )
+        })
+        .expect("the tour is non-empty");
+    let cycle1 = (0..size1).map(|i| at(best_start + i)).collect();
+    let cycle2 = (size1..n).map(|i| at(best_start + i)).collect();
+    Solution::new(cycle1, cycle2)
+}
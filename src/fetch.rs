@@ -0,0 +1,246 @@
+//! Downloads TSPLIB instances by name, for `imo fetch` and `--fetch-missing`. The transfer is
+//! left to the `curl` binary, as the external solvers are left to theirs, so the crate needs
+//! no HTTP dependency.
+//!
+//! Every fetched file is checked against `checksums.txt` in the instance directory, one
+//! `<name> fnv1a64:<hex>` line per instance. An instance without a line is trusted on first
+//! download and its checksum recorded, so later downloads (on this or, with the file
+//! committed, on other machines) must match it. No checksums ship with the repository, and
+//! the default mirror is plain http, so this is trust on first use: it catches a file that
+//! changes or gets truncated between downloads, not one that was tampered with before the
+//! first. FNV-1a is not a cryptographic hash and is not meant to be one.
+
+use crate::utils::fnv1a;
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// The TSPLIB95 distribution, which serves `<name>.tsp.gz`.
+pub const DEFAULT_MIRROR: &str = "http://comopt.ifi.uni-heidelberg.de/software/TSPLIB95/tsp";
+/// Known checksums of the instances in a directory.
+pub const CHECKSUM_FILE: &str = "checksums.txt";
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Cannot download {name} from {mirror}: {message}")]
+    Download {
+        name: String,
+        mirror: String,
+        message: String,
+    },
+    #[error("Cannot decompress {0}: {1}")]
+    Decompress(String, String),
+    #[error(
+        "Checksum mismatch for {name}: expected fnv1a64:{expected:016x}, got fnv1a64:{actual:016x}"
+    )]
+    Checksum {
+        name: String,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("Invalid line in {0}: {1}")]
+    ChecksumFile(String, String),
+}
+
+/// What [`Fetcher::fetch`] did for one instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchOutcome {
+    /// The file was already there and matched its checksum, if one is known.
+    Present(PathBuf),
+    /// Downloaded and matching the known checksum.
+    Verified(PathBuf),
+    /// Downloaded with no checksum on record; its checksum was recorded.
+    Recorded(PathBuf, u64),
+}
+
+/// Fetches instances from a mirror into a directory.
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    mirror: String,
+    dir: PathBuf,
+}
+
+impl Fetcher {
+    /// Fetches from `mirror`, any URL `curl` understands (including `file://`), into `dir`.
+    pub fn new(mirror: impl Into<String>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            mirror: mirror.into().trim_end_matches('/').to_string(),
+            dir: dir.into(),
+        }
+    }
+
+    /// Makes sure `<dir>/<name>.tsp` exists and matches its checksum, downloading it if it
+    /// does not exist. The file name is lowercase, as in `tsplib/`; the mirror is asked for
+    /// the name as given and, failing that, in TSPLIB's spelling (`kroa200` as `kroA200`).
+    /// Tries `<name>.tsp.gz` before `<name>.tsp`.
+    pub fn fetch(&self, name: &str) -> Result<FetchOutcome, FetchError> {
+        let file_name = name.to_lowercase();
+        let target = self.dir.join(format!("{}.tsp", file_name));
+        let mut checksums = Checksums::read(&self.dir.join(CHECKSUM_FILE))?;
+        let expected = checksums.get(&file_name);
+
+        if target.exists() {
+            let actual = fnv1a(&fs::read(&target)?);
+            return match expected {
+                Some(expected) if expected != actual => Err(FetchError::Checksum {
+                    name: file_name,
+                    expected,
+                    actual,
+                }),
+                _ => Ok(FetchOutcome::Present(target)),
+            };
+        }
+
+        let contents = self.download(name)?;
+        let actual = fnv1a(&contents);
+        if let Some(expected) = expected
+            && expected != actual
+        {
+            return Err(FetchError::Checksum {
+                name: file_name,
+                expected,
+                actual,
+            });
+        }
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&target, contents)?;
+        match expected {
+            Some(_) => Ok(FetchOutcome::Verified(target)),
+            None => {
+                checksums.insert(&file_name, actual);
+                checksums.write()?;
+                Ok(FetchOutcome::Recorded(target, actual))
+            }
+        }
+    }
+
+    /// The decompressed `.tsp` file of `name`, from the first URL that works.
+    fn download(&self, name: &str) -> Result<Vec<u8>, FetchError> {
+        let mut spellings = vec![name.to_string()];
+        if let Some(tsplib) = tsplib_spelling(name)
+            && tsplib != name
+        {
+            spellings.push(tsplib);
+        }
+        let mut last_error = String::new();
+        for spelling in &spellings {
+            for (extension, compressed) in [("tsp.gz", true), ("tsp", false)] {
+                let url = format!("{}/{}.{}", self.mirror, spelling, extension);
+                match curl(&url) {
+                    Ok(bytes) if compressed => {
+                        return gunzip(&bytes)
+                            .map_err(|message| FetchError::Decompress(url.clone(), message));
+                    }
+                    Ok(bytes) => return Ok(bytes),
+                    Err(message) => last_error = message,
+                }
+            }
+        }
+        Err(FetchError::Download {
+            name: name.to_string(),
+            mirror: self.mirror.clone(),
+            message: last_error,
+        })
+    }
+}
+
+/// TSPLIB writes the letter before the trailing digits of some names in upper case
+/// (`kroA200`, `kroB150`); the lowercase name with that letter capitalised, if it has one.
+fn tsplib_spelling(name: &str) -> Option<String> {
+    let digits_start = name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    if digits_start == name.len() || digits_start < 2 {
+        return None;
+    }
+    let (prefix, digits) = name.split_at(digits_start);
+    let mut chars: Vec<char> = prefix.to_lowercase().chars().collect();
+    let last = chars.last_mut()?;
+    if !last.is_ascii_alphabetic() {
+        return None;
+    }
+    *last = last.to_ascii_uppercase();
+    Some(format!(
+        "{}{}",
+        chars.into_iter().collect::<String>(),
+        digits
+    ))
+}
+
+fn curl(url: &str) -> Result<Vec<u8>, String> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", url])
+        .output()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(bytes)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| e.to_string())?;
+    Ok(decompressed)
+}
+
+/// The lines of a checksum file, kept in order so rewriting it only appends.
+struct Checksums {
+    path: PathBuf,
+    entries: Vec<(String, u64)>,
+}
+
+impl Checksums {
+    /// The checksums in `path`; none if it does not exist.
+    fn read(path: &Path) -> Result<Self, FetchError> {
+        let mut entries = Vec::new();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || FetchError::ChecksumFile(path.display().to_string(), line.into());
+            let (name, checksum) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let checksum = checksum
+                .trim()
+                .strip_prefix("fnv1a64:")
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                .ok_or_else(invalid)?;
+            entries.push((name.to_string(), checksum));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|(known, _)| known == name)
+            .map(|&(_, checksum)| checksum)
+    }
+
+    fn insert(&mut self, name: &str, checksum: u64) {
+        self.entries.push((name.to_string(), checksum));
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let text: String = self
+            .entries
+            .iter()
+            .map(|(name, checksum)| format!("{} fnv1a64:{:016x}\n", name, checksum))
+            .collect();
+        fs::write(&self.path, text)
+    }
+}
@@ -0,0 +1,150 @@
+use crate::weight::{Cost, Weight};
+use std::collections::HashMap;
+
+/// Value of a field in a [`FlatObject`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    List(Vec<i64>),
+}
+
+/// A one-line JSON object whose values are numbers, booleans, plain strings (no escapes) or
+/// lists of integers. This is all the crate writes (trajectories, run archives), so it gets
+/// by without a JSON dependency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatObject(HashMap<String, Field>);
+
+impl FlatObject {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let inner = text
+            .trim()
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+            .ok_or("expected a JSON object")?;
+        let mut fields = HashMap::new();
+        let mut rest = inner.trim();
+        while !rest.is_empty() {
+            let after_quote = rest.strip_prefix('"').ok_or("expected a quoted key")?;
+            let (key, after_key) = after_quote.split_once('"').ok_or("unterminated key")?;
+            let after_colon = after_key
+                .trim_start()
+                .strip_prefix(':')
+                .ok_or("expected ':'")?
+                .trim_start();
+            let (value, after_value) = if let Some(string) = after_colon.strip_prefix('"') {
+                let (value, tail) = string.split_once('"').ok_or("unterminated string")?;
+                (Field::Str(value.to_string()), tail)
+            } else if let Some(list) = after_colon.strip_prefix('[') {
+                let (items, tail) = list.split_once(']').ok_or("unterminated list")?;
+                let items = items
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| item.parse().map_err(|_| format!("invalid number {}", item)))
+                    .collect::<Result<Vec<i64>, String>>()?;
+                (Field::List(items), tail)
+            } else {
+                let end = after_colon.find(',').unwrap_or(after_colon.len());
+                let token = after_colon[..end].trim();
+                let value = match token {
+                    "true" => Field::Bool(true),
+                    "false" => Field::Bool(false),
+                    _ => match token.parse() {
+                        Ok(value) => Field::Int(value),
+                        Err(_) => Field::Float(
+                            token
+                                .parse()
+                                .map_err(|_| format!("invalid value {}", token))?,
+                        ),
+                    },
+                };
+                (value, &after_colon[end..])
+            };
+            fields.insert(key.to_string(), value);
+            let tail = after_value.trim_start();
+            rest = match tail.strip_prefix(',') {
+                Some(next) => next.trim_start(),
+                None if tail.is_empty() => tail,
+                None => return Err(format!("unexpected text: {}", tail)),
+            };
+        }
+        Ok(Self(fields))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Field> {
+        self.0.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Field)> {
+        self.0.iter()
+    }
+
+    pub fn int(&self, key: &str) -> Result<i64, String> {
+        match self.get(key) {
+            Some(Field::Int(value)) => Ok(*value),
+            _ => Err(format!("missing integer field '{}'", key)),
+        }
+    }
+
+    pub fn usize(&self, key: &str) -> Result<usize, String> {
+        usize::try_from(self.int(key)?).map_err(|_| format!("negative '{}'", key))
+    }
+
+    pub fn u64(&self, key: &str) -> Result<u64, String> {
+        u64::try_from(self.int(key)?).map_err(|_| format!("negative '{}'", key))
+    }
+
+    /// A cost, written with [`Cost`]'s `Display`: an integer, or a decimal with
+    /// `float-distances`.
+    pub fn cost(&self, key: &str) -> Result<Cost, String> {
+        match self.get(key) {
+            Some(Field::Int(value)) => {
+                Cost::from_i64(*value).ok_or_else(|| format!("'{}' out of range", key))
+            }
+            Some(Field::Float(value)) => {
+                Cost::from_f64(*value).ok_or_else(|| format!("'{}' is not a valid cost", key))
+            }
+            _ => Err(format!("missing number field '{}'", key)),
+        }
+    }
+
+    pub fn bool(&self, key: &str) -> Result<bool, String> {
+        match self.get(key) {
+            Some(Field::Bool(value)) => Ok(*value),
+            _ => Err(format!("missing boolean field '{}'", key)),
+        }
+    }
+
+    pub fn str(&self, key: &str) -> Result<&str, String> {
+        match self.get(key) {
+            Some(Field::Str(value)) => Ok(value),
+            _ => Err(format!("missing string field '{}'", key)),
+        }
+    }
+
+    /// A list of vertex ids.
+    pub fn vertices(&self, key: &str) -> Result<Vec<usize>, String> {
+        match self.get(key) {
+            Some(Field::List(items)) => items
+                .iter()
+                .map(|&v| usize::try_from(v).map_err(|_| format!("negative vertex in '{}'", key)))
+                .collect(),
+            _ => Err(format!("missing list field '{}'", key)),
+        }
+    }
+}
+
+/// `vertices` as the comma-separated contents of a JSON list.
+pub fn join_list(vertices: &[usize]) -> String {
+    let items: Vec<String> = vertices.iter().map(|v| v.to_string()).collect();
+    items.join(",")
+}
+
+/// `text` with the characters [`FlatObject`] cannot read back (quotes and backslashes)
+/// replaced, for use as a string value.
+pub fn plain_string(text: &str) -> String {
+    text.replace(['"', '\\'], "'")
+}
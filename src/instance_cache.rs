@@ -0,0 +1,229 @@
+//! On-disk cache of the preprocessing of an instance: its distance matrix and candidate
+//! lists, which take minutes to build on large instances. An entry is keyed by the hash of
+//! the instance file, the candidate source and the distance type of the build, so editing
+//! the file or changing the parameters never reads a stale entry. Cache files are plain
+//! little-endian binaries of this module's own format.
+
+use crate::cli::instance_name;
+use crate::tsplib::{CandidateSource, TsplibError, TsplibInstance};
+use crate::utils::{fnv1a, safe_file_name};
+use crate::weight::{Cost, Weight};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const MAGIC: &[u8; 8] = b"IMOCACHE";
+/// Bumped whenever the layout of a cache file changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Corrupt cache file: {0}")]
+    Corrupt(&'static str),
+}
+
+/// Whether [`InstanceCache::load`] found the instance in the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheOutcome {
+    Hit,
+    /// Built from scratch and stored for the next run.
+    Stored,
+    /// Built from scratch; storing it failed or the candidate lists could not be built.
+    NotStored,
+}
+
+/// A directory of cached instance preprocessing.
+#[derive(Debug, Clone)]
+pub struct InstanceCache {
+    dir: PathBuf,
+}
+
+impl InstanceCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The instance in `path` with the full distance matrix and the candidate lists of
+    /// `candidates(dimension)`, read from the cache if it holds them, otherwise built and
+    /// stored. A cache entry that cannot be read is rebuilt; failing to store one is only
+    /// a warning.
+    pub fn load(
+        &self,
+        path: &Path,
+        candidates: impl FnOnce(usize) -> CandidateSource,
+    ) -> Result<(TsplibInstance, CacheOutcome), TsplibError> {
+        let checksum = fnv1a(&fs::read(path)?);
+        let (name, edge_weight_type, coordinates) = TsplibInstance::read_coordinates(path)?;
+        let source = candidates(coordinates.len());
+        let key = entry_key(checksum, source);
+        let entry = self.entry_path(path, key);
+
+        if entry.exists() {
+            match read_entry(&entry, key, coordinates.len()) {
+                Ok((distances, lists)) => {
+                    let instance = TsplibInstance::from_precomputed(
+                        name,
+                        edge_weight_type,
+                        coordinates,
+                        distances,
+                        source,
+                        lists,
+                    );
+                    return Ok((instance, CacheOutcome::Hit));
+                }
+                Err(e) => eprintln!(
+                    "[WARN] Ignoring the cached preprocessing in {}: {}",
+                    entry.display(),
+                    e
+                ),
+            }
+        }
+
+        let mut instance = TsplibInstance::from_coordinates(name, edge_weight_type, coordinates);
+        instance.precompute_candidates(source);
+        if instance.candidate_source() != Some(source) {
+            return Ok((instance, CacheOutcome::NotStored));
+        }
+        let outcome = match write_entry(&entry, key, &instance) {
+            Ok(()) => CacheOutcome::Stored,
+            Err(e) => {
+                eprintln!(
+                    "[WARN] Cannot cache the preprocessing in {}: {}",
+                    entry.display(),
+                    e
+                );
+                CacheOutcome::NotStored
+            }
+        };
+        Ok((instance, outcome))
+    }
+
+    fn entry_path(&self, path: &Path, key: u64) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{:016x}.bin",
+            safe_file_name(&instance_name(path)),
+            key
+        ))
+    }
+}
+
+/// Key of the cache entry of a file with hash `checksum` and candidate lists from `source`.
+fn entry_key(checksum: u64, source: CandidateSource) -> u64 {
+    fnv1a(
+        format!(
+            "v{} {:016x} {:?} cost{}",
+            FORMAT_VERSION,
+            checksum,
+            source,
+            Cost::BYTES
+        )
+        .as_bytes(),
+    )
+}
+
+/// Header (magic, version, key, dimension), the matrix row by row, then each candidate list
+/// as its length and its vertices.
+fn write_entry(entry: &Path, key: u64, instance: &TsplibInstance) -> Result<(), CacheError> {
+    let distances = instance
+        .full_distances()
+        .expect("An instance built from its coordinates has the full layout");
+    let lists = instance.candidate_lists();
+    let mut out = Vec::with_capacity(32 + distances.len() * Cost::BYTES);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.extend_from_slice(&key.to_le_bytes());
+    out.extend_from_slice(&(instance.size() as u64).to_le_bytes());
+    for &distance in distances {
+        distance.write_le(&mut out);
+    }
+    for list in lists {
+        out.extend_from_slice(&(list.len() as u32).to_le_bytes());
+        for &v in list {
+            out.extend_from_slice(&(v as u32).to_le_bytes());
+        }
+    }
+    if let Some(parent) = entry.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Written aside and renamed, so a reader never sees half a file.
+    let partial = entry.with_extension("partial");
+    fs::write(&partial, out)?;
+    fs::rename(&partial, entry)?;
+    Ok(())
+}
+
+/// The distance matrix and candidate lists of the entry, checked against `key` and the
+/// instance `dimension`.
+fn read_entry(
+    entry: &Path,
+    key: u64,
+    dimension: usize,
+) -> Result<(Vec<Cost>, Vec<Vec<usize>>), CacheError> {
+    let bytes = fs::read(entry)?;
+    let mut reader = Reader { bytes: &bytes };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(CacheError::Corrupt("not a cache file"));
+    }
+    if reader.u32()? != FORMAT_VERSION {
+        return Err(CacheError::Corrupt("written by another format version"));
+    }
+    if reader.u64()? != key {
+        return Err(CacheError::Corrupt(
+            "written for another file or parameters",
+        ));
+    }
+    if reader.u64()? != dimension as u64 {
+        return Err(CacheError::Corrupt("dimension differs from the instance"));
+    }
+    let distances = reader
+        .take(dimension * dimension * Cost::BYTES)?
+        .chunks_exact(Cost::BYTES)
+        .map(Cost::read_le)
+        .collect();
+    let mut lists = Vec::with_capacity(dimension);
+    for _ in 0..dimension {
+        let len = reader.u32()? as usize;
+        let list: Vec<usize> = (0..len)
+            .map(|_| reader.u32().map(|v| v as usize))
+            .collect::<Result<_, _>>()?;
+        if list.iter().any(|&v| v >= dimension) {
+            return Err(CacheError::Corrupt("candidate out of range"));
+        }
+        lists.push(list);
+    }
+    if !reader.bytes.is_empty() {
+        return Err(CacheError::Corrupt("trailing bytes"));
+    }
+    Ok((distances, lists))
+}
+
+/// Consumes a byte slice from the front.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CacheError> {
+        if self.bytes.len() < len {
+            return Err(CacheError::Corrupt("truncated"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, CacheError> {
+        Ok(u32::from_le_bytes(
+            self.take(4)?.try_into().expect("4 bytes"),
+        ))
+    }
+
+    fn u64(&mut self) -> Result<u64, CacheError> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?.try_into().expect("8 bytes"),
+        ))
+    }
+}
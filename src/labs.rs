@@ -0,0 +1,521 @@
+use crate::algorithm::{
+    ExperimentOptions, ExperimentStats, IterationInfo, ProgressCallback, TracedSolveFn,
+    TspAlgorithm, format_ranked_summary, run_experiment, run_local_search_experiment,
+    run_msls_experiment, run_traced_experiment, write_stats_csv,
+};
+use crate::algorithms::acceptance::Acceptance;
+use crate::algorithms::constructive::StartStrategy;
+use crate::algorithms::constructive::nearest_neighbor_insertion::NearestNeighborInsertion;
+use crate::algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
+use crate::algorithms::hae::{Crossover, Hae, HaeParams, Mutation, Replacement, SelectionStrategy};
+use crate::algorithms::hyperheuristic::Hyperheuristic;
+use crate::algorithms::ils::Ils;
+use crate::algorithms::lns::Lns;
+use crate::algorithms::local_search::base::{
+    HeuristicAlgorithm, InitialSolutionType, LocalSearch, NeighborhoodType, SearchVariant,
+};
+use crate::algorithms::msls::{Msls, Starter, StarterSelection};
+use crate::algorithms::perturbation::{
+    ClusterPerturbation, ClusterSize, LargePerturbation, Perturbation, SegmentPerturbation,
+    SmallPerturbation, WorstPerturbation,
+};
+use crate::algorithms::random_walk::RandomWalk;
+use crate::bounds;
+use crate::budget::Budget;
+use crate::cli::ExperimentArgs;
+use crate::convergence::ConvergenceTrace;
+use crate::manifest::{MANIFEST_FILE, RunManifest};
+use crate::output::{OUTPUT_ROOT, OutputManager};
+use crate::seed::SeedStream;
+use crate::time_limits::{TIME_LIMITS_FILE, TimeLimits, write_time_limits};
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::visualization;
+use crate::weight::Cost;
+use rand::RngCore;
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::Duration;
+
+const LAB_USAGE: &str = "Usage: imo lab<1-7> [--time-limits <file.json> | --reuse-time-limits] \
+     [--time-limit <instance>=<ms>]... [--seed <n>]";
+
+/// The instances every lab assignment uses.
+const INSTANCES: [&str; 2] = ["kroa200", "krob200"];
+/// Runs per algorithm and instance of the constructive and local search labs (1-3).
+const LS_LAB_RUNS: usize = 100;
+/// Runs per algorithm and instance of the timed labs (4-7).
+const TIMED_LAB_RUNS: usize = 10;
+/// Local search iterations of every MSLS run.
+const MSLS_ITERATIONS: usize = 200;
+
+/// Runs the preset of a lab assignment (`lab` is `lab1` to `lab7`): the algorithm set, run
+/// count and output its report needs, so reproducing the report is one command. The labs are
+/// 1 constructive heuristics, 2 local search variants, 3 candidate and move-list local search,
+/// 4 MSLS/ILS/LNS, 5 the hybrid evolutionary algorithm, 6 this project's own extensions
+/// (HAE with crowding, fitness-proportional selection and mutation, and the hyperheuristic),
+/// and 7 the alternative operators of MSLS, ILS, LNS and HAE against their defaults.
+/// Labs 4-7 take the time limit options of the default experiment.
+pub fn run_lab(lab: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let number: usize = lab
+        .strip_prefix("lab")
+        .and_then(|number| number.parse().ok())
+        .filter(|number| (1..=7).contains(number))
+        .ok_or_else(|| format!("Unknown lab: {}\n{}", lab, LAB_USAGE))?;
+    let experiment_args =
+        ExperimentArgs::parse(args).map_err(|e| format!("{}\n{}", e, LAB_USAGE))?;
+    let pinned_time_limits = experiment_args.pinned_time_limits()?;
+    let seeds = SeedStream::master(experiment_args.seed);
+
+    let mut manifest = RunManifest::collect(seeds.seed());
+    let mut instances = Vec::new();
+    for name in INSTANCES {
+        let path = format!("tsplib/{}.tsp", name);
+        let mut instance = TsplibInstance::from_file(Path::new(&path))?;
+        manifest.add_instance(name, Path::new(&path))?;
+        instance.precompute_nearest_neighbors(10);
+        instances.push(instance);
+    }
+    let output = OutputManager::create(Path::new(OUTPUT_ROOT))?;
+    println!(
+        "Lab {}: writing output to {} (seed {})",
+        number,
+        output.run_dir().display(),
+        seeds.seed()
+    );
+    let mut run = LabRun {
+        output,
+        manifest,
+        results: Vec::new(),
+        lower_bounds: HashMap::new(),
+        time_limits: pinned_time_limits.clone(),
+        pinned_time_limits,
+        seeds,
+    };
+
+    for instance in &instances {
+        println!("\nProcessing instance: {}", instance.name);
+        run.lower_bounds
+            .insert(instance.name.clone(), bounds::lower_bound(instance, 1000));
+        match number {
+            1 => lab1(&mut run, instance)?,
+            2 => lab2(&mut run, instance)?,
+            3 => lab3(&mut run, instance)?,
+            4 => lab4(&mut run, instance)?,
+            5 => lab5(&mut run, instance)?,
+            6 => lab6(&mut run, instance)?,
+            _ => lab7(&mut run, instance)?,
+        }
+    }
+    run.finish()
+}
+
+/// Greedy nearest neighbour, greedy cycle, 2-regret and weighted 2-regret, from random start
+/// vertices, and weighted 2-regret from the most distant pair as the deterministic reference.
+fn lab1(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    let algorithms: [&(dyn TspAlgorithm + Send + Sync); 5] = [
+        &NearestNeighborInsertion::new(StartStrategy::RandomNearAntipodal),
+        &WeightedRegretCycle::new(2, 0.0, -1.0),
+        &WeightedRegretCycle::new(2, 1.0, 0.0),
+        &WeightedRegretCycle::default(),
+        &WeightedRegretCycle::default().with_start_strategy(StartStrategy::MaxDistancePair),
+    ];
+    for algorithm in algorithms {
+        println!("  Running algorithm: {}", algorithm.name());
+        let stats = run_experiment(algorithm, instance, LS_LAB_RUNS, run.options());
+        run.record(instance, stats)?;
+    }
+    Ok(())
+}
+
+/// Steepest and greedy local search over both neighbourhoods, from random solutions and from
+/// those of the constructive heuristics, and a random walk as long as the slowest of them on
+/// average.
+fn lab2(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    let mut slowest_ms: f64 = 0.0;
+    for initial in [
+        InitialSolutionType::Random,
+        InitialSolutionType::Heuristic(HeuristicAlgorithm::WeightedRegret),
+        InitialSolutionType::Heuristic(HeuristicAlgorithm::NearestNeighborInsertion),
+        InitialSolutionType::Heuristic(HeuristicAlgorithm::ConvexHullInsertion),
+        InitialSolutionType::Heuristic(HeuristicAlgorithm::HilbertCurve),
+        InitialSolutionType::Heuristic(HeuristicAlgorithm::GreedyEdge),
+    ] {
+        for neighborhood in [
+            NeighborhoodType::VertexExchange,
+            NeighborhoodType::EdgeExchange,
+        ] {
+            for variant in [SearchVariant::Steepest, SearchVariant::Greedy] {
+                let local_search = LocalSearch::new(variant, neighborhood, initial);
+                println!("  Running algorithm: {}", local_search.name());
+                let stats = run_local_search_experiment(
+                    &local_search,
+                    instance,
+                    LS_LAB_RUNS,
+                    run.options(),
+                );
+                slowest_ms = slowest_ms.max(stats.avg_time_ms);
+                run.record(instance, stats)?;
+            }
+        }
+    }
+    let random_walk = RandomWalk::new(
+        Duration::from_millis(slowest_ms.round() as u64),
+        NeighborhoodType::EdgeExchange,
+    );
+    println!("  Running algorithm: {}", random_walk.name());
+    let stats = run_experiment(&random_walk, instance, LS_LAB_RUNS, run.options());
+    run.record(instance, stats)
+}
+
+/// Steepest edge-exchange local search plain, with a move list, with candidate moves and over
+/// a sampled quarter of the neighbourhood, and the weighted 2-regret heuristic for reference.
+fn lab3(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    for variant in [
+        SearchVariant::Steepest,
+        SearchVariant::MoveListSteepest,
+        SearchVariant::CandidateSteepest(10),
+        SearchVariant::SampledSteepest(0.25),
+    ] {
+        let local_search = LocalSearch::new(
+            variant,
+            NeighborhoodType::EdgeExchange,
+            InitialSolutionType::Random,
+        );
+        println!("  Running algorithm: {}", local_search.name());
+        let stats =
+            run_local_search_experiment(&local_search, instance, LS_LAB_RUNS, run.options());
+        run.record(instance, stats)?;
+    }
+    let heuristic = WeightedRegretCycle::default();
+    println!("  Running algorithm: {}", heuristic.name());
+    let stats = run_experiment(&heuristic, instance, LS_LAB_RUNS, run.options());
+    run.record(instance, stats)
+}
+
+/// MSLS, then ILS, LNS and LNS without local search after repair for as long as MSLS.
+fn lab4(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    let time_limit = run.time_limit(instance)?;
+    let base_ls = base_local_search();
+    let ils = Ils::new(base_ls.clone(), Box::new(SmallPerturbation::new(10)));
+    run.traced(
+        instance,
+        &ils,
+        ils.name(),
+        time_limit,
+        |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+    )?;
+    for apply_ls_after_repair in [true, false] {
+        let lns = Lns::new(
+            base_ls.clone(),
+            Box::new(LargePerturbation::new(0.2)),
+            apply_ls_after_repair,
+            true,
+        );
+        run.traced(
+            instance,
+            &lns,
+            lns.name(),
+            time_limit,
+            |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+        )?;
+    }
+    Ok(())
+}
+
+/// MSLS, then the hybrid evolutionary algorithm with and without local search on the
+/// children for as long as MSLS.
+fn lab5(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    let time_limit = run.time_limit(instance)?;
+    for with_local in [true, false] {
+        let hae = Hae::from_params(
+            base_local_search(),
+            HaeParams {
+                with_local,
+                ..HaeParams::default()
+            },
+        );
+        run.traced(
+            instance,
+            &hae,
+            hae.name(),
+            time_limit,
+            |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+        )?;
+    }
+    Ok(())
+}
+
+/// MSLS, the lab 5 HAE as the baseline, HAE with crowding replacement, fitness-proportional
+/// selection and LNS mutation, and the hyperheuristic, all for as long as MSLS.
+fn lab6(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    let time_limit = run.time_limit(instance)?;
+    let extended = HaeParams {
+        replacement: Replacement::Crowding,
+        selection: SelectionStrategy::FitnessProportional,
+        mutation: Some(Mutation {
+            probability: 0.2,
+            destroy_fraction: 0.1,
+        }),
+        ..HaeParams::default()
+    };
+    for params in [HaeParams::default(), extended] {
+        let hae = Hae::from_params(base_local_search(), params);
+        run.traced(
+            instance,
+            &hae,
+            hae.name(),
+            time_limit,
+            |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+        )?;
+    }
+    let hyperheuristic = Hyperheuristic::new(
+        Hyperheuristic::standard_configurations(),
+        Box::new(SmallPerturbation::new(10)),
+    );
+    run.traced(
+        instance,
+        &hyperheuristic,
+        hyperheuristic.name(),
+        time_limit,
+        |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+    )
+}
+
+/// MSLS from the other starters, alternating and drawn at random, next to the default MSLS
+/// that sets the time limit; then, for as long as MSLS, LNS with the other acceptance
+/// criteria, destroy operators and a noisy repair, and HAE with the common-edges crossover,
+/// with (μ+λ) generations and with fitness-proportional and Boltzmann selection.
+fn lab7(run: &mut LabRun, instance: &TsplibInstance) -> Result<(), Box<dyn Error>> {
+    let time_limit = run.time_limit(instance)?;
+    let starters = vec![
+        Starter::Random,
+        Starter::RandomizedRegret,
+        Starter::NearestNeighbor,
+    ];
+    for selection in [StarterSelection::Alternate, StarterSelection::Random] {
+        let msls = Msls::new(base_local_search(), MSLS_ITERATIONS)
+            .with_parallel()
+            .with_starters(starters.clone(), selection);
+        println!("  Running algorithm: {}", msls.name());
+        let stats = run_msls_experiment(&msls, instance, TIMED_LAB_RUNS, run.options());
+        run.record(instance, stats)?;
+    }
+
+    let acceptances = [
+        Acceptance::Always,
+        Acceptance::SimulatedAnnealing {
+            initial_temperature: 100.0,
+            cooling: 0.995,
+        },
+        Acceptance::RecordToRecord { deviation: 0.01 },
+    ];
+    let new_lns = |perturbation: Box<dyn Perturbation>| {
+        Lns::new(base_local_search(), perturbation, true, true)
+    };
+    let mut lns_variants: Vec<Lns> = acceptances
+        .into_iter()
+        .map(|acceptance| {
+            new_lns(Box::new(LargePerturbation::new(0.2))).with_acceptance(acceptance)
+        })
+        .collect();
+    // The other destroy operators remove about as many vertices as the default 20%; the
+    // segments, one per cycle, take 5-10% each.
+    let destroy_operators: Vec<Box<dyn Perturbation>> = vec![
+        Box::new(LargePerturbation::new(0.2).with_repair_noise(0.1)),
+        Box::new(ClusterPerturbation::new(ClusterSize::Nearest(
+            instance.size() / 5,
+        ))),
+        Box::new(WorstPerturbation::new(0.2, 3.0)),
+        Box::new(SegmentPerturbation::new(
+            (instance.size() / 20).max(1),
+            (instance.size() / 10).max(1),
+        )),
+    ];
+    lns_variants.extend(destroy_operators.into_iter().map(new_lns));
+    for lns in &lns_variants {
+        run.traced(
+            instance,
+            lns,
+            lns.name(),
+            time_limit,
+            |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+        )?;
+    }
+
+    let hae_variants = [
+        HaeParams {
+            crossover: Crossover::CommonEdges,
+            ..HaeParams::default()
+        },
+        HaeParams {
+            replacement: Replacement::MuPlusLambda { children: 10 },
+            ..HaeParams::default()
+        },
+        HaeParams {
+            selection: SelectionStrategy::FitnessProportional,
+            ..HaeParams::default()
+        },
+        HaeParams {
+            selection: SelectionStrategy::Boltzmann { temperature: 100.0 },
+            ..HaeParams::default()
+        },
+    ];
+    for params in hae_variants {
+        let hae = Hae::from_params(base_local_search(), params);
+        run.traced(
+            instance,
+            &hae,
+            hae.name(),
+            time_limit,
+            |algo, inst, budget, rng, cb| algo.solve_traced(inst, budget, rng, cb),
+        )?;
+    }
+    Ok(())
+}
+
+/// The local search inside MSLS, ILS, LNS and HAE.
+fn base_local_search() -> LocalSearch {
+    LocalSearch::new(
+        SearchVariant::CandidateSteepest(10),
+        NeighborhoodType::EdgeExchange,
+        InitialSolutionType::Random,
+    )
+}
+
+/// Output and results of a lab run.
+struct LabRun {
+    output: OutputManager,
+    manifest: RunManifest,
+    results: Vec<(String, ExperimentStats)>,
+    /// Lower bound of every instance, which the gaps of the summary are measured against.
+    lower_bounds: HashMap<String, Option<Cost>>,
+    pinned_time_limits: TimeLimits,
+    time_limits: TimeLimits,
+    /// Master stream every run of the lab draws from.
+    seeds: SeedStream,
+}
+
+impl LabRun {
+    /// Options of the untimed runs: the default ones, drawing from the lab's stream.
+    fn options(&self) -> ExperimentOptions<'static> {
+        ExperimentOptions {
+            seeds: self.seeds,
+            ..ExperimentOptions::default()
+        }
+    }
+
+    /// Keeps `stats` for the summary and plots its best solution.
+    fn record(
+        &mut self,
+        instance: &TsplibInstance,
+        stats: ExperimentStats,
+    ) -> Result<(), Box<dyn Error>> {
+        let plot_path =
+            self.output
+                .algorithm_file(&instance.name, &stats.algorithm_name, "best.png")?;
+        visualization::plot_solution(
+            instance,
+            &stats.best_solution,
+            &format!("{} - {}", stats.algorithm_name, instance.name),
+            &plot_path,
+        )?;
+        self.manifest.add_algorithm(&stats.algorithm_name);
+        self.results.push((instance.name.clone(), stats));
+        Ok(())
+    }
+
+    /// The time limit of the timed algorithms on `instance`: the pinned one, or else the
+    /// average summed iteration time of MSLS, which is run and recorded for it.
+    fn time_limit(&mut self, instance: &TsplibInstance) -> Result<Duration, Box<dyn Error>> {
+        let time_limit = match self.pinned_time_limits.get(&instance.name) {
+            Some(&limit) => {
+                println!("  Using the pinned time limit: {:?} (MSLS skipped)", limit);
+                limit
+            }
+            None => {
+                let msls = Msls::new(base_local_search(), MSLS_ITERATIONS).with_parallel();
+                println!("  Running algorithm: {}", msls.name());
+                let stats = run_msls_experiment(&msls, instance, TIMED_LAB_RUNS, self.options());
+                let avg_time_ms = stats.avg_cpu_time_ms.unwrap_or(stats.avg_time_ms);
+                let limit = Duration::from_millis(avg_time_ms.round() as u64);
+                println!("    Setting the time limit to {:?}", limit);
+                self.record(instance, stats)?;
+                limit
+            }
+        };
+        self.time_limits.insert(instance.name.clone(), time_limit);
+        write_time_limits(&self.time_limits, Path::new(TIME_LIMITS_FILE))?;
+        Ok(time_limit)
+    }
+
+    /// Runs `algorithm`, called `name`, for `time_limit` per run with its traced solver `solve`
+    /// and records it.
+    fn traced<T, F>(
+        &mut self,
+        instance: &TsplibInstance,
+        algorithm: &T,
+        name: &str,
+        time_limit: Duration,
+        solve: F,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: Send + Sync,
+        F: Fn(
+                &T,
+                &TsplibInstance,
+                &Budget,
+                &mut dyn RngCore,
+                ProgressCallback,
+            ) -> (Solution, IterationInfo, ConvergenceTrace)
+            + Send
+            + Sync,
+    {
+        println!("  Running algorithm: {}", name);
+        let solve_fn: TracedSolveFn<T> =
+            Box::new(|algo, inst, rng, cb| solve(algo, inst, &Budget::timed(time_limit), rng, cb));
+        let stats = run_traced_experiment(
+            algorithm,
+            solve_fn,
+            instance,
+            TIMED_LAB_RUNS,
+            name,
+            ExperimentOptions {
+                time_limit: Some(time_limit),
+                ..self.options()
+            },
+        );
+        self.record(instance, stats)
+    }
+
+    /// Prints the ranked summary and writes the summary CSV and the manifest.
+    fn finish(mut self) -> Result<(), Box<dyn Error>> {
+        println!("\nSummary of Results:");
+        let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        for line in format_ranked_summary(&self.results, &self.lower_bounds, color) {
+            println!("{}", line);
+        }
+        let mut best_known: HashMap<String, Cost> = HashMap::new();
+        for (instance_name, stats) in &self.results {
+            best_known
+                .entry(instance_name.clone())
+                .and_modify(|best| *best = (*best).min(stats.min_cost))
+                .or_insert(stats.min_cost);
+        }
+        let summary_path = self.output.file("summary.csv");
+        write_stats_csv(
+            self.results.iter().map(|(_, stats)| stats),
+            &best_known,
+            &summary_path,
+        )?;
+        self.manifest.time_limits = self.time_limits;
+        self.manifest.write(&self.output.file(MANIFEST_FILE))?;
+        println!(
+            "\nSummary saved to {}, visualizations to {}",
+            summary_path.display(),
+            self.output.run_dir().display()
+        );
+        Ok(())
+    }
+}
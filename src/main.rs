@@ -1,248 +1,860 @@
 mod algorithm;
 mod algorithms;
+mod analysis;
+mod bounds;
+mod budget;
+mod candidates;
+mod cli;
+mod convergence;
+mod counters;
+mod distance;
+mod elite;
+mod external;
+mod fetch;
+mod flat_json;
+mod instance_cache;
+mod labs;
+mod manifest;
 mod moves;
+mod objective;
+mod output;
+mod pareto;
+mod pivot;
+mod plan;
+mod postprocess;
+mod progress;
+mod seed;
+mod significance;
+mod table;
+mod time_limits;
 mod tsplib;
+mod tuning;
 mod utils;
+mod variance;
 mod visualization;
+mod weight;
 
 use algorithm::{
-    ExperimentStats, TimedSolveFn, TspAlgorithm, format_stats_row, run_experiment,
-    run_timed_experiment,
+    ExperimentOptions, ExperimentStats, TracedSolveFn, TspAlgorithm, format_combined_summary,
+    format_ranked_summary, run_experiment, run_msls_experiment, run_traced_experiment,
+    write_run_archive, write_stats_csv,
 };
-use algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
-use algorithms::ils::Ils;
+use algorithms::hae::{Hae, HaeParams};
+use algorithms::hyperheuristic::{Hyperheuristic, usage_distribution};
+use algorithms::ils::{Ils, operator_totals};
 use algorithms::lns::Lns;
-use algorithms::local_search::base::{
-    HeuristicAlgorithm, InitialSolutionType, LocalSearch, NeighborhoodType, SearchVariant,
-};
+use algorithms::local_search::base::{LocalSearch, NeighborhoodType, SearchVariant};
 use algorithms::msls::Msls;
-use algorithms::perturbation::{LargePerturbation, Perturbation, SmallPerturbation};
+use algorithms::perturbation::{Kick, SmallPerturbation};
+use algorithms::population::PopulationTelemetry;
 use algorithms::random_walk::RandomWalk;
-use algorithms::hae::Hae;
+use budget::Budget;
+use elite::EliteArchive;
+use instance_cache::{CacheOutcome, InstanceCache};
+use manifest::{MANIFEST_FILE, RunManifest};
+use output::{OUTPUT_ROOT, OutputManager};
+use pareto::ParetoArchive;
+use pivot::PivotTable;
+use plan::ExperimentPlan;
+use rand::RngCore;
+use seed::SeedStream;
 use std::collections::HashMap;
-use std::fs::create_dir_all;
+use std::io::IsTerminal;
 use std::path::Path;
-use std::sync::Arc; // Keep Arc for TsplibInstance if needed across threads, but not for algos here
+use std::sync::{Arc, Mutex}; // Keep Arc for TsplibInstance if needed across threads, but not for algos here
 use std::time::Duration;
-use tsplib::TsplibInstance;
+use time_limits::{TIME_LIMITS_FILE, read_time_limits, write_time_limits};
+use tsplib::{Objective, Solution, TsplibInstance};
+use utils::fnv1a;
+use variance::{VarianceThresholds, format_variance_report};
+use weight::Cost;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("validate") => return cli::run_validate(&args[1..]),
+        Some("replay") => return cli::run_replay(&args[1..]),
+        Some("incidents") => return cli::run_incidents(&args[1..]),
+        Some("fetch") => return cli::run_fetch(&args[1..]),
+        Some("sweep") => return cli::run_sweep(&args[1..]),
+        Some("tune") => return cli::run_tune(&args[1..]),
+        Some(lab) if lab.starts_with("lab") => return labs::run_lab(lab, &args[1..]),
+        _ => {}
+    }
+    let experiment_args = cli::ExperimentArgs::parse(&args)?;
+    let initial_solution = experiment_args
+        .initial_solution
+        .as_deref()
+        .map(Solution::read_file)
+        .transpose()?;
+    let prizes = experiment_args.read_prizes()?;
+    // Instances with a pinned time limit skip the MSLS calibration runs.
+    let pinned_time_limits = experiment_args.pinned_time_limits()?;
+
     println!("Loading instances...");
 
-    create_dir_all("output")?;
+    let seeds = SeedStream::master(experiment_args.seed);
+    println!("Seed {}", seeds.seed());
 
-    // Define instances
-    let instance_files = ["kroa200", "krob200"];
+    // Updated after every instance, so even an interrupted run says how its output was made.
+    let mut manifest = RunManifest::collect(seeds.seed());
+
+    let instance_paths = experiment_args.instance_paths()?;
+    let instance_cache = experiment_args
+        .instance_cache
+        .as_ref()
+        .map(InstanceCache::new);
     let mut instances = HashMap::new();
-    for name in instance_files {
-        match TsplibInstance::from_file(Path::new(&format!("tsplib/{}.tsp", name))) {
-            Ok(mut instance) => {
-                println!("  Precomputing nearest neighbors (k=10) for {}...", name);
-                instance.precompute_nearest_neighbors(10);
-                instances.insert(name.to_string(), Arc::new(instance)); // Keep Arc for instance for potential // parallelism
+    let mut failed_instances = Vec::new();
+    for path in &instance_paths {
+        let name = cli::instance_name(path);
+        let candidates = |dimension| {
+            experiment_args
+                .candidates
+                .with_list_size(neighbor_list_size(
+                    dimension,
+                    experiment_args.adaptive_k.unwrap_or(10),
+                ))
+        };
+        let loaded = match &instance_cache {
+            Some(cache) => cache.load(path, candidates),
+            None => TsplibInstance::from_file(path).map(|mut instance| {
+                let source = candidates(instance.size());
+                println!("  Precomputing candidates ({:?}) for {}...", source, name);
+                instance.precompute_candidates(source);
+                (instance, CacheOutcome::NotStored)
+            }),
+        };
+        match loaded {
+            Ok((mut instance, outcome)) => {
+                manifest.add_instance(&name, path)?;
+                match outcome {
+                    CacheOutcome::Hit => println!("  Loaded {} from the instance cache", name),
+                    CacheOutcome::Stored => {
+                        println!(
+                            "  Preprocessed {} and stored it in the instance cache",
+                            name
+                        )
+                    }
+                    CacheOutcome::NotStored => {}
+                }
+                instance.set_single_cycle(experiment_args.single_cycle);
+                match &prizes {
+                    Some(prizes) if prizes.len() == instance.size() => {
+                        instance.set_prizes(prizes.clone())
+                    }
+                    Some(prizes) => println!(
+                        "  [WARN] {} has {} vertices but {} prizes were given, keeping every vertex",
+                        name,
+                        instance.size(),
+                        prizes.len()
+                    ),
+                    None => {}
+                }
+                for &(vertex, cycle) in &experiment_args.pins {
+                    if vertex < instance.size() {
+                        instance.pin(vertex, cycle);
+                    } else {
+                        println!("  [WARN] {} has no vertex {}, not pinning it", name, vertex);
+                    }
+                }
+                let layout = experiment_args.distance_layout;
+                if let Err(e) = instance.set_distance_layout(layout) {
+                    println!(
+                        "  [WARN] Cannot store {} in the {} layout, keeping the full one: {}",
+                        name,
+                        layout.as_str(),
+                        e
+                    );
+                }
+                instances.insert(name, Arc::new(instance)); // Keep Arc for instance for potential // parallelism
+            }
+            Err(e) => {
+                println!("Error loading {}: {}", name, e);
+                failed_instances.push(name);
             }
-            Err(e) => println!("Error loading {}: {}", name, e),
         }
     }
 
     // Define base local search - No Arc needed here
-    let base_ls = LocalSearch::new(
-        SearchVariant::CandidateSteepest(10),
+    let mut base_ls = LocalSearch::new(
+        experiment_args
+            .local_search
+            .unwrap_or(SearchVariant::CandidateSteepest(10)),
         NeighborhoodType::EdgeExchange,
-        InitialSolutionType::Random,
-    );
+        experiment_args.initial_solution_type,
+    )
+    .with_objective(experiment_args.objective)
+    .with_sideways(experiment_args.sideways)
+    .with_size_slack(experiment_args.size_slack);
+    if let Some(depth) = experiment_args.lk_pass {
+        base_ls = base_ls.with_lk_pass(depth);
+    }
+    if let Some(max_k) = experiment_args.adaptive_k {
+        base_ls = base_ls.with_adaptive_k(max_k);
+    }
+    if experiment_args.full_fallback {
+        base_ls = base_ls.with_full_fallback();
+    }
+    if experiment_args.open_paths {
+        base_ls = base_ls.with_open_paths();
+    }
+    if experiment_args.oracle_check {
+        base_ls = base_ls.with_oracle_check();
+    }
 
     // Define algorithms - Use clone(), no Arc needed
     let msls_iterations = 200; // As per lab spec
-    let msls_algo = Msls::new(base_ls.clone(), msls_iterations);
+    let msls_algo = Msls::new(base_ls.clone(), msls_iterations).with_parallel();
 
     // Define perturbations - No Arc needed
     let small_perturb = SmallPerturbation::new(10); // Example: 10 random moves
-    let large_perturb = LargePerturbation::new(0.2); // Example: 20% destroy
+    let new_destroy = || {
+        experiment_args
+            .lns_destroy
+            .build(experiment_args.lns_repair_noise)
+    };
+
+    // The timed algorithms, before elites and warm starts are attached; --dry-run lists them
+    // from here too.
+    let new_ils = || match experiment_args.ils_kicks.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .fold(Ils::new(base_ls.clone(), first.build()), |ils, kick| {
+                ils.with_perturbation(kick.build())
+            }),
+        None => Ils::new(base_ls.clone(), Kick::default().build()),
+    };
+    let new_hyperheuristic = || {
+        Hyperheuristic::new(
+            Hyperheuristic::standard_configurations(),
+            Box::new(small_perturb.clone()),
+        )
+    };
+    let new_lns = || {
+        Lns::new(
+            base_ls.clone(),
+            new_destroy(),
+            true, // apply_ls_after_repair
+            true, // apply_ls_to_initial
+        )
+        .with_acceptance(experiment_args.lns_acceptance)
+    };
+    // LNSa: LNS without LS after repair
+    let new_lnsa = || {
+        Lns::new(
+            base_ls.clone(),
+            new_destroy(),
+            false, // apply_ls_after_repair = false
+            true,  // apply_ls_to_initial
+        )
+        .with_acceptance(experiment_args.lns_acceptance)
+    };
+    let hae_from = |params| {
+        let hae = Hae::from_params(base_ls.clone(), params);
+        if experiment_args.audit_children {
+            hae.with_child_audit()
+        } else {
+            hae
+        }
+    };
+    let new_hae = || hae_from(HaeParams::default());
+    let new_hae_nols = || {
+        hae_from(HaeParams {
+            with_local: false,
+            ..HaeParams::default()
+        })
+    };
+
+    // Baselines of the timed comparison, named without their time limit so the rows line up
+    // across instances.
+    let restarted_ls_name = format!("Restarted {}", base_ls.name());
+    let random_walk_name = format!("Random Walk ({:?})", NeighborhoodType::EdgeExchange);
 
     let num_runs = 10; // As per lab spec
     let mut all_results: Vec<(String, ExperimentStats)> = Vec::new();
-    let mut msls_avg_times: HashMap<String, Duration> = HashMap::new();
+    // The ILS/LNS/HAE time limits, saved after each instance so a later run can reuse them
+    // with --reuse-time-limits instead of recalibrating on MSLS. Pinned limits are kept even
+    // for the instances this run does not reach.
+    let mut time_limits_used = pinned_time_limits.clone();
+    // Gaps are measured against an external solver when --concorde or --lkh names one,
+    // otherwise against the lower bound.
+    let external_solver = experiment_args.external_solver.clone();
+    let stop_by_evaluations = experiment_args.stop_by_evaluations;
+    if stop_by_evaluations && counters::snapshot().is_none() {
+        return Err("--stop-by-evaluations needs a build with the op-counters feature".into());
+    }
+    let share_elites = experiment_args.share_elites;
+    let hae_telemetry = experiment_args.hae_telemetry;
+    let run_hyperheuristic = experiment_args.hyperheuristic;
+    let run_baselines = experiment_args.timed_baselines;
+    let mut gap_references: HashMap<String, Option<Cost>> = HashMap::new();
+
+    if experiment_args.dry_run {
+        let mut timed_algorithms = vec![new_ils().name().to_string()];
+        if run_hyperheuristic {
+            timed_algorithms.push(new_hyperheuristic().name().to_string());
+        }
+        timed_algorithms.extend([
+            new_lns().name().to_string(),
+            new_lnsa().name().to_string(),
+            new_hae().name().to_string(),
+            new_hae_nols().name().to_string(),
+        ]);
+        if run_baselines {
+            timed_algorithms.extend([restarted_ls_name.clone(), random_walk_name.clone()]);
+        }
+        let previous_time_limits = read_time_limits(Path::new(TIME_LIMITS_FILE)).ok();
+        let plan = ExperimentPlan {
+            instances: &instances,
+            failed_instances: &failed_instances,
+            pinned_time_limits: &pinned_time_limits,
+            previous_time_limits: previous_time_limits.as_ref(),
+            msls_name: msls_algo.name(),
+            timed_algorithms: &timed_algorithms,
+            num_runs,
+            extra_runs: usize::from(hae_telemetry)
+                + if experiment_args.pareto {
+                    2 * num_runs
+                } else {
+                    0
+                },
+            stop_by_evaluations,
+        };
+        return match plan.print() {
+            0 => Ok(()),
+            problems => Err(format!("The dry run found {} problem(s)", problems).into()),
+        };
+    }
+
+    // Every experiment writes to a fresh timestamped directory, see OutputManager.
+    let output = OutputManager::create(Path::new(OUTPUT_ROOT))?;
+    println!("Writing output to {}", output.run_dir().display());
 
     for (name, instance) in &instances {
         println!("\nProcessing instance: {}", name);
-
-        // --- Run MSLS first ---
-        println!("  Running algorithm: {}", msls_algo.name());
-        // Pass instance by reference, algo by reference
-        let msls_stats = run_experiment(&msls_algo, instance, num_runs);
-        let avg_time_ms = msls_stats.avg_time_ms;
-        let time_limit = Duration::from_millis(avg_time_ms.round() as u64);
-        msls_avg_times.insert(name.clone(), time_limit);
-        println!(
-            "    MSLS Avg Time: {:.2} ms. Setting Time Limit for ILS/LNS: {:?}",
-            avg_time_ms, time_limit
+        let lower_bound = bounds::lower_bound(instance, 1000);
+        if let Some(bound) = lower_bound {
+            println!("  Lower bound: {}", bound);
+        }
+        let reference = match &external_solver {
+            Some(solver) => match solver.reference_cost(instance) {
+                Ok(cost) => {
+                    println!("  {} reference: {}", solver.name(), cost);
+                    Some(cost)
+                }
+                Err(e) => {
+                    println!("  {} failed, using the lower bound: {}", solver.name(), e);
+                    lower_bound
+                }
+            },
+            // Single tours of classic instances have published optima, tighter than any bound.
+            None => match instance
+                .known_optimum()
+                .filter(|_| instance.is_single_cycle())
+            {
+                Some(optimum) => {
+                    println!("  Known optimum: {}", optimum);
+                    Some(optimum)
+                }
+                None => lower_bound,
+            },
+        };
+        // The bounds and reference costs are of the total length, so other objectives get
+        // no gap.
+        gap_references.insert(
+            name.clone(),
+            reference.filter(|_| experiment_args.objective.is_separable()),
         );
-        all_results.push((name.clone(), msls_stats.clone()));
-        // Plot best MSLS solution
-        let safe_algo_name = msls_algo
-            .name()
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
-            .replace("__", "_");
-        let output_path = format!("output/{}_{}.png", name, safe_algo_name);
-        visualization::plot_solution(
-            instance, // Pass the Arc<TsplibInstance>
-            &msls_stats.best_solution,
-            &format!("{} - {}", msls_algo.name(), name),
-            Path::new(&output_path),
-        )?;
+        let warm_start = match &initial_solution {
+            Some(solution) if solution.is_valid(instance) => Some(solution),
+            Some(_) => {
+                println!("  [WARN] The initial solution is not valid here, ignoring it");
+                None
+            }
+            None => None,
+        };
+        let elites = Arc::new(EliteArchive::new(10, 0.1));
+        let options = ExperimentOptions {
+            elites: share_elites.then_some(&*elites),
+            objective: experiment_args.objective,
+            // Under min-max, a last pass of inter-route moves shortens the longer cycle further.
+            rebalance: (experiment_args.objective == Objective::MaxCycle)
+                .then_some(base_ls.size_slack()),
+            seeds,
+            ..ExperimentOptions::default()
+        };
+
+        // --- Run the base local search from the initial solution ---
+        if let Some(solution) = warm_start {
+            let warm_ls = base_ls.clone().with_initial_solution(solution.clone());
+            println!("  Running algorithm: {}", warm_ls.name());
+            let warm_ls_stats = run_experiment(&warm_ls, instance, num_runs, options);
+            all_results.push((name.clone(), warm_ls_stats));
+        }
+        if experiment_args.trajectory {
+            let path = output.algorithm_file(name, base_ls.name(), "trajectory.jsonl")?;
+            let traced_ls = base_ls.clone().with_trajectory(&path);
+            let mut rng = extra_run_seeds(seeds, instance, base_ls.name(), "trajectory").rng();
+            let (_, report) = traced_ls.solve_with_report(instance, &mut rng, &mut |_| {});
+            println!(
+                "  Trajectory of a {} run (cost {}) saved to {}",
+                base_ls.name(),
+                report.final_cost,
+                path.display()
+            );
+        }
+
+        let (time_limit, evaluation_limit) = match pinned_time_limits.get(name) {
+            Some(&time_limit) => {
+                println!(
+                    "  Using the pinned time limit for ILS/LNS/HAE: {:?} (MSLS skipped)",
+                    time_limit
+                );
+                (time_limit, None)
+            }
+            None => {
+                // --- Run MSLS first ---
+                println!("  Running algorithm: {}", msls_algo.name());
+                // Pass instance by reference, algo by reference
+                let msls_stats = run_msls_experiment(
+                    &msls_algo,
+                    instance,
+                    num_runs,
+                    ExperimentOptions {
+                        keep_runs: true,
+                        ..options
+                    },
+                );
+                let archive_dir = output.algorithm_dir(name, msls_algo.name())?;
+                write_run_archive(&msls_stats, &archive_dir)?;
+                analysis::write_edge_frequency_csv(
+                    &analysis::edge_frequencies(msls_stats.runs.iter().map(|run| &run.solution)),
+                    &archive_dir.join("edge_frequencies.csv"),
+                )?;
+                analysis::write_similarity_csv(
+                    &analysis::similarity_analysis(&msls_stats.runs),
+                    &archive_dir.join("similarity.csv"),
+                )?;
+                println!("    Runs archived to {}", archive_dir.display());
+                // The iterations run in parallel, so the single-threaded ILS/LNS/HAE get the summed
+                // iteration time rather than the wall time.
+                let avg_time_ms = msls_stats.avg_cpu_time_ms.unwrap_or(msls_stats.avg_time_ms);
+                let time_limit = Duration::from_millis(avg_time_ms.round() as u64);
+                println!(
+                    "    MSLS Avg Time: {:.2} ms wall, {:.2} ms summed. Setting Time Limit for ILS/LNS: {:?}",
+                    msls_stats.avg_time_ms, avg_time_ms, time_limit
+                );
+                let evaluation_limit = msls_stats
+                    .op_counts
+                    .filter(|_| stop_by_evaluations)
+                    .map(|ops| ops.move_evaluations / num_runs as u64);
+                if let Some(limit) = evaluation_limit {
+                    println!(
+                        "    Stopping ILS/LNS/HAE after {} move evaluations instead",
+                        limit
+                    );
+                }
+                all_results.push((name.clone(), msls_stats.clone()));
+                // Plot best MSLS solution
+                let output_path = output.algorithm_file(name, msls_algo.name(), "best.png")?;
+                visualization::plot_solution(
+                    instance, // Pass the Arc<TsplibInstance>
+                    &msls_stats.best_solution,
+                    &format!("{} - {}", msls_algo.name(), name),
+                    &output_path,
+                )?;
+                (time_limit, evaluation_limit)
+            }
+        };
+        // Runs of the timed algorithms are checked against the limit they were given.
+        let options = ExperimentOptions {
+            time_limit: evaluation_limit.is_none().then_some(time_limit),
+            ..options
+        };
+        time_limits_used.insert(name.clone(), time_limit);
+        write_time_limits(&time_limits_used, Path::new(TIME_LIMITS_FILE))?;
+        let new_budget = || {
+            let budget = match evaluation_limit {
+                Some(limit) => Budget::unlimited().with_max_evaluations(limit),
+                None => Budget::timed(time_limit),
+            };
+            match experiment_args.max_solutions {
+                Some(solutions) => budget.with_max_solutions(solutions),
+                None => budget,
+            }
+        };
 
         // --- Run ILS ---
-        // Use clone for perturbation
-        let ils_algo = Ils::new(base_ls.clone(), small_perturb.clone());
+        let mut ils_algo = new_ils();
+        if share_elites {
+            ils_algo = ils_algo.with_elites(elites.clone());
+        }
+        if let Some(solution) = warm_start {
+            ils_algo = ils_algo.with_initial_solution(solution.clone());
+        }
         println!("  Running algorithm: {}", ils_algo.name());
         // Define the timed solve function as a closure
-        // Closure takes &Ils<SmallPerturbation>
-        let ils_solve_fn: TimedSolveFn<Ils<SmallPerturbation>> =
-            Box::new(|algo, inst, cb| algo.solve_timed(inst, time_limit, cb));
-        let ils_stats = run_timed_experiment(
+        let ils_usage = Mutex::new(Vec::new());
+        let ils_solve_fn: TracedSolveFn<Ils> = Box::new(|algo, inst, rng, cb| {
+            let (solution, iterations, trace, usage) =
+                algo.solve_traced_with_usage(inst, &new_budget(), rng, cb);
+            ils_usage.lock().expect("Usage lock poisoned").push(usage);
+            (solution, iterations, trace)
+        });
+        let ils_stats = run_traced_experiment(
             &ils_algo, // Pass reference to the algorithm struct
             ils_solve_fn,
             instance, // Pass Arc<TsplibInstance>
             num_runs,
             ils_algo.name(), // Pass name explicitly
+            options,
         );
         all_results.push((name.clone(), ils_stats.clone()));
+        if experiment_args.ils_kicks.len() > 1 {
+            println!("    Kick usage (uses, new bests, mean final weight):");
+            let runs = ils_usage.into_inner().expect("Usage lock poisoned");
+            for operator in operator_totals(&runs) {
+                println!(
+                    "      {:>7} {:>7} {:>6.3}  {}",
+                    operator.uses, operator.improvements, operator.weight, operator.name
+                );
+            }
+        }
         // Plot best ILS solution
-        let safe_algo_name = ils_algo
-            .name()
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
-            .replace("__", "_");
-        let output_path = format!("output/{}_{}.png", name, safe_algo_name);
+        let output_path = output.algorithm_file(name, ils_algo.name(), "best.png")?;
         visualization::plot_solution(
             instance,
             &ils_stats.best_solution,
             &format!("{} - {}", ils_algo.name(), name),
-            Path::new(&output_path),
+            &output_path,
         )?;
+        if experiment_args.pareto {
+            let pareto_seeds = extra_run_seeds(seeds, instance, ils_algo.name(), "pareto");
+            write_pareto_front(
+                &output,
+                instance,
+                ils_algo.name(),
+                num_runs,
+                pareto_seeds,
+                |rng, archive| {
+                    ils_algo.solve_with_archive(instance, &new_budget(), rng, &mut |_| {}, archive);
+                },
+            )?;
+        }
 
+        // --- Run the hyperheuristic ---
+        if run_hyperheuristic {
+            let hh_algo = new_hyperheuristic();
+            println!("  Running algorithm: {}", hh_algo.name());
+            let hh_usage = Mutex::new(Vec::new());
+            let hh_solve_fn: TracedSolveFn<Hyperheuristic> = Box::new(|algo, inst, rng, cb| {
+                let (solution, iterations, trace, usage) =
+                    algo.solve_traced_with_usage(inst, &new_budget(), rng, cb);
+                hh_usage.lock().expect("Usage lock poisoned").push(usage);
+                (solution, iterations, trace)
+            });
+            let hh_stats = run_traced_experiment(
+                &hh_algo,
+                hh_solve_fn,
+                instance,
+                num_runs,
+                hh_algo.name(),
+                options,
+            );
+            all_results.push((name.clone(), hh_stats));
+            println!("    LS configuration usage:");
+            let runs = hh_usage.into_inner().expect("Usage lock poisoned");
+            for (configuration, share) in usage_distribution(&runs) {
+                println!("      {:>5.1}%  {}", share * 100.0, configuration);
+            }
+        }
         // --- Run LNS ---
-        // Use clone for perturbation
-        let lns_algo = Lns::new(
-            base_ls.clone(),
-            large_perturb.clone(),
-            true, // apply_ls_after_repair
-            true, // apply_ls_to_initial
-        );
+        let mut lns_algo = new_lns();
+        if share_elites {
+            lns_algo = lns_algo.with_elites(elites.clone());
+        }
+        if let Some(solution) = warm_start {
+            lns_algo = lns_algo.with_initial_solution(solution.clone());
+        }
         println!("  Running algorithm: {}", lns_algo.name());
-        let lns_solve_fn: TimedSolveFn<Lns<LargePerturbation>> =
-            Box::new(|algo, inst, cb| algo.solve_timed(inst, time_limit, cb));
-        let lns_stats =
-            run_timed_experiment(&lns_algo, lns_solve_fn, instance, num_runs, lns_algo.name());
+        let lns_solve_fn: TracedSolveFn<Lns> =
+            Box::new(|algo, inst, rng, cb| algo.solve_traced(inst, &new_budget(), rng, cb));
+        let lns_stats = run_traced_experiment(
+            &lns_algo,
+            lns_solve_fn,
+            instance,
+            num_runs,
+            lns_algo.name(),
+            options,
+        );
         all_results.push((name.clone(), lns_stats.clone()));
         // Plot best LNS solution
-        let safe_algo_name = lns_algo
-            .name()
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
-            .replace("__", "_");
-        let output_path = format!("output/{}_{}.png", name, safe_algo_name);
+        let output_path = output.algorithm_file(name, lns_algo.name(), "best.png")?;
         visualization::plot_solution(
             instance,
             &lns_stats.best_solution,
             &format!("{} - {}", lns_algo.name(), name),
-            Path::new(&output_path),
+            &output_path,
         )?;
 
         // --- Run LNSa (LNS without LS after repair) ---
-        // Use clone for perturbation
-        let lnsa_algo = Lns::new(
-            base_ls.clone(),
-            large_perturb.clone(),
-            false, // apply_ls_after_repair = false
-            true,  // apply_ls_to_initial
-        );
+        let mut lnsa_algo = new_lnsa();
+        if share_elites {
+            lnsa_algo = lnsa_algo.with_elites(elites.clone());
+        }
+        if let Some(solution) = warm_start {
+            lnsa_algo = lnsa_algo.with_initial_solution(solution.clone());
+        }
         println!("  Running algorithm: {}", lnsa_algo.name());
-        let lnsa_solve_fn: TimedSolveFn<Lns<LargePerturbation>> =
-            Box::new(|algo, inst, cb| algo.solve_timed(inst, time_limit, cb));
-        let lnsa_stats = run_timed_experiment(
+        let lnsa_solve_fn: TracedSolveFn<Lns> =
+            Box::new(|algo, inst, rng, cb| algo.solve_traced(inst, &new_budget(), rng, cb));
+        let lnsa_stats = run_traced_experiment(
             &lnsa_algo,
             lnsa_solve_fn,
             instance,
             num_runs,
             lnsa_algo.name(),
+            options,
         );
         all_results.push((name.clone(), lnsa_stats.clone()));
         // Plot best LNSa solution
-        let safe_algo_name = lnsa_algo
-            .name()
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
-            .replace("__", "_");
-        let output_path = format!("output/{}_{}.png", name, safe_algo_name);
+        let output_path = output.algorithm_file(name, lnsa_algo.name(), "best.png")?;
         visualization::plot_solution(
             instance,
             &lnsa_stats.best_solution,
             &format!("{} - {}", lnsa_algo.name(), name),
-            Path::new(&output_path),
+            &output_path,
         )?;
         // --- Run HAE ---
-        let hae_algo = Hae::new(base_ls.clone(), 20, 40, true);
+        let mut hae_algo = new_hae();
+        if share_elites {
+            hae_algo = hae_algo.with_elites(elites.clone());
+        }
+        if let Some(solution) = warm_start {
+            hae_algo = hae_algo.with_initial_solution(solution.clone());
+        }
         println!("  Running algorithm: {}", hae_algo.name());
-        let hae_solve_fn: TimedSolveFn<Hae> =
-            Box::new(|algo, inst, cb| algo.solve_timed(inst, time_limit, cb));
-        let hae_stats =
-            run_timed_experiment(&hae_algo, hae_solve_fn, instance, num_runs, hae_algo.name());
+        let hae_solve_fn: TracedSolveFn<Hae> =
+            Box::new(|algo, inst, rng, cb| algo.solve_traced(inst, &new_budget(), rng, cb));
+        let hae_stats = run_traced_experiment(
+            &hae_algo,
+            hae_solve_fn,
+            instance,
+            num_runs,
+            hae_algo.name(),
+            options,
+        );
         all_results.push((name.clone(), hae_stats.clone()));
         // Plot best HAE solution
-        let safe_algo_name = hae_algo
-            .name()
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
-            .replace("__", "_");
-        let output_path = format!("output/{}_{}.png", name, safe_algo_name);
+        let output_path = output.algorithm_file(name, hae_algo.name(), "best.png")?;
         visualization::plot_solution(
             instance,
             &hae_stats.best_solution,
             &format!("{} - {}", hae_algo.name(), name),
-            Path::new(&output_path),
+            &output_path,
         )?;
+        if hae_telemetry {
+            let mut telemetry = PopulationTelemetry::new(50);
+            let mut rng = extra_run_seeds(seeds, instance, hae_algo.name(), "telemetry").rng();
+            hae_algo.solve_with_telemetry(
+                instance,
+                &new_budget(),
+                &mut rng,
+                &mut |_| {},
+                &mut telemetry,
+            );
+            let telemetry_path = output.algorithm_file(name, hae_algo.name(), "population.csv")?;
+            telemetry.write_csv(&telemetry_path)?;
+            if let Some(last) = telemetry.snapshots().last() {
+                println!("  {}", last.summary());
+            }
+            println!(
+                "  Population telemetry saved to {}",
+                telemetry_path.display()
+            );
+        }
+        if experiment_args.pareto {
+            let pareto_seeds = extra_run_seeds(seeds, instance, hae_algo.name(), "pareto");
+            write_pareto_front(
+                &output,
+                instance,
+                hae_algo.name(),
+                num_runs,
+                pareto_seeds,
+                |rng, archive| {
+                    hae_algo.solve_with_archive(instance, &new_budget(), rng, &mut |_| {}, archive);
+                },
+            )?;
+        }
         // --- Run HAE (no LS) ---
-        let hae_nols_algo = Hae::new(base_ls.clone(), 20, 40, false);
+        let mut hae_nols_algo = new_hae_nols();
+        if share_elites {
+            hae_nols_algo = hae_nols_algo.with_elites(elites.clone());
+        }
+        if let Some(solution) = warm_start {
+            hae_nols_algo = hae_nols_algo.with_initial_solution(solution.clone());
+        }
         println!("  Running algorithm: {}", hae_nols_algo.name());
-        let hae_nols_solve_fn: TimedSolveFn<Hae> =
-            Box::new(|algo, inst, cb| algo.solve_timed(inst, time_limit, cb));
-        let hae_nols_stats = run_timed_experiment(
+        let hae_nols_solve_fn: TracedSolveFn<Hae> =
+            Box::new(|algo, inst, rng, cb| algo.solve_traced(inst, &new_budget(), rng, cb));
+        let hae_nols_stats = run_traced_experiment(
             &hae_nols_algo,
             hae_nols_solve_fn,
             instance,
             num_runs,
             hae_nols_algo.name(),
+            options,
         );
         all_results.push((name.clone(), hae_nols_stats.clone()));
         // Plot best HAE (no LS) solution
-        let safe_algo_name = hae_nols_algo
-            .name()
-            .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
-            .replace("__", "_");
-        let output_path = format!("output/{}_{}.png", name, safe_algo_name);
+        let output_path = output.algorithm_file(name, hae_nols_algo.name(), "best.png")?;
         visualization::plot_solution(
             instance,
             &hae_nols_stats.best_solution,
             &format!("{} - {}", hae_nols_algo.name(), name),
-            Path::new(&output_path),
+            &output_path,
         )?;
+        // --- Run the baselines ---
+        if run_baselines {
+            println!("  Running algorithm: {}", restarted_ls_name);
+            let ls_solve_fn: TracedSolveFn<LocalSearch> =
+                Box::new(|algo, inst, rng, cb| algo.solve_traced(inst, &new_budget(), rng, cb));
+            let ls_stats = run_traced_experiment(
+                &base_ls,
+                ls_solve_fn,
+                instance,
+                num_runs,
+                &restarted_ls_name,
+                options,
+            );
+            all_results.push((name.clone(), ls_stats));
+
+            let random_walk = RandomWalk::new(time_limit, NeighborhoodType::EdgeExchange);
+            println!("  Running algorithm: {}", random_walk_name);
+            let random_walk_solve_fn: TracedSolveFn<RandomWalk> =
+                Box::new(|algo, inst, rng, cb| algo.solve_traced(inst, &new_budget(), rng, cb));
+            let random_walk_stats = run_traced_experiment(
+                &random_walk,
+                random_walk_solve_fn,
+                instance,
+                num_runs,
+                &random_walk_name,
+                options,
+            );
+            all_results.push((name.clone(), random_walk_stats));
+        }
+        if share_elites {
+            println!(
+                "  Elites ({} duplicate offers rejected):",
+                elites.duplicates()
+            );
+            for elite in elites.elites() {
+                println!("    {} from {}", elite.cost, elite.source);
+            }
+        }
+        for (_, stats) in &all_results {
+            manifest.add_algorithm(&stats.algorithm_name);
+        }
+        manifest.time_limits = time_limits_used.clone();
+        manifest.write(&output.file(MANIFEST_FILE))?;
     }
 
     println!("\nSummary of Results:");
-    // Use updated format string from algorithm.rs
+    // Colors only on a terminal, and never with NO_COLOR set (https://no-color.org).
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    for line in format_ranked_summary(&all_results, &gap_references, color) {
+        println!("{}", line);
+    }
+    if instances.len() > 1 {
+        println!();
+        for line in format_combined_summary(&all_results) {
+            println!("{}", line);
+        }
+    }
+    println!();
+    for line in format_variance_report(&all_results, &VarianceThresholds::default()) {
+        println!("{}", line);
+    }
+
+    // Primal integrals are measured against the best cost any algorithm found per instance.
+    let mut best_known: HashMap<String, Cost> = HashMap::new();
+    for (instance_name, stats) in &all_results {
+        best_known
+            .entry(instance_name.clone())
+            .and_modify(|best| *best = (*best).min(stats.min_cost))
+            .or_insert(stats.min_cost);
+    }
+    write_stats_csv(
+        all_results.iter().map(|(_, stats)| stats),
+        &best_known,
+        &output.file("summary.csv"),
+    )?;
     println!(
-        "| Instance | Algorithm                    | Cost (min - avg - max) | Time (ms, avg) | Iterations (avg) |"
+        "\nSummary with primal integrals saved to {}",
+        output.file("summary.csv").display()
     );
+    // The same results laid out for the reports: algorithms by instances.
+    PivotTable::new(&all_results).write_all(&output.file("summary_pivot"))?;
     println!(
-        "|----------|------------------------------|------------------------|----------------|------------------|"
+        "Pivoted summary saved to {}.{{md,tex,csv}}",
+        output.file("summary_pivot").display()
     );
-    for (instance_name, stats) in all_results {
-        // format_stats_row now handles padding
-        println!(
-            "| {} {}", // Removed extra spaces around {}
-            instance_name,
-            format_stats_row(&stats)
-        );
-    }
 
-    println!("\nVisualizations have been saved to the 'output' directory.");
+    println!(
+        "\nVisualizations have been saved to {}",
+        output.run_dir().display()
+    );
+    Ok(())
+}
+
+/// Offers the solutions `num_runs` runs of `solve` visit to one Pareto archive, run `r`
+/// drawing from child `r` of `seeds`, and writes its front as `pareto.csv` and `pareto.png` in the directory of `algorithm_name`.
+fn write_pareto_front(
+    output: &OutputManager,
+    instance: &TsplibInstance,
+    algorithm_name: &str,
+    num_runs: usize,
+    seeds: SeedStream,
+    solve: impl Fn(&mut dyn RngCore, &mut ParetoArchive),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut front = ParetoArchive::new();
+    for run in 0..num_runs {
+        let mut archive = ParetoArchive::new();
+        solve(&mut seeds.child(run as u64).rng(), &mut archive);
+        front.merge(&archive, instance);
+    }
+    let csv_path = output.algorithm_file(&instance.name, algorithm_name, "pareto.csv")?;
+    front.write_csv(&csv_path)?;
+    let plot_path = output.algorithm_file(&instance.name, algorithm_name, "pareto.png")?;
+    visualization::plot_pareto_front(
+        &front,
+        &format!("{} - {}", algorithm_name, instance.name),
+        &plot_path,
+    )?;
+    println!(
+        "  Pareto front ({} points) saved to {}",
+        front.len(),
+        csv_path.display()
+    );
     Ok(())
 }
+
+/// Stream of the extra `purpose` runs of `algorithm_name` on `instance`, such as the Pareto
+/// runs, apart from the streams of the experiment's own runs.
+fn extra_run_seeds(
+    seeds: SeedStream,
+    instance: &TsplibInstance,
+    algorithm_name: &str,
+    purpose: &str,
+) -> SeedStream {
+    seeds
+        .child(fnv1a(instance.name.as_bytes()))
+        .child(fnv1a(format!("{} {}", algorithm_name, purpose).as_bytes()))
+}
+
+/// Size of the candidate lists that take one: the `k` vertices the candidate moves of the base
+/// local search look at, or fewer on instances too small to have that many.
+fn neighbor_list_size(dimension: usize, k: usize) -> usize {
+    k.min(dimension.saturating_sub(1)).max(1)
+}
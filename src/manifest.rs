@@ -0,0 +1,164 @@
+use crate::flat_json::plain_string;
+use crate::time_limits::TimeLimits;
+use crate::utils::fnv1a;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name of the manifest in the experiment's output directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// An instance file the experiment loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceRecord {
+    pub name: String,
+    pub path: PathBuf,
+    /// FNV-1a hash of the file's bytes.
+    pub checksum: u64,
+}
+
+/// How an experiment was produced: the code, the machine, the instances and the algorithm
+/// configurations, so the files in `output/` can be traced back to the run that wrote them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunManifest {
+    /// `HEAD` of the working tree, `None` outside a git checkout.
+    pub git_commit: Option<String>,
+    /// Whether the working tree had uncommitted changes.
+    pub git_dirty: bool,
+    pub crate_version: String,
+    pub op_counters: bool,
+    pub hostname: String,
+    pub cpu: String,
+    /// Threads of the rayon pool the parallel algorithms run on.
+    pub threads: usize,
+    /// Master seed every run's random numbers derive from (see [`crate::seed`]).
+    pub seed: u64,
+    /// Command-line arguments of the experiment, without the program name.
+    pub arguments: Vec<String>,
+    pub instances: Vec<InstanceRecord>,
+    /// Full names of the algorithms run, which spell out their configurations.
+    pub algorithms: Vec<String>,
+    pub time_limits: TimeLimits,
+}
+
+impl RunManifest {
+    /// The manifest of this process run from the master seed `seed`, with no instances or
+    /// algorithms yet.
+    pub fn collect(seed: u64) -> Self {
+        let git_commit = git(&["rev-parse", "HEAD"]);
+        let git_dirty = git(&["status", "--porcelain"]).is_some_and(|status| !status.is_empty());
+        Self {
+            git_commit,
+            git_dirty,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            op_counters: cfg!(feature = "op-counters"),
+            hostname: hostname(),
+            cpu: cpu_model(),
+            threads: rayon::current_num_threads(),
+            seed,
+            arguments: std::env::args().skip(1).collect(),
+            instances: Vec::new(),
+            algorithms: Vec::new(),
+            time_limits: TimeLimits::new(),
+        }
+    }
+
+    /// Records the instance `name`, loaded from `path`.
+    pub fn add_instance(&mut self, name: &str, path: &Path) -> io::Result<()> {
+        self.instances.push(InstanceRecord {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            checksum: fnv1a(&fs::read(path)?),
+        });
+        Ok(())
+    }
+
+    /// Records the algorithm `name`, once however many instances it runs on.
+    pub fn add_algorithm(&mut self, name: &str) {
+        if !self.algorithms.iter().any(|known| known == name) {
+            self.algorithms.push(name.to_string());
+        }
+    }
+
+    /// Writes the manifest as a one-line JSON object, creating the parent directory. Lists
+    /// become numbered keys (`algorithm.0`, `instance.kroa200.checksum`, ...) so the file
+    /// stays readable by [`crate::flat_json::FlatObject`].
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut fields = vec![
+            string_field(
+                "git_commit",
+                self.git_commit.as_deref().unwrap_or("unknown"),
+            ),
+            format!("\"git_dirty\":{}", self.git_dirty),
+            string_field("crate_version", &self.crate_version),
+            format!("\"op_counters\":{}", self.op_counters),
+            string_field("hostname", &self.hostname),
+            string_field("cpu", &self.cpu),
+            format!("\"threads\":{}", self.threads),
+            // A string, as JSON readers that parse numbers as doubles would round it.
+            string_field("seed", &self.seed.to_string()),
+            string_field("arguments", &self.arguments.join(" ")),
+        ];
+        for instance in &self.instances {
+            fields.push(string_field(
+                &format!("instance.{}.file", instance.name),
+                &instance.path.display().to_string(),
+            ));
+            fields.push(string_field(
+                &format!("instance.{}.checksum", instance.name),
+                &format!("fnv1a64:{:016x}", instance.checksum),
+            ));
+        }
+        for (name, limit) in &self.time_limits {
+            fields.push(format!(
+                "\"time_limit_ms.{}\":{}",
+                plain_string(name),
+                limit.as_millis()
+            ));
+        }
+        for (i, algorithm) in self.algorithms.iter().enumerate() {
+            fields.push(string_field(&format!("algorithm.{}", i), algorithm));
+        }
+        fs::write(path, format!("{{{}}}\n", fields.join(",")))
+    }
+}
+
+fn string_field(key: &str, value: &str) -> String {
+    format!("\"{}\":\"{}\"", plain_string(key), plain_string(value))
+}
+
+/// Trimmed stdout of a successful `git` command, `None` if git is missing or fails.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn hostname() -> String {
+    fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The CPU model from `/proc/cpuinfo`, or just the architecture where that is unavailable.
+fn cpu_model() -> String {
+    fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|info| {
+            info.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string())
+}
@@ -0,0 +1,21 @@
+use crate::tsplib::TsplibInstance;
+use crate::weight::Cost;
+
+/// Edge costs the move evaluators work with. The `*_with` evaluators take one, so objectives
+/// that add terms to the distance (penalties, prizes, balance terms)
+/// reuse every evaluator instead of duplicating it; the plain evaluators use [`Distance`].
+pub trait CostModel {
+    /// Cost of the edge `(u, v)`.
+    fn edge_cost(&self, instance: &TsplibInstance, u: usize, v: usize) -> Cost;
+}
+
+/// The instance distance, with no penalty terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Distance;
+
+impl CostModel for Distance {
+    #[inline]
+    fn edge_cost(&self, instance: &TsplibInstance, u: usize, v: usize) -> Cost {
+        instance.distance(u, v)
+    }
+}
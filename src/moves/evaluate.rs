@@ -0,0 +1,197 @@
+//! Scoring of a [`Move`] as a value, for code that holds moves rather than the cycle
+//! positions the per-type evaluators take, such as the move list of
+//! `SearchVariant::MoveListSteepest` or code outside the searches that holds a plain
+//! [`Solution`]. A move names its vertices, so it never goes stale the way a position does
+//! after another move is applied; the positions are looked up when the move is scored. The
+//! plain entry points scan the solution, in `O(n)`; the `_indexed` ones read a
+//! [`SolutionIndex`] kept in sync with [`Move::apply_indexed`], in constant time.
+
+use crate::moves::cost_model::{CostModel, Distance};
+use crate::moves::inter_route::{
+    evaluate_inter_route_exchange_with, evaluate_inter_route_transfer_with,
+};
+use crate::moves::intra_route::{
+    evaluate_intra_route_edge_exchange_with, evaluate_intra_route_vertex_exchange_with,
+};
+use crate::moves::types::{CycleId, EvaluatedMove, Move};
+use crate::tsplib::{Solution, SolutionIndex, TsplibInstance};
+use crate::weight::Cost;
+
+// The plain entry points are the API for code that scores moves it did not generate (the
+// tests, scratch experiments, external bindings); the searches all go through the index.
+
+/// `m` evaluated on `solution` under `cost_model` by the evaluator of its type, or `None` if
+/// the move does not fit the solution (see [`Move::is_applicable`]) or its evaluator rejects
+/// it, e.g. for moving a pinned vertex. An inter-route exchange comes back with the vertex of
+/// cycle 1 as `v1`.
+#[allow(dead_code)]
+pub fn evaluate_move_with<C: CostModel + ?Sized>(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cost_model: &C,
+    m: &Move,
+) -> Option<EvaluatedMove> {
+    if !m.is_applicable(solution) {
+        return None;
+    }
+    evaluate_at(solution, instance, cost_model, m, |v| solution.find_node(v))
+}
+
+/// [`evaluate_move_with`] under the instance distance.
+#[allow(dead_code)]
+pub fn evaluate_move(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    m: &Move,
+) -> Option<EvaluatedMove> {
+    evaluate_move_with(solution, instance, &Distance, m)
+}
+
+/// Applies `m` to `solution` and returns its cost delta, as evaluated before the move, or
+/// leaves the solution untouched and returns `None` if [`evaluate_move`] rejects the move.
+#[allow(dead_code)]
+pub fn apply_and_get_delta(
+    solution: &mut Solution,
+    instance: &TsplibInstance,
+    m: &Move,
+) -> Option<Cost> {
+    let evaluated = evaluate_move(solution, instance, m)?;
+    m.apply(solution);
+    Some(evaluated.delta)
+}
+
+/// [`evaluate_move_with`] with the vertices looked up in `index`, which must be in sync with
+/// `solution`.
+pub fn evaluate_move_indexed_with<C: CostModel + ?Sized>(
+    solution: &Solution,
+    index: &SolutionIndex,
+    instance: &TsplibInstance,
+    cost_model: &C,
+    m: &Move,
+) -> Option<EvaluatedMove> {
+    if !m.is_applicable_indexed(index) {
+        return None;
+    }
+    evaluate_at(solution, instance, cost_model, m, |v| index.find_node(v))
+}
+
+/// [`evaluate_move_indexed_with`] under the instance distance.
+pub fn evaluate_move_indexed(
+    solution: &Solution,
+    index: &SolutionIndex,
+    instance: &TsplibInstance,
+    m: &Move,
+) -> Option<EvaluatedMove> {
+    evaluate_move_indexed_with(solution, index, instance, &Distance, m)
+}
+
+/// Calls the evaluator of the type of `m`, an applicable move, at the positions `find_node`
+/// gives for its vertices.
+fn evaluate_at<C: CostModel + ?Sized>(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cost_model: &C,
+    m: &Move,
+    find_node: impl Fn(usize) -> Option<(CycleId, usize)>,
+) -> Option<EvaluatedMove> {
+    let position = |v: usize| find_node(v).map(|(_, pos)| pos);
+    match *m {
+        Move::InterRouteExchange { v1, v2 } => {
+            let (pos1, pos2) = match find_node(v1)? {
+                (CycleId::Cycle1, pos1) => (pos1, position(v2)?),
+                (CycleId::Cycle2, pos2) => (position(v2)?, pos2),
+            };
+            evaluate_inter_route_exchange_with(solution, instance, cost_model, pos1, pos2)
+        }
+        Move::IntraRouteVertexExchange { v1, v2, cycle } => {
+            evaluate_intra_route_vertex_exchange_with(
+                solution,
+                instance,
+                cost_model,
+                cycle,
+                position(v1)?,
+                position(v2)?,
+            )
+        }
+        Move::IntraRouteEdgeExchange { a, c, cycle, .. } => {
+            evaluate_intra_route_edge_exchange_with(
+                solution,
+                instance,
+                cost_model,
+                cycle,
+                position(a)?,
+                position(c)?,
+            )
+        }
+        Move::InterRouteTransfer { v, from, after } => evaluate_inter_route_transfer_with(
+            solution,
+            instance,
+            cost_model,
+            from,
+            position(v)?,
+            position(after)?,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::local_search::base::NeighborhoodType;
+    use crate::algorithms::local_search::oracle::enumerate_moves;
+    use crate::seed::SeedStream;
+    use crate::tsplib::EdgeWeightType;
+    use crate::weight::Weight;
+    use rand::Rng;
+    use rand::seq::SliceRandom;
+
+    /// Every move of both neighbourhoods, scored on a plain solution, predicts the cost change
+    /// that applying it gives, and a move that does not fit is rejected without touching the
+    /// solution.
+    #[test]
+    fn evaluate_move_predicts_the_applied_delta() {
+        let mut rng = SeedStream::new(4443).rng();
+        let coordinates = (0..12)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect();
+        let instance = TsplibInstance::from_coordinates(
+            "random12".to_string(),
+            EdgeWeightType::Euc2D,
+            coordinates,
+        );
+        let mut vertices: Vec<usize> = (0..12).collect();
+        vertices.shuffle(&mut rng);
+        let cycle2 = vertices.split_off(6);
+        let solution = Solution::new(vertices, cycle2);
+        let cost = solution.calculate_cost(&instance);
+
+        for neighborhood in [
+            NeighborhoodType::VertexExchange,
+            NeighborhoodType::EdgeExchange,
+        ] {
+            for m in enumerate_moves(&solution, &instance, neighborhood, 0) {
+                let evaluated = evaluate_move(&solution, &instance, &m).expect("move fits");
+                let mut applied = solution.clone();
+                let delta = apply_and_get_delta(&mut applied, &instance, &m);
+                assert_eq!(delta, Some(evaluated.delta), "{:?}", m);
+                assert!(
+                    (applied.calculate_cost(&instance) - cost).approx_eq(evaluated.delta),
+                    "{:?}",
+                    m
+                );
+            }
+        }
+
+        let same_cycle = Move::InterRouteExchange {
+            v1: solution.cycle1[0],
+            v2: solution.cycle1[1],
+        };
+        let mut untouched = solution.clone();
+        assert!(evaluate_move(&solution, &instance, &same_cycle).is_none());
+        assert_eq!(
+            apply_and_get_delta(&mut untouched, &instance, &same_cycle),
+            None
+        );
+        assert_eq!(untouched, solution);
+    }
+}
@@ -1,12 +1,37 @@
-use crate::moves::types::{CycleId, EvaluatedMove, Move};
+use crate::counters::count_move_evaluation;
+use crate::moves::cost_model::{CostModel, Distance};
+use crate::moves::types::{CycleId, EvaluatedMove, Move, link_cost};
 use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Weight};
 
-pub fn evaluate_inter_route_exchange(
+/// Cost change of putting `new` in place of the vertex at `pos` of `cycle` (at least two
+/// vertices). With two vertices both links join the same pair, so a closed cycle pays the
+/// change twice.
+fn replacement_delta<C: CostModel + ?Sized>(
+    cycle: &[usize],
+    pos: usize,
+    new: usize,
+    closed: bool,
+    instance: &TsplibInstance,
+    cost_model: &C,
+) -> Cost {
+    let n = cycle.len();
+    let prev_pos = (pos + n - 1) % n;
+    let (prev, old, next) = (cycle[prev_pos], cycle[pos], cycle[(pos + 1) % n]);
+    let link = |at: usize, u: usize, v: usize| link_cost(instance, cost_model, closed, n, at, u, v);
+    link(prev_pos, prev, new) + link(pos, new, next)
+        - link(prev_pos, prev, old)
+        - link(pos, old, next)
+}
+
+pub fn evaluate_inter_route_exchange_with<C: CostModel + ?Sized>(
     solution: &Solution,
     instance: &TsplibInstance,
+    cost_model: &C,
     pos1: usize, // Position of node u in cycle 1
     pos2: usize, // Position of node v in cycle 2
 ) -> Option<EvaluatedMove> {
+    count_move_evaluation();
     let cycle1 = &solution.cycle1;
     let cycle2 = &solution.cycle2;
     let n1 = cycle1.len();
@@ -20,66 +45,108 @@ pub fn evaluate_inter_route_exchange(
     // Vertices to be swapped
     let u = cycle1[pos1]; // Node from cycle 1
     let v = cycle2[pos2]; // Node from cycle 2
+    if instance.is_pinned(u) || instance.is_pinned(v) {
+        return None;
+    }
 
-    let delta = if n1 == 1 && n2 == 1 {
-        // Swapping two single-node cycles doesn't change cost
-        0
-    } else if n1 == 1 {
-        // Cycle 1 has only node u
-        // Remove v from cycle 2 and insert u
-        let prev_v = cycle2[if pos2 == 0 { n2 - 1 } else { pos2 - 1 }];
-        let next_v = cycle2[(pos2 + 1) % n2];
-        // Delta = (dist(prev_v, u) + dist(u, next_v)) - (dist(prev_v, v) + dist(v, next_v))
-        // If n2 == 2, prev_v == next_v, delta = 2*dist(prev_v, u) - 2*dist(prev_v, v)
-        if n2 == 2 {
-            2 * instance.distance(prev_v, u) - 2 * instance.distance(prev_v, v)
+    // Cost change of (cycle 1, cycle 2); a single-node cycle always costs 0.
+    let closed = solution.is_closed;
+    let cycle_deltas = (
+        if n1 == 1 {
+            Cost::ZERO
         } else {
-            (instance.distance(prev_v, u) + instance.distance(u, next_v))
-                - (instance.distance(prev_v, v) + instance.distance(v, next_v))
-        }
-    } else if n2 == 1 {
-        // Cycle 2 has only node v
-        // Remove u from cycle 1 and insert v
-        let prev_u = cycle1[if pos1 == 0 { n1 - 1 } else { pos1 - 1 }];
-        let next_u = cycle1[(pos1 + 1) % n1];
-        // Delta = (dist(prev_u, v) + dist(v, next_u)) - (dist(prev_u, u) + dist(u, next_u))
-        // If n1 == 2, prev_u == next_u, delta = 2*dist(prev_u, v) - 2*dist(prev_u, u)
-        if n1 == 2 {
-            2 * instance.distance(prev_u, v) - 2 * instance.distance(prev_u, u)
+            replacement_delta(cycle1, pos1, v, closed, instance, cost_model)
+        },
+        if n2 == 1 {
+            Cost::ZERO
         } else {
-            (instance.distance(prev_u, v) + instance.distance(v, next_u))
-                - (instance.distance(prev_u, u) + instance.distance(u, next_u))
-        }
-    } else {
-        // Both cycles have >= 2 nodes
-        let prev_u = cycle1[if pos1 == 0 { n1 - 1 } else { pos1 - 1 }];
-        let next_u = cycle1[(pos1 + 1) % n1];
-        let prev_v = cycle2[if pos2 == 0 { n2 - 1 } else { pos2 - 1 }];
-        let next_v = cycle2[(pos2 + 1) % n2];
+            replacement_delta(cycle2, pos2, u, closed, instance, cost_model)
+        },
+    );
 
-        // Calculate cost change in Cycle 1 (replace u with v)
-        let delta_c1 = if n1 == 2 {
-            // remove 2*dist(prev_u, u), add 2*dist(prev_u, v)
-            2 * instance.distance(prev_u, v) - 2 * instance.distance(prev_u, u)
-        } else {
-            (instance.distance(prev_u, v) + instance.distance(v, next_u))
-                - (instance.distance(prev_u, u) + instance.distance(u, next_u))
-        };
+    Some(EvaluatedMove::new(
+        Move::InterRouteExchange { v1: u, v2: v }, // Store node IDs
+        cycle_deltas,
+    ))
+}
 
-        // Calculate cost change in Cycle 2 (replace v with u)
-        let delta_c2 = if n2 == 2 {
-            // remove 2*dist(prev_v, v), add 2*dist(prev_v, u)
-            2 * instance.distance(prev_v, u) - 2 * instance.distance(prev_v, v)
-        } else {
-            (instance.distance(prev_v, u) + instance.distance(u, next_v))
-                - (instance.distance(prev_v, v) + instance.distance(v, next_v))
-        };
+/// [`evaluate_inter_route_exchange_with`] under the instance distance.
+pub fn evaluate_inter_route_exchange(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    pos1: usize, // Position of node u in cycle 1
+    pos2: usize, // Position of node v in cycle 2
+) -> Option<EvaluatedMove> {
+    evaluate_inter_route_exchange_with(solution, instance, &Distance, pos1, pos2)
+}
 
-        delta_c1 + delta_c2
+/// Calculates the cost delta of moving the vertex at `pos` of cycle `from` into the other
+/// cycle between the vertices at `after_pos` and `after_pos + 1`.
+/// Returns `None` if it would empty the source cycle, the target cycle is empty, a position
+/// is out of range or the vertex is pinned.
+pub fn evaluate_inter_route_transfer_with<C: CostModel + ?Sized>(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cost_model: &C,
+    from: CycleId,
+    pos: usize,
+    after_pos: usize,
+) -> Option<EvaluatedMove> {
+    count_move_evaluation();
+    let (source, target) = match from {
+        CycleId::Cycle1 => (&solution.cycle1, &solution.cycle2),
+        CycleId::Cycle2 => (&solution.cycle2, &solution.cycle1),
     };
+    let ns = source.len();
+    let nt = target.len();
+    if ns < 2 || nt == 0 || pos >= ns || after_pos >= nt {
+        return None;
+    }
 
-    Some(EvaluatedMove {
-        move_type: Move::InterRouteExchange { v1: u, v2: v }, // Store node IDs
-        delta,
-    })
+    let v = source[pos];
+    if instance.is_pinned(v) {
+        return None;
+    }
+    let closed = solution.is_closed;
+    let prev_pos = (pos + ns - 1) % ns;
+    let prev = source[prev_pos];
+    let next = source[(pos + 1) % ns];
+    // The source loses both links of v and gains (prev, next), unless v was an end of an open
+    // path (then (prev, next) is the free closing link) or only one vertex remains.
+    let bridge = if ns == 2 || (!closed && (pos == 0 || pos == ns - 1)) {
+        Cost::ZERO
+    } else {
+        cost_model.edge_cost(instance, prev, next)
+    };
+    let removal = bridge
+        - link_cost(instance, cost_model, closed, ns, prev_pos, prev, v)
+        - link_cost(instance, cost_model, closed, ns, pos, v, next);
+
+    // v lands at `after_pos + 1`: (a, b) is replaced by (a, v) and (v, b), where (v, b)
+    // becomes the closing link if v is appended at the end.
+    let a = target[after_pos];
+    let b = target[(after_pos + 1) % nt];
+    let insertion = cost_model.edge_cost(instance, a, v)
+        + link_cost(instance, cost_model, closed, nt + 1, after_pos + 1, v, b)
+        - link_cost(instance, cost_model, closed, nt, after_pos, a, b);
+
+    let cycle_deltas = match from {
+        CycleId::Cycle1 => (removal, insertion),
+        CycleId::Cycle2 => (insertion, removal),
+    };
+    Some(EvaluatedMove::new(
+        Move::InterRouteTransfer { v, from, after: a },
+        cycle_deltas,
+    ))
+}
+
+/// [`evaluate_inter_route_transfer_with`] under the instance distance.
+pub fn evaluate_inter_route_transfer(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    from: CycleId,
+    pos: usize,
+    after_pos: usize,
+) -> Option<EvaluatedMove> {
+    evaluate_inter_route_transfer_with(solution, instance, &Distance, from, pos, after_pos)
 }
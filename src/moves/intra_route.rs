@@ -1,13 +1,18 @@
-use crate::moves::types::{CycleId, EvaluatedMove, Move};
+use crate::counters::count_move_evaluation;
+use crate::moves::cost_model::{CostModel, Distance};
+use crate::moves::types::{CycleId, EvaluatedMove, Move, link_cost};
 use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Weight};
 
-pub fn evaluate_intra_route_vertex_exchange(
+pub fn evaluate_intra_route_vertex_exchange_with<C: CostModel + ?Sized>(
     solution: &Solution,
     instance: &TsplibInstance,
+    cost_model: &C,
     cycle: CycleId,
     pos1: usize,
     pos2: usize,
 ) -> Option<EvaluatedMove> {
+    count_move_evaluation();
     let cycle_vec = solution.get_cycle(cycle);
     let n = cycle_vec.len();
 
@@ -21,49 +26,101 @@ pub fn evaluate_intra_route_vertex_exchange(
 
     let v1 = cycle_vec[pos1];
     let v2 = cycle_vec[pos2];
+    // Links keep their positions when two vertices swap, so the free closing link of an open
+    // path stays free.
+    let link = |at: usize, u: usize, v: usize| {
+        link_cost(instance, cost_model, solution.is_closed, n, at, u, v)
+    };
 
     // Calculate delta based on adjacency
     let delta = if n == 2 {
         // Only two nodes, swapping them doesn't change the cycle or cost.
-        0
+        Cost::ZERO
     } else if pos2 == pos1 + 1 || (pos1 == 0 && pos2 == n - 1) {
-        // Adjacent nodes (including wrap-around)
-        // Find neighbours correctly considering wrap-around for both cases
-        let prev1 = cycle_vec[if pos1 == 0 { n - 1 } else { pos1 - 1 }];
-        let next2 = cycle_vec[(pos2 + 1) % n]; // next of v2
-
-        // If adjacent: ..., prev1, v1, v2, next2, ... swapped to ..., prev1, v2, v1, next2, ...
-        // Edges removed: (prev1, v1), (v1, v2), (v2, next2)
-        // Edges added:   (prev1, v2), (v2, v1), (v1, next2)
+        // Adjacent nodes (including wrap-around). With wrap-around (pos1 == 0, pos2 == n - 1)
+        // v2 precedes v1 along the cycle, so the roles of the pair are swapped.
+        let (first, second, first_pos, second_pos) = if pos2 == pos1 + 1 {
+            (v1, v2, pos1, pos2)
+        } else {
+            (v2, v1, pos2, pos1)
+        };
+        let prev_pos = (first_pos + n - 1) % n;
+        let prev = cycle_vec[prev_pos];
+        let next = cycle_vec[(second_pos + 1) % n];
+
+        // ..., prev, first, second, next, ... swapped to ..., prev, second, first, next, ...
+        // Edges removed: (prev, first), (first, second), (second, next)
+        // Edges added:   (prev, second), (second, first), (first, next)
         // Delta = Added - Removed
-        (instance.distance(prev1, v2) + instance.distance(v2, v1) + instance.distance(v1, next2))
-            - (instance.distance(prev1, v1)
-                + instance.distance(v1, v2)
-                + instance.distance(v2, next2))
+        (link(prev_pos, prev, second)
+            + link(first_pos, second, first)
+            + link(second_pos, first, next))
+            - (link(prev_pos, prev, first)
+                + link(first_pos, first, second)
+                + link(second_pos, second, next))
     } else {
         // Non-adjacent nodes
-        let prev1 = cycle_vec[if pos1 == 0 { n - 1 } else { pos1 - 1 }];
+        let prev1_pos = (pos1 + n - 1) % n;
+        let prev2_pos = (pos2 + n - 1) % n;
+        let prev1 = cycle_vec[prev1_pos];
         let next1 = cycle_vec[(pos1 + 1) % n]; // Should exist since n > 2 and not adjacent
-        let prev2 = cycle_vec[if pos2 == 0 { n - 1 } else { pos2 - 1 }]; // Should exist
+        let prev2 = cycle_vec[prev2_pos]; // Should exist
         let next2 = cycle_vec[(pos2 + 1) % n];
 
         // Edges removed: (prev1, v1), (v1, next1), (prev2, v2), (v2, next2)
         // Edges added:   (prev1, v2), (v2, next1), (prev2, v1), (v1, next2)
         // Delta = Added - Removed
-        (instance.distance(prev1, v2)
-            + instance.distance(v2, next1)
-            + instance.distance(prev2, v1)
-            + instance.distance(v1, next2))
-            - (instance.distance(prev1, v1)
-                + instance.distance(v1, next1)
-                + instance.distance(prev2, v2)
-                + instance.distance(v2, next2))
+        (link(prev1_pos, prev1, v2)
+            + link(pos1, v2, next1)
+            + link(prev2_pos, prev2, v1)
+            + link(pos2, v1, next2))
+            - (link(prev1_pos, prev1, v1)
+                + link(pos1, v1, next1)
+                + link(prev2_pos, prev2, v2)
+                + link(pos2, v2, next2))
     };
 
-    Some(EvaluatedMove {
-        move_type: Move::IntraRouteVertexExchange { v1, v2, cycle }, // Use correct field names
+    Some(EvaluatedMove::within(
+        Move::IntraRouteVertexExchange { v1, v2, cycle },
+        cycle,
         delta,
-    })
+    ))
+}
+
+/// [`evaluate_intra_route_vertex_exchange_with`] under the instance distance.
+pub fn evaluate_intra_route_vertex_exchange(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cycle: CycleId,
+    pos1: usize,
+    pos2: usize,
+) -> Option<EvaluatedMove> {
+    evaluate_intra_route_vertex_exchange_with(solution, instance, &Distance, cycle, pos1, pos2)
+}
+
+/// Cost delta of the 2-opt move removing the links `(a, b)` at `pos1` and `(c, d)` at
+/// `pos2` and adding `(a, c)` and `(b, d)`. On an open path, [`Move::apply`] never reverses
+/// across the closing link, so if a removed link is the free closing one, the closing
+/// positions end up holding one of the added links, which is then free as well.
+fn two_opt_delta<C: CostModel + ?Sized>(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cost_model: &C,
+    n: usize,
+    (pos1, a, b): (usize, usize, usize),
+    (pos2, c, d): (usize, usize, usize),
+) -> Cost {
+    let closed = solution.is_closed;
+    let added = |removed_pos: usize, u: usize, v: usize| {
+        if !closed && removed_pos == n - 1 {
+            Cost::ZERO
+        } else {
+            cost_model.edge_cost(instance, u, v)
+        }
+    };
+    added(pos1, a, c) + added(pos2, b, d)
+        - link_cost(instance, cost_model, closed, n, pos1, a, b)
+        - link_cost(instance, cost_model, closed, n, pos2, c, d)
 }
 
 /// Calculates the cost delta for exchanging edges `(a, b)` and `(c, d)`
@@ -73,13 +130,15 @@ pub fn evaluate_intra_route_vertex_exchange(
 ///
 /// Assumes `pos1` and `pos2` represent the *start* indices of the edges to be removed.
 /// Returns `None` if the move is invalid (e.g., cycle size < 3, adjacent edges).
-pub fn evaluate_intra_route_edge_exchange(
+pub fn evaluate_intra_route_edge_exchange_with<C: CostModel + ?Sized>(
     solution: &Solution,
     instance: &TsplibInstance,
+    cost_model: &C,
     cycle: CycleId,
     pos1: usize, // Index of node `a`
     pos2: usize, // Index of node `c`
 ) -> Option<EvaluatedMove> {
+    count_move_evaluation();
     let cycle_vec = solution.get_cycle(cycle);
     let n = cycle_vec.len();
 
@@ -102,18 +161,31 @@ pub fn evaluate_intra_route_edge_exchange(
     let c = cycle_vec[pos2];
     let d = cycle_vec[(pos2 + 1) % n];
 
-    // Cost removed: dist(a, b) + dist(c, d)
-    let cost_removed = instance.distance(a, b) + instance.distance(c, d);
-
-    // Cost added: dist(a, c) + dist(b, d)
-    let cost_added = instance.distance(a, c) + instance.distance(b, d);
-
-    let delta = cost_added - cost_removed;
+    let delta = two_opt_delta(
+        solution,
+        instance,
+        cost_model,
+        n,
+        (pos1, a, b),
+        (pos2, c, d),
+    );
 
-    Some(EvaluatedMove {
-        move_type: Move::IntraRouteEdgeExchange { a, b, c, d, cycle }, // Use correct field names
+    Some(EvaluatedMove::within(
+        Move::IntraRouteEdgeExchange { a, b, c, d, cycle },
+        cycle,
         delta,
-    })
+    ))
+}
+
+/// [`evaluate_intra_route_edge_exchange_with`] under the instance distance.
+pub fn evaluate_intra_route_edge_exchange(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cycle: CycleId,
+    pos1: usize, // Index of node `a`
+    pos2: usize, // Index of node `c`
+) -> Option<EvaluatedMove> {
+    evaluate_intra_route_edge_exchange_with(solution, instance, &Distance, cycle, pos1, pos2)
 }
 
 /// Calculates the cost delta for a specific candidate 2-opt move:
@@ -122,13 +194,15 @@ pub fn evaluate_intra_route_edge_exchange(
 /// 2-opt move by removing edges (a, a_next) and (b, b_next), and adding
 /// edges (a, b) and (a_next, b_next).
 /// `pos_a` is the index of node `a`, `pos_b` is the index of node `b`.
-pub fn evaluate_candidate_intra_route_edge_exchange(
+pub fn evaluate_candidate_intra_route_edge_exchange_with<C: CostModel + ?Sized>(
     solution: &Solution,
     instance: &TsplibInstance,
+    cost_model: &C,
     cycle_id: CycleId,
     pos_a: usize,
     pos_b: usize,
 ) -> Option<EvaluatedMove> {
+    count_move_evaluation();
     let cycle_vec = solution.get_cycle(cycle_id);
     let n = cycle_vec.len();
 
@@ -152,25 +226,83 @@ pub fn evaluate_candidate_intra_route_edge_exchange(
     let a_next = cycle_vec[pos_a_next];
     let b_next = cycle_vec[pos_b_next];
 
-    // Cost removed: dist(a, a_next) + dist(b, b_next)
-    let cost_removed = instance.distance(a, a_next) + instance.distance(b, b_next);
-
-    // Cost added: dist(a, b) + dist(a_next, b_next)
-    let cost_added = instance.distance(a, b) + instance.distance(a_next, b_next);
-
-    let delta = cost_added - cost_removed;
+    // Removes (a, a_next) and (b, b_next), adds (a, b) and (a_next, b_next).
+    let delta = two_opt_delta(
+        solution,
+        instance,
+        cost_model,
+        n,
+        (pos_a, a, a_next),
+        (pos_b, b, b_next),
+    );
 
     // Store the move in the standard IntraRouteEdgeExchange format.
     // Removed edges were (a, a_next) and (b, b_next).
     // Apply function expects { a: w, b: x, c: y, d: z } where removed edges are (w, x) and (y, z).
-    Some(EvaluatedMove {
-        move_type: Move::IntraRouteEdgeExchange {
+    Some(EvaluatedMove::within(
+        Move::IntraRouteEdgeExchange {
             a,         // w = a
             b: a_next, // x = a_next
             c: b,      // y = b
             d: b_next, // z = b_next
             cycle: cycle_id,
         },
+        cycle_id,
         delta,
-    })
+    ))
+}
+
+/// [`evaluate_candidate_intra_route_edge_exchange_with`] under the instance distance.
+pub fn evaluate_candidate_intra_route_edge_exchange(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cycle_id: CycleId,
+    pos_a: usize,
+    pos_b: usize,
+) -> Option<EvaluatedMove> {
+    evaluate_candidate_intra_route_edge_exchange_with(
+        solution, instance, &Distance, cycle_id, pos_a, pos_b,
+    )
+}
+
+/// Mirror image of [`evaluate_candidate_intra_route_edge_exchange`]: the 2-opt move that
+/// also introduces the candidate edge (a, b), but by removing the edges *entering* the two
+/// nodes, (a_prev, a) and (b_prev, b), and adding (a, b) and (a_prev, b_prev).
+pub fn evaluate_candidate_intra_route_edge_exchange_backward_with<C: CostModel + ?Sized>(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cost_model: &C,
+    cycle_id: CycleId,
+    pos_a: usize,
+    pos_b: usize,
+) -> Option<EvaluatedMove> {
+    count_move_evaluation();
+    let n = solution.get_cycle(cycle_id).len();
+    if n < 3 || pos_a >= n || pos_b >= n || pos_a == pos_b {
+        return None;
+    }
+
+    // Removing (a_prev, a) and (b_prev, b) is the forward move anchored at the predecessors:
+    // it adds (a_prev, b_prev) and (a, b).
+    evaluate_candidate_intra_route_edge_exchange_with(
+        solution,
+        instance,
+        cost_model,
+        cycle_id,
+        (pos_a + n - 1) % n,
+        (pos_b + n - 1) % n,
+    )
+}
+
+/// [`evaluate_candidate_intra_route_edge_exchange_backward_with`] under the instance distance.
+pub fn evaluate_candidate_intra_route_edge_exchange_backward(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cycle_id: CycleId,
+    pos_a: usize,
+    pos_b: usize,
+) -> Option<EvaluatedMove> {
+    evaluate_candidate_intra_route_edge_exchange_backward_with(
+        solution, instance, &Distance, cycle_id, pos_a, pos_b,
+    )
 }
@@ -1,5 +1,5 @@
+pub mod cost_model;
+pub mod evaluate;
 pub mod inter_route;
 pub mod intra_route;
 pub mod types;
-
-pub use types::{EvaluatedMove, Move};
@@ -1,12 +1,18 @@
-use crate::tsplib::Solution;
+use crate::moves::cost_model::CostModel;
+use crate::tsplib::{Solution, SolutionIndex, TsplibInstance};
+use crate::weight::{Cost, Weight};
+use std::cmp::Ordering;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum CycleId {
     Cycle1,
     Cycle2,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Moves are ordered by type and then by operands, which breaks ties between moves of equal
+/// delta independently of the order they were generated in.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Move {
     InterRouteExchange {
         v1: usize,
@@ -24,106 +30,309 @@ pub enum Move {
         d: usize,
         cycle: CycleId,
     },
+    /// Moves `v` out of cycle `from` into the other cycle, right after vertex `after`.
+    /// Changes the cycle sizes, so it is only used when size slack is allowed.
+    InterRouteTransfer {
+        v: usize,
+        from: CycleId,
+        after: usize,
+    },
+}
+
+/// The type of a move without its operands, used for per-type statistics.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveKind {
+    InterRouteExchange,
+    IntraRouteVertexExchange,
+    IntraRouteEdgeExchange,
+    InterRouteTransfer,
+}
+
+impl MoveKind {
+    pub const ALL: [MoveKind; 4] = [
+        MoveKind::InterRouteExchange,
+        MoveKind::IntraRouteVertexExchange,
+        MoveKind::IntraRouteEdgeExchange,
+        MoveKind::InterRouteTransfer,
+    ];
+
+    /// Dense index in `0..ALL.len()`, for per-kind counter arrays.
+    pub fn index(self) -> usize {
+        self as usize
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct EvaluatedMove {
     pub move_type: Move,
-    pub delta: i32,
+    /// Change of the objective being minimised; the evaluators fill in the total cost delta.
+    pub delta: Cost,
+    /// Length changes of cycle 1 and cycle 2; they add up to the cost delta of the move.
+    pub cycle_deltas: (Cost, Cost),
+}
+
+impl EvaluatedMove {
+    /// A move changing the cycle lengths by `cycle_deltas`, scored by their sum.
+    pub fn new(move_type: Move, cycle_deltas: (Cost, Cost)) -> Self {
+        Self {
+            move_type,
+            delta: cycle_deltas.0 + cycle_deltas.1,
+            cycle_deltas,
+        }
+    }
+
+    /// Orders by delta and then by the move, so the steepest choice among moves of equal delta
+    /// stays the same whatever order (or thread) produced them.
+    pub fn steepest_order(&self, other: &Self) -> Ordering {
+        self.delta
+            .total_order(&other.delta)
+            .then_with(|| self.move_type.cmp(&other.move_type))
+    }
+
+    /// A move that only changes the length of `cycle`, by `delta`.
+    pub fn within(move_type: Move, cycle: CycleId, delta: Cost) -> Self {
+        match cycle {
+            CycleId::Cycle1 => Self::new(move_type, (delta, Cost::ZERO)),
+            CycleId::Cycle2 => Self::new(move_type, (Cost::ZERO, delta)),
+        }
+    }
 }
 
 impl Move {
+    pub fn kind(&self) -> MoveKind {
+        match self {
+            Move::InterRouteExchange { .. } => MoveKind::InterRouteExchange,
+            Move::IntraRouteVertexExchange { .. } => MoveKind::IntraRouteVertexExchange,
+            Move::IntraRouteEdgeExchange { .. } => MoveKind::IntraRouteEdgeExchange,
+            Move::InterRouteTransfer { .. } => MoveKind::InterRouteTransfer,
+        }
+    }
+
+    /// Whether the move still fits `solution`: its vertices are in the expected cycles and,
+    /// for 2-opt, both removed edges still exist.
+    pub fn is_applicable(&self, solution: &Solution) -> bool {
+        self.fits(
+            |v| solution.find_node(v).map(|(cycle, _)| cycle),
+            |a, b, cycle| solution.check_edge_in_cycle(solution.get_cycle(cycle), a, b) == Some(1),
+        )
+    }
+
+    /// [`Self::is_applicable`] in constant time, for an `index` in sync with the solution.
+    pub fn is_applicable_indexed(&self, index: &SolutionIndex) -> bool {
+        self.fits(
+            |v| index.find_node(v).map(|(cycle, _)| cycle),
+            |a, b, cycle| index.has_edge(a, b) == Some((cycle, 1)),
+        )
+    }
+
+    /// The rules of [`Self::is_applicable`], given the cycle of a vertex and whether `cycle`
+    /// traverses the edge `a -> b`.
+    fn fits(
+        &self,
+        cycle_of: impl Fn(usize) -> Option<CycleId>,
+        has_edge: impl Fn(usize, usize, CycleId) -> bool,
+    ) -> bool {
+        match *self {
+            Move::InterRouteExchange { v1, v2 } => match (cycle_of(v1), cycle_of(v2)) {
+                (Some(c1), Some(c2)) => c1 != c2,
+                _ => false,
+            },
+            Move::IntraRouteVertexExchange { v1, v2, cycle } => {
+                cycle_of(v1) == Some(cycle) && cycle_of(v2) == Some(cycle)
+            }
+            Move::IntraRouteEdgeExchange { a, b, c, d, cycle } => {
+                has_edge(a, b, cycle) && has_edge(c, d, cycle)
+            }
+            Move::InterRouteTransfer { v, from, after } => match (cycle_of(v), cycle_of(after)) {
+                (Some(cv), Some(ca)) => cv == from && ca != from,
+                _ => false,
+            },
+        }
+    }
+
+    /// The two vertices whose positions [`Self::apply`] needs: the exchanged vertices, the
+    /// ends `b` and `c` of the reversed segment, or the transferred vertex and its new
+    /// predecessor.
+    fn anchors(&self) -> (usize, usize) {
+        match *self {
+            Move::InterRouteExchange { v1, v2 } | Move::IntraRouteVertexExchange { v1, v2, .. } => {
+                (v1, v2)
+            }
+            Move::IntraRouteEdgeExchange { b, c, .. } => (b, c),
+            Move::InterRouteTransfer { v, after, .. } => (v, after),
+        }
+    }
+
+    /// Applies the move and keeps `index` in sync: only the positions and edges the move
+    /// touches are re-read, except for transfers, which shift every later position of both
+    /// cycles.
+    pub fn apply_indexed(&self, solution: &mut Solution, index: &mut SolutionIndex) {
+        let (x, y) = self.anchors();
+        let touched = self.apply_at(solution, index.find_node(x), index.find_node(y));
+        for (cycle_id, from, len) in touched.into_iter().flatten() {
+            index.refresh(solution, cycle_id, from, len);
+        }
+    }
+
     pub fn apply(&self, solution: &mut Solution) {
+        let (x, y) = self.anchors();
+        let (at_x, at_y) = (solution.find_node(x), solution.find_node(y));
+        self.apply_at(solution, at_x, at_y);
+    }
+
+    /// Applies the move given the cycles and positions of its [`Self::anchors`], and returns
+    /// the runs of positions `(cycle, from, len)`, wrapping around the cycle end, whose vertex
+    /// or neighbours changed. Nothing changes, with a warning, if the move does not fit.
+    fn apply_at(
+        &self,
+        solution: &mut Solution,
+        at_x: Option<(CycleId, usize)>,
+        at_y: Option<(CycleId, usize)>,
+    ) -> [Option<(CycleId, usize, usize)>; 2] {
+        // The positions around `pos`, for a single changed vertex.
+        let around = |cycle: CycleId, pos: usize, n: usize| Some((cycle, pos + n - 1, 3));
         match self {
-            Move::InterRouteExchange { v1, v2 } => {
-                let pos1_opt = solution.find_node(*v1);
-                let pos2_opt = solution.find_node(*v2);
-
-                if let (Some((CycleId::Cycle1, pos1)), Some((CycleId::Cycle2, pos2))) =
-                    (pos1_opt, pos2_opt)
-                {
-                    solution.cycle1[pos1] = *v2;
-                    solution.cycle2[pos2] = *v1;
-                } else if let (Some((CycleId::Cycle2, pos1)), Some((CycleId::Cycle1, pos2))) =
-                    (pos1_opt, pos2_opt)
-                {
-                    solution.cycle2[pos1] = *v2;
-                    solution.cycle1[pos2] = *v1;
-                } else {
+            Move::InterRouteExchange { v1, v2 } => match (at_x, at_y) {
+                (Some((c1, pos1)), Some((c2, pos2))) if c1 != c2 => {
+                    solution.get_cycle_mut(c1)[pos1] = *v2;
+                    solution.get_cycle_mut(c2)[pos2] = *v1;
+                    [
+                        around(c1, pos1, solution.get_cycle(c1).len()),
+                        around(c2, pos2, solution.get_cycle(c2).len()),
+                    ]
+                }
+                _ => {
                     eprintln!(
                         "Warning: InterRouteExchange apply failed. Nodes {} or {} not found in expected cycles.",
                         v1, v2
                     );
+                    [None, None]
                 }
-            }
-            Move::IntraRouteVertexExchange { v1, v2, cycle } => {
-                if let (Some((c1, pos1)), Some((c2, pos2))) =
-                    (solution.find_node(*v1), solution.find_node(*v2))
-                {
-                    if c1 == *cycle && c2 == *cycle {
-                        let cycle_vec = solution.get_cycle_mut(*cycle);
-                        cycle_vec.swap(pos1, pos2);
-                    } else {
-                        eprintln!(
-                            "Warning: IntraRouteVertexExchange apply failed. Nodes {} or {} not in cycle {:?}.",
-                            v1, v2, cycle
-                        );
-                    }
-                } else {
+            },
+            Move::IntraRouteVertexExchange { v1, v2, cycle } => match (at_x, at_y) {
+                (Some((c1, pos1)), Some((c2, pos2))) if c1 == *cycle && c2 == *cycle => {
+                    let cycle_vec = solution.get_cycle_mut(*cycle);
+                    cycle_vec.swap(pos1, pos2);
+                    let n = cycle_vec.len();
+                    [around(*cycle, pos1, n), around(*cycle, pos2, n)]
+                }
+                (Some(_), Some(_)) => {
+                    eprintln!(
+                        "Warning: IntraRouteVertexExchange apply failed. Nodes {} or {} not in cycle {:?}.",
+                        v1, v2, cycle
+                    );
+                    [None, None]
+                }
+                _ => {
                     eprintln!(
                         "Warning: IntraRouteVertexExchange apply failed. Nodes {} or {} not found.",
                         v1, v2
                     );
+                    [None, None]
                 }
-            }
+            },
             Move::IntraRouteEdgeExchange {
-                a,
+                a: _,
                 b,
                 c,
                 d: _,
                 cycle,
-            } => {
-                if let (Some((cb, pos_b)), Some((cc, pos_c))) =
-                    (solution.find_node(*b), solution.find_node(*c))
-                {
-                    if cb == *cycle && cc == *cycle {
-                        let cycle_vec = solution.get_cycle_mut(*cycle);
-                        let n = cycle_vec.len();
-                        if n < 2 {
-                            return;
-                        }
+            } => match (at_x, at_y) {
+                (Some((cb, pos_b)), Some((cc, pos_c))) if cb == *cycle && cc == *cycle => {
+                    let n = solution.get_cycle(*cycle).len();
+                    if n < 2 {
+                        return [None, None];
+                    }
+
+                    let start = pos_b;
+                    let end = pos_c;
+                    let is_closed = solution.is_closed;
+                    let cycle_vec = solution.get_cycle_mut(*cycle);
 
-                        let mut start = pos_b;
-                        let mut end = pos_c;
-
-                        if start > end {
-                            let mut temp_slice = Vec::with_capacity(n);
-                            temp_slice.extend_from_slice(&cycle_vec[start..]);
-                            temp_slice.extend_from_slice(&cycle_vec[..=end]);
-                            temp_slice.reverse();
-                            let mut temp_iter = temp_slice.into_iter();
-                            for i in start..n {
-                                cycle_vec[i] = temp_iter.next().unwrap();
-                            }
-                            for i in 0..=end {
-                                cycle_vec[i] = temp_iter.next().unwrap();
-                            }
-                        } else {
-                            cycle_vec[start..=end].reverse();
+                    // The reversed run, plus the position on either side of it.
+                    let touched = if start > end && !is_closed {
+                        // Reversing the complement gives the same tour without moving
+                        // the free closing link of an open path.
+                        cycle_vec[end + 1..start].reverse();
+                        (end, start - end + 1)
+                    } else if start > end {
+                        let mut temp_slice = Vec::with_capacity(n);
+                        temp_slice.extend_from_slice(&cycle_vec[start..]);
+                        temp_slice.extend_from_slice(&cycle_vec[..=end]);
+                        temp_slice.reverse();
+                        let mut temp_iter = temp_slice.into_iter();
+                        for slot in cycle_vec[start..].iter_mut() {
+                            *slot = temp_iter.next().unwrap();
+                        }
+                        for slot in cycle_vec[..=end].iter_mut() {
+                            *slot = temp_iter.next().unwrap();
                         }
+                        (start + n - 1, n - start + end + 3)
                     } else {
-                        eprintln!(
-                            "Warning: IntraRouteEdgeExchange apply failed. Nodes {} or {} not in cycle {:?}.",
-                            b, c, cycle
-                        );
-                    }
-                } else {
+                        cycle_vec[start..=end].reverse();
+                        (start + n - 1, end - start + 3)
+                    };
+                    [Some((*cycle, touched.0, touched.1)), None]
+                }
+                (Some(_), Some(_)) => {
+                    eprintln!(
+                        "Warning: IntraRouteEdgeExchange apply failed. Nodes {} or {} not in cycle {:?}.",
+                        b, c, cycle
+                    );
+                    [None, None]
+                }
+                _ => {
                     eprintln!(
                         "Warning: IntraRouteEdgeExchange apply failed. Nodes {} or {} not found.",
                         b, c
                     );
+                    [None, None]
+                }
+            },
+            Move::InterRouteTransfer { v, from, after } => {
+                let to = match from {
+                    CycleId::Cycle1 => CycleId::Cycle2,
+                    CycleId::Cycle2 => CycleId::Cycle1,
+                };
+                match (at_x, at_y) {
+                    (Some((cv, source_pos)), Some((ca, after_pos))) if cv == *from && ca == to => {
+                        solution.get_cycle_mut(*from).remove(source_pos);
+                        solution.get_cycle_mut(to).insert(after_pos + 1, *v);
+                        [
+                            Some((*from, 0, solution.get_cycle(*from).len())),
+                            Some((to, 0, solution.get_cycle(to).len())),
+                        ]
+                    }
+                    _ => {
+                        eprintln!(
+                            "Warning: InterRouteTransfer apply failed. Node {} not in {:?} or {} not in {:?}.",
+                            v, from, after, to
+                        );
+                        [None, None]
+                    }
                 }
             }
         }
     }
 }
+
+/// Cost under `cost_model` of the link between positions `pos` and `pos + 1` (mod `n`) of a
+/// cycle of `n` vertices, joining `u` and `v`. On open paths (`closed == false`) the link from
+/// the last position back to the first is free.
+#[inline]
+pub fn link_cost<C: CostModel + ?Sized>(
+    instance: &TsplibInstance,
+    cost_model: &C,
+    closed: bool,
+    n: usize,
+    pos: usize,
+    u: usize,
+    v: usize,
+) -> Cost {
+    if !closed && pos == n - 1 {
+        Cost::ZERO
+    } else {
+        cost_model.edge_cost(instance, u, v)
+    }
+}
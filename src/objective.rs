@@ -0,0 +1,59 @@
+use crate::weight::{Cost, Weight};
+
+/// What the solvers minimise, as a function of the two cycle lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Objective {
+    /// Total length of both cycles (the lab objective).
+    #[default]
+    SumOfCycles,
+    /// Length of the longer cycle, which favours balanced tours.
+    MaxCycle,
+    /// `sum_weight * (c1 + c2) + max_weight * max(c1, c2)`.
+    Weighted { sum_weight: i32, max_weight: i32 },
+}
+
+impl Objective {
+    /// `sum`, `max` or `weighted:<sum weight>,<max weight>`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "sum" => Some(Objective::SumOfCycles),
+            "max" => Some(Objective::MaxCycle),
+            _ => {
+                let (sum_weight, max_weight) = text.strip_prefix("weighted:")?.split_once(',')?;
+                Some(Objective::Weighted {
+                    sum_weight: sum_weight.trim().parse().ok()?,
+                    max_weight: max_weight.trim().parse().ok()?,
+                })
+            }
+        }
+    }
+
+    /// Objective value for the cycle lengths `(c1, c2)`.
+    pub fn value(self, (cost1, cost2): (Cost, Cost)) -> Cost {
+        match self {
+            Objective::SumOfCycles => cost1 + cost2,
+            Objective::MaxCycle => cost1.max(cost2),
+            Objective::Weighted {
+                sum_weight,
+                max_weight,
+            } => {
+                Cost::from_i32(sum_weight) * (cost1 + cost2)
+                    + Cost::from_i32(max_weight) * cost1.max(cost2)
+            }
+        }
+    }
+
+    /// Change of the objective when the cycle lengths `costs` change by `deltas`.
+    pub fn delta(self, costs: (Cost, Cost), deltas: (Cost, Cost)) -> Cost {
+        match self {
+            Objective::SumOfCycles => deltas.0 + deltas.1,
+            _ => self.value((costs.0 + deltas.0, costs.1 + deltas.1)) - self.value(costs),
+        }
+    }
+
+    /// Whether the objective is the plain sum, for which move deltas do not depend on the
+    /// rest of the solution.
+    pub fn is_separable(self) -> bool {
+        self == Objective::SumOfCycles
+    }
+}
@@ -0,0 +1,124 @@
+use crate::utils::{fnv1a, safe_file_name};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Root of all experiment output.
+pub const OUTPUT_ROOT: &str = "output";
+
+/// File in every algorithm directory holding the algorithm's full name.
+const ALGORITHM_NAME_FILE: &str = "algorithm.txt";
+
+/// Output layout of one experiment: `<root>/<timestamp>/` for run-wide files (summary,
+/// manifest) and `<root>/<timestamp>/<instance>/<algorithm id>/` for the files of one
+/// algorithm on one instance. Every experiment gets a fresh directory, so changing
+/// parameters between runs never overwrites earlier results.
+#[derive(Debug, Clone)]
+pub struct OutputManager {
+    run_dir: PathBuf,
+}
+
+impl OutputManager {
+    /// Creates the directory of a new experiment under `root`, named after the current UTC
+    /// time, with a `-<n>` suffix if an experiment started in the same second.
+    pub fn create(root: &Path) -> io::Result<Self> {
+        fs::create_dir_all(root)?;
+        let timestamp = utc_timestamp(SystemTime::now());
+        let mut attempt = 0;
+        loop {
+            let name = match attempt {
+                0 => timestamp.clone(),
+                n => format!("{}-{}", timestamp, n),
+            };
+            let run_dir = root.join(name);
+            match fs::create_dir(&run_dir) {
+                Ok(()) => return Ok(Self { run_dir }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub fn run_dir(&self) -> &Path {
+        &self.run_dir
+    }
+
+    /// Path of the run-wide file `file_name`.
+    pub fn file(&self, file_name: &str) -> PathBuf {
+        self.run_dir.join(file_name)
+    }
+
+    /// The directory of `algorithm` on `instance`, created with the algorithm's full name
+    /// alongside. Fails if the directory belongs to a different algorithm whose id collides.
+    pub fn algorithm_dir(&self, instance: &str, algorithm: &str) -> io::Result<PathBuf> {
+        let dir = self
+            .run_dir
+            .join(safe_file_name(instance))
+            .join(algorithm_id(algorithm));
+        fs::create_dir_all(&dir)?;
+        let name_file = dir.join(ALGORITHM_NAME_FILE);
+        match fs::read_to_string(&name_file) {
+            Ok(existing) if existing.trim_end() == algorithm => {}
+            Ok(existing) => {
+                return Err(io::Error::other(format!(
+                    "{} is already used by {}",
+                    dir.display(),
+                    existing.trim_end()
+                )));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                fs::write(&name_file, format!("{}\n", algorithm))?
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(dir)
+    }
+
+    /// Path of `file_name` in the directory of `algorithm` on `instance`.
+    pub fn algorithm_file(
+        &self,
+        instance: &str,
+        algorithm: &str,
+        file_name: &str,
+    ) -> io::Result<PathBuf> {
+        Ok(self.algorithm_dir(instance, algorithm)?.join(file_name))
+    }
+}
+
+/// Short directory name of an algorithm: the first word of its name, lowercased, and a hash
+/// of the full name, e.g. `hae-3f09c2a1d44e8b10`. Algorithm names spell out their parameters,
+/// so configurations that differ in any parameter get different ids.
+pub fn algorithm_id(algorithm: &str) -> String {
+    let prefix: String = algorithm
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .find(|word| !word.is_empty())
+        .unwrap_or("algorithm")
+        .to_lowercase();
+    format!("{}-{:016x}", prefix, fnv1a(algorithm.as_bytes()))
+}
+
+/// `time` in UTC as `YYYYMMDD-HHMMSS`.
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
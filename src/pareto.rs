@@ -0,0 +1,107 @@
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// A solution on the cost/balance front.
+#[derive(Debug, Clone)]
+pub struct ParetoPoint {
+    pub solution: Solution,
+    /// Lengths of cycle 1 and cycle 2.
+    pub cycle_costs: (Cost, Cost),
+}
+
+impl ParetoPoint {
+    /// Total length of both cycles.
+    pub fn cost(&self) -> Cost {
+        self.objectives().0
+    }
+
+    /// Length difference between the two cycles.
+    pub fn imbalance(&self) -> Cost {
+        self.objectives().1
+    }
+
+    fn objectives(&self) -> (Cost, Cost) {
+        objectives(self.cycle_costs)
+    }
+}
+
+/// (total cost, imbalance) of the cycle lengths.
+fn objectives((cost1, cost2): (Cost, Cost)) -> (Cost, Cost) {
+    (cost1 + cost2, (cost1 - cost2).abs())
+}
+
+/// Whether `a` is no worse than `b` in both objectives and better in at least one.
+fn dominates(a: (Cost, Cost), b: (Cost, Cost)) -> bool {
+    a.0 <= b.0 && a.1 <= b.1 && a != b
+}
+
+/// Non-dominated solutions with respect to (total cost, cycle-length imbalance), both
+/// minimised. Points with equal objectives are kept once.
+#[derive(Debug, Clone, Default)]
+pub struct ParetoArchive {
+    points: Vec<ParetoPoint>,
+}
+
+impl ParetoArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `solution` unless an archived point dominates or equals it, dropping the points it
+    /// dominates. The solution is only cloned when it enters the archive.
+    pub fn offer(&mut self, solution: &Solution, instance: &TsplibInstance) -> bool {
+        let cycle_costs = solution.cycle_costs(instance);
+        let candidate = objectives(cycle_costs);
+        if self.points.iter().any(|p| {
+            let archived = p.objectives();
+            archived == candidate || dominates(archived, candidate)
+        }) {
+            return false;
+        }
+        self.points
+            .retain(|p| !dominates(candidate, p.objectives()));
+        self.points.push(ParetoPoint {
+            solution: solution.clone(),
+            cycle_costs,
+        });
+        true
+    }
+
+    /// Offers every point of `other`, e.g. to combine the archives of several runs.
+    pub fn merge(&mut self, other: &ParetoArchive, instance: &TsplibInstance) {
+        for point in &other.points {
+            self.offer(&point.solution, instance);
+        }
+    }
+
+    /// The front sorted by increasing cost (and so by decreasing imbalance).
+    pub fn front(&self) -> Vec<&ParetoPoint> {
+        let mut front: Vec<&ParetoPoint> = self.points.iter().collect();
+        front.sort_by_key(|p| (Ordered(p.cost()), Ordered(p.imbalance())));
+        front
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Writes the front as CSV with columns `cost,imbalance,cycle1_cost,cycle2_cost`.
+    pub fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = String::from("cost,imbalance,cycle1_cost,cycle2_cost\n");
+        for point in self.front() {
+            writeln!(
+                out,
+                "{},{},{},{}",
+                point.cost(),
+                point.imbalance(),
+                point.cycle_costs.0,
+                point.cycle_costs.1
+            )
+            .expect("writing to a String cannot fail");
+        }
+        fs::write(path, out)
+    }
+}
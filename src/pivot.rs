@@ -0,0 +1,212 @@
+//! The results pivoted the way the lab reports lay them out: a row per algorithm, a column
+//! per instance and `avg (min–max)` of the final cost in each cell, in Markdown, LaTeX or CSV.
+
+use crate::algorithm::ExperimentStats;
+use crate::weight::Cost;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotFormat {
+    Markdown,
+    /// A booktabs `tabular`, as in `latex/main.tex`.
+    Latex,
+    Csv,
+}
+
+impl PivotFormat {
+    pub const ALL: [PivotFormat; 3] = [Self::Markdown, Self::Latex, Self::Csv];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Latex => "tex",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// One cell: the cost statistics of an algorithm on an instance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    avg: f64,
+    min: Cost,
+    max: Cost,
+}
+
+/// Algorithms by instances, both in order of first appearance in the results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PivotTable {
+    instances: Vec<String>,
+    /// Algorithm name and its cell per instance; `None` where it did not run.
+    rows: Vec<(String, Vec<Option<Cell>>)>,
+}
+
+impl PivotTable {
+    /// The table of `results`, `(instance, stats)` pairs as the experiment collects them.
+    /// Experiments without runs leave their cell empty.
+    pub fn new(results: &[(String, ExperimentStats)]) -> Self {
+        let mut instances: Vec<String> = Vec::new();
+        for (instance_name, _) in results {
+            if !instances.contains(instance_name) {
+                instances.push(instance_name.clone());
+            }
+        }
+        let mut rows: Vec<(String, Vec<Option<Cell>>)> = Vec::new();
+        for (instance_name, stats) in results {
+            let column = instances
+                .iter()
+                .position(|name| name == instance_name)
+                .expect("collected above");
+            let row = match rows
+                .iter()
+                .position(|(name, _)| *name == stats.algorithm_name)
+            {
+                Some(row) => row,
+                None => {
+                    rows.push((stats.algorithm_name.clone(), vec![None; instances.len()]));
+                    rows.len() - 1
+                }
+            };
+            if stats.num_runs > 0 {
+                rows[row].1[column] = Some(Cell {
+                    avg: stats.avg_cost,
+                    min: stats.min_cost,
+                    max: stats.max_cost,
+                });
+            }
+        }
+        Self { instances, rows }
+    }
+
+    /// The table in `format`. In Markdown and LaTeX the lowest average of each instance is in
+    /// bold.
+    pub fn render(&self, format: PivotFormat) -> String {
+        match format {
+            PivotFormat::Markdown => self.render_markdown(),
+            PivotFormat::Latex => self.render_latex(),
+            PivotFormat::Csv => self.render_csv(),
+        }
+    }
+
+    /// Writes the table in every format, to `<stem>.md`, `<stem>.tex` and `<stem>.csv`.
+    pub fn write_all(&self, stem: &Path) -> io::Result<()> {
+        for format in PivotFormat::ALL {
+            fs::write(stem.with_extension(format.extension()), self.render(format))?;
+        }
+        Ok(())
+    }
+
+    /// Whether `cell` has the lowest average of column `column`.
+    fn is_best(&self, column: usize, cell: &Cell) -> bool {
+        self.rows
+            .iter()
+            .filter_map(|(_, cells)| cells[column])
+            .all(|other| cell.avg <= other.avg)
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = String::from("| Algorithm |");
+        for instance in &self.instances {
+            write!(out, " {} |", instance.replace('|', "\\|")).expect("writing to a String");
+        }
+        out.push_str("\n|---|");
+        out.push_str(&"---|".repeat(self.instances.len()));
+        out.push('\n');
+        for (algorithm, cells) in &self.rows {
+            write!(out, "| {} |", algorithm.replace('|', "\\|")).expect("writing to a String");
+            for (column, cell) in cells.iter().enumerate() {
+                match cell {
+                    Some(cell) if self.is_best(column, cell) => {
+                        write!(out, " **{}** |", cell_text(cell, "–"))
+                    }
+                    Some(cell) => write!(out, " {} |", cell_text(cell, "–")),
+                    None => write!(out, " – |"),
+                }
+                .expect("writing to a String");
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn render_latex(&self) -> String {
+        let mut out = format!(
+            "\\begin{{tabular}}{{@{{}}l{}@{{}}}}\n\\toprule\n\\textbf{{Algorithm}}",
+            "c".repeat(self.instances.len())
+        );
+        for instance in &self.instances {
+            write!(out, " & \\textbf{{{}}}", latex_escape(instance)).expect("writing to a String");
+        }
+        out.push_str(" \\\\\n\\midrule\n");
+        for (algorithm, cells) in &self.rows {
+            out.push_str(&latex_escape(algorithm));
+            for (column, cell) in cells.iter().enumerate() {
+                match cell {
+                    Some(cell) if self.is_best(column, cell) => {
+                        write!(out, " & \\textbf{{{}}}", cell_text(cell, "--"))
+                    }
+                    Some(cell) => write!(out, " & {}", cell_text(cell, "--")),
+                    None => write!(out, " & --"),
+                }
+                .expect("writing to a String");
+            }
+            out.push_str(" \\\\\n");
+        }
+        out.push_str("\\bottomrule\n\\end{tabular}\n");
+        out
+    }
+
+    /// Cells are quoted strings, like the algorithm names of `summary.csv`; the unpivoted
+    /// numbers are in that file.
+    fn render_csv(&self) -> String {
+        let mut out = String::from("algorithm");
+        for instance in &self.instances {
+            write!(out, ",{}", csv_quote(instance)).expect("writing to a String");
+        }
+        out.push('\n');
+        for (algorithm, cells) in &self.rows {
+            out.push_str(&csv_quote(algorithm));
+            for cell in cells {
+                match cell {
+                    Some(cell) => write!(out, ",{}", csv_quote(&cell_text(cell, "–"))),
+                    None => write!(out, ","),
+                }
+                .expect("writing to a String");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// `avg (min<dash>max)`, the average with two decimals. The precision also rounds `min` and
+/// `max` under `float-distances` and is ignored by the integer costs.
+fn cell_text(cell: &Cell, dash: &str) -> String {
+    format!("{:.2} ({:.2}{}{:.2})", cell.avg, cell.min, dash, cell.max)
+}
+
+fn csv_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// `text` with the characters LaTeX treats specially escaped, e.g. the `%` of
+/// `LNS (Destroy-Repair 20%)`.
+fn latex_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '%' | '&' | '#' | '$' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\\' => out.push_str("\\textbackslash{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
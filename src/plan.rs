@@ -0,0 +1,117 @@
+use crate::time_limits::TimeLimits;
+use crate::tsplib::TsplibInstance;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What an experiment would run, printed by `--dry-run` instead of running it.
+pub struct ExperimentPlan<'a> {
+    pub instances: &'a HashMap<String, Arc<TsplibInstance>>,
+    /// Instances that failed to load.
+    pub failed_instances: &'a [String],
+    pub pinned_time_limits: &'a TimeLimits,
+    /// Limits of the previous experiment, to estimate the MSLS calibration of the instances
+    /// without a pinned limit.
+    pub previous_time_limits: Option<&'a TimeLimits>,
+    pub msls_name: &'a str,
+    /// The algorithms run with the MSLS-derived time limit, in order.
+    pub timed_algorithms: &'a [String],
+    pub num_runs: usize,
+    /// Runs on top of the `num_runs` of every algorithm: HAE population telemetry and the
+    /// runs collecting Pareto fronts.
+    pub extra_runs: usize,
+    /// Whether the timed algorithms stop after a number of evaluations instead of a time.
+    pub stop_by_evaluations: bool,
+}
+
+impl ExperimentPlan<'_> {
+    /// Prints the plan and returns the number of problems found: instances that failed to
+    /// load and pinned time limits of instances the experiment does not run.
+    pub fn print(&self) -> usize {
+        println!("\nExperiment plan (dry run, nothing is executed):");
+        println!("  Runs per algorithm: {}", self.num_runs);
+        println!("  Algorithms:");
+        println!(
+            "    {} (calibrates the time limit of instances without a pinned one)",
+            self.msls_name
+        );
+        for algorithm in self.timed_algorithms {
+            println!("    {}", algorithm);
+        }
+
+        let timed_runs = (self.timed_algorithms.len() * self.num_runs + self.extra_runs) as u32;
+        let mut total = Duration::ZERO;
+        let mut unestimated = 0;
+        let mut names: Vec<&String> = self.instances.keys().collect();
+        names.sort();
+        println!("  Instances:");
+        for name in names {
+            let size = self.instances[name].size();
+            let (limit, source) = match self.pinned_time_limits.get(name) {
+                Some(&limit) => (Some(limit), "pinned, MSLS skipped"),
+                None => (
+                    self.previous_time_limits
+                        .and_then(|limits| limits.get(name))
+                        .copied(),
+                    "from MSLS, estimated from the previous run",
+                ),
+            };
+            match limit {
+                Some(limit) => {
+                    // MSLS runs its iterations in parallel, so its summed iteration time, the
+                    // time limit, bounds its wall time from above.
+                    let msls = if self.pinned_time_limits.contains_key(name) {
+                        Duration::ZERO
+                    } else {
+                        limit * self.num_runs as u32
+                    };
+                    let estimate = msls + limit * timed_runs;
+                    if !self.stop_by_evaluations {
+                        total += estimate;
+                    }
+                    println!(
+                        "    {} ({} vertices): time limit {:?} ({}), at most {:?}",
+                        name, size, limit, source, estimate
+                    );
+                }
+                None => {
+                    unestimated += 1;
+                    println!(
+                        "    {} ({} vertices): time limit from MSLS, not yet known",
+                        name, size
+                    );
+                }
+            }
+        }
+
+        let mut problems = 0;
+        for name in self.failed_instances {
+            problems += 1;
+            println!("  [ERROR] Instance {} could not be loaded", name);
+        }
+        for name in self.pinned_time_limits.keys() {
+            if !self.instances.contains_key(name) {
+                problems += 1;
+                println!(
+                    "  [ERROR] Time limit pinned for {}, which the experiment does not run",
+                    name
+                );
+            }
+        }
+
+        if self.stop_by_evaluations {
+            println!(
+                "  Estimated runtime: unknown, the timed algorithms stop after a number of \
+                 evaluations"
+            );
+        } else if unestimated > 0 {
+            println!(
+                "  Estimated runtime: at most {:?}, plus {} instance(s) without a known time limit",
+                total, unestimated
+            );
+        } else {
+            println!("  Estimated runtime: at most {:?}", total);
+        }
+        problems
+    }
+}
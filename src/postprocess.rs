@@ -0,0 +1,101 @@
+//! Passes run on a finished solution, outside any search.
+
+use crate::moves::inter_route::{evaluate_inter_route_exchange, evaluate_inter_route_transfer};
+use crate::moves::types::{CycleId, EvaluatedMove};
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Weight};
+use std::cmp::Ordering;
+
+/// What [`rebalance`] did.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebalanceReport {
+    pub moves: usize,
+    /// Lengths of cycle 1 and cycle 2 before the pass.
+    pub cycle_costs_before: (Cost, Cost),
+    pub cycle_costs_after: (Cost, Cost),
+}
+
+/// Evens out the cycle lengths of `solution` for the min-max objective. Each step applies
+/// the inter-route move that shortens the longer cycle at the smallest total-length increase
+/// per unit of length difference removed: vertex transfers from the longer cycle into the
+/// shorter one, which keep cycle 1 within `size_slack` of `ceil(n/2)` vertices, and vertex
+/// exchanges between the cycles. Steps stop when no move lowers the longer cycle. On closed
+/// cycles a 2-opt* reconnection, one edge cut in each cycle, would merge the two, so it is
+/// not among the moves. Pinned vertices stay in their cycle.
+pub fn rebalance(
+    solution: &mut Solution,
+    instance: &TsplibInstance,
+    size_slack: usize,
+) -> RebalanceReport {
+    let cycle_costs_before = solution.cycle_costs(instance);
+    let mut cycle_costs = cycle_costs_before;
+    let mut moves = 0;
+    while let Some(best) = best_rebalancing_move(solution, instance, cycle_costs, size_slack) {
+        best.move_type.apply(solution);
+        cycle_costs.0 += best.cycle_deltas.0;
+        cycle_costs.1 += best.cycle_deltas.1;
+        moves += 1;
+    }
+    RebalanceReport {
+        moves,
+        cycle_costs_before,
+        cycle_costs_after: solution.cycle_costs(instance),
+    }
+}
+
+/// The move of [`rebalance`] for a solution with cycle lengths `cycle_costs`, if any lowers
+/// the longer cycle.
+fn best_rebalancing_move(
+    solution: &Solution,
+    instance: &TsplibInstance,
+    cycle_costs: (Cost, Cost),
+    size_slack: usize,
+) -> Option<EvaluatedMove> {
+    let longer = if cycle_costs.0.total_order(&cycle_costs.1) == Ordering::Less {
+        CycleId::Cycle2
+    } else {
+        CycleId::Cycle1
+    };
+    let (n1, n2) = (solution.cycle1.len(), solution.cycle2.len());
+    let balanced = (n1 + n2).div_ceil(2);
+    let len1_after_transfer = match longer {
+        CycleId::Cycle1 => n1.checked_sub(1),
+        CycleId::Cycle2 => Some(n1 + 1),
+    };
+    let transfers_allowed =
+        len1_after_transfer.is_some_and(|len1| len1.abs_diff(balanced) <= size_slack);
+    let (n_longer, n_shorter) = match longer {
+        CycleId::Cycle1 => (n1, n2),
+        CycleId::Cycle2 => (n2, n1),
+    };
+
+    let transfers = (0..n_longer)
+        .filter(|_| transfers_allowed)
+        .flat_map(|pos| (0..n_shorter).map(move |after_pos| (pos, after_pos)))
+        .filter_map(|(pos, after_pos)| {
+            evaluate_inter_route_transfer(solution, instance, longer, pos, after_pos)
+        });
+    let exchanges = (0..n1)
+        .flat_map(|pos1| (0..n2).map(move |pos2| (pos1, pos2)))
+        .filter_map(|(pos1, pos2)| evaluate_inter_route_exchange(solution, instance, pos1, pos2));
+
+    let max_before = cycle_costs.0.max(cycle_costs.1);
+    let gap_before = (cycle_costs.0 - cycle_costs.1).abs();
+    transfers
+        .chain(exchanges)
+        .filter_map(|m| {
+            let after = (
+                cycle_costs.0 + m.cycle_deltas.0,
+                cycle_costs.1 + m.cycle_deltas.1,
+            );
+            if !(after.0.max(after.1) - max_before).is_improvement() {
+                return None;
+            }
+            let gap_removed = (gap_before - (after.0 - after.1).abs()).to_f64();
+            // Price of the balance; moves that also shorten the total come first.
+            let price = m.delta.to_f64() / gap_removed.max(f64::MIN_POSITIVE);
+            Some((price, m))
+        })
+        .min_by(|(a, ma), (b, mb)| a.total_cmp(b).then_with(|| ma.steepest_order(mb)))
+        .map(|(_, m)| m)
+}
@@ -0,0 +1,129 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+/// Marks a status message forwarded from an algorithm's inner local search; see [`nested`].
+pub const NESTED_PREFIX: &str = "> ";
+
+/// `status` of an inner algorithm (e.g. the local search of ILS), forwarded through the outer
+/// algorithm's progress callback. The experiment shows it on its own transient line instead
+/// of the outer algorithm's iteration line.
+pub fn nested(status: &str) -> String {
+    format!("{}{}", NESTED_PREFIX, status)
+}
+
+/// Progress display of one experiment: a bar of the runs, below it a line with the running
+/// algorithm's iteration count and latest status, and below that the status of its inner
+/// local search. The two lower lines are cleared when the experiment ends.
+pub struct ExperimentProgress {
+    runs: ProgressBar,
+    iterations: ProgressBar,
+    inner: ProgressBar,
+}
+
+impl ExperimentProgress {
+    pub fn new(algorithm_name: &str, num_runs: usize) -> Self {
+        let multi = MultiProgress::new();
+        let runs = multi.add(ProgressBar::new(num_runs as u64));
+        runs.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}",
+                )
+                .unwrap()
+                .progress_chars("# >-"),
+        );
+        runs.set_prefix(format!("Running {}", algorithm_name));
+        let iterations = multi.add(ProgressBar::no_length());
+        let inner = multi.add(ProgressBar::no_length());
+        inner.set_style(
+            ProgressStyle::default_spinner()
+                .template("    {msg}")
+                .unwrap(),
+        );
+        Self {
+            runs,
+            iterations,
+            inner,
+        }
+    }
+
+    /// Resets the lower lines for run `run_index` (0-based).
+    pub fn start_run(&self, run_index: usize) {
+        self.runs.set_message(format!(
+            "Run {}/{}",
+            run_index + 1,
+            self.runs.length().unwrap_or(0)
+        ));
+        self.iterations.reset();
+        self.iterations.set_length(0);
+        self.set_iteration_style(false);
+        self.iterations.set_message("Starting...");
+        self.inner.set_message("");
+    }
+
+    /// Shows a progress message of the running algorithm. Nested messages go to the inner
+    /// line; the others to the iteration line, whose count follows their `[Iter n]`,
+    /// `[Iter: n]` or `[MSLS Iter i/n]` tag.
+    pub fn report(&self, message: String) {
+        if message.starts_with(NESTED_PREFIX) {
+            let mut status = message.as_str();
+            while let Some(rest) = status.strip_prefix(NESTED_PREFIX) {
+                status = rest;
+            }
+            self.inner.set_message(status.to_string());
+            return;
+        }
+        if let Some((iteration, total, status)) = parse_iteration_tag(&message) {
+            if let Some(total) = total
+                && self.iterations.length() != Some(total)
+            {
+                self.iterations.set_length(total);
+                self.set_iteration_style(true);
+            }
+            self.iterations.set_position(iteration);
+            self.iterations.set_message(status.to_string());
+        } else {
+            self.iterations.set_message(message);
+        }
+    }
+
+    pub fn finish_run(&self) {
+        self.runs.inc(1);
+    }
+
+    pub fn finish(&self) {
+        self.inner.finish_and_clear();
+        self.iterations.finish_and_clear();
+        self.runs.finish_with_message("Finished all runs.");
+    }
+
+    fn set_iteration_style(&self, bounded: bool) {
+        let template = if bounded {
+            "  [{bar:40.yellow/blue}] {pos}/{len} {msg}"
+        } else {
+            "  {spinner:.yellow} iteration {pos} {msg}"
+        };
+        self.iterations.set_style(
+            ProgressStyle::default_bar()
+                .template(template)
+                .unwrap()
+                .progress_chars("# >-"),
+        );
+    }
+}
+
+/// The iteration, the iteration count if the tag gives one, and the rest of a message that
+/// starts with `[Iter n]`, `[Iter: n]` or `[MSLS Iter i/n]`.
+fn parse_iteration_tag(message: &str) -> Option<(u64, Option<u64>, &str)> {
+    let rest = message.strip_prefix('[')?;
+    let (tag, status) = rest.split_once(']')?;
+    let counter = tag
+        .strip_prefix("Iter")
+        .or_else(|| tag.strip_prefix("MSLS Iter"))?
+        .trim_start_matches(':')
+        .trim();
+    let (iteration, total) = match counter.split_once('/') {
+        Some((iteration, total)) => (iteration.parse().ok()?, Some(total.parse().ok()?)),
+        None => (counter.parse().ok()?, None),
+    };
+    Some((iteration, total, status.trim_start()))
+}
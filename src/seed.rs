@@ -0,0 +1,68 @@
+//! Seeds for nested randomness, split from one parent seed by SplitMix64 (Steele, Lea and
+//! Flood, "Fast splittable pseudorandom number generators", 2014). A component that hands
+//! randomness to parts running in any order (parallel children, per-vertex noise, one
+//! perturbation per iteration) gives each part the stream of its index instead of drawing
+//! the parts' seeds one after another from a shared generator. A part's numbers then depend
+//! only on the parent seed and its index, not on the order the parts run in or on how many
+//! numbers the others drew, so a fixed master seed fixes the result.
+//!
+//! The root of every stream is the master seed of `--seed`, or one drawn from the OS when it
+//! is not given, which the manifest records. Run `r` of an algorithm on an instance draws
+//! from the child for the instance, then the algorithm, then `r`, so rerunning with the
+//! recorded seed repeats every run that does not stop on a time limit.
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// Weyl sequence increment of SplitMix64, the odd integer closest to 2^64 / golden ratio.
+const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// SplitMix64's output function: a bijective mix of all 64 bits.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A seed that derives independent child seeds by index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeedStream {
+    seed: u64,
+}
+
+impl SeedStream {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// The master stream of a command: the one of `seed`, or of a seed drawn from the
+    /// OS-seeded thread generator when none was given.
+    pub fn master(seed: Option<u64>) -> Self {
+        seed.map_or_else(|| Self::from_rng(&mut rand::rng()), Self::new)
+    }
+
+    /// A stream seeded with one draw from `rng`, which is all the parent spends however
+    /// many children the stream has.
+    pub fn from_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(rng.random())
+    }
+
+    pub fn seed(self) -> u64 {
+        self.seed
+    }
+
+    /// The stream of child `index`: the `index + 1`-th output of SplitMix64 started at this
+    /// seed. Children of children are streams of their own, so the parts of a part split
+    /// the same way.
+    pub fn child(self, index: u64) -> Self {
+        let state = self
+            .seed
+            .wrapping_add(GAMMA.wrapping_mul(index.wrapping_add(1)));
+        Self::new(mix(state))
+    }
+
+    /// A generator seeded with this stream.
+    pub fn rng(self) -> SmallRng {
+        SmallRng::seed_from_u64(self.seed)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::weight::{Cost, Ordered};
+
+/// Significance level below which two cost samples count as different.
+pub const ALPHA: f64 = 0.05;
+
+/// Two-sided p-value of the Mann-Whitney U test that the costs `a` and `b` come from the same
+/// distribution, using the normal approximation with tie and continuity corrections. `None` if
+/// either sample is empty.
+pub fn mann_whitney_p(a: &[Cost], b: &[Cost]) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+    let mut pooled: Vec<(Cost, bool)> = a
+        .iter()
+        .map(|&cost| (cost, true))
+        .chain(b.iter().map(|&cost| (cost, false)))
+        .collect();
+    pooled.sort_unstable_by_key(|&(cost, _)| Ordered(cost));
+
+    // Tied costs share the average of their ranks.
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < pooled.len() {
+        let end = start
+            + pooled[start..]
+                .iter()
+                .take_while(|&&(cost, _)| cost == pooled[start].0)
+                .count();
+        let average_rank = (start + end + 1) as f64 / 2.0;
+        let from_a = pooled[start..end].iter().filter(|&&(_, in_a)| in_a).count();
+        rank_sum_a += average_rank * from_a as f64;
+        let ties = (end - start) as f64;
+        tie_term += ties.powi(3) - ties;
+        start = end;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        // Every cost is the same.
+        return Some(1.0);
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    Some(erfc(z / std::f64::consts::SQRT_2).min(1.0))
+}
+
+/// Whether the costs `a` and `b` do not differ significantly at [`ALPHA`]. Samples too small
+/// to test count as indistinguishable.
+pub fn indistinguishable(a: &[Cost], b: &[Cost]) -> bool {
+    mann_whitney_p(a, b).is_none_or(|p| p >= ALPHA)
+}
+
+/// Complementary error function, with a fractional error below 1.2e-7 (Numerical Recipes'
+/// Chebyshev fit).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * poly.exp();
+    if x >= 0.0 { result } else { 2.0 - result }
+}
@@ -0,0 +1,76 @@
+/// Alignment of a [`TextTable`] column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A markdown-style console table whose columns are as wide as their widest cell, so long
+/// algorithm names keep the rows aligned.
+#[derive(Debug, Clone)]
+pub struct TextTable {
+    header: Vec<(String, Align)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl TextTable {
+    pub fn new(columns: &[(&str, Align)]) -> Self {
+        Self {
+            header: columns
+                .iter()
+                .map(|&(title, align)| (title.to_string(), align))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Appends a row, one cell per column.
+    pub fn push(&mut self, cells: Vec<String>) {
+        assert_eq!(cells.len(), self.header.len(), "One cell per column");
+        self.rows.push(cells);
+    }
+
+    /// The header, the separator and then one line per row, in the order pushed.
+    pub fn lines(&self) -> Vec<String> {
+        let widths: Vec<usize> = (0..self.header.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .map(|row| row[column].chars().count())
+                    .chain([self.header[column].0.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |cells: &mut dyn Iterator<Item = (&str, Align)>| {
+            let mut out = String::from("|");
+            for ((cell, align), &width) in cells.zip(&widths) {
+                out += &match align {
+                    Align::Left => format!(" {:<width$} |", cell),
+                    Align::Right => format!(" {:>width$} |", cell),
+                };
+            }
+            out
+        };
+        let mut lines = vec![
+            line(
+                &mut self
+                    .header
+                    .iter()
+                    .map(|(title, _)| (title.as_str(), Align::Left)),
+            ),
+            widths.iter().fold(String::from("|"), |out, &width| {
+                out + &"-".repeat(width + 2) + "|"
+            }),
+        ];
+        for row in &self.rows {
+            lines.push(line(
+                &mut row
+                    .iter()
+                    .zip(&self.header)
+                    .map(|(cell, &(_, align))| (cell.as_str(), align)),
+            ));
+        }
+        lines
+    }
+}
@@ -0,0 +1,52 @@
+use crate::flat_json::{Field, FlatObject, plain_string};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Where the experiment saves the time limits it ran with, for `--reuse-time-limits`.
+pub const TIME_LIMITS_FILE: &str = "output/time_limits.json";
+
+/// Time limit of the timed algorithms (ILS, LNS, HAE) per instance name.
+pub type TimeLimits = BTreeMap<String, Duration>;
+
+#[derive(Debug, Error)]
+pub enum TimeLimitsError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid time limits: {0}")]
+    Parse(String),
+}
+
+/// Reads time limits from a one-line JSON object mapping instance names to milliseconds,
+/// e.g. `{"kroa200":1234,"krob200":1250}`.
+pub fn read_time_limits(path: &Path) -> Result<TimeLimits, TimeLimitsError> {
+    let text = fs::read_to_string(path)?;
+    let fields = FlatObject::parse(&text).map_err(TimeLimitsError::Parse)?;
+    fields
+        .iter()
+        .map(|(name, value)| match value {
+            Field::Int(ms) => u64::try_from(*ms)
+                .map(|ms| (name.clone(), Duration::from_millis(ms)))
+                .map_err(|_| TimeLimitsError::Parse(format!("negative limit for {}", name))),
+            _ => Err(TimeLimitsError::Parse(format!(
+                "the limit for {} is not a number of milliseconds",
+                name
+            ))),
+        })
+        .collect()
+}
+
+/// Writes `limits` in the format [`read_time_limits`] reads, creating the parent directory.
+pub fn write_time_limits(limits: &TimeLimits, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let fields: Vec<String> = limits
+        .iter()
+        .map(|(name, limit)| format!("\"{}\":{}", plain_string(name), limit.as_millis()))
+        .collect();
+    fs::write(path, format!("{{{}}}\n", fields.join(",")))
+}
@@ -1,11 +1,23 @@
+use flate2::read::GzDecoder;
 use lazy_static::lazy_static;
 use regex::Regex;
+use rustc_hash::{FxHashSet, FxHasher};
 use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 use thiserror::Error;
 
+pub use crate::candidates::CandidateSource;
+use crate::candidates::{delaunay_neighbors, quadrant_neighbors};
+use crate::counters::{count_distance_lookup, count_solution_copy};
+pub use crate::distance::DistanceLayout;
+use crate::distance::{DistanceMatrix, DistanceOverflow, euclidean, euclidean_row};
+use crate::flat_json::FlatObject;
 pub use crate::moves::types::CycleId;
+use crate::moves::types::Move;
+pub use crate::objective::Objective;
+use crate::weight::{Cost, Ordered, Weight};
 
 #[derive(Debug, Error)]
 pub enum TsplibError {
@@ -15,6 +27,78 @@ pub enum TsplibError {
     Parse(String),
     #[error("Invalid format: {0}")]
     Format(String),
+    #[error("Distance storage error: {0}")]
+    Distance(#[from] DistanceOverflow),
+}
+
+/// Why [`Solution::diff`] found no moves between two solutions.
+#[derive(Debug, Error, PartialEq)]
+pub enum DiffError {
+    #[error("Cannot diff a closed and an open solution")]
+    ShapeMismatch,
+    #[error("Vertex {0} is visited by only one of the solutions")]
+    VertexSetMismatch(usize),
+    #[error("Cannot transfer a vertex into the empty {0:?}")]
+    EmptyCycle(CycleId),
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SolutionError {
+    #[error("Vertex {0} is out of range")]
+    VertexOutOfRange(usize),
+    #[error("Vertex {0} appears more than once")]
+    DuplicateVertex(usize),
+    #[error("Vertex {0} is not in any cycle")]
+    MissingVertex(usize),
+    #[error("Single-cycle instance, but cycle 2 holds {0} vertices")]
+    SecondCycleUsed(usize),
+    #[error("Vertex {vertex} is pinned to {cycle:?}")]
+    PinViolation { vertex: usize, cycle: CycleId },
+    #[error("Cycle sizes {cycle1}/{cycle2} exceed the allowed slack of {slack}")]
+    SizeImbalance {
+        cycle1: usize,
+        cycle2: usize,
+        slack: usize,
+    },
+}
+
+/// Optimal tour lengths of classic TSPLIB instances, keyed by lower-case name.
+const KNOWN_OPTIMA: &[(&str, i32)] = &[
+    ("berlin52", 7542),
+    ("eil51", 426),
+    ("eil76", 538),
+    ("eil101", 629),
+    ("st70", 675),
+    ("pr76", 108159),
+    ("rat99", 1211),
+    ("kroa100", 21282),
+    ("krob100", 22141),
+    ("kroc100", 20749),
+    ("krod100", 21294),
+    ("kroe100", 22068),
+    ("lin105", 14379),
+    ("ch130", 6110),
+    ("ch150", 6528),
+    ("kroa150", 26524),
+    ("krob150", 26130),
+    ("kroa200", 29368),
+    ("krob200", 29437),
+];
+
+/// Best known single-tour length for the TSPLIB instance `name` (case-insensitive).
+pub fn known_optimum(name: &str) -> Option<Cost> {
+    let name = name.to_lowercase();
+    KNOWN_OPTIMA
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|&(_, optimum)| Cost::from_i32(optimum))
+}
+
+/// Whether `path` names a gzip-compressed file such as `kroa200.tsp.gz`, which TSPLIB mirrors
+/// distribute.
+pub fn is_gzipped(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gz"))
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -26,26 +110,50 @@ pub enum EdgeWeightType {
     Att,
 }
 
+/// Name, edge weight type and node coordinates read from a TSPLIB file.
+pub(crate) type ParsedTsplib = (String, EdgeWeightType, Vec<(f64, f64)>);
+
 #[derive(Debug, Clone)]
 pub struct TsplibInstance {
     pub name: String,
     pub dimension: usize,
     pub edge_weight_type: EdgeWeightType,
     pub coordinates: Vec<(f64, f64)>,
-    distances: Vec<Vec<i32>>,
+    distances: DistanceMatrix,
     nearest_neighbors: Vec<Vec<usize>>,
+    candidate_source: Option<CandidateSource>,
+    /// Row-major bitset: bit `j` of row `i` is set iff `j` is in the k-NN list of `i`.
+    neighbor_bits: Vec<u64>,
+    /// Per-vertex prizes; when set, vertices are optional and a solution pays its length
+    /// minus the prizes it collects.
+    prizes: Option<Vec<Cost>>,
+    /// Cycle each vertex is pinned to, if any; empty when nothing is pinned.
+    pins: Vec<Option<CycleId>>,
+    /// Classic TSP mode: all vertices form one tour in cycle 1 and cycle 2 stays empty.
+    single_cycle: bool,
 }
 
 impl TsplibInstance {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TsplibError> {
+        let (name, edge_weight_type, coordinates) = Self::read_coordinates(path)?;
+        Ok(Self::from_coordinates(name, edge_weight_type, coordinates))
+    }
+
+    /// The name, edge weight type and node coordinates of a TSPLIB file, without building
+    /// the distance matrix. A `.gz` file is decompressed while it is read.
+    pub(crate) fn read_coordinates<P: AsRef<Path>>(path: P) -> Result<ParsedTsplib, TsplibError> {
         lazy_static! {
             static ref KEYWORD_RE: Regex = Regex::new(r"^([A-Za-z_]+)\s*:\s*(.+)$").unwrap();
             static ref NODE_COORD_RE: Regex = Regex::new(r"^\s*(\d+)\s+(\S+)\s+(\S+)\s*$").unwrap();
         }
 
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
+        let file = File::open(&path)?;
+        let reader: Box<dyn BufRead> = if is_gzipped(path.as_ref()) {
+            Box::new(BufReader::new(GzDecoder::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        let lines = reader.lines();
 
         let mut name = String::new();
         let mut dimension = 0;
@@ -53,7 +161,7 @@ impl TsplibInstance {
         let mut coordinates = Vec::new();
         let mut in_node_coord_section = false;
 
-        while let Some(line) = lines.next() {
+        for line in lines {
             let line = line?;
             let line = line.trim();
 
@@ -124,45 +232,134 @@ impl TsplibInstance {
             )));
         }
 
+        Ok((name, edge_weight_type, coordinates))
+    }
+
+    /// Builds an instance directly from node coordinates, e.g. for generated test instances.
+    pub fn from_coordinates(
+        name: String,
+        edge_weight_type: EdgeWeightType,
+        coordinates: Vec<(f64, f64)>,
+    ) -> Self {
+        let dimension = coordinates.len();
         let mut instance = Self {
             name,
             dimension,
             edge_weight_type,
             coordinates,
-            distances: vec![vec![0; dimension]; dimension],
+            distances: DistanceMatrix::Full {
+                n: 0,
+                data: Vec::new(),
+            },
             nearest_neighbors: vec![Vec::new(); dimension],
+            candidate_source: None,
+            neighbor_bits: Vec::new(),
+            prizes: None,
+            pins: Vec::new(),
+            single_cycle: false,
         };
         instance.calculate_distance_matrix();
-        Ok(instance)
+        instance
+    }
+
+    /// An instance with a full distance matrix (row-major, `n x n`) and candidate lists
+    /// computed earlier, e.g. read back from the instance cache. `candidate_lists` must be
+    /// what [`Self::precompute_candidates`] builds from `candidate_source`.
+    pub(crate) fn from_precomputed(
+        name: String,
+        edge_weight_type: EdgeWeightType,
+        coordinates: Vec<(f64, f64)>,
+        distances: Vec<Cost>,
+        candidate_source: CandidateSource,
+        candidate_lists: Vec<Vec<usize>>,
+    ) -> Self {
+        let dimension = coordinates.len();
+        assert_eq!(distances.len(), dimension * dimension);
+        assert_eq!(candidate_lists.len(), dimension);
+        let mut instance = Self {
+            name,
+            dimension,
+            edge_weight_type,
+            coordinates,
+            distances: DistanceMatrix::Full {
+                n: dimension,
+                data: distances,
+            },
+            nearest_neighbors: candidate_lists,
+            candidate_source: Some(candidate_source),
+            neighbor_bits: Vec::new(),
+            prizes: None,
+            pins: Vec::new(),
+            single_cycle: false,
+        };
+        instance.build_neighbor_bits();
+        instance
+    }
+
+    /// The distance matrix row by row, if it is stored in the full layout.
+    pub(crate) fn full_distances(&self) -> Option<&[Cost]> {
+        match &self.distances {
+            DistanceMatrix::Full { data, .. } => Some(data),
+            _ => None,
+        }
+    }
+
+    /// The candidate list of every vertex, empty until candidates are precomputed.
+    pub(crate) fn candidate_lists(&self) -> &[Vec<usize>] {
+        &self.nearest_neighbors
     }
 
     fn calculate_distance_matrix(&mut self) {
-        for i in 0..self.dimension {
-            for j in 0..self.dimension {
-                self.distances[i][j] = self.calculate_distance(i, j);
+        self.distances = self
+            .build_distance_matrix(DistanceLayout::Full)
+            .expect("The full layout cannot overflow");
+    }
+
+    fn build_distance_matrix(
+        &self,
+        layout: DistanceLayout,
+    ) -> Result<DistanceMatrix, DistanceOverflow> {
+        if self.edge_weight_type == EdgeWeightType::Euc2D {
+            let (xs, ys): (Vec<f64>, Vec<f64>) = self.coordinates.iter().copied().unzip();
+            return DistanceMatrix::build(layout, self.dimension, |i, row| {
+                euclidean_row(xs[i], ys[i], &xs, &ys, row);
+                row[i] = Cost::ZERO;
+            });
+        }
+
+        DistanceMatrix::build(layout, self.dimension, |i, row| {
+            for (j, slot) in row.iter_mut().enumerate() {
+                *slot = self.calculate_distance(i, j);
             }
+        })
+    }
+
+    /// Re-stores the distance matrix in `layout`. Fails without modifying the instance if
+    /// some distance does not fit the element type of the layout, e.g. a distance over
+    /// `u16::MAX` for the `u16` layouts.
+    pub fn set_distance_layout(&mut self, layout: DistanceLayout) -> Result<(), TsplibError> {
+        if layout != self.distances.layout() {
+            self.distances = self.build_distance_matrix(layout)?;
         }
+        Ok(())
     }
 
-    pub fn distance(&self, i: usize, j: usize) -> i32 {
-        self.distances[i][j]
+    #[inline]
+    pub fn distance(&self, i: usize, j: usize) -> Cost {
+        count_distance_lookup();
+        self.distances.get(i, j)
     }
 
-    fn calculate_distance(&self, i: usize, j: usize) -> i32 {
+    fn calculate_distance(&self, i: usize, j: usize) -> Cost {
         if i == j {
-            return 0;
+            return Cost::ZERO;
         }
 
         let (x1, y1) = self.coordinates[i];
         let (x2, y2) = self.coordinates[j];
 
         match self.edge_weight_type {
-            EdgeWeightType::Euc2D => {
-                let dx = x2 - x1;
-                let dy = y2 - y1;
-                let dist = (dx * dx + dy * dy).sqrt();
-                dist.round() as i32
-            }
+            EdgeWeightType::Euc2D => euclidean(x1, y1, x2, y2),
             _ => panic!("Only EUC_2D is supported for this task"),
         }
     }
@@ -171,32 +368,173 @@ impl TsplibInstance {
         self.dimension
     }
 
+    /// Turns the instance into a prize-collecting one: vertex `v` may be left out of both
+    /// cycles, forfeiting `prizes[v]`.
+    pub fn set_prizes(&mut self, prizes: Vec<Cost>) {
+        assert_eq!(
+            prizes.len(),
+            self.dimension,
+            "Expected one prize per vertex"
+        );
+        self.prizes = Some(prizes);
+    }
+
+    /// Switches to the classic single-tour TSP: constructives, repair and validation keep
+    /// every vertex in cycle 1, and the intra-route moves do the optimisation.
+    pub fn set_single_cycle(&mut self, single_cycle: bool) {
+        self.single_cycle = single_cycle;
+    }
+
+    pub fn is_single_cycle(&self) -> bool {
+        self.single_cycle
+    }
+
+    /// Best known tour length of a classic TSPLIB instance with this name, for checking
+    /// single-cycle results.
+    pub fn known_optimum(&self) -> Option<Cost> {
+        known_optimum(&self.name)
+    }
+
+    pub fn is_prize_collecting(&self) -> bool {
+        self.prizes.is_some()
+    }
+
+    /// Prize of vertex `v`; 0 unless the instance is prize-collecting.
+    #[inline]
+    pub fn prize(&self, v: usize) -> Cost {
+        self.prizes.as_ref().map_or(Cost::ZERO, |prizes| prizes[v])
+    }
+
+    /// Forces vertex `v` into `cycle` (e.g. a depot). Pinned vertices are never moved between
+    /// cycles and must be visited even on a prize-collecting instance.
+    pub fn pin(&mut self, v: usize, cycle: CycleId) {
+        assert!(v < self.dimension, "Vertex {} is out of range", v);
+        if self.pins.is_empty() {
+            self.pins = vec![None; self.dimension];
+        }
+        self.pins[v] = Some(cycle);
+    }
+
+    pub fn has_pins(&self) -> bool {
+        !self.pins.is_empty()
+    }
+
+    /// The cycle `v` is pinned to, if any.
+    #[inline]
+    pub fn pinned_cycle(&self, v: usize) -> Option<CycleId> {
+        self.pins.get(v).copied().flatten()
+    }
+
+    #[inline]
+    pub fn is_pinned(&self, v: usize) -> bool {
+        self.pinned_cycle(v).is_some()
+    }
+
     pub fn precompute_nearest_neighbors(&mut self, k: usize) {
-        if k == 0 || k >= self.dimension {
+        self.precompute_candidates(CandidateSource::NearestNeighbors(k));
+    }
+
+    /// Builds the per-vertex candidate lists (each sorted by distance) from `source`.
+    pub fn precompute_candidates(&mut self, source: CandidateSource) {
+        if let CandidateSource::NearestNeighbors(k)
+        | CandidateSource::DelaunayWithNearest(k)
+        | CandidateSource::Quadrant(k) = source
+            && (k == 0 || k >= self.dimension)
+        {
             eprintln!(
                 "Warning: Invalid k value ({}) for nearest neighbors. Must be 0 < k < dimension.",
                 k
             );
             self.nearest_neighbors = vec![Vec::new(); self.dimension];
+            self.candidate_source = None;
+            self.neighbor_bits.clear();
             return;
         }
 
-        if !self.nearest_neighbors[0].is_empty() && self.nearest_neighbors[0].len() == k {
+        if self.candidate_source == Some(source) {
             return;
         }
 
-        self.nearest_neighbors = vec![Vec::with_capacity(k); self.dimension];
+        let mut lists = match source {
+            CandidateSource::NearestNeighbors(k) => self.k_nearest(k),
+            CandidateSource::Delaunay => delaunay_neighbors(&self.coordinates),
+            CandidateSource::DelaunayWithNearest(k) => {
+                let mut lists = delaunay_neighbors(&self.coordinates);
+                for (list, nearest) in lists.iter_mut().zip(self.k_nearest(k)) {
+                    for v in nearest {
+                        if !list.contains(&v) {
+                            list.push(v);
+                        }
+                    }
+                }
+                lists
+            }
+            CandidateSource::Quadrant(k) => {
+                quadrant_neighbors(&self.coordinates, k, |i, j| self.distances.get(i, j))
+            }
+        };
+        for (i, list) in lists.iter_mut().enumerate() {
+            list.sort_by_key(|&j| Ordered(self.distances.get(i, j)));
+        }
+
+        self.nearest_neighbors = lists;
+        self.candidate_source = Some(source);
+        self.build_neighbor_bits();
+    }
 
-        for i in 0..self.dimension {
-            let mut neighbors: Vec<_> = (0..self.dimension)
-                .filter(|&j| i != j)
-                .map(|j| (j, self.distances[i][j]))
-                .collect();
+    pub fn candidate_source(&self) -> Option<CandidateSource> {
+        self.candidate_source
+    }
 
-            neighbors.sort_unstable_by_key(|&(_, dist)| dist);
+    /// The `k` nearest vertices of every vertex. EUC_2D rows are recomputed with the batched
+    /// kernel, which is exact whatever layout the matrix is stored in; other types read the
+    /// matrix.
+    fn k_nearest(&self, k: usize) -> Vec<Vec<usize>> {
+        let columns: Option<(Vec<f64>, Vec<f64>)> = (self.edge_weight_type
+            == EdgeWeightType::Euc2D)
+            .then(|| self.coordinates.iter().copied().unzip());
+        let mut row = vec![Cost::ZERO; self.dimension];
+        (0..self.dimension)
+            .map(|i| {
+                match &columns {
+                    Some((xs, ys)) => euclidean_row(xs[i], ys[i], xs, ys, &mut row),
+                    None => {
+                        for (j, slot) in row.iter_mut().enumerate() {
+                            *slot = self.distances.get(i, j);
+                        }
+                    }
+                }
+                let mut neighbors: Vec<usize> = (0..self.dimension).filter(|&j| i != j).collect();
+                neighbors.sort_unstable_by_key(|&j| Ordered(row[j]));
+                neighbors.truncate(k);
+                neighbors
+            })
+            .collect()
+    }
+
+    fn neighbor_words_per_row(&self) -> usize {
+        self.dimension.div_ceil(64)
+    }
+
+    fn build_neighbor_bits(&mut self) {
+        let words = self.neighbor_words_per_row();
+        self.neighbor_bits = vec![0; words * self.dimension];
+        for (i, neighbors) in self.nearest_neighbors.iter().enumerate() {
+            for &j in neighbors {
+                self.neighbor_bits[i * words + j / 64] |= 1 << (j % 64);
+            }
+        }
+    }
 
-            self.nearest_neighbors[i] = neighbors.into_iter().take(k).map(|(idx, _)| idx).collect();
+    /// O(1) test whether `b` is in the precomputed nearest-neighbor list of `a`.
+    pub fn is_neighbor(&self, a: usize, b: usize) -> bool {
+        if self.neighbor_bits.is_empty() {
+            panic!(
+                "Nearest neighbors requested but not precomputed. Call precompute_nearest_neighbors first."
+            );
         }
+        let words = self.neighbor_words_per_row();
+        self.neighbor_bits[a * words + b / 64] & (1 << (b % 64)) != 0
     }
 
     pub fn get_nearest_neighbors(&self, node_id: usize) -> &[usize] {
@@ -215,29 +553,135 @@ impl TsplibInstance {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Undirected edge as `(min, max)`.
+pub type Edge = (usize, usize);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Solution {
     pub cycle1: Vec<usize>,
     pub cycle2: Vec<usize>,
+    /// Whether each cycle is closed. Open solutions are two Hamiltonian paths: the link from
+    /// the last vertex of a cycle back to its first is free.
+    pub is_closed: bool,
+}
+
+impl Clone for Solution {
+    fn clone(&self) -> Self {
+        count_solution_copy();
+        Self {
+            cycle1: self.cycle1.clone(),
+            cycle2: self.cycle2.clone(),
+            is_closed: self.is_closed,
+        }
+    }
+
+    /// Reuses the existing cycle allocations, so hot loops can copy the incumbent into a
+    /// working buffer without allocating.
+    fn clone_from(&mut self, source: &Self) {
+        count_solution_copy();
+        self.cycle1.clone_from(&source.cycle1);
+        self.cycle2.clone_from(&source.cycle2);
+        self.is_closed = source.is_closed;
+    }
 }
 
 impl Solution {
     pub fn new(cycle1: Vec<usize>, cycle2: Vec<usize>) -> Self {
-        Self { cycle1, cycle2 }
+        Self {
+            cycle1,
+            cycle2,
+            is_closed: true,
+        }
     }
 
-    pub fn calculate_cost(&self, instance: &TsplibInstance) -> i32 {
+    pub fn calculate_cost(&self, instance: &TsplibInstance) -> Cost {
         let cost1 = self.calculate_cycle_cost(&self.cycle1, instance);
         let cost2 = self.calculate_cycle_cost(&self.cycle2, instance);
         cost1 + cost2
     }
 
-    fn calculate_cycle_cost(&self, cycle: &[usize], instance: &TsplibInstance) -> i32 {
+    /// Joins cycle 2 into cycle 1 by replacing one edge of each with the cheapest pair of
+    /// connecting edges, leaving cycle 2 empty.
+    pub fn merge_cycles(&mut self, instance: &TsplibInstance) {
+        let other = std::mem::take(&mut self.cycle2);
+        let (n1, n2) = (self.cycle1.len(), other.len());
+        if n2 == 0 {
+            return;
+        }
+        if n1 == 0 {
+            self.cycle1 = other;
+            return;
+        }
+
+        // (cost, i, j, forward): replace (cycle1[i], cycle1[i+1]) and (other[j], other[j+1]);
+        // forward splices other[j+1], ..., other[j], otherwise other[j], ..., other[j+1].
+        let mut best = (Cost::MAX, 0, 0, true);
+        for i in 0..n1 {
+            let (a, b) = (self.cycle1[i], self.cycle1[(i + 1) % n1]);
+            for j in 0..n2 {
+                let (c, d) = (other[j], other[(j + 1) % n2]);
+                let removed = instance.distance(a, b) + instance.distance(c, d);
+                let forward = instance.distance(a, d) + instance.distance(c, b) - removed;
+                let backward = instance.distance(a, c) + instance.distance(d, b) - removed;
+                if forward < best.0 {
+                    best = (forward, i, j, true);
+                }
+                if backward < best.0 {
+                    best = (backward, i, j, false);
+                }
+            }
+        }
+
+        let (_, i, j, forward) = best;
+        let mut spliced: Vec<usize> = if forward {
+            (1..=n2).map(|k| other[(j + k) % n2]).collect()
+        } else {
+            (0..n2).map(|k| other[(j + n2 - k) % n2]).collect()
+        };
+        let tail = self.cycle1.split_off(i + 1);
+        self.cycle1.append(&mut spliced);
+        self.cycle1.extend(tail);
+    }
+
+    /// Lengths of cycle 1 and cycle 2.
+    pub fn cycle_costs(&self, instance: &TsplibInstance) -> (Cost, Cost) {
+        (
+            self.calculate_cycle_cost(&self.cycle1, instance),
+            self.calculate_cycle_cost(&self.cycle2, instance),
+        )
+    }
+
+    /// Value of `objective` for this solution, minus the collected prizes on a
+    /// prize-collecting instance; equals [`Self::calculate_cost`] for
+    /// [`Objective::SumOfCycles`] otherwise.
+    pub fn calculate_objective(&self, instance: &TsplibInstance, objective: Objective) -> Cost {
+        objective.value(self.cycle_costs(instance)) - self.collected_prize(instance)
+    }
+
+    /// Sum of the prizes of the visited vertices.
+    pub fn collected_prize(&self, instance: &TsplibInstance) -> Cost {
+        if !instance.is_prize_collecting() {
+            return Cost::ZERO;
+        }
+        self.cycle1
+            .iter()
+            .chain(&self.cycle2)
+            .map(|&v| instance.prize(v))
+            .sum()
+    }
+
+    fn calculate_cycle_cost(&self, cycle: &[usize], instance: &TsplibInstance) -> Cost {
         if cycle.is_empty() {
-            return 0;
+            return Cost::ZERO;
         }
-        let mut cost = 0;
-        for i in 0..cycle.len() {
+        // An open path skips the closing link from the last vertex back to the first.
+        let links = if self.is_closed {
+            cycle.len()
+        } else {
+            cycle.len() - 1
+        };
+        let mut cost = Cost::ZERO;
+        for i in 0..links {
             let from = cycle[i];
             let to = cycle[(i + 1) % cycle.len()];
             cost += instance.distance(from, to);
@@ -245,36 +689,338 @@ impl Solution {
         cost
     }
 
+    /// Whether the two cycles partition the instance's vertices (sizes are not checked).
     pub fn is_valid(&self, instance: &TsplibInstance) -> bool {
-        let mut used = vec![false; instance.size()];
-        let mut count = 0;
+        self.validate(instance, usize::MAX).is_ok()
+    }
 
-        for &v in &self.cycle1 {
-            if v >= instance.size() || used[v] {
-                return false;
+    /// Checks that the cycles partition the vertices and that cycle 1 holds `ceil(n/2)`
+    /// vertices give or take `size_slack`. On a prize-collecting instance vertices may be
+    /// left out, and the balance applies to the `n` visited vertices. Pinned vertices must be
+    /// in their cycle. Returns the first problem found; see [`Solution::validation_errors`]
+    /// for all of them.
+    pub fn validate(
+        &self,
+        instance: &TsplibInstance,
+        size_slack: usize,
+    ) -> Result<(), SolutionError> {
+        match self
+            .validation_errors(instance, size_slack)
+            .into_iter()
+            .next()
+        {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Every rule of [`Solution::validate`] the solution breaks, in the order the checks run.
+    pub fn validation_errors(
+        &self,
+        instance: &TsplibInstance,
+        size_slack: usize,
+    ) -> Vec<SolutionError> {
+        let n = instance.size();
+        let mut errors = Vec::new();
+        let mut used = vec![false; n];
+        for &v in self.cycle1.iter().chain(&self.cycle2) {
+            if v >= n {
+                errors.push(SolutionError::VertexOutOfRange(v));
+            } else if std::mem::replace(&mut used[v], true) {
+                errors.push(SolutionError::DuplicateVertex(v));
+            }
+        }
+        if !instance.is_prize_collecting() {
+            errors.extend(
+                (0..n)
+                    .filter(|&v| !used[v])
+                    .map(SolutionError::MissingVertex),
+            );
+        }
+        for vertex in 0..n {
+            if let Some(cycle) = instance.pinned_cycle(vertex)
+                && !self.get_cycle(cycle).contains(&vertex)
+            {
+                errors.push(SolutionError::PinViolation { vertex, cycle });
             }
-            used[v] = true;
-            count += 1;
         }
 
-        for &v in &self.cycle2 {
-            if v >= instance.size() || used[v] {
-                return false;
+        if instance.is_single_cycle() {
+            if !self.cycle2.is_empty() {
+                errors.push(SolutionError::SecondCycleUsed(self.cycle2.len()));
             }
-            used[v] = true;
-            count += 1;
+            return errors;
         }
 
-        count == instance.size() && used.iter().all(|&x| x)
+        let visited = self.cycle1.len() + self.cycle2.len();
+        let expected = visited.div_ceil(2);
+        if self.cycle1.len().abs_diff(expected) > size_slack {
+            errors.push(SolutionError::SizeImbalance {
+                cycle1: self.cycle1.len(),
+                cycle2: self.cycle2.len(),
+                slack: size_slack,
+            });
+        }
+        errors
+    }
+
+    /// Edges of both cycles (the closing links only for closed solutions). A cycle of two
+    /// vertices contributes its single edge once.
+    pub fn to_edge_set(&self) -> FxHashSet<Edge> {
+        let mut edges = FxHashSet::default();
+        for cycle in [&self.cycle1, &self.cycle2] {
+            let n = cycle.len();
+            if n < 2 {
+                continue;
+            }
+            let links = if self.is_closed { n } else { n - 1 };
+            for i in 0..links {
+                let (u, v) = (cycle[i], cycle[(i + 1) % n]);
+                edges.insert((u.min(v), u.max(v)));
+            }
+        }
+        edges
+    }
+
+    /// Rewrites the solution in a canonical form, so equal tours compare and hash equal: a
+    /// closed cycle starts at its smallest vertex and continues to the smaller of its two
+    /// neighbours, an open path starts at its smaller end, and of two cycles of equal length
+    /// cycle 1 is the one that then starts with the smaller vertex. Cycles of different lengths keep
+    /// their labels, which the size balance depends on. Pins are not consulted: relabelling
+    /// can move pinned vertices out of their cycle.
+    pub fn canonicalize(&mut self) {
+        canonicalize_cycle(&mut self.cycle1, self.is_closed);
+        canonicalize_cycle(&mut self.cycle2, self.is_closed);
+        if self.cycle1.len() == self.cycle2.len() && self.cycle2.first() < self.cycle1.first() {
+            std::mem::swap(&mut self.cycle1, &mut self.cycle2);
+        }
+    }
+
+    /// The solution in the canonical form of [`Solution::canonicalize`].
+    pub fn canonical(&self) -> Self {
+        let mut canonical = self.clone();
+        canonical.canonicalize();
+        canonical
+    }
+
+    /// Moves that turn this solution into `other`, up to the canonical form. Exchanges, and
+    /// transfers where the cycle sizes differ, first bring every vertex into its cycle of
+    /// `other`; then, cycle by cycle, 2-opt moves grow the prefix that already follows
+    /// `other` one vertex at a time. Both solutions must visit the same vertices and be
+    /// closed or open alike, and a transfer needs a vertex to follow in its new cycle, so an
+    /// empty cycle can only stay empty.
+    ///
+    /// The moves are not the shortest sequence: fixing one position at a time can reverse a
+    /// segment whose edges already match `other`, where pairing up the differing edges would
+    /// need fewer 2-opt moves. Finding that pairing is a search of its own, while this takes
+    /// at most one move per vertex and always ends in `other`; the replay tool only reports
+    /// the move counts as a rough distance between the tours.
+    pub fn diff(&self, other: &Solution) -> Result<Vec<Move>, DiffError> {
+        if self.is_closed != other.is_closed {
+            return Err(DiffError::ShapeMismatch);
+        }
+        let size = self
+            .cycle1
+            .iter()
+            .chain(&self.cycle2)
+            .chain(&other.cycle1)
+            .chain(&other.cycle2)
+            .max()
+            .map_or(0, |&max| max + 1);
+        let mut visits = vec![0i32; size];
+        for &v in self.cycle1.iter().chain(&self.cycle2) {
+            visits[v] += 1;
+        }
+        for &v in other.cycle1.iter().chain(&other.cycle2) {
+            visits[v] -= 1;
+        }
+        if let Some(v) = visits.iter().position(|&count| count != 0) {
+            return Err(DiffError::VertexSetMismatch(v));
+        }
+        let mut in_cycle1 = vec![false; size];
+        for &v in &other.cycle1 {
+            in_cycle1[v] = true;
+        }
+        // Cycles of equal length may trade labels, so take the labelling that moves fewer
+        // vertices between them.
+        let stays = self.cycle1.iter().filter(|&&v| in_cycle1[v]).count();
+        let swapped = other.cycle1.len() == other.cycle2.len() && 2 * stays < self.cycle1.len();
+        let (target1, target2) = if swapped {
+            (&other.cycle2, &other.cycle1)
+        } else {
+            (&other.cycle1, &other.cycle2)
+        };
+        if swapped {
+            in_cycle1.iter_mut().for_each(|inside| *inside = !*inside);
+        }
+
+        let mut current = self.clone();
+        let mut moves = Vec::new();
+        let push = |current: &mut Solution, moves: &mut Vec<Move>, m: Move| {
+            m.apply(current);
+            moves.push(m);
+        };
+        let leaving1: Vec<usize> = self
+            .cycle1
+            .iter()
+            .copied()
+            .filter(|&v| !in_cycle1[v])
+            .collect();
+        let leaving2: Vec<usize> = self
+            .cycle2
+            .iter()
+            .copied()
+            .filter(|&v| in_cycle1[v])
+            .collect();
+        for (&v1, &v2) in leaving1.iter().zip(&leaving2) {
+            push(
+                &mut current,
+                &mut moves,
+                Move::InterRouteExchange { v1, v2 },
+            );
+        }
+        let paired = leaving1.len().min(leaving2.len());
+        for (from, to, target, leaving) in [
+            (CycleId::Cycle1, CycleId::Cycle2, target2, &leaving1),
+            (CycleId::Cycle2, CycleId::Cycle1, target1, &leaving2),
+        ] {
+            for &v in &leaving[paired..] {
+                // After its predecessor in `other` if that is already there.
+                let at = target
+                    .iter()
+                    .position(|&u| u == v)
+                    .expect("v belongs to target");
+                let predecessor = target[(at + target.len() - 1) % target.len()];
+                let after = if current.get_cycle(to).contains(&predecessor) {
+                    predecessor
+                } else {
+                    *current
+                        .get_cycle(to)
+                        .last()
+                        .ok_or(DiffError::EmptyCycle(to))?
+                };
+                push(
+                    &mut current,
+                    &mut moves,
+                    Move::InterRouteTransfer { v, from, after },
+                );
+            }
+        }
+
+        for (cycle, target) in [(CycleId::Cycle1, target1), (CycleId::Cycle2, target2)] {
+            reorder_cycle(&mut current, cycle, target, &mut moves);
+        }
+        Ok(moves)
+    }
+
+    /// Hash of the canonical form, equal for solutions describing the same tours; sets of
+    /// these detect exact duplicates in O(1).
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        self.canonical().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Reads a TSPLIB-style tour file whose `TOUR_SECTION` lists cycle 1 and then cycle 2
+    /// as 1-based vertex ids, each terminated by `-1`. A file with a single tour gives an
+    /// empty cycle 2. Header lines before the section are ignored.
+    pub fn read_tour<P: AsRef<Path>>(path: P) -> Result<Self, TsplibError> {
+        let text = std::fs::read_to_string(path)?;
+        let section = text
+            .split_once("TOUR_SECTION")
+            .map(|(_, rest)| rest)
+            .ok_or_else(|| TsplibError::Format("Missing TOUR_SECTION".to_string()))?;
+
+        let mut cycles: Vec<Vec<usize>> = vec![Vec::new()];
+        for token in section.split_whitespace() {
+            if token == "EOF" {
+                break;
+            }
+            let id: i64 = token
+                .parse()
+                .map_err(|_| TsplibError::Parse(format!("Invalid tour entry: {}", token)))?;
+            match id {
+                -1 => cycles.push(Vec::new()),
+                id if id >= 1 => cycles.last_mut().unwrap().push(id as usize - 1),
+                _ => {
+                    return Err(TsplibError::Parse(format!(
+                        "Vertex ids are 1-based, got {}",
+                        id
+                    )));
+                }
+            }
+        }
+        cycles.retain(|cycle| !cycle.is_empty());
+        if cycles.len() > 2 {
+            return Err(TsplibError::Format(format!(
+                "Expected at most 2 tours, found {}",
+                cycles.len()
+            )));
+        }
+        let mut cycles = cycles.into_iter();
+        Ok(Solution::new(
+            cycles.next().unwrap_or_default(),
+            cycles.next().unwrap_or_default(),
+        ))
+    }
+
+    /// Reads a solution from a one-line JSON object with `cycle1` and `cycle2` vertex lists
+    /// (0-based) and an optional `closed` flag, as in the run files of
+    /// `algorithm::write_run_archive`.
+    pub fn read_json<P: AsRef<Path>>(path: P) -> Result<Self, TsplibError> {
+        let fields =
+            FlatObject::parse(&std::fs::read_to_string(path)?).map_err(TsplibError::Parse)?;
+        let mut solution = Solution::new(
+            fields.vertices("cycle1").map_err(TsplibError::Format)?,
+            fields.vertices("cycle2").map_err(TsplibError::Format)?,
+        );
+        if fields.get("closed").is_some() {
+            solution.is_closed = fields.bool("closed").map_err(TsplibError::Format)?;
+        }
+        Ok(solution)
+    }
+
+    /// [`Solution::read_json`] for `.json` files, [`Solution::read_tour`] otherwise.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Self, TsplibError> {
+        let is_json = path
+            .as_ref()
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        if is_json {
+            Self::read_json(path)
+        } else {
+            Self::read_tour(path)
+        }
+    }
+
+    /// Writes the solution in the format read by [`Solution::read_tour`].
+    pub fn write_tour<P: AsRef<Path>>(&self, path: P, name: &str) -> io::Result<()> {
+        let mut out = format!(
+            "NAME : {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n",
+            name,
+            self.cycle1.len() + self.cycle2.len()
+        );
+        for cycle in [&self.cycle1, &self.cycle2] {
+            if cycle.is_empty() {
+                continue;
+            }
+            for &v in cycle {
+                out.push_str(&format!("{}\n", v + 1));
+            }
+            out.push_str("-1\n");
+        }
+        out.push_str("EOF\n");
+        std::fs::write(path, out)
     }
 
     pub fn find_node(&self, node_id: usize) -> Option<(CycleId, usize)> {
         if let Some(pos) = self.cycle1.iter().position(|&n| n == node_id) {
             Some((CycleId::Cycle1, pos))
-        } else if let Some(pos) = self.cycle2.iter().position(|&n| n == node_id) {
-            Some((CycleId::Cycle2, pos))
         } else {
-            None
+            self.cycle2
+                .iter()
+                .position(|&n| n == node_id)
+                .map(|pos| (CycleId::Cycle2, pos))
         }
     }
 
@@ -292,16 +1038,6 @@ impl Solution {
         }
     }
 
-    pub fn has_edge(&self, a: usize, b: usize) -> Option<(CycleId, i8)> {
-        if let Some(direction) = self.check_edge_in_cycle(&self.cycle1, a, b) {
-            Some((CycleId::Cycle1, direction))
-        } else if let Some(direction) = self.check_edge_in_cycle(&self.cycle2, a, b) {
-            Some((CycleId::Cycle2, direction))
-        } else {
-            None
-        }
-    }
-
     pub fn check_edge_in_cycle(&self, cycle: &[usize], a: usize, b: usize) -> Option<i8> {
         let n = cycle.len();
         if n < 2 {
@@ -320,3 +1056,237 @@ impl Solution {
         None
     }
 }
+
+/// Applies to `solution` and appends to `moves` the 2-opt moves that turn its `cycle`, which
+/// holds the vertices of `target`, into the tour of `target`: each move reverses the segment
+/// from the first vertex out of place to the vertex that belongs there.
+fn reorder_cycle(solution: &mut Solution, cycle: CycleId, target: &[usize], moves: &mut Vec<Move>) {
+    let n = target.len();
+    if n < 3 {
+        return;
+    }
+    let position = |solution: &Solution, v: usize| {
+        solution
+            .get_cycle(cycle)
+            .iter()
+            .position(|&u| u == v)
+            .expect("The cycle holds the vertices of its target")
+    };
+    let reverse = |solution: &mut Solution, moves: &mut Vec<Move>, from: usize, to: usize| {
+        let vertices = solution.get_cycle(cycle);
+        let m = Move::IntraRouteEdgeExchange {
+            a: vertices[(from + n - 1) % n],
+            b: vertices[from],
+            c: vertices[to],
+            d: vertices[(to + 1) % n],
+            cycle,
+        };
+        m.apply(solution);
+        moves.push(m);
+    };
+
+    // Positions count from where `target` starts: anywhere on a closed cycle, but the first
+    // vertex of an open path, which a reversal of its prefix brings there.
+    let mut target = target.to_vec();
+    let start = if solution.is_closed {
+        let start = position(solution, target[0]);
+        if solution.get_cycle(cycle)[(start + n - 1) % n] == target[1] {
+            target[1..].reverse();
+        }
+        start
+    } else {
+        if position(solution, target[0]) > position(solution, target[n - 1]) {
+            target.reverse();
+        }
+        let first = position(solution, target[0]);
+        if first != 0 {
+            reverse(solution, moves, 0, first);
+        }
+        0
+    };
+    for (i, &v) in target.iter().enumerate().take(n - 1).skip(1) {
+        let at = (start + i) % n;
+        if solution.get_cycle(cycle)[at] != v {
+            let to = position(solution, v);
+            reverse(solution, moves, at, to);
+        }
+    }
+}
+
+/// Rotates a closed `cycle` to start at its smallest vertex, followed by the smaller of its
+/// neighbours; an open path only turns to start at its smaller end.
+fn canonicalize_cycle(cycle: &mut [usize], is_closed: bool) {
+    let n = cycle.len();
+    if n < 2 {
+        return;
+    }
+    if !is_closed {
+        if cycle[n - 1] < cycle[0] {
+            cycle.reverse();
+        }
+        return;
+    }
+    let start = (0..n).min_by_key(|&i| cycle[i]).expect("non-empty");
+    cycle.rotate_left(start);
+    if cycle[n - 1] < cycle[1] {
+        cycle[1..].reverse();
+    }
+}
+
+/// Successor/predecessor/position lookup for a `Solution`, giving O(1) `has_edge` and
+/// `find_node` instead of scanning the cycles. The index is a snapshot: apply moves with
+/// `Move::apply_indexed`, which updates only the positions and edges the move touches, or
+/// call `rebuild_cycle` after mutating the solution in other ways.
+#[derive(Debug, Clone)]
+pub struct SolutionIndex {
+    cycle_of: Vec<Option<CycleId>>,
+    position: Vec<usize>,
+    succ: Vec<usize>,
+    pred: Vec<usize>,
+}
+
+impl SolutionIndex {
+    pub fn new(solution: &Solution) -> Self {
+        let size = solution
+            .cycle1
+            .iter()
+            .chain(solution.cycle2.iter())
+            .max()
+            .map_or(0, |&max| max + 1);
+        let mut index = Self {
+            cycle_of: vec![None; size],
+            position: vec![0; size],
+            succ: vec![0; size],
+            pred: vec![0; size],
+        };
+        index.rebuild_cycle(solution, CycleId::Cycle1);
+        index.rebuild_cycle(solution, CycleId::Cycle2);
+        index
+    }
+
+    /// Re-reads one cycle of `solution`. Nodes that left the cycle must be re-indexed
+    /// through the cycle they moved into.
+    pub fn rebuild_cycle(&mut self, solution: &Solution, cycle_id: CycleId) {
+        let cycle = solution.get_cycle(cycle_id);
+        let n = cycle.len();
+        if let Some(&max) = cycle.iter().max()
+            && max >= self.cycle_of.len()
+        {
+            self.cycle_of.resize(max + 1, None);
+            self.position.resize(max + 1, 0);
+            self.succ.resize(max + 1, 0);
+            self.pred.resize(max + 1, 0);
+        }
+        for (pos, &node) in cycle.iter().enumerate() {
+            self.cycle_of[node] = Some(cycle_id);
+            self.position[node] = pos;
+            self.succ[node] = cycle[(pos + 1) % n];
+            self.pred[node] = cycle[(pos + n - 1) % n];
+        }
+    }
+
+    /// Re-reads `len` consecutive positions of one cycle of `solution`, starting at `from`
+    /// and wrapping around its end, with the successors and predecessors of their vertices.
+    /// The vertices must already be indexed.
+    pub fn refresh(&mut self, solution: &Solution, cycle_id: CycleId, from: usize, len: usize) {
+        let cycle = solution.get_cycle(cycle_id);
+        let n = cycle.len();
+        for offset in 0..len.min(n) {
+            let pos = (from + offset) % n;
+            let node = cycle[pos];
+            self.cycle_of[node] = Some(cycle_id);
+            self.position[node] = pos;
+            self.succ[node] = cycle[(pos + 1) % n];
+            self.pred[node] = cycle[(pos + n - 1) % n];
+        }
+    }
+
+    pub fn find_node(&self, node: usize) -> Option<(CycleId, usize)> {
+        let cycle_id = (*self.cycle_of.get(node)?)?;
+        Some((cycle_id, self.position[node]))
+    }
+
+    /// The cycle containing edge `(a, b)` and `1` if it is traversed as `a -> b`, `-1` if as
+    /// `b -> a`.
+    pub fn has_edge(&self, a: usize, b: usize) -> Option<(CycleId, i8)> {
+        let (cycle_a, _) = self.find_node(a)?;
+        let (cycle_b, _) = self.find_node(b)?;
+        if cycle_a != cycle_b || a == b {
+            return None;
+        }
+        if self.succ[a] == b {
+            Some((cycle_a, 1))
+        } else if self.pred[a] == b {
+            Some((cycle_a, -1))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::SeedStream;
+    use rand::seq::SliceRandom;
+
+    /// Applying `from.diff(to)` to `from` gives `to`, up to the canonical form, for closed and
+    /// open solutions with equal and unequal cycle sizes.
+    #[test]
+    fn diff_reproduces_the_other_solution() {
+        let stream = SeedStream::new(4437);
+        for case in 0..200 {
+            let mut rng = stream.child(case).rng();
+            let n = 4 + case as usize % 17;
+            let is_closed = case % 2 == 0;
+            let random = |rng: &mut rand::rngs::SmallRng, split: usize| {
+                let mut vertices: Vec<usize> = (0..n).collect();
+                vertices.shuffle(rng);
+                let cycle2 = vertices.split_off(split);
+                Solution {
+                    cycle1: vertices,
+                    cycle2,
+                    is_closed,
+                }
+            };
+            let from = random(&mut rng, n / 2);
+            let to = random(&mut rng, if case % 3 == 0 { n / 2 - 1 } else { n / 2 });
+
+            let mut current = from.clone();
+            for m in from.diff(&to).unwrap() {
+                m.apply(&mut current);
+            }
+            assert_eq!(
+                current.canonical(),
+                to.canonical(),
+                "case {}: {:?} -> {:?}",
+                case,
+                from,
+                to
+            );
+        }
+    }
+
+    /// Solutions that differ in their vertices or shape, or need a transfer into an empty
+    /// cycle, are errors rather than panics.
+    #[test]
+    fn diff_rejects_incompatible_solutions() {
+        let closed = Solution::new(vec![0, 1, 2], vec![3, 4, 5]);
+        let mut open = closed.clone();
+        open.is_closed = false;
+        assert_eq!(closed.diff(&open), Err(DiffError::ShapeMismatch));
+
+        let other_vertices = Solution::new(vec![0, 1, 2], vec![3, 4, 6]);
+        assert_eq!(
+            closed.diff(&other_vertices),
+            Err(DiffError::VertexSetMismatch(5))
+        );
+
+        let single = Solution::new(vec![0, 1, 2, 3, 4, 5], Vec::new());
+        assert_eq!(
+            single.diff(&closed),
+            Err(DiffError::EmptyCycle(CycleId::Cycle2))
+        );
+        assert!(closed.diff(&single).is_ok());
+    }
+}
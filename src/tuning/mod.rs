@@ -0,0 +1,2 @@
+pub mod race;
+pub mod sweep;
@@ -0,0 +1,256 @@
+use crate::algorithm::{ProgressCallback, TspAlgorithm};
+use crate::budget::Budget;
+use crate::seed::SeedStream;
+use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::{Cost, Ordered, Weight};
+use rand::RngCore;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Builds the algorithm to race from a configuration, such as an algorithm's params struct.
+pub type AlgorithmFactory<'a, C> = Box<dyn Fn(&C) -> Box<dyn TspAlgorithm + Send + Sync> + 'a>;
+
+/// Solves an instance within a budget, drawing from the given generator.
+type BudgetedSolveFn =
+    Box<dyn Fn(&TsplibInstance, &Budget, &mut dyn RngCore) -> Solution + Send + Sync>;
+
+/// A metaheuristic that runs until its budget is exhausted, raced with a fixed time per run.
+pub struct TimedAlgorithm {
+    name: String,
+    time_limit: Duration,
+    solve: BudgetedSolveFn,
+}
+
+impl TimedAlgorithm {
+    pub fn new(
+        name: impl Into<String>,
+        time_limit: Duration,
+        solve: impl Fn(&TsplibInstance, &Budget, &mut dyn RngCore) -> Solution + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            time_limit,
+            solve: Box::new(solve),
+        }
+    }
+}
+
+impl TspAlgorithm for TimedAlgorithm {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn solve_with_feedback(
+        &self,
+        instance: &TsplibInstance,
+        rng: &mut dyn RngCore,
+        _progress_callback: ProgressCallback,
+    ) -> Solution {
+        (self.solve)(instance, &Budget::timed(self.time_limit), rng)
+    }
+}
+
+/// F-Race style tuning: every surviving configuration is run once per block (one instance,
+/// next repetition), costs are ranked within the block, and after `min_blocks` blocks a
+/// Friedman test drops the configurations whose rank sums are significantly worse than the
+/// leader's.
+pub struct Race {
+    /// Blocks evaluated before the first elimination test.
+    pub min_blocks: usize,
+    /// Upper bound on the number of blocks; the race also stops when one configuration is left.
+    pub max_blocks: usize,
+    /// Master stream of the race. Every configuration in block `b` draws from its child `b`,
+    /// so within a block the configurations see the same random numbers.
+    pub seeds: SeedStream,
+}
+
+/// A configuration's standing at the end of the race.
+#[derive(Debug, Clone)]
+pub struct RaceEntry<C> {
+    pub config: C,
+    pub name: String,
+    pub mean_rank: f64,
+    pub mean_cost: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RaceResult<C> {
+    /// Surviving configurations, best (lowest mean rank) first.
+    pub survivors: Vec<RaceEntry<C>>,
+    pub blocks_run: usize,
+}
+
+impl Race {
+    /// Upper 5% quantile of the standard normal distribution.
+    const Z_95: f64 = 1.644_853_626_951_472;
+    /// Two-sided 5% quantile of the standard normal distribution.
+    const Z_975: f64 = 1.959_963_984_540_054;
+
+    pub fn new(min_blocks: usize, max_blocks: usize, seeds: SeedStream) -> Self {
+        Self {
+            min_blocks: min_blocks.max(2),
+            max_blocks,
+            seeds,
+        }
+    }
+
+    pub fn run<C: Clone>(
+        &self,
+        configs: Vec<C>,
+        factory: &AlgorithmFactory<C>,
+        instances: &[TsplibInstance],
+    ) -> RaceResult<C> {
+        assert!(!instances.is_empty(), "Racing needs at least one instance");
+        let algorithms: Vec<_> = configs.iter().map(factory).collect();
+        let mut alive: Vec<usize> = (0..configs.len()).collect();
+        // Per block, the cost of every configuration alive at that point.
+        let mut blocks: Vec<Vec<(usize, Cost)>> = Vec::new();
+
+        while blocks.len() < self.max_blocks && alive.len() > 1 {
+            let instance = &instances[blocks.len() % instances.len()];
+            let block_seeds = self.seeds.child(blocks.len() as u64);
+            let block: Vec<(usize, Cost)> = alive
+                .iter()
+                .map(|&c| {
+                    let solution = algorithms[c].solve_with_feedback(
+                        instance,
+                        &mut block_seeds.rng(),
+                        &mut |_| {},
+                    );
+                    (c, solution.calculate_cost(instance))
+                })
+                .collect();
+            blocks.push(block);
+
+            if blocks.len() >= self.min_blocks {
+                alive = self.eliminate(&alive, &blocks);
+            }
+        }
+
+        let (rank_sums, _) = Self::rank_sums(&alive, &blocks);
+        let mut survivors: Vec<RaceEntry<C>> = alive
+            .iter()
+            .zip(rank_sums)
+            .map(|(&c, rank_sum)| {
+                let costs: Vec<Cost> = blocks
+                    .iter()
+                    .filter_map(|b| b.iter().find(|&&(id, _)| id == c).map(|&(_, cost)| cost))
+                    .collect();
+                RaceEntry {
+                    config: configs[c].clone(),
+                    name: algorithms[c].name().to_string(),
+                    mean_rank: rank_sum / blocks.len().max(1) as f64,
+                    mean_cost: costs.iter().map(|&x| x.to_f64()).sum::<f64>()
+                        / costs.len().max(1) as f64,
+                }
+            })
+            .collect();
+        survivors.sort_by(|a, b| a.mean_rank.total_cmp(&b.mean_rank));
+
+        RaceResult {
+            survivors,
+            blocks_run: blocks.len(),
+        }
+    }
+
+    /// Ranks (1 = cheapest, ties averaged) of the `alive` configurations within one block.
+    fn block_ranks(alive: &[usize], block: &[(usize, Cost)]) -> Vec<(usize, f64)> {
+        let mut costs: Vec<(usize, Cost)> = block
+            .iter()
+            .copied()
+            .filter(|(c, _)| alive.contains(c))
+            .collect();
+        costs.sort_by_key(|&(_, cost)| Ordered(cost));
+        let mut ranks = Vec::with_capacity(costs.len());
+        let mut i = 0;
+        while i < costs.len() {
+            let mut j = i;
+            while j + 1 < costs.len() && costs[j + 1].1 == costs[i].1 {
+                j += 1;
+            }
+            let rank = (i + j) as f64 / 2.0 + 1.0;
+            ranks.extend(costs[i..=j].iter().map(|&(c, _)| (c, rank)));
+            i = j + 1;
+        }
+        ranks
+    }
+
+    /// Rank sums of the `alive` configurations (in `alive` order) over all blocks, and the
+    /// total of squared ranks (for the tie-corrected Friedman statistic).
+    fn rank_sums(alive: &[usize], blocks: &[Vec<(usize, Cost)>]) -> (Vec<f64>, f64) {
+        let mut sums = vec![0.0; alive.len()];
+        let mut sum_sq = 0.0;
+        for block in blocks {
+            for (c, rank) in Self::block_ranks(alive, block) {
+                let slot = alive
+                    .iter()
+                    .position(|&a| a == c)
+                    .expect("ranked config is alive");
+                sums[slot] += rank;
+                sum_sq += rank * rank;
+            }
+        }
+        (sums, sum_sq)
+    }
+
+    /// Friedman test over the `alive` configurations; if the ranks differ significantly,
+    /// keeps only those within the critical rank-sum difference of the best one.
+    fn eliminate(&self, alive: &[usize], blocks: &[Vec<(usize, Cost)>]) -> Vec<usize> {
+        let m = alive.len() as f64;
+        let b = blocks.len() as f64;
+        let (sums, sum_sq) = Self::rank_sums(alive, blocks);
+
+        // Friedman statistic with the tie correction: (m - 1) * sum_j (R_j - b(m+1)/2)^2 /
+        // (sum of squared ranks - b m (m+1)^2 / 4).
+        let expected = b * (m + 1.0) / 2.0;
+        let numerator: f64 = sums.iter().map(|r| (r - expected).powi(2)).sum::<f64>() * (m - 1.0);
+        let denominator = sum_sq - b * m * (m + 1.0).powi(2) / 4.0;
+        if denominator <= 0.0 {
+            return alive.to_vec();
+        }
+        let statistic = numerator / denominator;
+        if statistic <= Self::chi_square_95(m - 1.0) {
+            return alive.to_vec();
+        }
+
+        // Rank-sum differences have variance b m (m+1) / 6 under the null hypothesis.
+        let critical = Self::Z_975 * (b * m * (m + 1.0) / 6.0).sqrt();
+        let best = sums.iter().copied().fold(f64::INFINITY, f64::min);
+        alive
+            .iter()
+            .zip(&sums)
+            .filter(|&(_, &sum)| sum - best <= critical)
+            .map(|(&c, _)| c)
+            .collect()
+    }
+
+    /// Upper 5% quantile of the chi-square distribution (Wilson–Hilferty approximation).
+    fn chi_square_95(df: f64) -> f64 {
+        let a = 2.0 / (9.0 * df);
+        df * (1.0 - a + Self::Z_95 * a.sqrt()).powi(3)
+    }
+}
+
+impl<C> RaceResult<C> {
+    pub fn best(&self) -> Option<&RaceEntry<C>> {
+        self.survivors.first()
+    }
+}
+
+/// Writes `config` as pretty-printed JSON.
+pub fn write_config<C: Serialize>(path: &Path, config: &C) -> io::Result<()> {
+    let mut out = serde_json::to_string_pretty(config).map_err(io::Error::other)?;
+    out.push('\n');
+    fs::write(path, out)
+}
+
+/// Reads a configuration written by [`write_config`]. For the params structs, fields left
+/// out keep their defaults and unknown fields are an error.
+pub fn read_config<C: DeserializeOwned>(path: &Path) -> io::Result<C> {
+    serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
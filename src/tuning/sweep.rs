@@ -0,0 +1,136 @@
+use crate::algorithm::{
+    ExperimentOptions, ExperimentStats, STATS_COLUMNS, TspAlgorithm, run_experiment,
+    run_local_search_experiment, stats_cells,
+};
+use crate::algorithms::constructive::weighted_regret_cycle::WeightedRegretCycle;
+use crate::algorithms::local_search::base::{
+    InitialSolutionType, LocalSearch, NeighborhoodType, SearchVariant,
+};
+use crate::seed::SeedStream;
+use crate::table::TextTable;
+use crate::tsplib::TsplibInstance;
+use crate::weight::Weight;
+
+/// Results of one parameter sweep on one instance, in the order the configurations ran.
+#[derive(Debug, Clone)]
+pub struct SweepReport {
+    pub instance_name: String,
+    pub results: Vec<ExperimentStats>,
+}
+
+impl SweepReport {
+    /// The configuration with the lowest average cost (ties go to the lower best cost).
+    pub fn best(&self) -> Option<&ExperimentStats> {
+        self.results.iter().min_by(|a, b| {
+            a.avg_cost
+                .total_cmp(&b.avg_cost)
+                .then(a.min_cost.total_order(&b.min_cost))
+        })
+    }
+
+    /// Results table in the format of the main experiment summary, followed by the winner.
+    pub fn format(&self) -> String {
+        let mut out = format!("Sweep on {}\n", self.instance_name);
+        let mut table = TextTable::new(&STATS_COLUMNS);
+        for stats in &self.results {
+            table.push(stats_cells(stats));
+        }
+        let mut lines = table.lines().into_iter();
+        for line in lines.by_ref().take(2) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+        for (line, stats) in lines.zip(&self.results) {
+            out.push_str(&line);
+            out.push('\n');
+            if let Some(report) = &stats.ls_report {
+                out.push_str(&format!("    {}\n", report.format_summary()));
+            }
+            if let Some(ops) = &stats.op_counts {
+                out.push_str(&format!("    {}\n", ops.format_per_run(stats.num_runs)));
+            }
+        }
+        if let Some(best) = self.best() {
+            out.push_str(&format!(
+                "Best: {} (avg {:.2}, min {})\n",
+                best.algorithm_name, best.avg_cost, best.min_cost
+            ));
+        }
+        out
+    }
+}
+
+/// Runs every configuration `runs_per_config` times on `instance`, from the master stream
+/// `seeds`.
+pub fn sweep<A: TspAlgorithm + Send + Sync>(
+    instance: &TsplibInstance,
+    configs: &[A],
+    runs_per_config: usize,
+    seeds: SeedStream,
+) -> SweepReport {
+    let options = ExperimentOptions {
+        seeds,
+        ..ExperimentOptions::default()
+    };
+    SweepReport {
+        instance_name: instance.name.clone(),
+        results: configs
+            .iter()
+            .map(|algorithm| run_experiment(algorithm, instance, runs_per_config, options))
+            .collect(),
+    }
+}
+
+/// Sweeps the full grid of `regret_weights x greedy_weights` for the weighted k-regret
+/// construction.
+pub fn sweep_regret_weights(
+    instance: &TsplibInstance,
+    k_regret: usize,
+    regret_weights: &[f64],
+    greedy_weights: &[f64],
+    runs_per_config: usize,
+    seeds: SeedStream,
+) -> SweepReport {
+    let configs: Vec<WeightedRegretCycle> = regret_weights
+        .iter()
+        .flat_map(|&rw| {
+            greedy_weights
+                .iter()
+                .map(move |&gw| WeightedRegretCycle::new(k_regret, rw, gw))
+        })
+        .collect();
+    sweep(instance, &configs, runs_per_config, seeds)
+}
+
+/// Sweeps candidate-list sizes for candidate steepest local search. Each k needs its own
+/// k-NN lists, so the instance is cloned and re-precomputed per configuration.
+pub fn sweep_candidate_k(
+    instance: &TsplibInstance,
+    ks: &[usize],
+    neighborhood: NeighborhoodType,
+    initial_solution_type: InitialSolutionType,
+    runs_per_config: usize,
+    seeds: SeedStream,
+) -> SweepReport {
+    let options = ExperimentOptions {
+        seeds,
+        ..ExperimentOptions::default()
+    };
+    let results = ks
+        .iter()
+        .map(|&k| {
+            let mut with_k = instance.clone();
+            with_k.precompute_nearest_neighbors(k);
+            let local_search = LocalSearch::new(
+                SearchVariant::CandidateSteepest(k),
+                neighborhood,
+                initial_solution_type,
+            );
+            run_local_search_experiment(&local_search, &with_k, runs_per_config, options)
+        })
+        .collect();
+    SweepReport {
+        instance_name: instance.name.clone(),
+        results,
+    }
+}
@@ -1,14 +1,41 @@
+use crate::algorithms::constructive::common::enforce_pins;
 use crate::tsplib::{Solution, TsplibInstance};
+use rand::Rng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 
-pub fn generate_random_solution(instance: &TsplibInstance) -> Solution {
+/// A random split of the vertices into the two cycles, drawn from `rng`.
+pub fn generate_random_solution<R: Rng + ?Sized>(
+    instance: &TsplibInstance,
+    rng: &mut R,
+) -> Solution {
     let mut vertices: Vec<usize> = (0..instance.size()).collect();
-    vertices.shuffle(&mut thread_rng());
+    vertices.shuffle(rng);
 
-    let half = vertices.len() / 2;
+    if instance.is_single_cycle() {
+        return Solution::new(vertices, Vec::new());
+    }
+
+    // Cycle 1 takes the extra vertex of an odd instance, as validation expects.
+    let half = vertices.len().div_ceil(2);
     let cycle1 = vertices[0..half].to_vec();
     let cycle2 = vertices[half..].to_vec();
 
-    Solution::new(cycle1, cycle2)
+    let mut solution = Solution::new(cycle1, cycle2);
+    enforce_pins(&mut solution, instance);
+    solution
+}
+
+/// `name` with everything but alphanumerics and `-` replaced by `_`, for output file and
+/// directory names.
+pub fn safe_file_name(name: &str) -> String {
+    name.replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
+        .replace("__", "_")
+}
+
+/// 64-bit FNV-1a hash of `bytes`, for checksums and short ids that must not change between
+/// builds (unlike `std`'s hashers).
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
@@ -0,0 +1,186 @@
+//! Run-to-run variance of the final costs: flags the experiments whose averages are not yet
+//! stable, because the costs spread too much or the best run lies far from the others, and
+//! estimates how many runs would pin the average down.
+
+use crate::algorithm::ExperimentStats;
+use crate::table::{Align, TextTable};
+use crate::weight::{Ordered, Weight};
+
+/// Two-sided 95% quantile of the standard normal distribution.
+const Z_95: f64 = 1.959_964;
+/// Scales the median absolute deviation to the standard deviation of normal data.
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+/// When [`diagnose`] flags an experiment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VarianceThresholds {
+    /// Largest acceptable coefficient of variation (sample standard deviation over mean).
+    pub max_cv: f64,
+    /// Robust z-score (Iglewicz and Hoaglin: distance from the median over the scaled median
+    /// absolute deviation) beyond which the best run counts as an outlier.
+    pub outlier_z: f64,
+    /// Half-width of the 95% confidence interval of the average, relative to it, that the
+    /// suggested number of runs aims for.
+    pub relative_precision: f64,
+}
+
+impl Default for VarianceThresholds {
+    fn default() -> Self {
+        Self {
+            max_cv: 0.01,
+            outlier_z: 3.5,
+            relative_precision: 0.005,
+        }
+    }
+}
+
+/// Variance statistics of one experiment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarianceDiagnosis {
+    pub algorithm: String,
+    pub instance: String,
+    pub runs: usize,
+    pub cv: f64,
+    /// Robust z-score of the best run; infinite if it differs from a majority of identical
+    /// costs.
+    pub best_run_z: f64,
+    /// Runs for the 95% confidence interval of the average to shrink to the relative
+    /// precision of the thresholds, by the normal approximation.
+    pub runs_needed: usize,
+    pub high_cv: bool,
+    pub best_run_outlier: bool,
+}
+
+impl VarianceDiagnosis {
+    pub fn is_flagged(&self) -> bool {
+        self.high_cv || self.best_run_outlier || self.needs_more_runs()
+    }
+
+    pub fn needs_more_runs(&self) -> bool {
+        self.runs_needed > self.runs
+    }
+}
+
+/// The variance statistics of `stats`, from its per-run costs, or `None` with fewer than two
+/// runs or an average of zero.
+pub fn diagnose(
+    stats: &ExperimentStats,
+    thresholds: &VarianceThresholds,
+) -> Option<VarianceDiagnosis> {
+    let n = stats.costs.len();
+    if n < 2 {
+        return None;
+    }
+    let costs: Vec<f64> = stats.costs.iter().map(|cost| cost.to_f64()).collect();
+    let mean = costs.iter().sum::<f64>() / n as f64;
+    if mean == 0.0 {
+        return None;
+    }
+    let variance = costs.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let cv = variance.sqrt() / mean.abs();
+
+    let center = median(costs.clone());
+    let mad = median(costs.iter().map(|c| (c - center).abs()).collect());
+    let best = stats
+        .costs
+        .iter()
+        .min_by_key(|&&cost| Ordered(cost))
+        .expect("at least two runs")
+        .to_f64();
+    let best_run_z = if best == center {
+        0.0
+    } else if mad == 0.0 {
+        f64::INFINITY
+    } else {
+        (center - best) / (MAD_TO_SIGMA * mad)
+    };
+
+    let runs_needed = (Z_95 * cv / thresholds.relative_precision)
+        .powi(2)
+        .ceil()
+        .max(2.0) as usize;
+    Some(VarianceDiagnosis {
+        algorithm: stats.algorithm_name.clone(),
+        instance: stats.instance_name.clone(),
+        runs: n,
+        cv,
+        best_run_z,
+        runs_needed,
+        high_cv: cv > thresholds.max_cv,
+        best_run_outlier: best_run_z > thresholds.outlier_z,
+    })
+}
+
+/// The variance section of the summary: a row per flagged experiment of `results` with what
+/// flagged it, and how many runs a stable average needs.
+pub fn format_variance_report(
+    results: &[(String, ExperimentStats)],
+    thresholds: &VarianceThresholds,
+) -> Vec<String> {
+    let flagged: Vec<VarianceDiagnosis> = results
+        .iter()
+        .filter_map(|(_, stats)| diagnose(stats, thresholds))
+        .filter(VarianceDiagnosis::is_flagged)
+        .collect();
+    if flagged.is_empty() {
+        return vec![format!(
+            "Run-to-run variance: every average is stable (CV <= {:.2}%, no outlying best run, \
+             95% interval within ±{:.2}%).",
+            100.0 * thresholds.max_cv,
+            100.0 * thresholds.relative_precision
+        )];
+    }
+    let mut lines = vec![format!(
+        "Run-to-run variance ({} flagged; CV above {:.2}%, best run beyond z={:.1}, or \
+             runs short of a 95% interval within ±{:.2}%):",
+        flagged.len(),
+        100.0 * thresholds.max_cv,
+        thresholds.outlier_z,
+        100.0 * thresholds.relative_precision
+    )];
+    let mut table = TextTable::new(&[
+        ("Instance", Align::Left),
+        ("Algorithm", Align::Left),
+        ("Runs", Align::Right),
+        ("CV", Align::Right),
+        ("Best run z", Align::Right),
+        ("Suggestion", Align::Left),
+    ]);
+    for diagnosis in flagged {
+        let cv = format!(
+            "{:.2}%{}",
+            100.0 * diagnosis.cv,
+            if diagnosis.high_cv { "!" } else { " " }
+        );
+        let z = format!(
+            "{:.1}{}",
+            diagnosis.best_run_z,
+            if diagnosis.best_run_outlier { "!" } else { " " }
+        );
+        let suggestion = if diagnosis.needs_more_runs() {
+            format!("run {} times", diagnosis.runs_needed)
+        } else {
+            "enough runs".to_string()
+        };
+        table.push(vec![
+            diagnosis.instance,
+            diagnosis.algorithm,
+            diagnosis.runs.to_string(),
+            cv,
+            z,
+            suggestion,
+        ]);
+    }
+    lines.extend(table.lines());
+    lines
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
@@ -1,4 +1,6 @@
+use crate::pareto::ParetoArchive;
 use crate::tsplib::{Solution, TsplibInstance};
+use crate::weight::Weight;
 use plotters::prelude::*;
 use std::path::Path;
 
@@ -50,7 +52,7 @@ pub fn plot_solution(
         chart
             .draw_series(LineSeries::new(line_data, BLUE.stroke_width(LINE_WIDTH)))?
             .label("Cycle 1")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE.clone()));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
 
         chart.draw_series(
             points
@@ -74,7 +76,7 @@ pub fn plot_solution(
         chart
             .draw_series(LineSeries::new(line_data, RED.stroke_width(LINE_WIDTH)))?
             .label("Cycle 2")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED.clone()));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
         chart.draw_series(
             points
@@ -85,8 +87,8 @@ pub fn plot_solution(
 
     chart
         .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .position(SeriesLabelPosition::UpperRight)
         .draw()?;
 
@@ -94,3 +96,61 @@ pub fn plot_solution(
 
     Ok(())
 }
+
+/// Plots the archive's front as total cost against cycle-length imbalance.
+pub fn plot_pareto_front(
+    archive: &ParetoArchive,
+    title: &str,
+    output_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let points: Vec<(f64, f64)> = archive
+        .front()
+        .iter()
+        .map(|p| (p.cost().to_f64(), p.imbalance().to_f64()))
+        .collect();
+    if points.is_empty() {
+        return Err("Pareto archive is empty".into());
+    }
+
+    let (min_x, max_x, min_y, max_y) = points
+        .iter()
+        .fold((f64::MAX, f64::MIN, f64::MAX, f64::MIN), |acc, &(x, y)| {
+            (acc.0.min(x), acc.1.max(x), acc.2.min(y), acc.3.max(y))
+        });
+
+    let padding_x = (max_x - min_x).max(1.0) * 0.05;
+    let padding_y = (max_y - min_y).max(1.0) * 0.05;
+
+    let root = BitMapBackend::new(output_path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 30))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            (min_x - padding_x)..(max_x + padding_x),
+            (min_y - padding_y)..(max_y + padding_y),
+        )?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Total cost")
+        .y_desc("Imbalance")
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(
+        points.iter().copied(),
+        BLUE.stroke_width(LINE_WIDTH),
+    ))?;
+    chart.draw_series(
+        points
+            .iter()
+            .map(|&(x, y)| Circle::new((x, y), POINT_SIZE + 1, RED.filled())),
+    )?;
+
+    root.present()?;
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Numeric type of distances, costs and move deltas.
+//!
+//! By default distances are TSPLIB's rounded integers. The `float-distances` feature switches
+//! [`Cost`] to `f64` and keeps the exact Euclidean lengths, to study how the rounding shapes
+//! local optima. Code that compares or orders costs goes through [`Weight`] so it builds with
+//! either type.
+
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Distance, cost and delta type of this build.
+#[cfg(not(feature = "float-distances"))]
+pub type Cost = i32;
+/// Distance, cost and delta type of this build.
+#[cfg(feature = "float-distances")]
+pub type Cost = f64;
+
+/// A numeric type distances can be measured in.
+pub trait Weight:
+    Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + Debug
+    + Display
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + Sum
+{
+    const ZERO: Self;
+
+    /// Weight of an edge of Euclidean length `length`: TSPLIB's `nint` for integers.
+    fn from_length(length: f64) -> Self;
+
+    fn from_i32(value: i32) -> Self;
+
+    /// `value` if this type represents it exactly.
+    fn from_i64(value: i64) -> Option<Self>;
+
+    /// `value` if this type represents it exactly.
+    fn from_f64(value: f64) -> Option<Self>;
+
+    /// `value` rounded to this type, for scaled or averaged costs.
+    fn round_from(value: f64) -> Self;
+
+    /// The smallest value of this type not below `value`, e.g. to round a lower bound.
+    fn ceil_from(value: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    /// `self` as an integer, if it is one.
+    fn to_i64(self) -> Option<i64>;
+
+    /// Total order, for sorting and for minima and maxima.
+    fn total_order(&self, other: &Self) -> Ordering;
+
+    /// Whether `self`, a cost delta, improves the cost by more than the rounding noise of the
+    /// type.
+    fn is_improvement(self) -> bool;
+
+    /// Whether `self` and `other` are equal up to the rounding noise of the type.
+    fn approx_eq(self, other: Self) -> bool;
+
+    /// Length of the little-endian encoding of [`Self::write_le`].
+    const BYTES: usize;
+
+    /// Appends the little-endian bytes of `self`, e.g. for a cache file.
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// The value [`Self::write_le`] encoded in `bytes`, which holds [`Self::BYTES`] bytes.
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+impl Weight for i32 {
+    const ZERO: Self = 0;
+
+    #[inline]
+    fn from_length(length: f64) -> Self {
+        length.round() as i32
+    }
+
+    #[inline]
+    fn from_i32(value: i32) -> Self {
+        value
+    }
+
+    fn from_i64(value: i64) -> Option<Self> {
+        i32::try_from(value).ok()
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        (value.fract() == 0.0 && value >= i32::MIN as f64 && value <= i32::MAX as f64)
+            .then_some(value as i32)
+    }
+
+    fn round_from(value: f64) -> Self {
+        value.round() as i32
+    }
+
+    fn ceil_from(value: f64) -> Self {
+        value.ceil() as i32
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn to_i64(self) -> Option<i64> {
+        Some(self as i64)
+    }
+
+    #[inline]
+    fn total_order(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    #[inline]
+    fn is_improvement(self) -> bool {
+        self < 0
+    }
+
+    #[inline]
+    fn approx_eq(self, other: Self) -> bool {
+        self == other
+    }
+
+    const BYTES: usize = 4;
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        i32::from_le_bytes(bytes.try_into().expect("an i32 takes 4 bytes"))
+    }
+}
+
+/// Deltas below this many length units count as rounding noise of `f64` costs.
+const F64_TOLERANCE: f64 = 1e-6;
+
+impl Weight for f64 {
+    const ZERO: Self = 0.0;
+
+    #[inline]
+    fn from_length(length: f64) -> Self {
+        length
+    }
+
+    #[inline]
+    fn from_i32(value: i32) -> Self {
+        value as f64
+    }
+
+    fn from_i64(value: i64) -> Option<Self> {
+        Some(value as f64)
+    }
+
+    fn from_f64(value: f64) -> Option<Self> {
+        value.is_finite().then_some(value)
+    }
+
+    fn round_from(value: f64) -> Self {
+        value
+    }
+
+    fn ceil_from(value: f64) -> Self {
+        value
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn to_i64(self) -> Option<i64> {
+        (self.fract() == 0.0 && self.abs() < i64::MAX as f64).then_some(self as i64)
+    }
+
+    #[inline]
+    fn total_order(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn is_improvement(self) -> bool {
+        self < -F64_TOLERANCE
+    }
+
+    #[inline]
+    fn approx_eq(self, other: Self) -> bool {
+        (self - other).abs() <= F64_TOLERANCE * self.abs().max(other.abs()).max(1.0)
+    }
+
+    const BYTES: usize = 8;
+
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        f64::from_le_bytes(bytes.try_into().expect("an f64 takes 8 bytes"))
+    }
+}
+
+/// A weight ordered by [`Weight::total_order`], as the key of `sort_by_key`, `min_by_key` and
+/// the like, which need [`Ord`].
+#[derive(Debug, Clone, Copy)]
+pub struct Ordered<W>(pub W);
+
+impl<W: Weight> PartialEq for Ordered<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Weight> Eq for Ordered<W> {}
+
+impl<W: Weight> PartialOrd for Ordered<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Weight> Ord for Ordered<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_order(&other.0)
+    }
+}